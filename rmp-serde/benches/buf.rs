@@ -33,3 +33,39 @@ fn bench_strings(bencher: &mut Bencher, size: usize) {
         <Vec<String>>::deserialize(&mut rmp_serde::Deserializer::new(&buf[..])).unwrap();
     });
 }
+
+#[derive(Deserialize, Serialize)]
+struct Record {
+    id: u64,
+    name: String,
+    score: f64,
+    active: bool,
+}
+
+#[bench]
+fn bench_named_structs_from_slice(bencher: &mut Bencher) {
+    let buf = named_records(1000);
+
+    // Field names are matched against slices of the input, only the `name` values are allocated,
+    // as `tests/alloc.rs` checks.
+    bencher.iter(|| {
+        rmp_serde::from_slice::<Vec<Record>>(&buf).unwrap();
+    });
+}
+
+#[bench]
+fn bench_named_structs_from_read(bencher: &mut Bencher) {
+    let buf = named_records(1000);
+
+    // Field names are matched against a buffer reused by the reader.
+    bencher.iter(|| {
+        rmp_serde::from_read::<_, Vec<Record>>(&buf[..]).unwrap();
+    });
+}
+
+fn named_records(size: u64) -> Vec<u8> {
+    let records: Vec<Record> = (0..size)
+        .map(|id| Record { id, name: format!("record {id}"), score: id as f64 / 2.0, active: id % 2 == 0 })
+        .collect();
+    rmp_serde::to_vec_named(&records).unwrap()
+}
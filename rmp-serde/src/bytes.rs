@@ -1,10 +1,17 @@
-//! Hacky serializer that only allows `u8`
+//! Hacky serializers that only allow `u8`, or only check the shape of a value
 
 use serde::ser::Impossible;
 use serde::Serialize;
 use std::fmt;
 
 pub(crate) struct OnlyBytes;
+/// Succeeds only for the given shape of value, without serializing anything.
+pub(crate) enum OnlyShape {
+    /// `None`.
+    None,
+    /// A unit variant of the enum with the given name.
+    UnitVariantOf(&'static str),
+}
 pub(crate) struct Nope;
 
 impl std::error::Error for Nope {
@@ -169,3 +176,145 @@ impl serde::Serializer for OnlyBytes {
         Err(Nope)
     }
 }
+
+impl serde::Serializer for OnlyShape {
+    type Ok = ();
+    type Error = Nope;
+    type SerializeSeq = Impossible<(), Nope>;
+    type SerializeTuple = Impossible<(), Nope>;
+    type SerializeTupleStruct = Impossible<(), Nope>;
+    type SerializeTupleVariant = Impossible<(), Nope>;
+    type SerializeMap = Impossible<(), Nope>;
+    type SerializeStruct = Impossible<(), Nope>;
+    type SerializeStructVariant = Impossible<(), Nope>;
+
+    fn serialize_u8(self, _: u8) -> Result<(), Nope> {
+        Err(Nope)
+    }
+
+    fn serialize_bool(self, _: bool) -> Result<(), Nope> {
+        Err(Nope)
+    }
+
+    fn serialize_i8(self, _: i8) -> Result<(), Nope> {
+        Err(Nope)
+    }
+
+    fn serialize_i16(self, _: i16) -> Result<(), Nope> {
+        Err(Nope)
+    }
+
+    fn serialize_i32(self, _: i32) -> Result<(), Nope> {
+        Err(Nope)
+    }
+
+    fn serialize_i64(self, _: i64) -> Result<(), Nope> {
+        Err(Nope)
+    }
+
+    fn serialize_u16(self, _: u16) -> Result<(), Nope> {
+        Err(Nope)
+    }
+
+    fn serialize_u32(self, _: u32) -> Result<(), Nope> {
+        Err(Nope)
+    }
+
+    fn serialize_u64(self, _: u64) -> Result<(), Nope> {
+        Err(Nope)
+    }
+
+    fn serialize_f32(self, _: f32) -> Result<(), Nope> {
+        Err(Nope)
+    }
+
+    fn serialize_f64(self, _: f64) -> Result<(), Nope> {
+        Err(Nope)
+    }
+
+    fn serialize_char(self, _: char) -> Result<(), Nope> {
+        Err(Nope)
+    }
+
+    fn serialize_str(self, _: &str) -> Result<(), Nope> {
+        Err(Nope)
+    }
+
+    fn serialize_bytes(self, _: &[u8]) -> Result<(), Nope> {
+        Err(Nope)
+    }
+
+    fn serialize_none(self) -> Result<(), Nope> {
+        match self {
+            Self::None => Ok(()),
+            Self::UnitVariantOf(..) => Err(Nope),
+        }
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, _: &T) -> Result<(), Nope> {
+        Err(Nope)
+    }
+
+    fn serialize_unit(self) -> Result<(), Nope> {
+        Err(Nope)
+    }
+
+    fn serialize_unit_struct(self, _: &'static str) -> Result<(), Nope> {
+        Err(Nope)
+    }
+
+    fn serialize_unit_variant(self, name: &'static str, _: u32, _: &'static str) -> Result<(), Nope> {
+        match self {
+            Self::UnitVariantOf(enum_name) if enum_name == name => Ok(()),
+            _ => Err(Nope),
+        }
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(self, _: &'static str, _: &T) -> Result<(), Nope> {
+        Err(Nope)
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(self, _: &'static str, _: u32, _: &'static str, _: &T) -> Result<(), Nope> {
+        Err(Nope)
+    }
+
+    fn serialize_seq(self, _: Option<usize>) -> Result<Self::SerializeSeq, Nope> {
+        Err(Nope)
+    }
+
+    fn serialize_tuple(self, _: usize) -> Result<Self::SerializeTuple, Nope> {
+        Err(Nope)
+    }
+
+    fn serialize_tuple_struct(self, _: &'static str, _: usize) -> Result<Self::SerializeTupleStruct, Nope> {
+        Err(Nope)
+    }
+
+    fn serialize_tuple_variant(self, _: &'static str, _: u32, _: &'static str, _: usize) -> Result<Self::SerializeTupleVariant, Nope> {
+        Err(Nope)
+    }
+
+    fn serialize_map(self, _: Option<usize>) -> Result<Self::SerializeMap, Nope> {
+        Err(Nope)
+    }
+
+    fn serialize_struct(self, _: &'static str, _: usize) -> Result<Self::SerializeStruct, Nope> {
+        Err(Nope)
+    }
+
+    fn serialize_struct_variant(self, _: &'static str, _: u32, _: &'static str, _: usize) -> Result<Self::SerializeStructVariant, Nope> {
+        Err(Nope)
+    }
+
+    fn collect_seq<I>(self, _: I) -> Result<(), Nope> where I: IntoIterator, <I as IntoIterator>::Item: Serialize {
+        Err(Nope)
+    }
+
+    fn collect_map<K, V, I>(self, _: I) -> Result<(), Nope> where K: Serialize, V: Serialize, I: IntoIterator<Item = (K, V)> {
+        Err(Nope)
+    }
+
+    fn collect_str<T: ?Sized + fmt::Display>(self, _: &T) -> Result<(), Nope> {
+        Err(Nope)
+    }
+}
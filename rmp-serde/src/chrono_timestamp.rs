@@ -0,0 +1,141 @@
+//! Serialize a `chrono::DateTime<Utc>` as the MessagePack timestamp ext, of type -1.
+//!
+//! Intended to be used with `#[serde(with = "rmp_serde::chrono_timestamp")]`. Requires the
+//! `chrono` feature. [`to_ext_data`] and [`from_ext_data`] convert between a `DateTime<Utc>` and
+//! the data of the ext directly, for example for an `rmpv::Value::Ext`.
+//!
+//! # Format
+//!
+//! The timestamp is written in the smallest of the three formats of the MessagePack
+//! specification:
+//!
+//! - timestamp 32, a `fixext 4` holding the seconds since the epoch as an unsigned 32-bit
+//!   integer, when there are no nanoseconds and the seconds fit;
+//! - timestamp 64, a `fixext 8` holding the nanoseconds in its upper 30 bits and the seconds in
+//!   its lower 34 bits, when the seconds are positive and fit;
+//! - timestamp 96, an `ext 8` of 12 bytes holding the nanoseconds as an unsigned 32-bit integer,
+//!   then the seconds as a signed 64-bit integer, for all other times, including those before
+//!   the epoch.
+//!
+//! All three are accepted when deserializing. The nanoseconds are always counted forward from
+//! the seconds, so 1.5 seconds before the epoch is -2 seconds and 500 000 000 nanoseconds.
+//! MessagePack can not represent a leap second, which is written as the first nanoseconds of the
+//! following second instead.
+//!
+//! # Examples
+//!
+//! ```
+//! use chrono::{DateTime, Utc};
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Debug, PartialEq, Deserialize, Serialize)]
+//! struct Event {
+//!     #[serde(with = "rmp_serde::chrono_timestamp")]
+//!     at: DateTime<Utc>,
+//! }
+//!
+//! let val = Event { at: DateTime::from_timestamp(1_700_000_000, 0).unwrap() };
+//! let buf = rmp_serde::to_vec(&val).unwrap();
+//!
+//! // [timestamp 32]
+//! assert_eq!(vec![0x91, 0xd6, 0xff, 0x65, 0x53, 0xf1, 0x00], buf);
+//! assert_eq!(val, rmp_serde::from_slice(&buf).unwrap());
+//! ```
+
+use std::fmt;
+
+use chrono::{DateTime, Utc};
+use serde::de::{self, Deserialize, Visitor};
+use serde::{Deserializer, Serializer};
+
+use crate::overrides::AsBin;
+use crate::MSGPACK_EXT_STRUCT_NAME;
+
+/// The ext type of MessagePack timestamps.
+pub const EXT_TYPE: i8 = -1;
+
+const NANOS_PER_SEC: u32 = 1_000_000_000;
+
+/// Returns the data of the timestamp ext representing the time, in the smallest format.
+#[must_use]
+pub fn to_ext_data(time: &DateTime<Utc>) -> Vec<u8> {
+    let mut secs = time.timestamp();
+    let mut nanos = time.timestamp_subsec_nanos();
+    if nanos >= NANOS_PER_SEC {
+        // A leap second.
+        secs += 1;
+        nanos -= NANOS_PER_SEC;
+    }
+
+    match u64::try_from(secs) {
+        Ok(secs) if secs >> 34 == 0 => {
+            if nanos == 0 && secs >> 32 == 0 {
+                (secs as u32).to_be_bytes().to_vec()
+            } else {
+                (u64::from(nanos) << 34 | secs).to_be_bytes().to_vec()
+            }
+        }
+        _ => [&nanos.to_be_bytes()[..], &secs.to_be_bytes()].concat(),
+    }
+}
+
+/// Returns the time represented by the data of a timestamp ext, in any of the three formats.
+///
+/// Returns `None` if the data has another length, if the nanoseconds are not below one second,
+/// or if the time is out of the range of `DateTime`.
+#[must_use]
+pub fn from_ext_data(data: &[u8]) -> Option<DateTime<Utc>> {
+    let (secs, nanos) = match *data {
+        [a, b, c, d] => (u32::from_be_bytes([a, b, c, d]).into(), 0),
+        [a, b, c, d, e, f, g, h] => {
+            let val = u64::from_be_bytes([a, b, c, d, e, f, g, h]);
+            ((val & ((1 << 34) - 1)) as i64, (val >> 34) as u32)
+        }
+        [a, b, c, d, ref secs @ ..] if secs.len() == 8 => {
+            (i64::from_be_bytes(secs.try_into().ok()?), u32::from_be_bytes([a, b, c, d]))
+        }
+        _ => return None,
+    };
+    if nanos >= NANOS_PER_SEC {
+        return None;
+    }
+    DateTime::from_timestamp(secs, nanos)
+}
+
+/// Serializes the time as a timestamp ext.
+///
+/// # Errors
+///
+/// Serialization can fail if the underlying serializer fails.
+#[inline]
+pub fn serialize<S: Serializer>(time: &DateTime<Utc>, se: S) -> Result<S::Ok, S::Error> {
+    se.serialize_newtype_struct(MSGPACK_EXT_STRUCT_NAME, &(EXT_TYPE, AsBin(to_ext_data(time))))
+}
+
+/// Deserializes a time from a timestamp ext.
+///
+/// # Errors
+///
+/// Deserialization fails if the input is not an ext of type -1 holding a valid timestamp.
+#[inline]
+pub fn deserialize<'de, D: Deserializer<'de>>(de: D) -> Result<DateTime<Utc>, D::Error> {
+    de.deserialize_newtype_struct(MSGPACK_EXT_STRUCT_NAME, TimestampVisitor)
+}
+
+struct TimestampVisitor;
+
+impl<'de> Visitor<'de> for TimestampVisitor {
+    type Value = DateTime<Utc>;
+
+    fn expecting(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.write_str("a timestamp ext")
+    }
+
+    fn visit_newtype_struct<D: Deserializer<'de>>(self, de: D) -> Result<Self::Value, D::Error> {
+        let (ty, AsBin(data)) = <(i8, AsBin<Vec<u8>>)>::deserialize(de)?;
+        if ty != EXT_TYPE {
+            return Err(de::Error::invalid_value(de::Unexpected::Other("an ext of another type"), &self));
+        }
+        from_ext_data(&data).ok_or_else(|| de::Error::invalid_value(de::Unexpected::Bytes(&data), &self))
+    }
+}
@@ -0,0 +1,278 @@
+//! A [`tokio_util::codec`] implementation framing MessagePack messages with a length prefix.
+//!
+//! Requires the `tokio-util` feature.
+//!
+//! # Examples
+//!
+//! ```
+//! use bytes::BytesMut;
+//! use rmp_serde::codec::MsgpackCodec;
+//! use serde::{Deserialize, Serialize};
+//! use tokio_util::codec::{Decoder, Encoder};
+//!
+//! #[derive(Debug, PartialEq, Deserialize, Serialize)]
+//! struct Ping {
+//!     seq: u32,
+//! }
+//!
+//! let mut codec = MsgpackCodec::<Ping>::new().with_length_width(2);
+//!
+//! let mut buf = BytesMut::new();
+//! codec.encode(Ping { seq: 1 }, &mut buf).unwrap();
+//! codec.encode(Ping { seq: 2 }, &mut buf).unwrap();
+//! assert_eq!([0x00, 0x02, 0x91, 0x01, 0x00, 0x02, 0x91, 0x02], buf[..]);
+//!
+//! // Frames may arrive in pieces.
+//! let rest = buf.split_off(3);
+//! assert_eq!(None, codec.decode(&mut buf).unwrap());
+//! buf.unsplit(rest);
+//! assert_eq!(Some(Ping { seq: 1 }), codec.decode(&mut buf).unwrap());
+//! assert_eq!(Some(Ping { seq: 2 }), codec.decode(&mut buf).unwrap());
+//! assert_eq!(None, codec.decode(&mut buf).unwrap());
+//! ```
+//!
+//! With tokio the codec is used through `tokio_util::codec::Framed`, `FramedRead` and `FramedWrite`
+//! to turn a byte stream into a `Stream` and a `Sink` of messages.
+
+use std::error;
+use std::fmt::{self, Debug, Display, Formatter};
+use std::io;
+use std::marker::PhantomData;
+
+use ::bytes::{Buf, BufMut, BytesMut};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use tokio_util::codec::{Decoder, Encoder};
+
+use crate::{decode, encode, Deserializer, Serializer};
+
+/// An error which can occur when encoding or decoding a frame with [`MsgpackCodec`].
+#[derive(Debug)]
+pub enum Error {
+    /// Failed to read or write the underlying stream.
+    Io(io::Error),
+    /// Failed to serialize a message.
+    Encode(encode::Error),
+    /// Failed to deserialize a message.
+    Decode(decode::Error),
+    /// The length of a frame, given here, exceeds the maximum frame length, or does not fit into
+    /// the length prefix.
+    FrameTooLarge(usize),
+    /// A frame holds the given number of bytes after the message it contains.
+    TrailingBytes(usize),
+}
+
+impl error::Error for Error {
+    #[cold]
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            Self::Io(ref err) => Some(err),
+            Self::Encode(ref err) => Some(err),
+            Self::Decode(ref err) => Some(err),
+            Self::FrameTooLarge(..) | Self::TrailingBytes(..) => None,
+        }
+    }
+}
+
+impl Display for Error {
+    #[cold]
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), fmt::Error> {
+        match *self {
+            Self::Io(ref err) => write!(f, "I/O error: {err}"),
+            Self::Encode(ref err) => write!(f, "failed to encode frame: {err}"),
+            Self::Decode(ref err) => write!(f, "failed to decode frame: {err}"),
+            Self::FrameTooLarge(len) => write!(f, "frame of {len} bytes is too large"),
+            Self::TrailingBytes(len) => write!(f, "{len} trailing bytes after the message in a frame"),
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    #[cold]
+    fn from(err: io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+impl From<encode::Error> for Error {
+    #[cold]
+    fn from(err: encode::Error) -> Self {
+        Self::Encode(err)
+    }
+}
+
+impl From<decode::Error> for Error {
+    #[cold]
+    fn from(err: decode::Error) -> Self {
+        Self::Decode(err)
+    }
+}
+
+/// Encodes and decodes MessagePack messages of type `T`, each one framed by its length in bytes as
+/// a big-endian unsigned integer.
+///
+/// By default the length prefix is 4 bytes wide, frames may be at most 8 MiB long and structs are
+/// serialized as tuples, like [`to_vec`](crate::to_vec) does.
+///
+/// When decoding, incoming bytes are buffered until a whole frame is available. A frame longer than
+/// the maximum frame length is rejected as soon as its prefix is read, before buffering any of it,
+/// and a frame must hold exactly one message, with no bytes after it.
+pub struct MsgpackCodec<T> {
+    length_width: usize,
+    max_frame_len: usize,
+    is_named: bool,
+    _marker: PhantomData<fn(T) -> T>,
+}
+
+impl<T> MsgpackCodec<T> {
+    /// The default maximum frame length, see [`with_max_frame_len`](Self::with_max_frame_len).
+    pub const DEFAULT_MAX_FRAME_LEN: usize = 8 * 1024 * 1024;
+
+    /// Creates a codec with the default settings.
+    #[inline]
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            length_width: 4,
+            max_frame_len: Self::DEFAULT_MAX_FRAME_LEN,
+            is_named: false,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Sets the width of the length prefix in bytes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the width is not between 1 and 8.
+    #[inline]
+    #[must_use]
+    pub fn with_length_width(mut self, width: usize) -> Self {
+        assert!((1..=8).contains(&width), "length prefix width must be between 1 and 8 bytes");
+        self.length_width = width;
+        self
+    }
+
+    /// Sets the maximum length of a frame in bytes, excluding its length prefix.
+    ///
+    /// Frames are also limited to the largest length the prefix can hold.
+    #[inline]
+    #[must_use]
+    pub const fn with_max_frame_len(mut self, len: usize) -> Self {
+        self.max_frame_len = len;
+        self
+    }
+
+    /// Serializes structs as maps, like [`to_vec_named`](crate::to_vec_named) does.
+    #[inline]
+    #[must_use]
+    pub const fn with_struct_map(mut self) -> Self {
+        self.is_named = true;
+        self
+    }
+
+    /// Returns the width of the length prefix in bytes.
+    #[inline]
+    #[must_use]
+    pub const fn length_width(&self) -> usize {
+        self.length_width
+    }
+
+    /// Returns the maximum length of a frame in bytes.
+    #[inline]
+    #[must_use]
+    pub const fn max_frame_len(&self) -> usize {
+        self.max_frame_len
+    }
+
+    fn check_frame_len(&self, len: u64) -> Result<usize, Error> {
+        let too_large = || Error::FrameTooLarge(usize::try_from(len).unwrap_or(usize::MAX));
+        let len = usize::try_from(len).map_err(|_| too_large())?;
+        if len > self.max_frame_len || (self.length_width < 8 && len >> (self.length_width * 8) != 0) {
+            return Err(too_large());
+        }
+        Ok(len)
+    }
+}
+
+impl<T> Default for MsgpackCodec<T> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Clone for MsgpackCodec<T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self { _marker: PhantomData, ..*self }
+    }
+}
+
+impl<T> Debug for MsgpackCodec<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MsgpackCodec")
+            .field("length_width", &self.length_width)
+            .field("max_frame_len", &self.max_frame_len)
+            .field("is_named", &self.is_named)
+            .finish()
+    }
+}
+
+impl<T: DeserializeOwned> Decoder for MsgpackCodec<T> {
+    type Item = T;
+    type Error = Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<T>, Error> {
+        let width = self.length_width;
+        if src.len() < width {
+            src.reserve(width - src.len());
+            return Ok(None);
+        }
+
+        let len = self.check_frame_len((&src[..width]).get_uint(width))?;
+        if src.len() - width < len {
+            src.reserve(width + len - src.len());
+            return Ok(None);
+        }
+
+        src.advance(width);
+        let frame = src.split_to(len);
+        let mut rd = &frame[..];
+        let item = T::deserialize(&mut Deserializer::new(&mut rd))?;
+        if !rd.is_empty() {
+            return Err(Error::TrailingBytes(rd.len()));
+        }
+        Ok(Some(item))
+    }
+}
+
+impl<T, I: Serialize> Encoder<I> for MsgpackCodec<T> {
+    type Error = Error;
+
+    fn encode(&mut self, item: I, dst: &mut BytesMut) -> Result<(), Error> {
+        let start = dst.len();
+        let width = self.length_width;
+        dst.put_bytes(0, width);
+
+        let res = if self.is_named {
+            item.serialize(&mut Serializer::new((&mut *dst).writer()).with_struct_map())
+        } else {
+            item.serialize(&mut Serializer::new((&mut *dst).writer()))
+        };
+        let res = res.map_err(Error::from).and_then(|()| {
+            self.check_frame_len((dst.len() - start - width) as u64)
+        });
+
+        match res {
+            Ok(len) => {
+                dst[start..start + width].copy_from_slice(&(len as u64).to_be_bytes()[8 - width..]);
+                Ok(())
+            }
+            Err(err) => {
+                dst.truncate(start);
+                Err(err)
+            }
+        }
+    }
+}
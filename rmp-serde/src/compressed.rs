@@ -0,0 +1,227 @@
+//! Transparent compression of large binaries, see [`Compressed`].
+//!
+//! Requires the `compression` feature. No compression codec is bundled: the codec is supplied by
+//! implementing [`Compressor`], for example on top of the `zstd` or `lz4_flex` crates.
+//!
+//! # Format
+//!
+//! A payload of at most [`Compressor::THRESHOLD`] bytes is written as a plain MessagePack binary.
+//! A larger one is compressed, and written as an ext of type [`Compressor::EXT_TYPE`] whose data
+//! is exactly the output of [`Compressor::compress`], without any additional header. If the
+//! compressed data is not smaller than the payload, the payload is written as a plain binary
+//! instead. Readers in other languages therefore only need to decompress exts of that type and
+//! treat binaries as they are.
+//!
+//! Both forms are accepted when deserializing, so payloads written without the wrapper, as a
+//! binary or an array of bytes, are read as well.
+//!
+//! # Examples
+//!
+//! A run-length encoding, standing in for a real codec:
+//!
+//! ```
+//! use rmp_serde::compressed::{Compressed, Compressor};
+//! use serde::{Deserialize, Serialize};
+//!
+//! struct Rle;
+//!
+//! impl Compressor for Rle {
+//!     const EXT_TYPE: i8 = 42;
+//!     const THRESHOLD: usize = 16;
+//!     type Error = &'static str;
+//!
+//!     fn compress(data: &[u8]) -> Result<Vec<u8>, Self::Error> {
+//!         let mut out = Vec::new();
+//!         for chunk in data.chunk_by(|a, b| a == b) {
+//!             for run in chunk.chunks(255) {
+//!                 out.extend([run.len() as u8, run[0]]);
+//!             }
+//!         }
+//!         Ok(out)
+//!     }
+//!
+//!     fn decompress(data: &[u8]) -> Result<Vec<u8>, Self::Error> {
+//!         if data.len() % 2 != 0 {
+//!             return Err("truncated run");
+//!         }
+//!         Ok(data.chunks(2).flat_map(|run| vec![run[1]; run[0].into()]).collect())
+//!     }
+//! }
+//!
+//! #[derive(Debug, PartialEq, Deserialize, Serialize)]
+//! struct Blob {
+//!     payload: Compressed<Rle>,
+//! }
+//!
+//! let val = Blob { payload: Compressed::new(vec![0; 1000]) };
+//! let buf = rmp_serde::to_vec(&val).unwrap();
+//!
+//! // [fixext8 type 42: 255 x 0, 255 x 0, 255 x 0, 235 x 0]
+//! assert_eq!([0x91, 0xd7, 0x2a, 0xff, 0x00, 0xff], buf[..6]);
+//! assert_eq!(val, rmp_serde::from_slice(&buf).unwrap());
+//!
+//! // Small payloads are left uncompressed.
+//! let buf = rmp_serde::to_vec(&Blob { payload: Compressed::new(vec![0; 4]) }).unwrap();
+//! assert_eq!(vec![0x91, 0xc4, 0x04, 0, 0, 0, 0], buf);
+//! ```
+
+use std::fmt::{self, Debug, Display, Formatter};
+use std::marker::PhantomData;
+use std::ops::Deref;
+
+use serde::de::{self, SeqAccess, Visitor};
+use serde::{ser, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::overrides::AsBin;
+use crate::MSGPACK_EXT_STRUCT_NAME;
+
+/// A compression codec used by [`Compressed`].
+pub trait Compressor {
+    /// The ext type of compressed payloads.
+    const EXT_TYPE: i8;
+    /// The length in bytes above which payloads are compressed.
+    const THRESHOLD: usize = 1024;
+
+    /// The error returned when compressing or decompressing fails.
+    type Error: Display;
+
+    /// Compresses the payload.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the payload can not be compressed, which fails the serialization.
+    fn compress(data: &[u8]) -> Result<Vec<u8>, Self::Error>;
+
+    /// Decompresses the data of an ext of type [`EXT_TYPE`](Self::EXT_TYPE).
+    ///
+    /// The data comes from the input being deserialized, so a codec used with untrusted input
+    /// should limit the length of the decompressed payload.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the data is not valid, which fails the deserialization.
+    fn decompress(data: &[u8]) -> Result<Vec<u8>, Self::Error>;
+}
+
+/// Bytes which are compressed with the codec `C` when larger than its threshold, see the
+/// [module documentation](self) for the format.
+pub struct Compressed<C> {
+    data: Vec<u8>,
+    _compressor: PhantomData<fn() -> C>,
+}
+
+impl<C> Compressed<C> {
+    /// Wraps the uncompressed payload.
+    #[inline]
+    #[must_use]
+    pub fn new(data: Vec<u8>) -> Self {
+        Self { data, _compressor: PhantomData }
+    }
+
+    /// Returns the uncompressed payload.
+    #[inline]
+    #[must_use]
+    pub fn into_inner(self) -> Vec<u8> {
+        self.data
+    }
+}
+
+impl<C> Deref for Compressed<C> {
+    type Target = [u8];
+
+    #[inline]
+    fn deref(&self) -> &[u8] {
+        &self.data
+    }
+}
+
+impl<C> From<Vec<u8>> for Compressed<C> {
+    #[inline]
+    fn from(data: Vec<u8>) -> Self {
+        Self::new(data)
+    }
+}
+
+impl<C> Debug for Compressed<C> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Compressed").field(&self.data).finish()
+    }
+}
+
+impl<C> Clone for Compressed<C> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self::new(self.data.clone())
+    }
+}
+
+impl<C> Default for Compressed<C> {
+    #[inline]
+    fn default() -> Self {
+        Self::new(Vec::new())
+    }
+}
+
+impl<C> PartialEq for Compressed<C> {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.data == other.data
+    }
+}
+
+impl<C> Eq for Compressed<C> {}
+
+impl<C: Compressor> Serialize for Compressed<C> {
+    fn serialize<S: Serializer>(&self, se: S) -> Result<S::Ok, S::Error> {
+        if self.data.len() > C::THRESHOLD {
+            let packed = C::compress(&self.data).map_err(ser::Error::custom)?;
+            if packed.len() < self.data.len() {
+                return se.serialize_newtype_struct(MSGPACK_EXT_STRUCT_NAME, &(C::EXT_TYPE, AsBin(&packed[..])));
+            }
+        }
+        se.serialize_bytes(&self.data)
+    }
+}
+
+impl<'de, C: Compressor> Deserialize<'de> for Compressed<C> {
+    #[inline]
+    fn deserialize<D: Deserializer<'de>>(de: D) -> Result<Self, D::Error> {
+        de.deserialize_any(CompressedVisitor::<C>(PhantomData)).map(Self::new)
+    }
+}
+
+struct CompressedVisitor<C>(PhantomData<fn() -> C>);
+
+impl<'de, C: Compressor> Visitor<'de> for CompressedVisitor<C> {
+    type Value = Vec<u8>;
+
+    fn expecting(&self, fmt: &mut Formatter<'_>) -> fmt::Result {
+        write!(fmt, "a binary or an ext of type {}", C::EXT_TYPE)
+    }
+
+    #[inline]
+    fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<Self::Value, E> {
+        Ok(v.to_vec())
+    }
+
+    #[inline]
+    fn visit_byte_buf<E: de::Error>(self, v: Vec<u8>) -> Result<Self::Value, E> {
+        Ok(v)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut buf = Vec::with_capacity(seq.size_hint().unwrap_or(0).min(4096));
+        while let Some(b) = seq.next_element()? {
+            buf.push(b);
+        }
+        Ok(buf)
+    }
+
+    fn visit_newtype_struct<D: Deserializer<'de>>(self, de: D) -> Result<Self::Value, D::Error> {
+        let (ty, AsBin(data)) = <(i8, AsBin<Vec<u8>>)>::deserialize(de)?;
+        if ty != C::EXT_TYPE {
+            return Err(de::Error::invalid_value(de::Unexpected::Other("an ext of another type"), &self));
+        }
+        C::decompress(&data).map_err(de::Error::custom)
+    }
+}
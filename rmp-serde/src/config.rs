@@ -1,5 +1,7 @@
 //! Change MessagePack behavior with configuration wrappers.
 
+use rmp::Marker;
+
 /// Represents configuration that dicatates what the serializer does.
 ///
 /// Implemented as an empty trait depending on a hidden trait in order to allow changing the
@@ -9,7 +11,7 @@ pub trait SerializerConfig: sealed::SerializerConfig {}
 impl<T: sealed::SerializerConfig> SerializerConfig for T {}
 
 pub(crate) mod sealed {
-    use crate::config::BytesMode;
+    use crate::config::{BytesMode, DiscriminantMode, IntEncoding};
 
     /// This is the inner trait - the real `SerializerConfig`.
     ///
@@ -23,6 +25,14 @@ pub(crate) mod sealed {
         /// String struct fields
         fn is_named(&self) -> bool;
         fn bytes(&self) -> BytesMode;
+        fn discriminant(&self) -> DiscriminantMode;
+        fn elide_trailing_nils(&self) -> bool;
+        fn skip_none_fields(&self) -> bool;
+        fn compact_whole_floats(&self) -> bool;
+        fn sort_map_keys(&self) -> bool;
+        fn int_encoding(&self) -> Option<&'static dyn IntEncoding>;
+        fn maps_as_structs(&self) -> bool;
+        fn forbid_ext(&self) -> bool;
     }
 }
 
@@ -31,6 +41,14 @@ pub(crate) struct RuntimeConfig {
     pub(crate) is_human_readable: bool,
     pub(crate) is_named: bool,
     pub(crate) bytes: BytesMode,
+    pub(crate) discriminant: DiscriminantMode,
+    pub(crate) elide_trailing_nils: bool,
+    pub(crate) skip_none_fields: bool,
+    pub(crate) compact_whole_floats: bool,
+    pub(crate) sort_map_keys: bool,
+    pub(crate) int_encoding: Option<&'static dyn IntEncoding>,
+    pub(crate) maps_as_structs: bool,
+    pub(crate) forbid_ext: bool,
 }
 
 /// When to encode `[u8]` as `bytes` rather than a sequence
@@ -56,12 +74,128 @@ pub enum BytesMode {
     ForceAll,
 }
 
+/// How enum variants are identified when serializing.
+///
+/// The `Deserializer` accepts all of these representations regardless of this setting.
+#[non_exhaustive]
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub enum DiscriminantMode {
+    /// Use the name of the variant as a string.
+    #[default]
+    Name,
+    /// Use the index of the variant, always encoded as a `u8`.
+    ///
+    /// Serialization fails for enums with more than 256 variants.
+    IndexU8,
+    /// Use the index of the variant, always encoded as a `u32`.
+    IndexU32,
+}
+
+/// Chooses how integers are encoded when serializing, see `Serializer::with_int_encoding`.
+///
+/// Each method returns the marker to write the integer with, which must be able to represent it:
+/// `FixPos` and `FixNeg` holding the integer itself, or one of the unsigned and signed integer
+/// markers whose range includes it. Serialization fails with `Error::InvalidDataModel` otherwise.
+/// Any integer marker can be read back into any integer type whose range includes the value.
+///
+/// [`ShortestInt`] matches the default behavior, and [`FixedWidthInt`] always uses 64 bits.
+///
+/// # Examples
+///
+/// ```
+/// use rmp::Marker;
+/// use rmp_serde::config::IntEncoding;
+///
+/// /// Writes every integer in at least 4 bytes, for example to patch them in place later.
+/// #[derive(Debug)]
+/// struct AtLeast32;
+///
+/// impl IntEncoding for AtLeast32 {
+///     fn uint_marker(&self, val: u64) -> Marker {
+///         if val >> 32 == 0 { Marker::U32 } else { Marker::U64 }
+///     }
+///
+///     fn sint_marker(&self, val: i64) -> Marker {
+///         if i32::try_from(val).is_ok() { Marker::I32 } else { Marker::I64 }
+///     }
+/// }
+///
+/// let mut buf = Vec::new();
+/// let mut se = rmp_serde::Serializer::new(&mut buf).with_int_encoding(&AtLeast32);
+/// serde::Serialize::serialize(&(1u8, -1i8), &mut se).unwrap();
+///
+/// assert_eq!(vec![0x92, 0xce, 0x00, 0x00, 0x00, 0x01, 0xd2, 0xff, 0xff, 0xff, 0xff], buf);
+/// ```
+pub trait IntEncoding: std::fmt::Debug + Sync {
+    /// Returns the marker to write the unsigned integer with.
+    ///
+    /// This is used for unsigned integers, from `u8` to `u64`.
+    fn uint_marker(&self, val: u64) -> Marker;
+
+    /// Returns the marker to write the signed integer with.
+    ///
+    /// This is used for signed integers, from `i8` to `i64`, including non-negative ones.
+    fn sint_marker(&self, val: i64) -> Marker;
+}
+
+/// Encodes each integer in its shortest form, which is the default.
+///
+/// Non-negative signed integers are encoded like unsigned ones.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct ShortestInt;
+
+impl IntEncoding for ShortestInt {
+    fn uint_marker(&self, val: u64) -> Marker {
+        match val {
+            0..=0x7f => Marker::FixPos(val as u8),
+            0x80..=0xff => Marker::U8,
+            0x100..=0xffff => Marker::U16,
+            0x1_0000..=0xffff_ffff => Marker::U32,
+            _ => Marker::U64,
+        }
+    }
+
+    fn sint_marker(&self, val: i64) -> Marker {
+        match val {
+            -32..=-1 => Marker::FixNeg(val as i8),
+            -128..=-33 => Marker::I8,
+            -32768..=-129 => Marker::I16,
+            -2147483648..=-32769 => Marker::I32,
+            i64::MIN..=-2147483649 => Marker::I64,
+            _ => self.uint_marker(val as u64),
+        }
+    }
+}
+
+/// Encodes every integer in 64 bits, unsigned integers as `uint 64` and signed integers as
+/// `int 64`, so that each integer takes 9 bytes whatever its value.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct FixedWidthInt;
+
+impl IntEncoding for FixedWidthInt {
+    fn uint_marker(&self, _val: u64) -> Marker {
+        Marker::U64
+    }
+
+    fn sint_marker(&self, _val: i64) -> Marker {
+        Marker::I64
+    }
+}
+
 impl RuntimeConfig {
     pub(crate) fn new(other: impl sealed::SerializerConfig) -> Self {
         Self {
             is_human_readable: other.is_human_readable(),
             is_named: other.is_named(),
             bytes: other.bytes(),
+            discriminant: other.discriminant(),
+            elide_trailing_nils: other.elide_trailing_nils(),
+            skip_none_fields: other.skip_none_fields(),
+            compact_whole_floats: other.compact_whole_floats(),
+            sort_map_keys: other.sort_map_keys(),
+            int_encoding: other.int_encoding(),
+            maps_as_structs: other.maps_as_structs(),
+            forbid_ext: other.forbid_ext(),
         }
     }
 }
@@ -81,13 +215,53 @@ impl sealed::SerializerConfig for RuntimeConfig {
     fn bytes(&self) -> BytesMode {
         self.bytes
     }
+
+    #[inline]
+    fn discriminant(&self) -> DiscriminantMode {
+        self.discriminant
+    }
+
+    #[inline]
+    fn elide_trailing_nils(&self) -> bool {
+        self.elide_trailing_nils
+    }
+
+    #[inline]
+    fn skip_none_fields(&self) -> bool {
+        self.skip_none_fields
+    }
+
+    #[inline]
+    fn compact_whole_floats(&self) -> bool {
+        self.compact_whole_floats
+    }
+
+    #[inline]
+    fn sort_map_keys(&self) -> bool {
+        self.sort_map_keys
+    }
+
+    #[inline]
+    fn int_encoding(&self) -> Option<&'static dyn IntEncoding> {
+        self.int_encoding
+    }
+
+    #[inline]
+    fn maps_as_structs(&self) -> bool {
+        self.maps_as_structs
+    }
+
+    #[inline]
+    fn forbid_ext(&self) -> bool {
+        self.forbid_ext
+    }
 }
 
 /// The default serializer/deserializer configuration.
 ///
 /// This configuration:
 /// - Writes structs as a tuple, without field names
-/// - Writes enum variants as integers
+/// - Writes enum variants by name, see [`DiscriminantMode`]
 /// - Writes and reads types as binary, not human-readable
 //
 /// This is the most compact representation.
@@ -109,6 +283,46 @@ impl sealed::SerializerConfig for DefaultConfig {
     fn bytes(&self) -> BytesMode {
         BytesMode::default()
     }
+
+    #[inline(always)]
+    fn discriminant(&self) -> DiscriminantMode {
+        DiscriminantMode::default()
+    }
+
+    #[inline(always)]
+    fn elide_trailing_nils(&self) -> bool {
+        false
+    }
+
+    #[inline(always)]
+    fn skip_none_fields(&self) -> bool {
+        false
+    }
+
+    #[inline(always)]
+    fn compact_whole_floats(&self) -> bool {
+        false
+    }
+
+    #[inline(always)]
+    fn sort_map_keys(&self) -> bool {
+        false
+    }
+
+    #[inline(always)]
+    fn int_encoding(&self) -> Option<&'static dyn IntEncoding> {
+        None
+    }
+
+    #[inline(always)]
+    fn maps_as_structs(&self) -> bool {
+        false
+    }
+
+    #[inline(always)]
+    fn forbid_ext(&self) -> bool {
+        false
+    }
 }
 
 /// Config wrapper, that overrides struct serialization by packing as a map with field names.
@@ -146,6 +360,38 @@ where
     fn bytes(&self) -> BytesMode {
         self.0.bytes()
     }
+
+    fn discriminant(&self) -> DiscriminantMode {
+        self.0.discriminant()
+    }
+
+    fn elide_trailing_nils(&self) -> bool {
+        self.0.elide_trailing_nils()
+    }
+
+    fn skip_none_fields(&self) -> bool {
+        self.0.skip_none_fields()
+    }
+
+    fn compact_whole_floats(&self) -> bool {
+        self.0.compact_whole_floats()
+    }
+
+    fn sort_map_keys(&self) -> bool {
+        self.0.sort_map_keys()
+    }
+
+    fn int_encoding(&self) -> Option<&'static dyn IntEncoding> {
+        self.0.int_encoding()
+    }
+
+    fn maps_as_structs(&self) -> bool {
+        self.0.maps_as_structs()
+    }
+
+    fn forbid_ext(&self) -> bool {
+        self.0.forbid_ext()
+    }
 }
 
 /// Config wrapper that overrides struct serlization by packing as a tuple without field
@@ -178,6 +424,38 @@ where
     fn bytes(&self) -> BytesMode {
         self.0.bytes()
     }
+
+    fn discriminant(&self) -> DiscriminantMode {
+        self.0.discriminant()
+    }
+
+    fn elide_trailing_nils(&self) -> bool {
+        self.0.elide_trailing_nils()
+    }
+
+    fn skip_none_fields(&self) -> bool {
+        self.0.skip_none_fields()
+    }
+
+    fn compact_whole_floats(&self) -> bool {
+        self.0.compact_whole_floats()
+    }
+
+    fn sort_map_keys(&self) -> bool {
+        self.0.sort_map_keys()
+    }
+
+    fn int_encoding(&self) -> Option<&'static dyn IntEncoding> {
+        self.0.int_encoding()
+    }
+
+    fn maps_as_structs(&self) -> bool {
+        self.0.maps_as_structs()
+    }
+
+    fn forbid_ext(&self) -> bool {
+        self.0.forbid_ext()
+    }
 }
 
 /// Config wrapper that overrides `Serializer::is_human_readable` and
@@ -210,6 +488,38 @@ where
     fn bytes(&self) -> BytesMode {
         self.0.bytes()
     }
+
+    fn discriminant(&self) -> DiscriminantMode {
+        self.0.discriminant()
+    }
+
+    fn elide_trailing_nils(&self) -> bool {
+        self.0.elide_trailing_nils()
+    }
+
+    fn skip_none_fields(&self) -> bool {
+        self.0.skip_none_fields()
+    }
+
+    fn compact_whole_floats(&self) -> bool {
+        self.0.compact_whole_floats()
+    }
+
+    fn sort_map_keys(&self) -> bool {
+        self.0.sort_map_keys()
+    }
+
+    fn int_encoding(&self) -> Option<&'static dyn IntEncoding> {
+        self.0.int_encoding()
+    }
+
+    fn maps_as_structs(&self) -> bool {
+        self.0.maps_as_structs()
+    }
+
+    fn forbid_ext(&self) -> bool {
+        self.0.forbid_ext()
+    }
 }
 
 /// Config wrapper that overrides `Serializer::is_human_readable` and
@@ -242,4 +552,36 @@ where
     fn bytes(&self) -> BytesMode {
         self.0.bytes()
     }
+
+    fn discriminant(&self) -> DiscriminantMode {
+        self.0.discriminant()
+    }
+
+    fn elide_trailing_nils(&self) -> bool {
+        self.0.elide_trailing_nils()
+    }
+
+    fn skip_none_fields(&self) -> bool {
+        self.0.skip_none_fields()
+    }
+
+    fn compact_whole_floats(&self) -> bool {
+        self.0.compact_whole_floats()
+    }
+
+    fn sort_map_keys(&self) -> bool {
+        self.0.sort_map_keys()
+    }
+
+    fn int_encoding(&self) -> Option<&'static dyn IntEncoding> {
+        self.0.int_encoding()
+    }
+
+    fn maps_as_structs(&self) -> bool {
+        self.0.maps_as_structs()
+    }
+
+    fn forbid_ext(&self) -> bool {
+        self.0.forbid_ext()
+    }
 }
@@ -4,7 +4,8 @@ use crate::config::sealed::SerializerConfig as _;
 use std::convert::TryInto;
 use std::error;
 use std::fmt::{self, Display, Formatter};
-use std::io::{self, Cursor, ErrorKind, Read};
+use std::io::{self, BufRead, Cursor, ErrorKind, Read};
+use std::iter;
 use std::marker::PhantomData;
 use std::num::TryFromIntError;
 use std::str::{self, Utf8Error};
@@ -12,7 +13,7 @@ use std::str::{self, Utf8Error};
 use byteorder::{self, ReadBytesExt};
 
 use serde;
-use serde::de::value::SeqDeserializer;
+use serde::de::value::{MapDeserializer, SeqDeserializer};
 use serde::de::{self, Deserialize, DeserializeOwned, DeserializeSeed, Unexpected, Visitor};
 use serde::forward_to_deserialize_any;
 
@@ -25,6 +26,7 @@ use crate::MSGPACK_EXT_STRUCT_NAME;
 
 /// Enum representing errors that can occur while decoding MessagePack data.
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum Error {
     /// The enclosed I/O error occurred while trying to read a MessagePack
     /// marker.
@@ -48,6 +50,13 @@ pub enum Error {
     Utf8Error(Utf8Error),
     /// The depth limit was exceeded.
     DepthLimitExceeded,
+    /// A string or binary declared the enclosed length, which is more than the limit set with
+    /// `Deserializer::set_max_string_len` or `Deserializer::set_max_bin_len`.
+    LengthLimitExceeded(u32),
+    /// The never used marker `0xc1` was found, which means that the data is corrupt.
+    ReservedMarker,
+    /// An ext was found, which `Deserializer::with_ext_forbidden` disallows.
+    ExtForbidden,
 }
 
 macro_rules! depth_count(
@@ -77,6 +86,9 @@ impl error::Error for Error {
             Self::Syntax(..) => None,
             Self::Utf8Error(ref err) => Some(err),
             Self::DepthLimitExceeded => None,
+            Self::LengthLimitExceeded(..) => None,
+            Self::ReservedMarker => None,
+            Self::ExtForbidden => None,
         }
     }
 }
@@ -106,6 +118,9 @@ impl Display for Error {
             Self::Syntax(ref msg) => fmt.write_str(msg),
             Self::Utf8Error(ref err) => write!(fmt, "string found to be invalid utf8: {err}"),
             Self::DepthLimitExceeded => fmt.write_str("depth limit exceeded"),
+            Self::LengthLimitExceeded(len) => write!(fmt, "length limit exceeded by declared length {len}"),
+            Self::ReservedMarker => fmt.write_str("reserved msgpack marker 0xc1 found"),
+            Self::ExtForbidden => fmt.write_str("msgpack ext types are forbidden"),
         }
     }
 }
@@ -171,6 +186,11 @@ impl From<TryFromIntError> for Error {
 
 /// A Deserializer that reads bytes from a buffer.
 ///
+/// Structs are accepted both as arrays, matching fields by position, and as maps, matching
+/// fields by name, regardless of the configuration. Data written by either
+/// `Serializer::with_struct_tuple` or `Serializer::with_struct_map` can therefore be read back
+/// without knowing which one the producer used.
+///
 /// # Note
 ///
 /// All instances of `ErrorKind::Interrupted` are handled by this function and the underlying
@@ -180,27 +200,81 @@ pub struct Deserializer<R, C = DefaultConfig> {
     rd: R,
     _config: PhantomData<C>,
     is_human_readable: bool,
+    pad_trailing_nils: bool,
+    ignore_extra_array_elements: bool,
+    nil_as_default: bool,
+    forbid_ext: bool,
+    key_order: KeyOrder,
+    unknown_fields: UnknownFields,
     marker: Option<Marker>,
     depth: u16,
+    max_string_len: u32,
+    max_bin_len: u32,
+}
+
+/// The state of `Deserializer::with_key_order_recorded`.
+#[derive(Debug, Default)]
+struct KeyOrder {
+    enabled: bool,
+    /// Whether the first map has been found, and its keys are or were recorded.
+    started: bool,
+    /// Whether the next string read is a key to record.
+    pending: bool,
+    keys: Vec<String>,
+}
+
+/// The function given to `Deserializer::on_unknown_field`.
+type UnknownFieldHook = Box<dyn FnMut(&str) + Send + Sync>;
+
+/// The state of `Deserializer::on_unknown_field`.
+#[derive(Default)]
+struct UnknownFields {
+    hook: Option<UnknownFieldHook>,
+    /// The fields of the struct whose map is read next.
+    pending_struct: Option<&'static [&'static str]>,
+    /// The fields of the struct whose key is read next.
+    pending_key: Option<&'static [&'static str]>,
+}
+
+impl fmt::Debug for UnknownFields {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("UnknownFields")
+            .field("hook", &self.hook.as_ref().map(|_| ".."))
+            .field("pending_struct", &self.pending_struct)
+            .field("pending_key", &self.pending_key)
+            .finish()
+    }
 }
 
 impl<R: Read, C> Deserializer<R, C> {
     #[inline]
-    fn take_or_read_marker(&mut self) -> Result<Marker, MarkerReadError> {
-        self.marker
-            .take()
-            .map_or_else(|| rmp::decode::read_marker(&mut self.rd), Ok)
+    fn take_or_read_marker(&mut self) -> Result<Marker, Error> {
+        match self.marker.take() {
+            Some(m) => Ok(m),
+            None => read_marker(&mut self.rd),
+        }
     }
 
     #[inline]
-    fn peek_or_read_marker(&mut self) -> Result<Marker, MarkerReadError> {
+    fn peek_or_read_marker(&mut self) -> Result<Marker, Error> {
         if let Some(m) = self.marker {
             Ok(m)
         } else {
-            let m = rmp::decode::read_marker(&mut self.rd)?;
+            let m = read_marker(&mut self.rd)?;
             Ok(self.marker.insert(m).to_owned())
         }
     }
+
+    /// Consumes the next marker and returns `true` if it is `nil` and should be deserialized as
+    /// the default value of the requested type.
+    #[inline]
+    fn take_nil_as_default(&mut self) -> Result<bool, Error> {
+        if !self.nil_as_default || self.peek_or_read_marker()? != Marker::Null {
+            return Ok(false);
+        }
+        self.marker = None;
+        Ok(true)
+    }
 }
 
 impl<R: Read> Deserializer<ReadReader<R>, DefaultConfig> {
@@ -211,9 +285,17 @@ impl<R: Read> Deserializer<ReadReader<R>, DefaultConfig> {
             rd: ReadReader::new(rd),
             _config: PhantomData,
             is_human_readable: DefaultConfig.is_human_readable(),
+            pad_trailing_nils: false,
+            ignore_extra_array_elements: false,
+            nil_as_default: false,
+            forbid_ext: false,
+            key_order: KeyOrder::default(),
+            unknown_fields: UnknownFields::default(),
             // Cached marker in case of deserializing optional values.
             marker: None,
             depth: 1024,
+            max_string_len: u32::MAX,
+            max_bin_len: u32::MAX,
         }
     }
 }
@@ -238,6 +320,70 @@ impl<R: Read, C> Deserializer<ReadReader<R>, C> {
     }
 }
 
+impl<R: BufRead> Deserializer<BufReadReader<R>, DefaultConfig> {
+    /// Constructs a new `Deserializer` by consuming the given buffered reader.
+    ///
+    /// Unlike with `new`, strings and binaries which are entirely in the buffer of the reader are
+    /// not copied, and neither are markers and scalars read through `Read`. This is faster when
+    /// decoding from a `BufReader` around a file or a pipe, or from any other `BufRead`.
+    ///
+    /// ```rust
+    /// use std::io::BufReader;
+    ///
+    /// // ["le", "message"]
+    /// let buf = [0x92, 0xa2, b'l', b'e', 0xa7, b'm', b'e', b's', b's', b'a', b'g', b'e'];
+    /// let rd = BufReader::new(&buf[..]);
+    /// let mut de = rmp_serde::Deserializer::from_buf_read(rd);
+    /// let val: Vec<String> = serde::Deserialize::deserialize(&mut de).unwrap();
+    ///
+    /// assert_eq!(vec!["le", "message"], val);
+    /// ```
+    #[inline]
+    pub fn from_buf_read(rd: R) -> Self {
+        Self {
+            rd: BufReadReader::new(rd),
+            _config: PhantomData,
+            is_human_readable: DefaultConfig.is_human_readable(),
+            pad_trailing_nils: false,
+            ignore_extra_array_elements: false,
+            nil_as_default: false,
+            forbid_ext: false,
+            key_order: KeyOrder::default(),
+            unknown_fields: UnknownFields::default(),
+            marker: None,
+            depth: 1024,
+            max_string_len: u32::MAX,
+            max_bin_len: u32::MAX,
+        }
+    }
+}
+
+impl<R: BufRead, C> Deserializer<BufReadReader<R>, C> {
+    /// Gets a reference to the underlying reader in this decoder.
+    ///
+    /// The last string or binary read may still be at the start of its buffer, see `get_mut`.
+    #[inline(always)]
+    pub fn get_ref(&self) -> &R {
+        &self.rd.rd
+    }
+
+    /// Gets a mutable reference to the underlying reader in this decoder, positioned right
+    /// after the last value read.
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut R {
+        self.rd.consume_pending();
+        &mut self.rd.rd
+    }
+
+    /// Consumes this deserializer returning the underlying reader, positioned right after the
+    /// last value read.
+    #[inline]
+    pub fn into_inner(mut self) -> R {
+        self.rd.consume_pending();
+        self.rd.rd
+    }
+}
+
 impl<R: Read, C: SerializerConfig> Deserializer<R, C> {
     /// Consumes this deserializer and returns a new one, which will deserialize types with
     /// human-readable representations (`Deserializer::is_human_readable` will return `true`).
@@ -246,13 +392,21 @@ impl<R: Read, C: SerializerConfig> Deserializer<R, C> {
     /// versions of `rmp-serde`.
     #[inline]
     pub fn with_human_readable(self) -> Deserializer<R, HumanReadableConfig<C>> {
-        let Self { rd, _config: _, is_human_readable: _, marker, depth } = self;
+        let Self { rd, _config: _, is_human_readable: _, pad_trailing_nils, ignore_extra_array_elements, nil_as_default, forbid_ext, key_order, unknown_fields, marker, depth, max_string_len, max_bin_len } = self;
         Deserializer {
             rd,
             is_human_readable: true,
+            pad_trailing_nils,
+            ignore_extra_array_elements,
+            nil_as_default,
+            forbid_ext,
+            key_order,
+            unknown_fields,
             _config: PhantomData,
             marker,
             depth,
+            max_string_len,
+            max_bin_len,
         }
     }
 
@@ -263,15 +417,188 @@ impl<R: Read, C: SerializerConfig> Deserializer<R, C> {
     /// representation.
     #[inline]
     pub fn with_binary(self) -> Deserializer<R, BinaryConfig<C>> {
-        let Self { rd, _config: _, is_human_readable: _, marker, depth } = self;
+        let Self { rd, _config: _, is_human_readable: _, pad_trailing_nils, ignore_extra_array_elements, nil_as_default, forbid_ext, key_order, unknown_fields, marker, depth, max_string_len, max_bin_len } = self;
         Deserializer {
             rd,
             is_human_readable: false,
+            pad_trailing_nils,
+            ignore_extra_array_elements,
+            nil_as_default,
+            forbid_ext,
+            key_order,
+            unknown_fields,
             _config: PhantomData,
             marker,
             depth,
+            max_string_len,
+            max_bin_len,
         }
     }
+
+    /// Consumes this deserializer and returns a new one, which will accept structs and tuple
+    /// structs encoded as arrays with fewer elements than they have fields, deserializing each
+    /// missing trailing field as if it were `nil`.
+    ///
+    /// This reads the output of `Serializer::with_trailing_nils_elided`. Missing `Option` fields
+    /// become `None` and missing unit fields become `()`, while any other missing field still
+    /// fails with a type error, unless `with_nil_as_default` is also used: then every missing
+    /// field becomes its default value, without any annotation on the struct. Arrays that are
+    /// longer than a struct are rejected as usual, unless `with_extra_elements_ignored` is also
+    /// used, and structs encoded as maps, tuples and newtype structs are not affected.
+    ///
+    /// Without this option, trailing fields marked with `#[serde(default)]`, or all the fields of
+    /// a struct marked with it, are filled in with their default values by Serde itself when the
+    /// array ends early. With it, such fields are passed `nil` instead, which fails for the types
+    /// that can not be read from `nil`.
+    #[inline]
+    #[must_use]
+    pub fn with_trailing_nils_padded(mut self) -> Self {
+        self.pad_trailing_nils = true;
+        self
+    }
+
+    /// Consumes this deserializer and returns a new one, which will accept structs encoded as
+    /// arrays with more elements than the struct has fields, skipping the extra trailing elements.
+    ///
+    /// This reads the output of a producer which appended fields to a struct that is encoded as
+    /// an array, without updating the struct on this side. The skipped elements are still read
+    /// and must be valid MessagePack. Tuples, tuple structs and sequences keep rejecting arrays
+    /// of unexpected lengths, and structs encoded as maps are not affected, as Serde already
+    /// ignores their unknown fields unless `#[serde(deny_unknown_fields)]` is used.
+    #[inline]
+    #[must_use]
+    pub fn with_extra_elements_ignored(mut self) -> Self {
+        self.ignore_extra_array_elements = true;
+        self
+    }
+
+    /// Consumes this deserializer and returns a new one, which will deserialize `nil` into a
+    /// value that is not an `Option` as its default value.
+    ///
+    /// This reads the output of producers which write `nil` for absent values, into fields that
+    /// are not `Option`s. Booleans become `false`, numbers become zero, chars become `'\0'`,
+    /// strings and binaries become empty, and sequences and maps become empty, as their `Default`
+    /// implementations return. Structs are deserialized from an empty map and tuple structs from an
+    /// empty array, so `nil` becomes the default value of a struct marked with `#[serde(default)]`,
+    /// or of a tuple struct whose fields all are, and is rejected otherwise.
+    ///
+    /// Without this option, `nil` can only be deserialized into an `Option`, a unit or a unit
+    /// struct. Options are not affected, and `nil` still becomes `None`. Values deserialized
+    /// through `deserialize_any`, such as untagged enums or `rmpv::Value`, also keep seeing `nil`.
+    #[inline]
+    #[must_use]
+    pub fn with_nil_as_default(mut self) -> Self {
+        self.nil_as_default = true;
+        self
+    }
+
+    /// Consumes this deserializer and returns a new one, which will fail with
+    /// `Error::ExtForbidden` when an ext is deserialized, for contexts restricted to the other
+    /// MessagePack types.
+    ///
+    /// This covers every ext deserialized through this crate, such as into `rmpv::Value::Ext` or
+    /// with `chrono_timestamp`, whatever type was expected. Values which are skipped rather than
+    /// deserialized, like the extra elements discarded with `with_extra_elements_ignored`, are not
+    /// inspected.
+    #[inline]
+    #[must_use]
+    pub fn with_ext_forbidden(mut self) -> Self {
+        self.forbid_ext = true;
+        self
+    }
+
+    /// Consumes this deserializer and returns a new one, which will record the string keys of
+    /// the first map it reads, in the order in which they appear, see `key_order`.
+    ///
+    /// Structs and maps such as `HashMap` lose the order of the keys they are deserialized from.
+    /// This lets a proxy deserialize a struct and still re-emit its fields in their original
+    /// order, for example by serializing it with `Serializer::with_struct_map` into an
+    /// `rmpv::Value` and sorting its entries by the position of their key in the recorded order.
+    /// Only the keys of the first map are recorded, which is the outermost one when the value is a
+    /// map, as that is usually the struct being forwarded. Keys which are not valid UTF-8 strings
+    /// are left out.
+    ///
+    /// An `rmpv::Value` keeps the order of its map entries on its own, so this is not needed to
+    /// decode one.
+    ///
+    /// ```rust
+    /// use serde::Deserialize;
+    ///
+    /// #[derive(Deserialize)]
+    /// struct Request {
+    ///     id: u32,
+    ///     method: String,
+    /// }
+    ///
+    /// // {"method": "get", "id": 1}
+    /// let buf = [0x82, 0xa6, b'm', b'e', b't', b'h', b'o', b'd', 0xa3, b'g', b'e', b't', 0xa2, b'i', b'd', 0x01];
+    /// let mut de = rmp_serde::Deserializer::new(&buf[..]).with_key_order_recorded();
+    /// let req = Request::deserialize(&mut de).unwrap();
+    ///
+    /// assert_eq!(1, req.id);
+    /// assert_eq!(["method", "id"], de.key_order());
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn with_key_order_recorded(mut self) -> Self {
+        self.key_order.enabled = true;
+        self
+    }
+
+    /// Returns the string keys of the first map read so far, in the order in which they appear,
+    /// when enabled with `with_key_order_recorded`.
+    #[inline]
+    #[must_use]
+    pub fn key_order(&self) -> &[String] {
+        &self.key_order.keys
+    }
+
+    /// Returns the keys recorded by `with_key_order_recorded`, like `key_order`, and starts
+    /// recording again with the next map read, such as the one of the next value in a stream.
+    #[inline]
+    pub fn take_key_order(&mut self) -> Vec<String> {
+        self.key_order.started = false;
+        std::mem::take(&mut self.key_order.keys)
+    }
+
+    /// Consumes this deserializer and returns a new one, which calls the given function with the
+    /// name of every field of a struct encoded as a map which is not one of the fields of the
+    /// struct, right before it is skipped.
+    ///
+    /// This lets a service log or count the fields it does not know about, to notice that its
+    /// peers have moved to a newer schema, without failing like `#[serde(deny_unknown_fields)]`
+    /// does. Whether the field is skipped or rejected is left to the struct. The names of the
+    /// struct are those it reports to Serde, aliases included, so the function is not called for
+    /// structs with flattened fields, which are deserialized as maps, nor for keys which are not
+    /// valid UTF-8 strings.
+    ///
+    /// ```rust
+    /// use std::sync::{Arc, Mutex};
+    ///
+    /// use serde::Deserialize;
+    ///
+    /// #[derive(Deserialize)]
+    /// struct Request {
+    ///     id: u32,
+    /// }
+    ///
+    /// // {"id": 1, "trace": true}
+    /// let buf = [0x82, 0xa2, b'i', b'd', 0x01, 0xa5, b't', b'r', b'a', b'c', b'e', 0xc3];
+    /// let unknown = Arc::new(Mutex::new(Vec::new()));
+    /// let hook = Arc::clone(&unknown);
+    /// let mut de = rmp_serde::Deserializer::new(&buf[..])
+    ///     .on_unknown_field(move |name| hook.lock().unwrap().push(name.to_owned()));
+    /// let req = Request::deserialize(&mut de).unwrap();
+    ///
+    /// assert_eq!(1, req.id);
+    /// assert_eq!(vec!["trace"], *unknown.lock().unwrap());
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn on_unknown_field(mut self, hook: impl FnMut(&str) + Send + Sync + 'static) -> Self {
+        self.unknown_fields.hook = Some(Box::new(hook));
+        self
+    }
 }
 
 impl<R: AsRef<[u8]>> Deserializer<ReadReader<Cursor<R>>> {
@@ -292,9 +619,17 @@ where
         Deserializer {
             rd: ReadRefReader::new(rd),
             is_human_readable: DefaultConfig.is_human_readable(),
+            pad_trailing_nils: false,
+            ignore_extra_array_elements: false,
+            nil_as_default: false,
+            forbid_ext: false,
+            key_order: KeyOrder::default(),
+            unknown_fields: UnknownFields::default(),
             _config: PhantomData,
             marker: None,
             depth: 1024,
+            max_string_len: u32::MAX,
+            max_bin_len: u32::MAX,
         }
     }
 
@@ -308,10 +643,33 @@ where
 
 impl<'de, R: ReadSlice<'de>, C: SerializerConfig> Deserializer<R, C> {
     /// Changes the maximum nesting depth that is allowed
+    ///
+    /// Each array, map, ext and enum variant with data nests one level deeper. Exceeding the
+    /// limit fails with `Error::DepthLimitExceeded`, so that hostile input can not overflow the
+    /// stack. The default is 1024, which needs a few megabytes of stack in unoptimized builds;
+    /// threads with small stacks should set a lower limit.
     #[inline(always)]
     pub fn set_max_depth(&mut self, depth: usize) {
         self.depth = depth.min(u16::MAX as _) as u16;
     }
+
+    /// Changes the maximum length of strings, in bytes, that is allowed.
+    ///
+    /// A string declaring a longer length fails with `Error::LengthLimitExceeded` before any of
+    /// its data is read or allocated. There is no limit by default.
+    #[inline(always)]
+    pub fn set_max_string_len(&mut self, len: usize) {
+        self.max_string_len = len.try_into().unwrap_or(u32::MAX);
+    }
+
+    /// Changes the maximum length of binaries, in bytes, that is allowed.
+    ///
+    /// A binary declaring a longer length fails with `Error::LengthLimitExceeded` before any of
+    /// its data is read or allocated. There is no limit by default.
+    #[inline(always)]
+    pub fn set_max_bin_len(&mut self, len: usize) {
+        self.max_bin_len = len.try_into().unwrap_or(u32::MAX);
+    }
 }
 
 #[inline(never)]
@@ -350,10 +708,10 @@ fn read_128_buf<'de, R: ReadSlice<'de>>(rd: &mut R, len: u8) -> Result<i128, Err
     Ok(i128::from_be_bytes(buf.try_into().map_err(|_| Error::LengthMismatch(16))?))
 }
 
-fn read_str_data<'de, V, R>(rd: &mut R, len: u32, visitor: V) -> Result<V::Value, Error>
-    where V: Visitor<'de>, R: ReadSlice<'de>
+fn visit_str_data<'de, V>(data: Reference<'de, '_, [u8]>, visitor: V) -> Result<V::Value, Error>
+    where V: Visitor<'de>
 {
-    match read_bin_data(rd, len)? {
+    match data {
         Reference::Borrowed(buf) => {
             match str::from_utf8(buf) {
                 Ok(s) => visitor.visit_borrowed_str(s),
@@ -385,6 +743,15 @@ fn read_bin_data<'a, 'de, R: ReadSlice<'de>>(rd: &'a mut R, len: u32) -> Result<
     rd.read_slice(len as usize).map_err(Error::InvalidDataRead)
 }
 
+/// Reads a marker, rejecting the reserved one so that it is never mistaken for a type mismatch.
+#[inline]
+fn read_marker<R: Read>(rd: &mut R) -> Result<Marker, Error> {
+    match rmp::decode::read_marker(rd)? {
+        Marker::Reserved => Err(Error::ReservedMarker),
+        marker => Ok(marker),
+    }
+}
+
 fn read_u8<R: Read>(rd: &mut R) -> Result<u8, Error> {
     byteorder::ReadBytesExt::read_u8(rd).map_err(Error::InvalidDataRead)
 }
@@ -536,7 +903,7 @@ fn any_num<'de, R: ReadSlice<'de>, V: Visitor<'de>>(rd: &mut R, visitor: V, mark
 
 fn consume_unexpected_values<'de, R: ReadSlice<'de>>(rd: &mut R, count: usize) -> Result<(), Error>{
     for _ in 0..count {
-        let marker = rmp::decode::read_marker(rd)?;
+        let marker = read_marker(rd)?;
         consume_unexpected_value(rd, marker)?;
     }
     Ok(())
@@ -599,8 +966,55 @@ fn consume_unexpected_value<'de, R: ReadSlice<'de>>(rd: &mut R, marker: Marker)
 }
 
 impl<'de, R: ReadSlice<'de>, C: SerializerConfig> Deserializer<R, C> {
+    /// Reads an array with the given marker, padding it with `nil` elements if it has less than
+    /// `pad_to` elements.
+    fn read_array<V: Visitor<'de>>(&mut self, visitor: V, marker: Marker, pad_to: u32) -> Result<V::Value, Error> {
+        let len = self.read_array_len(marker)?;
+        self.visit_array(visitor, len, pad_to, false)
+    }
+
+    /// Reads an array with the given marker as a tuple, which must have exactly `expected`
+    /// elements.
+    fn read_tuple<V: Visitor<'de>>(&mut self, visitor: V, marker: Marker, expected: usize) -> Result<V::Value, Error> {
+        let len = self.read_array_len(marker)?;
+        if len as usize != expected {
+            return Err(Error::LengthMismatch(expected.try_into().unwrap_or(u32::MAX)));
+        }
+        self.visit_array(visitor, len, 0, false)
+    }
+
+    fn read_array_len(&mut self, marker: Marker) -> Result<u32, Error> {
+        Ok(match marker {
+            Marker::FixArray(len) => len.into(),
+            Marker::Array16 => read_u16(&mut self.rd)?.into(),
+            Marker::Array32 => read_u32(&mut self.rd)?,
+            _ => return Err(Error::TypeMismatch(Marker::Reserved)),
+        })
+    }
+
+    /// Visits an array of `len` elements, padding it with `nil` elements if it has less than
+    /// `pad_to` elements, and skipping the elements left by the visitor if `skip_extra` is set.
+    fn visit_array<V: Visitor<'de>>(&mut self, visitor: V, len: u32, pad_to: u32, skip_extra: bool) -> Result<V::Value, Error> {
+        depth_count!(self.depth, {
+            let mut seq = SeqAccess::new(self, len);
+            seq.pad = pad_to.saturating_sub(len);
+            let res = visitor.visit_seq(&mut seq)?;
+            match seq.left {
+                0 => Ok(res),
+                excess if skip_extra => {
+                    consume_unexpected_values(&mut self.rd, excess as usize)?;
+                    Ok(res)
+                }
+                excess => Err(Error::LengthMismatch(len - excess)),
+            }
+        })
+    }
+
     fn any_inner<V: Visitor<'de>>(&mut self, visitor: V, allow_bytes: bool) -> Result<V::Value, Error> {
         let marker = self.take_or_read_marker()?;
+        let is_key = std::mem::take(&mut self.key_order.pending);
+        let struct_fields = self.unknown_fields.pending_struct.take();
+        let key_fields = self.unknown_fields.pending_key.take();
         match marker {
             Marker::Null |
             Marker::True |
@@ -625,27 +1039,26 @@ impl<'de, R: ReadSlice<'de>, C: SerializerConfig> Deserializer<R, C> {
                     Marker::Str32 => read_u32(&mut self.rd),
                     _ => return Err(Error::TypeMismatch(Marker::Reserved)),
                 }?;
-                read_str_data(&mut self.rd, len, visitor)
+                if len > self.max_string_len {
+                    return Err(Error::LengthLimitExceeded(len));
+                }
+                let data = read_bin_data(&mut self.rd, len)?;
+                if is_key {
+                    let (Reference::Borrowed(buf) | Reference::Copied(buf)) = data;
+                    self.key_order.keys.extend(str::from_utf8(buf).ok().map(str::to_owned));
+                }
+                if let (Some(fields), Some(hook)) = (key_fields, &mut self.unknown_fields.hook) {
+                    let (Reference::Borrowed(buf) | Reference::Copied(buf)) = data;
+                    match str::from_utf8(buf) {
+                        Ok(name) if !fields.contains(&name) => hook(name),
+                        _ => {}
+                    }
+                }
+                visit_str_data(data, visitor)
             }
             Marker::FixArray(_) |
             Marker::Array16 |
-            Marker::Array32 => {
-                let len = match marker {
-                    Marker::FixArray(len) => len.into(),
-                    Marker::Array16 => read_u16(&mut self.rd)?.into(),
-                    Marker::Array32 => read_u32(&mut self.rd)?,
-                    _ => return Err(Error::TypeMismatch(Marker::Reserved)),
-                };
-
-                depth_count!(self.depth, {
-                    let mut seq = SeqAccess::new(self, len);
-                    let res = visitor.visit_seq(&mut seq)?;
-                    match seq.left {
-                        0 => Ok(res),
-                        excess => Err(Error::LengthMismatch(len - excess)),
-                    }
-                })
-            }
+            Marker::Array32 => self.read_array(visitor, marker, 0),
             Marker::FixMap(_) |
             Marker::Map16 |
             Marker::Map32 => {
@@ -656,8 +1069,12 @@ impl<'de, R: ReadSlice<'de>, C: SerializerConfig> Deserializer<R, C> {
                     _ => return Err(Error::TypeMismatch(Marker::Reserved)),
                 };
 
+                let record_keys = self.key_order.enabled && !self.key_order.started;
+                self.key_order.started |= record_keys;
                 depth_count!(self.depth, {
                     let mut seq = MapAccess::new(self, len);
+                    seq.record_keys = record_keys;
+                    seq.fields = struct_fields;
                     let res = visitor.visit_map(&mut seq)?;
                     match seq.left {
                         0 => Ok(res),
@@ -672,6 +1089,9 @@ impl<'de, R: ReadSlice<'de>, C: SerializerConfig> Deserializer<R, C> {
                     Marker::Bin32 => read_u32(&mut self.rd),
                     _ => return Err(Error::TypeMismatch(Marker::Reserved)),
                 }?;
+                if len > self.max_bin_len {
+                    return Err(Error::LengthLimitExceeded(len));
+                }
                 match read_bin_data(&mut self.rd, len)? {
                     Reference::Borrowed(buf) if allow_bytes => visitor.visit_borrowed_bytes(buf),
                     Reference::Copied(buf) if allow_bytes => visitor.visit_bytes(buf),
@@ -688,10 +1108,13 @@ impl<'de, R: ReadSlice<'de>, C: SerializerConfig> Deserializer<R, C> {
             Marker::Ext8 |
             Marker::Ext16 |
             Marker::Ext32 => {
+                if self.forbid_ext {
+                    return Err(Error::ExtForbidden);
+                }
                 let len = ext_len(&mut self.rd, marker)?;
                 depth_count!(self.depth, visitor.visit_newtype_struct(ExtDeserializer::new(self, len)))
             }
-            Marker::Reserved => Err(Error::TypeMismatch(Marker::Reserved)),
+            Marker::Reserved => Err(Error::ReservedMarker),
         }
     }
 }
@@ -749,7 +1172,7 @@ impl<'de, R: ReadSlice<'de>, C: SerializerConfig> serde::Deserializer<'de> for &
                 // or as just the variant
                 1 => {
                     self.marker = None;
-                    visitor.visit_enum(VariantAccess::new(self))
+                    depth_count!(self.depth, visitor.visit_enum(VariantAccess::new(self)))
                 }
                 n => Err(Error::LengthMismatch(n)),
             },
@@ -765,6 +1188,9 @@ impl<'de, R: ReadSlice<'de>, C: SerializerConfig> serde::Deserializer<'de> for &
             let marker = self.take_or_read_marker()?;
 
             let len = ext_len(&mut self.rd, marker)?;
+            if self.forbid_ext {
+                return Err(Error::ExtForbidden);
+            }
             let ext_de = ExtDeserializer::new(self, len);
             return visitor.visit_newtype_struct(ext_de);
         }
@@ -792,6 +1218,9 @@ impl<'de, R: ReadSlice<'de>, C: SerializerConfig> serde::Deserializer<'de> for &
     where
         V: Visitor<'de>,
     {
+        if self.take_nil_as_default()? {
+            return visitor.visit_i128(0);
+        }
         visitor.visit_i128(read_i128_marker(self.take_or_read_marker()?, &mut self.rd)?)
     }
 
@@ -800,86 +1229,196 @@ impl<'de, R: ReadSlice<'de>, C: SerializerConfig> serde::Deserializer<'de> for &
     where
         V: Visitor<'de>,
     {
+        if self.take_nil_as_default()? {
+            return visitor.visit_u128(0);
+        }
         visitor.visit_u128(read_i128_marker(self.take_or_read_marker()?, &mut self.rd)? as u128)
     }
 
     #[inline]
     fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value, Self::Error> where V: Visitor<'de> {
+        if self.take_nil_as_default()? {
+            return visitor.visit_seq(SeqDeserializer::new(iter::empty::<()>()));
+        }
         self.any_inner(visitor, false)
     }
 
     #[inline]
-    fn deserialize_tuple<V>(self, _len: usize, visitor: V) -> Result<V::Value, Self::Error> where V: Visitor<'de> {
+    fn deserialize_tuple<V>(self, len: usize, visitor: V) -> Result<V::Value, Self::Error> where V: Visitor<'de> {
+        let marker = self.take_or_read_marker()?;
+        if let Marker::FixArray(_) | Marker::Array16 | Marker::Array32 = marker {
+            return self.read_tuple(visitor, marker, len);
+        }
+        self.marker = Some(marker);
         self.any_inner(visitor, false)
     }
 
     #[inline]
-    fn deserialize_struct<V>(self, _: &'static str, _: &'static [&'static str], visitor: V) -> Result<V::Value, Self::Error> where V: Visitor<'de> {
+    fn deserialize_struct<V>(self, _: &'static str, fields: &'static [&'static str], visitor: V) -> Result<V::Value, Self::Error> where V: Visitor<'de> {
+        if self.take_nil_as_default()? {
+            return visitor.visit_map(MapDeserializer::new(iter::empty::<((), ())>()));
+        }
+        if self.pad_trailing_nils || self.ignore_extra_array_elements {
+            let marker = self.take_or_read_marker()?;
+            if let Marker::FixArray(_) | Marker::Array16 | Marker::Array32 = marker {
+                let pad_to = if self.pad_trailing_nils {
+                    fields.len().try_into().unwrap_or(u32::MAX)
+                } else {
+                    0
+                };
+                let len = self.read_array_len(marker)?;
+                return self.visit_array(visitor, len, pad_to, self.ignore_extra_array_elements);
+            }
+            self.marker = Some(marker);
+        }
+        if self.unknown_fields.hook.is_some() {
+            self.unknown_fields.pending_struct = Some(fields);
+        }
         self.any_inner(visitor, false)
     }
 
     #[inline]
-    fn deserialize_tuple_struct<V>(self, _: &'static str, _: usize, visitor: V) -> Result<V::Value, Self::Error> where V: Visitor<'de> {
+    fn deserialize_tuple_struct<V>(self, _: &'static str, len: usize, visitor: V) -> Result<V::Value, Self::Error> where V: Visitor<'de> {
+        if self.take_nil_as_default()? {
+            return visitor.visit_seq(SeqDeserializer::new(iter::empty::<()>()));
+        }
+        if self.pad_trailing_nils {
+            let marker = self.take_or_read_marker()?;
+            if let Marker::FixArray(_) | Marker::Array16 | Marker::Array32 = marker {
+                let array_len = self.read_array_len(marker)?;
+                return self.visit_array(visitor, array_len, len.try_into().unwrap_or(u32::MAX), false);
+            }
+            self.marker = Some(marker);
+        }
         self.any_inner(visitor, false)
     }
 
     forward_to_deserialize_any! {
-        bytes byte_buf unit
-        map identifier str string char
-        ignored_any
+        unit identifier ignored_any
+    }
+
+    fn deserialize_char<V>(self, visitor: V) -> Result<V::Value, Self::Error> where V: Visitor<'de> {
+        if self.take_nil_as_default()? {
+            return visitor.visit_char('\0');
+        }
+        self.deserialize_any(visitor)
+    }
+
+    fn deserialize_str<V>(self, visitor: V) -> Result<V::Value, Self::Error> where V: Visitor<'de> {
+        if self.take_nil_as_default()? {
+            return visitor.visit_borrowed_str("");
+        }
+        self.deserialize_any(visitor)
+    }
+
+    #[inline]
+    fn deserialize_string<V>(self, visitor: V) -> Result<V::Value, Self::Error> where V: Visitor<'de> {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_bytes<V>(self, visitor: V) -> Result<V::Value, Self::Error> where V: Visitor<'de> {
+        if self.take_nil_as_default()? {
+            return visitor.visit_borrowed_bytes(&[]);
+        }
+        self.deserialize_any(visitor)
+    }
+
+    #[inline]
+    fn deserialize_byte_buf<V>(self, visitor: V) -> Result<V::Value, Self::Error> where V: Visitor<'de> {
+        self.deserialize_bytes(visitor)
+    }
+
+    fn deserialize_map<V>(self, visitor: V) -> Result<V::Value, Self::Error> where V: Visitor<'de> {
+        if self.take_nil_as_default()? {
+            return visitor.visit_map(MapDeserializer::new(iter::empty::<((), ())>()));
+        }
+        self.deserialize_any(visitor)
     }
 
     fn deserialize_bool<V>(self, visitor: V) -> Result<V::Value, Self::Error> where V: Visitor<'de> {
+        if self.take_nil_as_default()? {
+            return visitor.visit_bool(false);
+        }
         let marker = self.take_or_read_marker()?;
         any_num(&mut self.rd, visitor, marker)
     }
 
     fn deserialize_u8<V>(self, visitor: V) -> Result<V::Value, Self::Error> where V: Visitor<'de> {
+        if self.take_nil_as_default()? {
+            return visitor.visit_u8(0);
+        }
         let marker = self.take_or_read_marker()?;
         any_num(&mut self.rd, visitor, marker)
     }
 
     fn deserialize_i8<V>(self, visitor: V) -> Result<V::Value, Self::Error> where V: Visitor<'de> {
+        if self.take_nil_as_default()? {
+            return visitor.visit_i8(0);
+        }
         let marker = self.take_or_read_marker()?;
         any_num(&mut self.rd, visitor, marker)
     }
 
     fn deserialize_i16<V>(self, visitor: V) -> Result<V::Value, Self::Error> where V: Visitor<'de> {
+        if self.take_nil_as_default()? {
+            return visitor.visit_i16(0);
+        }
         let marker = self.take_or_read_marker()?;
         any_num(&mut self.rd, visitor, marker)
     }
 
     fn deserialize_u16<V>(self, visitor: V) -> Result<V::Value, Self::Error> where V: Visitor<'de> {
+        if self.take_nil_as_default()? {
+            return visitor.visit_u16(0);
+        }
         let marker = self.take_or_read_marker()?;
         any_num(&mut self.rd, visitor, marker)
     }
 
     fn deserialize_i32<V>(self, visitor: V) -> Result<V::Value, Self::Error> where V: Visitor<'de> {
+        if self.take_nil_as_default()? {
+            return visitor.visit_i32(0);
+        }
         let marker = self.take_or_read_marker()?;
         any_num(&mut self.rd, visitor, marker)
     }
 
     fn deserialize_u32<V>(self, visitor: V) -> Result<V::Value, Self::Error> where V: Visitor<'de> {
+        if self.take_nil_as_default()? {
+            return visitor.visit_u32(0);
+        }
         let marker = self.take_or_read_marker()?;
         any_num(&mut self.rd, visitor, marker)
     }
 
     fn deserialize_i64<V>(self, visitor: V) -> Result<V::Value, Self::Error> where V: Visitor<'de> {
+        if self.take_nil_as_default()? {
+            return visitor.visit_i64(0);
+        }
         let marker = self.take_or_read_marker()?;
         any_num(&mut self.rd, visitor, marker)
     }
 
     fn deserialize_u64<V>(self, visitor: V) -> Result<V::Value, Self::Error> where V: Visitor<'de> {
+        if self.take_nil_as_default()? {
+            return visitor.visit_u64(0);
+        }
         let marker = self.take_or_read_marker()?;
         any_num(&mut self.rd, visitor, marker)
     }
 
     fn deserialize_f32<V>(self, visitor: V) -> Result<V::Value, Self::Error> where V: Visitor<'de> {
+        if self.take_nil_as_default()? {
+            return visitor.visit_f32(0.0);
+        }
         let marker = self.take_or_read_marker()?;
         any_num(&mut self.rd, visitor, marker)
     }
 
     fn deserialize_f64<V>(self, visitor: V) -> Result<V::Value, Self::Error> where V: Visitor<'de> {
+        if self.take_nil_as_default()? {
+            return visitor.visit_f64(0.0);
+        }
         let marker = self.take_or_read_marker()?;
         any_num(&mut self.rd, visitor, marker)
     }
@@ -888,12 +1427,14 @@ impl<'de, R: ReadSlice<'de>, C: SerializerConfig> serde::Deserializer<'de> for &
 struct SeqAccess<'a, R, C> {
     de: &'a mut Deserializer<R, C>,
     left: u32,
+    /// Number of `nil` elements to yield after the encoded ones.
+    pad: u32,
 }
 
 impl<'a, R: 'a, C> SeqAccess<'a, R, C> {
     #[inline]
     const fn new(de: &'a mut Deserializer<R, C>, len: u32) -> Self {
-        SeqAccess { de, left: len }
+        SeqAccess { de, left: len, pad: 0 }
     }
 }
 
@@ -907,6 +1448,12 @@ impl<'de, 'a, R: ReadSlice<'de> + 'a, C: SerializerConfig> de::SeqAccess<'de> fo
         if self.left > 0 {
             self.left -= 1;
             Ok(Some(seed.deserialize(&mut *self.de)?))
+        } else if self.pad > 0 {
+            self.pad -= 1;
+            // Going through the deserializer rather than a `UnitDeserializer` lets the padding
+            // honor the other options, such as `nil_as_default`.
+            self.de.marker = Some(Marker::Null);
+            Ok(Some(seed.deserialize(&mut *self.de)?))
         } else {
             Ok(None)
         }
@@ -914,19 +1461,23 @@ impl<'de, 'a, R: ReadSlice<'de> + 'a, C: SerializerConfig> de::SeqAccess<'de> fo
 
     #[inline(always)]
     fn size_hint(&self) -> Option<usize> {
-        self.left.try_into().ok()
+        (self.left + self.pad).try_into().ok()
     }
 }
 
 struct MapAccess<'a, R, C> {
     de: &'a mut Deserializer<R, C>,
     left: u32,
+    /// Whether the string keys are recorded, see `Deserializer::with_key_order_recorded`.
+    record_keys: bool,
+    /// The fields of the struct being read, see `Deserializer::on_unknown_field`.
+    fields: Option<&'static [&'static str]>,
 }
 
 impl<'a, R: 'a, C> MapAccess<'a, R, C> {
     #[inline]
     const fn new(de: &'a mut Deserializer<R, C>, len: u32) -> Self {
-        MapAccess { de, left: len }
+        MapAccess { de, left: len, record_keys: false, fields: None }
     }
 }
 
@@ -939,7 +1490,12 @@ impl<'de, 'a, R: ReadSlice<'de> + 'a, C: SerializerConfig> de::MapAccess<'de> fo
     {
         if self.left > 0 {
             self.left -= 1;
-            seed.deserialize(&mut *self.de).map(Some)
+            self.de.key_order.pending = self.record_keys;
+            self.de.unknown_fields.pending_key = self.fields;
+            let key = seed.deserialize(&mut *self.de).map(Some);
+            self.de.key_order.pending = false;
+            self.de.unknown_fields.pending_key = None;
+            key
         } else {
             Ok(None)
         }
@@ -1077,7 +1633,7 @@ impl<'de, R: ReadSlice<'de>, C: SerializerConfig> de::VariantAccess<'de> for Var
     fn struct_variant<V>(self, fields: &'static [&'static str], visitor: V) -> Result<V::Value, Error>
         where V: Visitor<'de>
     {
-        de::Deserializer::deserialize_tuple(self.de, fields.len(), visitor)
+        de::Deserializer::deserialize_struct(self.de, "", fields, visitor)
     }
 }
 
@@ -1140,6 +1696,80 @@ impl<R: Read> Read for ReadReader<R> {
     }
 }
 
+/// Buffered reader wrapper.
+///
+/// Reads straight from the buffer of the `BufRead`: markers, scalars and lengths are copied out
+/// of it without going through `Read`, and a string or binary which is entirely in the current
+/// buffer is handed out as a slice of it, without being copied. A value which is not is copied
+/// while the buffer is refilled, like with `ReadReader`.
+#[derive(Debug)]
+pub struct BufReadReader<R: BufRead> {
+    rd: R,
+    // The length of the last slice returned by `read_slice`, which is still at the start of the
+    // buffer of `rd` and is consumed by the next read.
+    pending: usize,
+    buf: Vec<u8>,
+}
+
+impl<R: BufRead> BufReadReader<R> {
+    #[inline]
+    fn new(rd: R) -> Self {
+        Self {
+            rd,
+            pending: 0,
+            buf: Vec::new(),
+        }
+    }
+
+    #[inline]
+    fn consume_pending(&mut self) {
+        if self.pending != 0 {
+            self.rd.consume(self.pending);
+            self.pending = 0;
+        }
+    }
+}
+
+impl<'de, R: BufRead> ReadSlice<'de> for BufReadReader<R> {
+    #[inline]
+    fn read_slice<'a>(&'a mut self, len: usize) -> Result<Reference<'de, 'a, [u8]>, io::Error> {
+        self.consume_pending();
+        if self.rd.fill_buf()?.len() >= len {
+            // The buffer is not empty, so this returns it again without reading.
+            self.pending = len;
+            return Ok(Reference::Copied(&self.rd.fill_buf()?[..len]));
+        }
+
+        self.buf.clear();
+        let read = self.rd.by_ref().take(len as u64).read_to_end(&mut self.buf)?;
+        if read != len {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+
+        Ok(Reference::Copied(&self.buf[..]))
+    }
+}
+
+impl<R: BufRead> Read for BufReadReader<R> {
+    #[inline]
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.consume_pending();
+        self.rd.read(buf)
+    }
+
+    #[inline]
+    fn read_exact(&mut self, buf: &mut [u8]) -> io::Result<()> {
+        self.consume_pending();
+        let data = self.rd.fill_buf()?;
+        if data.len() < buf.len() {
+            return self.rd.read_exact(buf);
+        }
+        buf.copy_from_slice(&data[..buf.len()]);
+        self.rd.consume(buf.len());
+        Ok(())
+    }
+}
+
 /// Borrowed reader wrapper.
 #[derive(Debug)]
 pub struct ReadRefReader<'a, R: ?Sized> {
@@ -1215,6 +1845,22 @@ where R: Read,
     Deserialize::deserialize(&mut Deserializer::new(rd))
 }
 
+/// Deserialize an instance of type `T` from a buffered I/O stream of MessagePack, see
+/// `Deserializer::from_buf_read`.
+///
+/// # Errors
+///
+/// This conversion can fail if the structure of the Value does not match the structure expected
+/// by `T`. It can also fail if the structure is correct but `T`'s implementation of `Deserialize`
+/// decides that something is wrong with the data, for example required struct fields are missing.
+#[inline]
+pub fn from_buf_read<R, T>(rd: R) -> Result<T, Error>
+where R: BufRead,
+      T: DeserializeOwned
+{
+    Deserialize::deserialize(&mut Deserializer::from_buf_read(rd))
+}
+
 /// Deserialize a temporary scope-bound instance of type `T` from a slice, with zero-copy if possible.
 ///
 /// Deserialization will be performed in zero-copy manner whenever it is possible, borrowing the
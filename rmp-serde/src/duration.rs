@@ -0,0 +1,59 @@
+//! Serialize a `Duration` as a 2-element array `[secs, nanos]`, whatever the struct encoding.
+//!
+//! Intended to be used with `#[serde(with = "rmp_serde::duration")]`. Requires the `duration`
+//! feature.
+//!
+//! Serde serializes a `Duration` as a struct with the fields `secs` and `nanos`, so it becomes a
+//! map with both field names when structs are serialized as maps. This helper always writes the
+//! array, holding the whole seconds as an unsigned integer and the remaining nanoseconds, from 0 to
+//! 999 999 999, as another one, each in its smallest representation. It covers the whole range of
+//! `Duration`, and is the same as the default encoding with structs serialized as arrays.
+//!
+//! Deserialization accepts the array, and the map written by serde's own implementation.
+//!
+//! # Examples
+//!
+//! ```
+//! use std::time::Duration;
+//!
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Debug, PartialEq, Deserialize, Serialize)]
+//! struct Job {
+//!     #[serde(with = "rmp_serde::duration")]
+//!     timeout: Duration,
+//! }
+//!
+//! let val = Job { timeout: Duration::from_millis(1500) };
+//! let buf = rmp_serde::to_vec_named(&val).unwrap();
+//!
+//! // {"timeout": [1, 500000000]}
+//! assert_eq!([0x81, 0xa7], buf[..2]);
+//! assert_eq!([0x92, 0x01, 0xce, 0x1d, 0xcd, 0x65, 0x00], buf[9..]);
+//! assert_eq!(val, rmp_serde::from_slice(&buf).unwrap());
+//! ```
+
+use std::time::Duration;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// Serializes the `Duration` as `[secs, nanos]`.
+///
+/// # Errors
+///
+/// Serialization can fail if the underlying serializer fails.
+#[inline]
+pub fn serialize<S: Serializer>(duration: &Duration, se: S) -> Result<S::Ok, S::Error> {
+    (duration.as_secs(), duration.subsec_nanos()).serialize(se)
+}
+
+/// Deserializes a `Duration` from `[secs, nanos]`, or from a map with the keys `secs` and
+/// `nanos`.
+///
+/// # Errors
+///
+/// Deserialization fails if the input has another shape, or if the duration overflows.
+#[inline]
+pub fn deserialize<'de, D: Deserializer<'de>>(de: D) -> Result<Duration, D::Error> {
+    Duration::deserialize(de)
+}
@@ -1,10 +1,10 @@
 //! Serialize a Rust data structure into MessagePack data.
 
-use crate::bytes::OnlyBytes;
-use crate::config::BytesMode;
+use crate::bytes::{OnlyBytes, OnlyShape};
+use crate::config::{BytesMode, DiscriminantMode, IntEncoding};
 use std::error;
 use std::fmt::{self, Display};
-use std::io::Write;
+use std::io::{BufWriter, Write};
 use std::marker::PhantomData;
 
 use serde;
@@ -20,11 +20,13 @@ use rmp::{encode, Marker};
 use crate::config::{
     BinaryConfig, DefaultConfig, HumanReadableConfig, RuntimeConfig, SerializerConfig, StructMapConfig, StructTupleConfig
 };
+use crate::overrides::MSGPACK_FIXED_U64_STRUCT_NAME;
 use crate::MSGPACK_EXT_STRUCT_NAME;
 
 /// This type represents all possible errors that can occur when serializing or
 /// deserializing MessagePack data.
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum Error {
     /// Failed to write a MessagePack value.
     InvalidValueWrite(ValueWriteError),
@@ -37,6 +39,10 @@ pub enum Error {
     DepthLimitExceeded,
     /// Catchall for syntax error messages.
     Syntax(String),
+    /// The output would exceed the byte limit of a [`LimitedWriter`], given here.
+    OutputTooLarge(usize),
+    /// An ext was about to be written, which `Serializer::with_ext_forbidden` disallows.
+    ExtForbidden,
 }
 
 impl error::Error for Error {
@@ -48,6 +54,8 @@ impl error::Error for Error {
             Self::InvalidDataModel(_) => None,
             Self::DepthLimitExceeded => None,
             Self::Syntax(..) => None,
+            Self::OutputTooLarge(..) => None,
+            Self::ExtForbidden => None,
         }
     }
 }
@@ -63,6 +71,8 @@ impl Display for Error {
             Self::InvalidDataModel(r) => write!(f, "serialize data model is invalid: {r}"),
             Self::DepthLimitExceeded => f.write_str("depth limit exceeded"),
             Self::Syntax(ref msg) => f.write_str(msg),
+            Self::OutputTooLarge(limit) => write!(f, "output exceeds the limit of {limit} bytes"),
+            Self::ExtForbidden => f.write_str("ext types are forbidden"),
         }
     }
 }
@@ -70,7 +80,11 @@ impl Display for Error {
 impl From<ValueWriteError> for Error {
     #[cold]
     fn from(err: ValueWriteError) -> Self {
-        Self::InvalidValueWrite(err)
+        let (ValueWriteError::InvalidMarkerWrite(ref io) | ValueWriteError::InvalidDataWrite(ref io)) = err;
+        match io.get_ref().and_then(|inner| inner.downcast_ref::<LimitExceeded>()) {
+            Some(&LimitExceeded(limit)) => Self::OutputTooLarge(limit),
+            None => Self::InvalidValueWrite(err),
+        }
     }
 }
 
@@ -118,6 +132,9 @@ pub struct Serializer<W, C = DefaultConfig> {
     wr: W,
     depth: u16,
     config: RuntimeConfig,
+    /// The largest number of bytes a sequence, map or struct may take while it is buffered, see
+    /// `with_output_limit`.
+    buffer_limit: usize,
     _back_compat_config: PhantomData<C>,
 }
 
@@ -166,6 +183,7 @@ impl<W: Write> Serializer<W, DefaultConfig> {
             wr,
             depth: 1024,
             config: RuntimeConfig::new(DefaultConfig),
+            buffer_limit: usize::MAX,
             _back_compat_config: PhantomData,
         }
     }
@@ -174,11 +192,28 @@ impl<W: Write> Serializer<W, DefaultConfig> {
 impl<'a, W: Write + 'a, C> Serializer<W, C> {
     #[inline]
     const fn compound(&'a mut self) -> Result<Compound<'a, W, C>, Error> {
-        Ok(Compound { se: self })
+        Ok(Compound { se: self, fields: None, pending: None, named: false })
     }
 }
 
 impl<'a, W: Write + 'a, C: SerializerConfig> Serializer<W, C> {
+    /// Starts a struct with `len` fields. The `name` is given for plain structs, but not for
+    /// struct variants, to detect adjacently tagged enums, see `PendingStruct`.
+    fn struct_compound(&'a mut self, name: Option<&'static str>, len: u32) -> Result<Compound<'a, W, C>, Error> {
+        let named = self.config.is_named;
+        let fields = if named { self.config.skip_none_fields } else { self.config.elide_trailing_nils }
+            .then(|| BufferedFieldsCompound { compound: UnknownLengthCompound::from(&*self), kept_count: 0, kept_len: 0 });
+        let pending = if named {
+            if fields.is_none() {
+                encode::write_map_len(self.get_mut(), len)?;
+            }
+            None
+        } else {
+            Some(PendingStruct { name, len })
+        };
+        Ok(Compound { se: self, fields, pending, named })
+    }
+
     #[inline]
     fn maybe_unknown_len_compound<F>(&'a mut self, len: Option<u32>, f: F) -> Result<MaybeUnknownLengthCompound<'a, W, C>, Error>
     where F: Fn(&mut W, u32) -> Result<Marker, ValueWriteError>
@@ -191,9 +226,20 @@ impl<'a, W: Write + 'a, C: SerializerConfig> Serializer<W, C> {
                 }
                 None => Some(UnknownLengthCompound::from(&*self)),
             },
+            entries: None,
             se: self,
         })
     }
+
+    /// Starts a map whose entries are buffered, to be written sorted by their encoded keys.
+    #[inline]
+    fn sorted_map_compound(&'a mut self) -> MaybeUnknownLengthCompound<'a, W, C> {
+        MaybeUnknownLengthCompound {
+            compound: Some(UnknownLengthCompound::from(&*self)),
+            entries: Some(Vec::new()),
+            se: self,
+        }
+    }
 }
 
 impl<W: Write, C> Serializer<W, C> {
@@ -203,10 +249,11 @@ impl<W: Write, C> Serializer<W, C> {
     /// requirements.
     #[inline]
     pub fn with_struct_map(self) -> Serializer<W, StructMapConfig<C>> {
-        let Self { wr, depth, config, _back_compat_config: _ } = self;
+        let Self { wr, depth, config, buffer_limit, _back_compat_config: _ } = self;
         Serializer {
             wr,
             depth,
+            buffer_limit,
             config: RuntimeConfig::new(StructMapConfig::new(config)),
             _back_compat_config: PhantomData,
         }
@@ -219,10 +266,11 @@ impl<W: Write, C> Serializer<W, C> {
     /// representation.
     #[inline]
     pub fn with_struct_tuple(self) -> Serializer<W, StructTupleConfig<C>> {
-        let Self { wr, depth, config, _back_compat_config: _ } = self;
+        let Self { wr, depth, config, buffer_limit, _back_compat_config: _ } = self;
         Serializer {
             wr,
             depth,
+            buffer_limit,
             config: RuntimeConfig::new(StructTupleConfig::new(config)),
             _back_compat_config: PhantomData,
         }
@@ -237,10 +285,11 @@ impl<W: Write, C> Serializer<W, C> {
     /// versions of `rmp-serde`.
     #[inline]
     pub fn with_human_readable(self) -> Serializer<W, HumanReadableConfig<C>> {
-        let Self { wr, depth, config, _back_compat_config: _ } = self;
+        let Self { wr, depth, config, buffer_limit, _back_compat_config: _ } = self;
         Serializer {
             wr,
             depth,
+            buffer_limit,
             config: RuntimeConfig::new(HumanReadableConfig::new(config)),
             _back_compat_config: PhantomData,
         }
@@ -253,15 +302,49 @@ impl<W: Write, C> Serializer<W, C> {
     /// representation.
     #[inline]
     pub fn with_binary(self) -> Serializer<W, BinaryConfig<C>> {
-        let Self { wr, depth, config, _back_compat_config: _ } = self;
+        let Self { wr, depth, config, buffer_limit, _back_compat_config: _ } = self;
         Serializer {
             wr,
             depth,
+            buffer_limit,
             config: RuntimeConfig::new(BinaryConfig::new(config)),
             _back_compat_config: PhantomData,
         }
     }
 
+    /// Consumes this serializer returning the new one, which writes at most `limit` bytes and
+    /// fails with [`Error::OutputTooLarge`] as soon as the output would exceed them.
+    ///
+    /// The writer is wrapped into a [`LimitedWriter`]. Unlike passing a `LimitedWriter` to
+    /// [`Serializer::new`], this also limits the sequences, maps and structs which are encoded into
+    /// a buffer before being written: sequences and maps of unknown length, maps whose keys are
+    /// sorted, and structs whose `nil` or `None` fields are left out. Encoding them fails once the
+    /// buffer exceeds the limit, rather than once the whole of them is written out.
+    ///
+    /// ```rust
+    /// use rmp_serde::encode::Error;
+    /// use serde::Serializer;
+    ///
+    /// // The length of the elements is not known in advance, so they are buffered.
+    /// let elems = (0..1_000_000).filter(|_| true);
+    /// let mut se = rmp_serde::Serializer::new(Vec::new()).with_output_limit(8);
+    /// match se.collect_seq(elems) {
+    ///     Err(Error::OutputTooLarge(8)) => {}
+    ///     other => panic!("unexpected result: {other:?}"),
+    /// }
+    /// ```
+    #[inline]
+    pub fn with_output_limit(self, limit: usize) -> Serializer<LimitedWriter<W>, C> {
+        let Self { wr, depth, config, buffer_limit: _, _back_compat_config: _ } = self;
+        Serializer {
+            wr: LimitedWriter::new(wr, limit),
+            depth,
+            buffer_limit: limit,
+            config,
+            _back_compat_config: PhantomData,
+        }
+    }
+
     /// Prefer encoding sequences of `u8` as bytes, rather than
     /// as a sequence of variable-size integers.
     ///
@@ -281,6 +364,254 @@ impl<W: Write, C> Serializer<W, C> {
         self.config.bytes = mode;
         self
     }
+
+    /// Changes how enum variants are identified: by name, which is the default, or by index
+    /// encoded as an integer of a fixed width.
+    ///
+    /// ```rust
+    /// use serde::ser::Serialize;
+    /// let mut msgpack_data = Vec::new();
+    /// let mut serializer = rmp_serde::Serializer::new(&mut msgpack_data)
+    ///     .with_discriminant(rmp_serde::config::DiscriminantMode::IndexU8);
+    /// Some(()).serialize(&mut serializer).unwrap();
+    /// ```
+    #[inline]
+    pub const fn with_discriminant(mut self, mode: DiscriminantMode) -> Self {
+        self.config.discriminant = mode;
+        self
+    }
+
+    /// Leaves out the trailing fields of structs serialized as tuples which are encoded as `nil`,
+    /// such as `None`, shortening the declared array length accordingly.
+    ///
+    /// Only the fields after the last non-`nil` one are left out, so the position of every
+    /// remaining field is unchanged. Structs serialized as maps, tuples and sequences are never
+    /// shortened. The output can be read back with `Deserializer::with_trailing_nils_padded`,
+    /// which fills the missing fields in with `nil` again.
+    ///
+    /// ```rust
+    /// use serde::Serialize;
+    ///
+    /// #[derive(Serialize)]
+    /// struct Point {
+    ///     x: u8,
+    ///     y: Option<u8>,
+    ///     z: Option<u8>,
+    /// }
+    ///
+    /// let mut msgpack_data = Vec::new();
+    /// let mut serializer = rmp_serde::Serializer::new(&mut msgpack_data)
+    ///     .with_trailing_nils_elided();
+    /// Point { x: 1, y: None, z: None }.serialize(&mut serializer).unwrap();
+    /// // [1]
+    /// assert_eq!(vec![0x91, 0x01], msgpack_data);
+    /// ```
+    #[inline]
+    pub const fn with_trailing_nils_elided(mut self) -> Self {
+        self.config.elide_trailing_nils = true;
+        self
+    }
+
+    /// Leaves out the fields of structs serialized as maps whose value is `None`, shortening the
+    /// declared map length accordingly, as if every such field was marked with
+    /// `#[serde(skip_serializing_if = "Option::is_none")]`.
+    ///
+    /// Only fields that serialize as `None` are left out; fields that are encoded as `nil` for
+    /// other reasons, such as `()`, are still written. Structs serialized as tuples are never
+    /// affected, because their fields are identified by position. When deserializing, the derived
+    /// implementations treat the missing `Option` fields as `None`.
+    ///
+    /// ```rust
+    /// use serde::Serialize;
+    ///
+    /// #[derive(Serialize)]
+    /// struct Point {
+    ///     x: Option<u8>,
+    ///     y: u8,
+    /// }
+    ///
+    /// let mut msgpack_data = Vec::new();
+    /// let mut serializer = rmp_serde::Serializer::new(&mut msgpack_data)
+    ///     .with_struct_map()
+    ///     .with_none_fields_skipped();
+    /// Point { x: None, y: 2 }.serialize(&mut serializer).unwrap();
+    /// // {"y": 2}
+    /// assert_eq!(vec![0x81, 0xa1, b'y', 0x02], msgpack_data);
+    /// ```
+    #[inline]
+    pub const fn with_none_fields_skipped(mut self) -> Self {
+        self.config.skip_none_fields = true;
+        self
+    }
+
+    /// Writes floats with no fractional part, such as `3.0`, as integers, which take less space.
+    ///
+    /// An `f64` is written as an integer when it is in the range of `i64` or `u64`, and an `f32`
+    /// when it is in the range of `i32` or `u32`, so that the integer is never larger than the
+    /// float. `-0.0`, infinities and NaN are always written as floats.
+    ///
+    /// The `Deserializer` reads integers into float fields, so such values round-trip through
+    /// Rust types expecting floats. However, the distinction between floats and integers is lost:
+    /// a reader expecting any type, such as `rmpv::Value`, sees integers instead, and an integer
+    /// above 2<sup>53</sup> read into an `f64` loses precision.
+    ///
+    /// ```rust
+    /// use serde::ser::Serialize;
+    /// let mut msgpack_data = Vec::new();
+    /// let mut serializer = rmp_serde::Serializer::new(&mut msgpack_data)
+    ///     .with_whole_floats_compacted();
+    /// (3.0, 0.5).serialize(&mut serializer).unwrap();
+    /// assert_eq!(vec![0x92, 0x03, 0xcb, 0x3f, 0xe0, 0, 0, 0, 0, 0, 0], msgpack_data);
+    /// ```
+    #[inline]
+    pub const fn with_whole_floats_compacted(mut self) -> Self {
+        self.config.compact_whole_floats = true;
+        self
+    }
+
+    /// Writes the entries of maps sorted by the encoding of their keys, so that maps whose
+    /// iteration order is unspecified, such as `HashMap`, always produce the same output.
+    ///
+    /// Keys are compared byte by byte as they are encoded, which orders, for example, strings of
+    /// the same length alphabetically. The entries of each map are buffered until the map ends,
+    /// one map at a time, and entries with equal keys keep their order. Only maps are sorted:
+    /// structs serialized as maps keep the order of their fields, unless they have flattened
+    /// fields, which serde serializes as maps.
+    ///
+    /// ```rust
+    /// use serde::ser::Serialize;
+    /// use std::collections::HashMap;
+    ///
+    /// let map: HashMap<_, _> = [("b", 2), ("a", 1), ("c", 3)].into_iter().collect();
+    /// let mut msgpack_data = Vec::new();
+    /// let mut serializer = rmp_serde::Serializer::new(&mut msgpack_data)
+    ///     .with_map_keys_sorted();
+    /// map.serialize(&mut serializer).unwrap();
+    /// assert_eq!(vec![0x83, 0xa1, b'a', 0x01, 0xa1, b'b', 0x02, 0xa1, b'c', 0x03], msgpack_data);
+    /// ```
+    #[inline]
+    pub const fn with_map_keys_sorted(mut self) -> Self {
+        self.config.sort_map_keys = true;
+        self
+    }
+
+    /// Writes integers with the markers chosen by the given [`IntEncoding`], instead of in their
+    /// shortest form.
+    ///
+    /// This applies to all the integers from `i8` to `i64` and from `u8` to `u64`, but not to
+    /// enum discriminants written as indices, nor to whole floats written as integers by
+    /// `with_whole_floats_compacted`, which keep their own encoding.
+    ///
+    /// ```rust
+    /// use rmp_serde::config::FixedWidthInt;
+    /// use serde::ser::Serialize;
+    ///
+    /// let mut msgpack_data = Vec::new();
+    /// let mut serializer = rmp_serde::Serializer::new(&mut msgpack_data)
+    ///     .with_int_encoding(&FixedWidthInt);
+    /// 1u8.serialize(&mut serializer).unwrap();
+    /// assert_eq!(vec![0xcf, 0, 0, 0, 0, 0, 0, 0, 0x01], msgpack_data);
+    /// ```
+    #[inline]
+    pub const fn with_int_encoding(mut self, encoding: &'static dyn IntEncoding) -> Self {
+        self.config.int_encoding = Some(encoding);
+        self
+    }
+
+    /// Writes maps the way structs are written when structs are written as maps, see
+    /// `with_struct_map`: in the order of their entries, even when `with_map_keys_sorted` is
+    /// used, as structs always keep the order of their fields.
+    ///
+    /// This makes a map holding the fields of a struct, such as an `rmpv::Value::Map` with their
+    /// names as keys, serialize to the same bytes as the struct itself, nested maps included.
+    ///
+    /// This has no effect when structs are written as arrays, the default: a map can not be
+    /// written as an array of its values without losing its keys, and real maps such as
+    /// `HashMap` go through the same path, so they are still written as maps, sorted if asked.
+    ///
+    /// ```rust
+    /// use serde::ser::{Serialize, Serializer};
+    ///
+    /// /// A map of struct fields, in order.
+    /// struct Fields(Vec<(&'static str, u8)>);
+    ///
+    /// impl Serialize for Fields {
+    ///     fn serialize<S: Serializer>(&self, se: S) -> Result<S::Ok, S::Error> {
+    ///         se.collect_map(self.0.iter().map(|&(name, val)| (name, val)))
+    ///     }
+    /// }
+    ///
+    /// let map = Fields(vec![("b", 1), ("a", 2)]);
+    /// let mut msgpack_data = Vec::new();
+    /// let mut serializer = rmp_serde::Serializer::new(&mut msgpack_data)
+    ///     .with_struct_map()
+    ///     .with_map_keys_sorted()
+    ///     .with_maps_as_structs();
+    /// map.serialize(&mut serializer).unwrap();
+    /// assert_eq!(vec![0x82, 0xa1, b'b', 0x01, 0xa1, b'a', 0x02], msgpack_data);
+    /// ```
+    #[inline]
+    pub const fn with_maps_as_structs(mut self) -> Self {
+        self.config.maps_as_structs = true;
+        self
+    }
+
+    /// Fails with `Error::ExtForbidden` instead of writing an ext, for contexts restricted to the
+    /// other MessagePack types.
+    ///
+    /// This covers every ext written through this crate, such as `rmpv::Value::Ext`, the
+    /// timestamps of `chrono_timestamp` or the compressed binaries of `compressed`. Nothing is
+    /// written for the rejected ext, but the values before it may already be in the writer.
+    ///
+    /// ```rust
+    /// use rmp_serde::encode::Error;
+    /// use serde::Serialize;
+    ///
+    /// #[derive(Serialize)]
+    /// #[serde(rename = "_ExtStruct")]
+    /// struct ExtStruct((i8, serde_bytes::ByteBuf));
+    ///
+    /// let ext = ExtStruct((1, serde_bytes::ByteBuf::from(vec![0x2a])));
+    /// let mut msgpack_data = Vec::new();
+    /// let mut serializer = rmp_serde::Serializer::new(&mut msgpack_data)
+    ///     .with_ext_forbidden();
+    /// assert!(matches!(ext.serialize(&mut serializer), Err(Error::ExtForbidden)));
+    /// ```
+    #[inline]
+    pub const fn with_ext_forbidden(mut self) -> Self {
+        self.config.forbid_ext = true;
+        self
+    }
+}
+
+impl<W: Write, C> Serializer<W, C> {
+    /// Writes a float as an integer if compacting whole floats is enabled and it has no fractional
+    /// part and is within `min..max`, returning whether it was written.
+    fn write_whole_float(&mut self, v: f64, min: f64, max: f64) -> Result<bool, Error> {
+        let is_whole = v.fract() == 0.0 && !(v == 0.0 && v.is_sign_negative());
+        if !self.config.compact_whole_floats || !is_whole || v < min || v >= max {
+            return Ok(false);
+        }
+        if v >= 0.0 {
+            encode::write_uint(&mut self.wr, v as u64)?;
+        } else {
+            encode::write_sint(&mut self.wr, v as i64)?;
+        }
+        Ok(true)
+    }
+
+    fn serialize_variant_id(&mut self, idx: u32, variant: &'static str) -> Result<(), Error> {
+        match self.config.discriminant {
+            DiscriminantMode::Name => encode::write_str(&mut self.wr, variant)?,
+            DiscriminantMode::IndexU8 => {
+                let idx = u8::try_from(idx)
+                    .map_err(|_| Error::InvalidDataModel("enum variant index does not fit into u8"))?;
+                encode::write_u8(&mut self.wr, idx)?;
+            }
+            DiscriminantMode::IndexU32 => encode::write_u32(&mut self.wr, idx)?,
+        }
+        Ok(())
+    }
 }
 
 impl<W: Write, C> UnderlyingWrite for Serializer<W, C> {
@@ -351,6 +682,94 @@ impl<'a, W: Write + 'a, C: SerializerConfig> SerializeTuple for Tuple<'a, W, C>
 #[doc(hidden)]
 pub struct Compound<'a, W, C> {
     se: &'a mut Serializer<W, C>,
+    fields: Option<BufferedFieldsCompound>,
+    pending: Option<PendingStruct>,
+    /// Whether the struct is written as a map of its field names to their values.
+    named: bool,
+}
+
+/// A struct serialized as a tuple, whose header is only written with its first field.
+///
+/// Serde writes adjacently tagged enums as a struct named after the enum, with a tag field holding
+/// a unit variant of that same enum, and a content field that is left out for unit variants. As a
+/// tuple the missing content can not be told apart from the tag, and serde can only read struct
+/// variants back from a map, so such structs and their content are always written as maps.
+///
+/// The same calls could come from a struct named after the enum of its first field, which is
+/// written as a map too, as serde does not say whether the enum is tagged. Internally tagged enums
+/// are written through the very calls of structs, so they can not be detected and follow
+/// `Serializer::with_struct_map`.
+#[derive(Debug)]
+struct PendingStruct {
+    name: Option<&'static str>,
+    len: u32,
+}
+
+impl<'a, W: Write + 'a, C: SerializerConfig> Compound<'a, W, C> {
+    fn serialize_struct_field<T: ?Sized + Serialize>(&mut self, key: &'static str, value: &T) -> Result<(), Error> {
+        if let Some(pending) = self.pending.take() {
+            self.named = pending.name.is_some_and(|name| value.serialize(OnlyShape::UnitVariantOf(name)).is_ok());
+            match self.fields.as_mut() {
+                Some(fields) => fields.compound.se.config.is_named |= self.named,
+                None if self.named => {
+                    encode::write_map_len(self.se.get_mut(), pending.len)?;
+                }
+                None => {
+                    encode::write_array_len(self.se.get_mut(), pending.len)?;
+                }
+            }
+        }
+
+        match self.fields.as_mut() {
+            None => {
+                if self.named {
+                    encode::write_str(self.se.get_mut(), key)?;
+                }
+                // The content of adjacently tagged enums is written as maps as well.
+                let is_named = std::mem::replace(&mut self.se.config.is_named, self.named);
+                let res = value.serialize(&mut *self.se);
+                self.se.config.is_named = is_named;
+                res
+            }
+            Some(fields) if self.named => {
+                if self.se.config.skip_none_fields && value.serialize(OnlyShape::None).is_ok() {
+                    return Ok(());
+                }
+                encode::write_str(&mut fields.compound.se.wr, key)?;
+                value.serialize(&mut fields.compound.se)?;
+                fields.compound.elem_count += 1;
+                fields.kept_count = fields.compound.elem_count;
+                fields.kept_len = fields.compound.buf().len();
+                Ok(())
+            }
+            Some(fields) => {
+                let start = fields.compound.buf().len();
+                value.serialize(&mut fields.compound.se)?;
+                fields.compound.elem_count += 1;
+                if fields.compound.buf()[start..] != [Marker::Null.to_u8()] {
+                    fields.kept_count = fields.compound.elem_count;
+                    fields.kept_len = fields.compound.buf().len();
+                }
+                Ok(())
+            }
+        }
+    }
+
+    fn end_struct(self) -> Result<(), Error> {
+        if let (Some(pending), None) = (&self.pending, &self.fields) {
+            encode::write_array_len(self.se.get_mut(), pending.len)?;
+        }
+        if let Some(fields) = self.fields {
+            if self.named {
+                encode::write_map_len(&mut self.se.wr, fields.kept_count)?;
+            } else {
+                encode::write_array_len(&mut self.se.wr, fields.kept_count)?;
+            }
+            self.se.wr.write_all(&fields.compound.buf()[..fields.kept_len])
+                .map_err(ValueWriteError::InvalidDataWrite)?;
+        }
+        Ok(())
+    }
 }
 
 #[derive(Debug)]
@@ -423,15 +842,12 @@ impl<'a, W: Write + 'a, C: SerializerConfig> SerializeStruct for Compound<'a, W,
         key: &'static str,
         value: &T,
     ) -> Result<(), Self::Error> {
-        if self.se.config.is_named {
-            encode::write_str(self.se.get_mut(), key)?;
-        }
-        value.serialize(&mut *self.se)
+        self.serialize_struct_field(key, value)
     }
 
-    #[inline(always)]
+    #[inline]
     fn end(self) -> Result<Self::Ok, Self::Error> {
-        Ok(())
+        self.end_struct()
     }
 }
 
@@ -459,17 +875,12 @@ impl<'a, W: Write + 'a, C: SerializerConfig> SerializeStructVariant for Compound
         key: &'static str,
         value: &T,
     ) -> Result<(), Self::Error> {
-        if self.se.config.is_named {
-            encode::write_str(self.se.get_mut(), key)?;
-            value.serialize(&mut *self.se)
-        } else {
-            value.serialize(&mut *self.se)
-        }
+        self.serialize_struct_field(key, value)
     }
 
-    #[inline(always)]
+    #[inline]
     fn end(self) -> Result<Self::Ok, Self::Error> {
-        Ok(())
+        self.end_struct()
     }
 }
 
@@ -477,17 +888,26 @@ impl<'a, W: Write + 'a, C: SerializerConfig> SerializeStructVariant for Compound
 /// and a counter for the number of elements that are encoded by the `Serializer`.
 #[derive(Debug)]
 struct UnknownLengthCompound {
-    se: Serializer<Vec<u8>, DefaultConfig>,
+    se: Serializer<LimitedWriter<Vec<u8>>, DefaultConfig>,
     elem_count: u32,
 }
 
+impl UnknownLengthCompound {
+    /// Returns the elements encoded so far.
+    #[inline]
+    fn buf(&self) -> &[u8] {
+        self.se.wr.get_ref()
+    }
+}
+
 impl<W, C: SerializerConfig> From<&Serializer<W, C>> for UnknownLengthCompound {
     fn from(se: &Serializer<W, C>) -> Self {
         Self {
             se: Serializer {
-                wr: Vec::with_capacity(128),
+                wr: LimitedWriter::new(Vec::with_capacity(128), se.buffer_limit),
                 config: RuntimeConfig::new(se.config),
                 depth: se.depth,
+                buffer_limit: se.buffer_limit,
                 _back_compat_config: PhantomData,
             },
             elem_count: 0,
@@ -495,6 +915,18 @@ impl<W, C: SerializerConfig> From<&Serializer<W, C>> for UnknownLengthCompound {
     }
 }
 
+/// Buffers the fields of a struct, so that the length written on `end()` only counts the fields
+/// that are kept: for a struct serialized as a tuple the trailing fields encoded as `nil` are left
+/// out, and for a struct serialized as a map the fields that are `None` are never written.
+#[derive(Debug)]
+struct BufferedFieldsCompound {
+    compound: UnknownLengthCompound,
+    /// Number of fields, and length of their encoding, up to and including the last field that
+    /// is kept.
+    kept_count: u32,
+    kept_len: usize,
+}
+
 /// Contains a `Serializer` for encoding elements of sequences and maps.
 ///
 /// # Note
@@ -515,6 +947,9 @@ impl<W, C: SerializerConfig> From<&Serializer<W, C>> for UnknownLengthCompound {
 pub struct MaybeUnknownLengthCompound<'a, W, C> {
     se: &'a mut Serializer<W, C>,
     compound: Option<UnknownLengthCompound>,
+    /// For a map whose entries are sorted, the offsets in the buffer at which the key and the
+    /// value of each entry start.
+    entries: Option<Vec<(usize, usize)>>,
 }
 
 impl<'a, W: Write + 'a, C: SerializerConfig> SerializeSeq for MaybeUnknownLengthCompound<'a, W, C> {
@@ -535,7 +970,7 @@ impl<'a, W: Write + 'a, C: SerializerConfig> SerializeSeq for MaybeUnknownLength
     fn end(self) -> Result<Self::Ok, Self::Error> {
         if let Some(compound) = self.compound {
             encode::write_array_len(&mut self.se.wr, compound.elem_count)?;
-            self.se.wr.write_all(&compound.se.into_inner())
+            self.se.wr.write_all(&compound.se.into_inner().into_inner())
                 .map_err(ValueWriteError::InvalidDataWrite)?;
         }
         Ok(())
@@ -547,18 +982,39 @@ impl<'a, W: Write + 'a, C: SerializerConfig> SerializeMap for MaybeUnknownLength
     type Error = Error;
 
     fn serialize_key<T: ?Sized + Serialize>(&mut self, key: &T) -> Result<(), Self::Error> {
+        if let (Some(entries), Some(buf)) = (self.entries.as_mut(), self.compound.as_ref()) {
+            let start = buf.buf().len();
+            entries.push((start, start));
+        }
         <Self as SerializeSeq>::serialize_element(self, key)
     }
 
     fn serialize_value<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Self::Error> {
+        if let (Some(entries), Some(buf)) = (self.entries.as_mut(), self.compound.as_ref()) {
+            if let Some(entry) = entries.last_mut() {
+                entry.1 = buf.buf().len();
+            }
+        }
         <Self as SerializeSeq>::serialize_element(self, value)
     }
 
     fn end(self) -> Result<Self::Ok, Self::Error> {
         if let Some(compound) = self.compound {
             encode::write_map_len(&mut self.se.wr, compound.elem_count / 2)?;
-            self.se.wr.write_all(&compound.se.into_inner())
-                .map_err(ValueWriteError::InvalidDataWrite)?;
+            let buf = compound.se.into_inner().into_inner();
+            match self.entries {
+                Some(entries) => {
+                    // Each entry ends where the next one starts.
+                    let ends = entries.iter().skip(1).map(|&(key, _)| key).chain([buf.len()]);
+                    let mut ranges: Vec<_> = entries.iter().zip(ends).map(|(&(key, val), end)| (key, val, end)).collect();
+                    ranges.sort_by(|a, b| buf[a.0..a.1].cmp(&buf[b.0..b.1]));
+                    for (key, _, end) in ranges {
+                        self.se.wr.write_all(&buf[key..end])
+                            .map_err(ValueWriteError::InvalidDataWrite)?;
+                    }
+                }
+                None => self.se.wr.write_all(&buf).map_err(ValueWriteError::InvalidDataWrite)?,
+            }
         }
         Ok(())
     }
@@ -587,7 +1043,7 @@ where
 
     fn serialize_bool(self, v: bool) -> Result<Self::Ok, Self::Error> {
         encode::write_bool(&mut self.wr, v)
-            .map_err(|err| Error::InvalidValueWrite(ValueWriteError::InvalidMarkerWrite(err)))
+            .map_err(|err| Error::from(ValueWriteError::InvalidMarkerWrite(err)))
     }
 
     fn serialize_i8(self, v: i8) -> Result<Self::Ok, Self::Error> {
@@ -603,8 +1059,13 @@ where
     }
 
     fn serialize_i64(self, v: i64) -> Result<Self::Ok, Self::Error> {
-        encode::write_sint(&mut self.wr, v)?;
-        Ok(())
+        match self.config.int_encoding {
+            Some(encoding) => write_int(&mut self.wr, encoding.sint_marker(v), v.into()),
+            None => {
+                encode::write_sint(&mut self.wr, v)?;
+                Ok(())
+            }
+        }
     }
 
     fn serialize_i128(self, v: i128) -> Result<Self::Ok, Self::Error> {
@@ -624,8 +1085,13 @@ where
     }
 
     fn serialize_u64(self, v: u64) -> Result<Self::Ok, Self::Error> {
-        encode::write_uint(&mut self.wr, v)?;
-        Ok(())
+        match self.config.int_encoding {
+            Some(encoding) => write_int(&mut self.wr, encoding.uint_marker(v), v.into()),
+            None => {
+                encode::write_uint(&mut self.wr, v)?;
+                Ok(())
+            }
+        }
     }
 
     fn serialize_u128(self, v: u128) -> Result<Self::Ok, Self::Error> {
@@ -633,12 +1099,16 @@ where
     }
 
     fn serialize_f32(self, v: f32) -> Result<Self::Ok, Self::Error> {
-        encode::write_f32(&mut self.wr, v)?;
+        if !self.write_whole_float(v.into(), i32::MIN.into(), f64::from(u32::MAX) + 1.0)? {
+            encode::write_f32(&mut self.wr, v)?;
+        }
         Ok(())
     }
 
     fn serialize_f64(self, v: f64) -> Result<Self::Ok, Self::Error> {
-        encode::write_f64(&mut self.wr, v)?;
+        if !self.write_whole_float(v, i64::MIN as f64, u64::MAX as f64)? {
+            encode::write_f64(&mut self.wr, v)?;
+        }
         Ok(())
     }
 
@@ -667,7 +1137,7 @@ where
 
     fn serialize_unit(self) -> Result<Self::Ok, Self::Error> {
         encode::write_nil(&mut self.wr)
-            .map_err(|err| Error::InvalidValueWrite(ValueWriteError::InvalidMarkerWrite(err)))
+            .map_err(|err| Error::from(ValueWriteError::InvalidMarkerWrite(err)))
     }
 
     fn serialize_unit_struct(self, _name: &'static str) -> Result<Self::Ok, Self::Error> {
@@ -675,28 +1145,41 @@ where
         Ok(())
     }
 
-    fn serialize_unit_variant(self, _name: &str, _: u32, variant: &'static str) ->
+    fn serialize_unit_variant(self, _name: &str, idx: u32, variant: &'static str) ->
         Result<Self::Ok, Self::Error>
     {
-        self.serialize_str(variant)
+        self.serialize_variant_id(idx, variant)
     }
 
     fn serialize_newtype_struct<T: ?Sized + serde::Serialize>(self, name: &'static str, value: &T) -> Result<(), Self::Error> {
         if name == MSGPACK_EXT_STRUCT_NAME {
+            if self.config.forbid_ext {
+                return Err(Error::ExtForbidden);
+            }
             let mut ext_se = ExtSerializer::new(self);
             value.serialize(&mut ext_se)?;
 
             return ext_se.end();
         }
 
+        if name == MSGPACK_FIXED_U64_STRUCT_NAME {
+            // Encode the integer as usual and re-encode it with the full width.
+            let mut buf = Vec::with_capacity(9);
+            value.serialize(&mut Serializer::new(&mut buf))?;
+            let val = rmp::decode::read_int(&mut &buf[..])
+                .map_err(|_| Error::InvalidDataModel("expected u64"))?;
+            encode::write_u64(&mut self.wr, val)?;
+            return Ok(());
+        }
+
         // Encode as if it's inner type.
         value.serialize(self)
     }
 
-    fn serialize_newtype_variant<T: ?Sized + serde::Serialize>(self, _name: &'static str, _: u32, variant: &'static str, value: &T) -> Result<Self::Ok, Self::Error> {
+    fn serialize_newtype_variant<T: ?Sized + serde::Serialize>(self, _name: &'static str, idx: u32, variant: &'static str, value: &T) -> Result<Self::Ok, Self::Error> {
         // encode as a map from variant idx to its attributed data, like: {idx => value}
         encode::write_map_len(&mut self.wr, 1)?;
-        self.serialize_str(variant)?;
+        self.serialize_variant_id(idx, variant)?;
         value.serialize(self)
     }
 
@@ -728,41 +1211,40 @@ where
         self.compound()
     }
 
-    fn serialize_tuple_variant(self, _name: &'static str, _: u32, variant: &'static str, len: usize) ->
+    fn serialize_tuple_variant(self, _name: &'static str, idx: u32, variant: &'static str, len: usize) ->
         Result<Self::SerializeTupleVariant, Error>
     {
         // encode as a map from variant idx to a sequence of its attributed data, like: {idx => [v1,...,vN]}
         encode::write_map_len(&mut self.wr, 1)?;
-        self.serialize_str(variant)?;
+        self.serialize_variant_id(idx, variant)?;
         encode::write_array_len(&mut self.wr, len as u32)?;
         self.compound()
     }
 
     #[inline]
     fn serialize_map(self, len: Option<usize>) -> Result<Self::SerializeMap, Error> {
+        // Structs written as maps keep the order of their fields.
+        if self.config.sort_map_keys && !(self.config.maps_as_structs && self.config.is_named) {
+            return Ok(self.sorted_map_compound());
+        }
         self.maybe_unknown_len_compound(len.map(|len| len as u32), |wr, len| encode::write_map_len(wr, len))
     }
 
     fn serialize_struct(
         self,
-        _name: &'static str,
+        name: &'static str,
         len: usize,
     ) -> Result<Self::SerializeStruct, Self::Error> {
-        if self.config.is_named {
-            encode::write_map_len(self.get_mut(), len as u32)?;
-        } else {
-            encode::write_array_len(self.get_mut(), len as u32)?;
-        }
-        self.compound()
+        self.struct_compound(Some(name), len as u32)
     }
 
-    fn serialize_struct_variant(self, name: &'static str, _: u32, variant: &'static str, len: usize) ->
+    fn serialize_struct_variant(self, _name: &'static str, idx: u32, variant: &'static str, len: usize) ->
         Result<Self::SerializeStructVariant, Error>
     {
         // encode as a map from variant idx to a sequence of its attributed data, like: {idx => [v1,...,vN]}
         encode::write_map_len(&mut self.wr, 1)?;
-        self.serialize_str(variant)?;
-        self.serialize_struct(name, len)
+        self.serialize_variant_id(idx, variant)?;
+        self.struct_compound(None, len as u32)
     }
 
     fn collect_seq<I>(self, iter: I) -> Result<Self::Ok, Self::Error> where I: IntoIterator, I::Item: Serialize {
@@ -795,6 +1277,60 @@ where
         iter.try_for_each(|item| serializer.serialize_element(&item))?;
         SerializeSeq::end(serializer)
     }
+
+    /// Writes the `Display` output of the value as a string without collecting it into a
+    /// `String` first: the value is formatted once to measure the length written in the header,
+    /// and once more straight into the writer.
+    ///
+    /// Fails with `Error::InvalidDataModel` if the second output has another length than the
+    /// first, which leaves a truncated or overlong string in the writer.
+    fn collect_str<T: ?Sized + Display>(self, value: &T) -> Result<Self::Ok, Self::Error> {
+        use std::fmt::Write as _;
+
+        let mut counter = StrLenCounter(0);
+        // Formatting into the counter can not fail, unless `Display` itself does.
+        write!(counter, "{value}").map_err(|_| Error::InvalidDataModel("Display implementation failed"))?;
+        let len = u32::try_from(counter.0).map_err(|_| Error::InvalidDataModel("string is too long"))?;
+        encode::write_str_len(&mut self.wr, len)?;
+
+        let mut wr = StrWriter { wr: &mut self.wr, left: counter.0, err: None };
+        let res = write!(wr, "{value}");
+        if let Some(err) = wr.err {
+            return Err(Error::from(ValueWriteError::InvalidDataWrite(err)));
+        }
+        if res.is_err() || wr.left != 0 {
+            return Err(Error::InvalidDataModel("Display output changed between two calls"));
+        }
+        Ok(())
+    }
+}
+
+/// Counts the bytes formatted into it.
+struct StrLenCounter(usize);
+
+impl fmt::Write for StrLenCounter {
+    #[inline]
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.0 += s.len();
+        Ok(())
+    }
+}
+
+/// Writes at most `left` formatted bytes into the writer, keeping the I/O error if there is one.
+struct StrWriter<'a, W> {
+    wr: &'a mut W,
+    left: usize,
+    err: Option<std::io::Error>,
+}
+
+impl<W: Write> fmt::Write for StrWriter<'_, W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.left = self.left.checked_sub(s.len()).ok_or(fmt::Error)?;
+        self.wr.write_all(s.as_bytes()).map_err(|err| {
+            self.err = Some(err);
+            fmt::Error
+        })
+    }
 }
 
 impl<W: Write, C: SerializerConfig> Serializer<W, C> {
@@ -837,7 +1373,7 @@ impl<'a, W: Write + 'a> serde::Serializer for &mut ExtFieldSerializer<'a, W> {
             encode::write_ext_meta(self.wr, val.len() as u32, tag)?;
             self.wr
                 .write_all(val)
-                .map_err(|err| Error::InvalidValueWrite(ValueWriteError::InvalidDataWrite(err)))?;
+                .map_err(|err| Error::from(ValueWriteError::InvalidDataWrite(err)))?;
 
             self.finish = true;
 
@@ -1256,6 +1792,145 @@ where
     Ok(wr.0)
 }
 
+/// Serialize the given data structure as MessagePack directly into the given writer, such as a
+/// `File` or a `TcpStream`.
+/// This function uses compact representation - structures as arrays
+///
+/// Unlike [`write()`], the writer is taken by value and is wrapped into a `BufWriter`, so the many
+/// small writes made by the serializer are coalesced. The buffer is flushed before returning.
+///
+/// This is the counterpart of [`from_read`](crate::decode::from_read).
+///
+/// # Errors
+///
+/// Serialization can fail if `T`'s implementation of `Serialize` decides to fail, or if the
+/// underlying writer fails.
+///
+/// # Examples
+///
+/// ```
+/// let mut buf = Vec::new();
+/// rmp_serde::to_writer(&mut buf, &(42, "the Answer")).unwrap();
+///
+/// assert_eq!((42, "the Answer"), rmp_serde::from_slice(&buf).unwrap());
+/// ```
+#[inline]
+pub fn to_writer<W, T>(wr: W, val: &T) -> Result<(), Error>
+where
+    W: Write,
+    T: Serialize + ?Sized,
+{
+    let mut wr = BufWriter::new(wr);
+    write(&mut wr, val)?;
+    flush(wr)
+}
+
+/// Serialize the given data structure as MessagePack directly into the given writer as a map.
+/// Resulting MessagePack message will contain field names
+///
+/// The writer is buffered in the same way as in [`to_writer`].
+///
+/// # Errors
+///
+/// Serialization can fail if `T`'s implementation of `Serialize` decides to fail, or if the
+/// underlying writer fails.
+#[inline]
+pub fn to_writer_named<W, T>(wr: W, val: &T) -> Result<(), Error>
+where
+    W: Write,
+    T: Serialize + ?Sized,
+{
+    let mut wr = BufWriter::new(wr);
+    write_named(&mut wr, val)?;
+    flush(wr)
+}
+
+/// Serialize the given data structure as MessagePack into the given asynchronous writer.
+/// This method uses compact representation, structs are serialized as arrays
+///
+/// Requires the `tokio` feature. The value is serialized into a buffer first, and then written
+/// with a single [`write_all`](tokio::io::AsyncWriteExt::write_all), rather than with one awaited
+/// write per marker. The writer is not flushed, so a buffered writer has to be flushed by the
+/// caller.
+///
+/// # Errors
+///
+/// Serialization can fail if `T`'s implementation of `Serialize` decides to fail, or if the
+/// underlying writer fails.
+///
+/// # Examples
+///
+/// ```
+/// # tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(async {
+/// let mut buf = Vec::new();
+/// rmp_serde::to_writer_async(&mut buf, &(42, "the Answer")).await.unwrap();
+///
+/// assert_eq!((42, "the Answer"), rmp_serde::from_slice(&buf).unwrap());
+/// # });
+/// ```
+#[cfg(feature = "tokio")]
+pub async fn to_writer_async<W, T>(wr: &mut W, val: &T) -> Result<(), Error>
+where
+    W: tokio::io::AsyncWrite + Unpin,
+    T: Serialize + ?Sized,
+{
+    write_all_async(wr, &to_vec(val)?).await
+}
+
+/// Serialize the given data structure as MessagePack into the given asynchronous writer as a map.
+/// Resulting MessagePack message will contain field names
+///
+/// The value is buffered in the same way as in [`to_writer_async`].
+///
+/// # Errors
+///
+/// Serialization can fail if `T`'s implementation of `Serialize` decides to fail, or if the
+/// underlying writer fails.
+#[cfg(feature = "tokio")]
+pub async fn to_writer_named_async<W, T>(wr: &mut W, val: &T) -> Result<(), Error>
+where
+    W: tokio::io::AsyncWrite + Unpin,
+    T: Serialize + ?Sized,
+{
+    write_all_async(wr, &to_vec_named(val)?).await
+}
+
+#[cfg(feature = "tokio")]
+async fn write_all_async<W: tokio::io::AsyncWrite + Unpin>(wr: &mut W, buf: &[u8]) -> Result<(), Error> {
+    use tokio::io::AsyncWriteExt;
+
+    wr.write_all(buf)
+        .await
+        .map_err(|err| Error::from(ValueWriteError::InvalidDataWrite(err)))
+}
+
+/// Writes the integer with the given marker, chosen by an [`IntEncoding`].
+fn write_int<W: Write>(wr: &mut W, marker: Marker, val: i128) -> Result<(), Error> {
+    let res = match marker {
+        Marker::FixPos(fix) if val == i128::from(fix) && fix <= 0x7f => {
+            encode::write_pfix(wr, fix).map_err(ValueWriteError::InvalidMarkerWrite)
+        }
+        Marker::FixNeg(fix) if val == i128::from(fix) && (-32..0).contains(&fix) => {
+            encode::write_nfix(wr, fix).map_err(ValueWriteError::InvalidMarkerWrite)
+        }
+        Marker::U8 if u8::try_from(val).is_ok() => encode::write_u8(wr, val as u8),
+        Marker::U16 if u16::try_from(val).is_ok() => encode::write_u16(wr, val as u16),
+        Marker::U32 if u32::try_from(val).is_ok() => encode::write_u32(wr, val as u32),
+        Marker::U64 if u64::try_from(val).is_ok() => encode::write_u64(wr, val as u64),
+        Marker::I8 if i8::try_from(val).is_ok() => encode::write_i8(wr, val as i8),
+        Marker::I16 if i16::try_from(val).is_ok() => encode::write_i16(wr, val as i16),
+        Marker::I32 if i32::try_from(val).is_ok() => encode::write_i32(wr, val as i32),
+        Marker::I64 if i64::try_from(val).is_ok() => encode::write_i64(wr, val as i64),
+        _ => return Err(Error::InvalidDataModel("integer marker chosen by the IntEncoding can not represent it")),
+    };
+    Ok(res?)
+}
+
+fn flush<W: Write>(mut wr: BufWriter<W>) -> Result<(), Error> {
+    wr.flush()
+        .map_err(|err| Error::from(ValueWriteError::InvalidDataWrite(err)))
+}
+
 #[repr(transparent)]
 struct FallibleWriter(Vec<u8>);
 
@@ -1277,3 +1952,111 @@ impl Write for FallibleWriter {
         Ok(())
     }
 }
+
+/// A writer that accepts at most a given number of bytes, so that serializing into it fails with
+/// [`Error::OutputTooLarge`] as soon as the output would exceed that limit.
+///
+/// This is meant for messages which have to fit into a fixed-size frame: serialization stops at
+/// the first write that does not fit, without encoding the rest of the value. The bytes of that
+/// write are not written at all, but the bytes written before it are kept, so the output of a
+/// failed serialization is incomplete and should be discarded.
+///
+/// Some sequences, maps and structs are encoded into a buffer of the `Serializer` before being
+/// written, see [`Serializer::with_output_limit`], which wraps the writer into a `LimitedWriter`
+/// and limits those buffers as well. A `LimitedWriter` given to [`Serializer::new`] only sees such
+/// a value once it has been encoded in full.
+///
+/// Writing fails with an [`io::Error`](std::io::Error) of kind `Other`, which the `Serializer`
+/// turns into [`Error::OutputTooLarge`].
+///
+/// # Examples
+///
+/// ```
+/// use rmp_serde::encode::{Error, LimitedWriter};
+/// use rmp_serde::Serializer;
+/// use serde::Serialize;
+///
+/// let mut se = Serializer::new(LimitedWriter::new(Vec::new(), 8));
+/// (1, "abc").serialize(&mut se).unwrap();
+/// assert_eq!(6, se.get_ref().written());
+///
+/// let mut se = Serializer::new(LimitedWriter::new(Vec::new(), 8));
+/// match (1, "too long").serialize(&mut se) {
+///     Err(Error::OutputTooLarge(8)) => {}
+///     other => panic!("unexpected result: {other:?}"),
+/// }
+/// ```
+#[derive(Debug)]
+pub struct LimitedWriter<W> {
+    wr: W,
+    limit: usize,
+    written: usize,
+}
+
+impl<W: Write> LimitedWriter<W> {
+    /// Creates a writer accepting at most `limit` bytes before failing.
+    #[inline]
+    pub const fn new(wr: W, limit: usize) -> Self {
+        Self { wr, limit, written: 0 }
+    }
+
+    /// Returns the maximum number of bytes this writer accepts.
+    #[inline]
+    #[must_use]
+    pub const fn limit(&self) -> usize {
+        self.limit
+    }
+
+    /// Returns the number of bytes written so far.
+    #[inline]
+    #[must_use]
+    pub const fn written(&self) -> usize {
+        self.written
+    }
+
+    /// Gets a reference to the underlying writer.
+    #[inline]
+    pub const fn get_ref(&self) -> &W {
+        &self.wr
+    }
+
+    /// Gets a mutable reference to the underlying writer.
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.wr
+    }
+
+    /// Unwraps this writer, returning the underlying writer.
+    #[inline]
+    pub fn into_inner(self) -> W {
+        self.wr
+    }
+}
+
+impl<W: Write> Write for LimitedWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        if buf.len() > self.limit - self.written {
+            return Err(std::io::Error::other(LimitExceeded(self.limit)));
+        }
+        let len = self.wr.write(buf)?;
+        self.written += len;
+        Ok(len)
+    }
+
+    #[inline]
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.wr.flush()
+    }
+}
+
+/// The error with which a [`LimitedWriter`] fails, recognized by `From<ValueWriteError>`.
+#[derive(Debug)]
+struct LimitExceeded(usize);
+
+impl Display for LimitExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "output exceeds the limit of {} bytes", self.0)
+    }
+}
+
+impl error::Error for LimitExceeded {}
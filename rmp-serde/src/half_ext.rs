@@ -0,0 +1,112 @@
+//! Serialize a `half::f16` as an ext holding its two bytes, of type 16.
+//!
+//! Intended to be used with `#[serde(with = "rmp_serde::half_ext")]`. Requires the `half`
+//! feature. MessagePack has no half-precision float, so producers storing compact floats put
+//! them in an ext instead. [`to_ext_data`] and [`from_ext_data`] convert between an `f16` and the
+//! data of the ext directly, for example for an `rmpv::Value::Ext` or another ext type.
+//!
+//! # Format
+//!
+//! The float is written as a `fixext 2` of type [`EXT_TYPE`], holding its IEEE 754 binary16
+//! representation as a big-endian 16-bit integer, so that infinities, NaNs and subnormals are
+//! kept bit for bit.
+//!
+//! When deserializing, an `f32` or `f64` is accepted as well and rounded to the nearest `f16`,
+//! for producers which write half-precision values as ordinary floats.
+//!
+//! # Examples
+//!
+//! ```
+//! use half::f16;
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Debug, PartialEq, Deserialize, Serialize)]
+//! struct Pixel {
+//!     #[serde(with = "rmp_serde::half_ext")]
+//!     luma: f16,
+//! }
+//!
+//! let val = Pixel { luma: f16::from_f32(1.5) };
+//! let buf = rmp_serde::to_vec(&val).unwrap();
+//!
+//! // [fixext 2]
+//! assert_eq!(vec![0x91, 0xd5, 0x10, 0x3e, 0x00], buf);
+//! assert_eq!(val, rmp_serde::from_slice(&buf).unwrap());
+//!
+//! // [1.5f32]
+//! assert_eq!(val, rmp_serde::from_slice(&[0x91, 0xca, 0x3f, 0xc0, 0x00, 0x00]).unwrap());
+//! ```
+
+use std::fmt;
+
+use half::f16;
+use serde::de::{self, Deserialize, Visitor};
+use serde::{Deserializer, Serializer};
+
+use crate::overrides::AsBin;
+use crate::MSGPACK_EXT_STRUCT_NAME;
+
+/// The ext type of half-precision floats.
+pub const EXT_TYPE: i8 = 16;
+
+/// Returns the data of the ext representing the float.
+#[must_use]
+pub fn to_ext_data(val: f16) -> [u8; 2] {
+    val.to_bits().to_be_bytes()
+}
+
+/// Returns the float represented by the data of an ext.
+///
+/// Returns `None` if the data is not two bytes long.
+#[must_use]
+pub fn from_ext_data(data: &[u8]) -> Option<f16> {
+    let bits = u16::from_be_bytes(data.try_into().ok()?);
+    Some(f16::from_bits(bits))
+}
+
+/// Serializes the float as an ext.
+///
+/// # Errors
+///
+/// Serialization can fail if the underlying serializer fails.
+#[inline]
+pub fn serialize<S: Serializer>(val: &f16, se: S) -> Result<S::Ok, S::Error> {
+    se.serialize_newtype_struct(MSGPACK_EXT_STRUCT_NAME, &(EXT_TYPE, AsBin(to_ext_data(*val).to_vec())))
+}
+
+/// Deserializes a float from an ext, or from an `f32` or `f64`.
+///
+/// # Errors
+///
+/// Deserialization fails if the input is neither a float nor an ext of type 16 holding two
+/// bytes.
+#[inline]
+pub fn deserialize<'de, D: Deserializer<'de>>(de: D) -> Result<f16, D::Error> {
+    de.deserialize_any(HalfVisitor)
+}
+
+struct HalfVisitor;
+
+impl<'de> Visitor<'de> for HalfVisitor {
+    type Value = f16;
+
+    fn expecting(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.write_str("a half-precision float ext or a float")
+    }
+
+    fn visit_f32<E: de::Error>(self, val: f32) -> Result<Self::Value, E> {
+        Ok(f16::from_f32(val))
+    }
+
+    fn visit_f64<E: de::Error>(self, val: f64) -> Result<Self::Value, E> {
+        Ok(f16::from_f64(val))
+    }
+
+    fn visit_newtype_struct<D: Deserializer<'de>>(self, de: D) -> Result<Self::Value, D::Error> {
+        let (ty, AsBin(data)) = <(i8, AsBin<Vec<u8>>)>::deserialize(de)?;
+        if ty != EXT_TYPE {
+            return Err(de::Error::invalid_value(de::Unexpected::Other("an ext of another type"), &self));
+        }
+        from_ext_data(&data).ok_or_else(|| de::Error::invalid_value(de::Unexpected::Bytes(&data), &self))
+    }
+}
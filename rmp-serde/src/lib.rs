@@ -10,15 +10,33 @@ use serde::{Deserialize, Serialize};
 
 #[allow(deprecated)]
 pub use crate::decode::from_read_ref;
-pub use crate::decode::{from_read, Deserializer};
-pub use crate::encode::{to_vec, to_vec_named, Serializer};
+pub use crate::decode::{from_buf_read, from_read, Deserializer};
+pub use crate::encode::{to_vec, to_vec_named, to_writer, to_writer_named, Serializer};
+#[cfg(feature = "tokio")]
+pub use crate::encode::{to_writer_async, to_writer_named_async};
 
 pub use crate::decode::from_slice;
 
 mod bytes;
+#[cfg(feature = "chrono")]
+pub mod chrono_timestamp;
+#[cfg(feature = "tokio-util")]
+pub mod codec;
+#[cfg(feature = "compression")]
+pub mod compressed;
 pub mod config;
 pub mod decode;
+#[cfg(feature = "duration")]
+pub mod duration;
 pub mod encode;
+#[cfg(feature = "half")]
+pub mod half_ext;
+pub mod map_pairs;
+pub mod overrides;
+#[cfg(feature = "bytes")]
+pub mod shared_bytes;
+#[cfg(feature = "uuid")]
+pub mod uuid_bin;
 
 /// Hack used to serialize MessagePack Extension types.
 ///
@@ -0,0 +1,98 @@
+//! Serialize a `Vec<(K, V)>` as a MessagePack map, keeping the order and duplicates of its
+//! entries.
+//!
+//! MessagePack maps are sequences of key-value pairs, which may repeat a key. Collecting them
+//! into a `HashMap` or a `BTreeMap` keeps only one of the entries of each key, while a plain
+//! `Vec<(K, V)>` is written as an array of arrays. Intended to be used with
+//! `#[serde(with = "rmp_serde::map_pairs")]` on a `Vec<(K, V)>` field, this module reads and
+//! writes such a `Vec` as a map with exactly the entries it holds, in the same order.
+//!
+//! For compatibility with data written without this helper, deserialization also accepts a
+//! sequence of key-value pairs.
+//!
+//! # Examples
+//!
+//! ```
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Debug, PartialEq, Deserialize, Serialize)]
+//! struct Headers {
+//!     #[serde(with = "rmp_serde::map_pairs")]
+//!     entries: Vec<(String, u32)>,
+//! }
+//!
+//! // [{"a": 1, "a": 2}]
+//! let buf = [0x91, 0x82, 0xa1, 0x61, 0x01, 0xa1, 0x61, 0x02];
+//!
+//! let val: Headers = rmp_serde::from_slice(&buf).unwrap();
+//! assert_eq!(vec![("a".to_owned(), 1), ("a".to_owned(), 2)], val.entries);
+//! assert_eq!(buf[..], rmp_serde::to_vec(&val).unwrap());
+//! ```
+
+use std::fmt;
+use std::marker::PhantomData;
+
+use serde::de::{MapAccess, SeqAccess, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// Serializes the pairs as a map with one entry per pair, in order.
+///
+/// # Errors
+///
+/// Serialization can fail if the underlying serializer fails, or if a key or a value fails to
+/// serialize.
+#[inline]
+pub fn serialize<K, V, S>(pairs: &[(K, V)], se: S) -> Result<S::Ok, S::Error>
+where
+    K: Serialize,
+    V: Serialize,
+    S: Serializer,
+{
+    se.collect_map(pairs.iter().map(|(key, val)| (key, val)))
+}
+
+/// Deserializes a map into its entries, in order and including those with duplicate keys.
+///
+/// # Errors
+///
+/// Deserialization fails if the value is neither a map nor a sequence of pairs, or if a key or a
+/// value fails to deserialize.
+#[inline]
+pub fn deserialize<'de, K, V, D>(de: D) -> Result<Vec<(K, V)>, D::Error>
+where
+    K: Deserialize<'de>,
+    V: Deserialize<'de>,
+    D: Deserializer<'de>,
+{
+    de.deserialize_map(PairsVisitor(PhantomData))
+}
+
+struct PairsVisitor<K, V>(PhantomData<fn() -> (K, V)>);
+
+impl<'de, K, V> Visitor<'de> for PairsVisitor<K, V>
+where
+    K: Deserialize<'de>,
+    V: Deserialize<'de>,
+{
+    type Value = Vec<(K, V)>;
+
+    fn expecting(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.write_str("a map")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut pairs = Vec::with_capacity(map.size_hint().unwrap_or(0).min(4096));
+        while let Some(pair) = map.next_entry()? {
+            pairs.push(pair);
+        }
+        Ok(pairs)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut pairs = Vec::with_capacity(seq.size_hint().unwrap_or(0).min(4096));
+        while let Some(pair) = seq.next_element()? {
+            pairs.push(pair);
+        }
+        Ok(pairs)
+    }
+}
@@ -0,0 +1,152 @@
+//! Wrapper types which override how a single field is encoded, regardless of the `Serializer`
+//! configuration.
+//!
+//! Each wrapper only changes the MessagePack representation written by this crate's
+//! `Serializer`. With other serde formats they behave like the wrapped type.
+//!
+//! # Examples
+//!
+//! ```
+//! use rmp_serde::overrides::{AsBin, ForceU64};
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Debug, PartialEq, Deserialize, Serialize)]
+//! struct Record {
+//!     id: ForceU64,
+//!     key: AsBin<String>,
+//! }
+//!
+//! let val = Record { id: ForceU64(1), key: AsBin("k".into()) };
+//! let buf = rmp_serde::to_vec(&val).unwrap();
+//!
+//! // [u64 1, bin8(1) "k"]
+//! assert_eq!(vec![0x92, 0xcf, 0, 0, 0, 0, 0, 0, 0, 1, 0xc4, 0x01, 0x6b], buf);
+//! assert_eq!(val, rmp_serde::from_slice(&buf).unwrap());
+//! ```
+
+use std::fmt;
+
+use serde::de::{self, SeqAccess, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// Name of the Serde newtype struct which is recognized by the `Serializer` in order to write
+/// [`ForceU64`] with a fixed width.
+pub(crate) const MSGPACK_FIXED_U64_STRUCT_NAME: &str = "_FixedU64";
+
+/// An integer which is always encoded as a MessagePack `uint 64`, taking 9 bytes.
+///
+/// By default integers use the smallest representation that fits the value, so `ForceU64(1)` is
+/// encoded as `[0xcf, 0, 0, 0, 0, 0, 0, 0, 1]` instead of `[0x01]`. A fixed width allows, for
+/// example, patching the value in place later on.
+///
+/// Deserialization accepts any integer that fits into a `u64`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ForceU64(pub u64);
+
+impl From<u64> for ForceU64 {
+    #[inline]
+    fn from(val: u64) -> Self {
+        Self(val)
+    }
+}
+
+impl From<ForceU64> for u64 {
+    #[inline]
+    fn from(val: ForceU64) -> Self {
+        val.0
+    }
+}
+
+impl Serialize for ForceU64 {
+    #[inline]
+    fn serialize<S: Serializer>(&self, se: S) -> Result<S::Ok, S::Error> {
+        se.serialize_newtype_struct(MSGPACK_FIXED_U64_STRUCT_NAME, &self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for ForceU64 {
+    #[inline]
+    fn deserialize<D: Deserializer<'de>>(de: D) -> Result<Self, D::Error> {
+        u64::deserialize(de).map(Self)
+    }
+}
+
+/// Bytes which are always encoded as a MessagePack binary, even when the wrapped type is a
+/// string or a `Vec<u8>`, which would otherwise be encoded as a string or as an array of integers.
+///
+/// For example `AsBin("k")` is encoded as `[0xc4, 0x01, 0x6b]` instead of `[0xa1, 0x6b]`, and
+/// `AsBin(vec![1])` as `[0xc4, 0x01, 0x01]` instead of `[0x91, 0x01]`.
+///
+/// `AsBin<Vec<u8>>` and `AsBin<String>` can be deserialized from a binary, a string or an array
+/// of integers, so data written without the wrapper is read as well. For `AsBin<String>` the
+/// bytes must be valid UTF-8.
+#[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct AsBin<T = Vec<u8>>(pub T);
+
+impl<T> From<T> for AsBin<T> {
+    #[inline]
+    fn from(val: T) -> Self {
+        Self(val)
+    }
+}
+
+impl<T: AsRef<[u8]>> Serialize for AsBin<T> {
+    #[inline]
+    fn serialize<S: Serializer>(&self, se: S) -> Result<S::Ok, S::Error> {
+        se.serialize_bytes(self.0.as_ref())
+    }
+}
+
+impl<'de> Deserialize<'de> for AsBin<Vec<u8>> {
+    #[inline]
+    fn deserialize<D: Deserializer<'de>>(de: D) -> Result<Self, D::Error> {
+        de.deserialize_byte_buf(ByteBufVisitor).map(Self)
+    }
+}
+
+impl<'de> Deserialize<'de> for AsBin<String> {
+    fn deserialize<D: Deserializer<'de>>(de: D) -> Result<Self, D::Error> {
+        let buf = de.deserialize_byte_buf(ByteBufVisitor)?;
+        String::from_utf8(buf)
+            .map(Self)
+            .map_err(|err| de::Error::invalid_value(de::Unexpected::Bytes(err.as_bytes()), &"a UTF-8 string"))
+    }
+}
+
+struct ByteBufVisitor;
+
+impl<'de> Visitor<'de> for ByteBufVisitor {
+    type Value = Vec<u8>;
+
+    fn expecting(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.write_str("a binary, a string or an array of bytes")
+    }
+
+    #[inline]
+    fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<Self::Value, E> {
+        Ok(v.to_vec())
+    }
+
+    #[inline]
+    fn visit_byte_buf<E: de::Error>(self, v: Vec<u8>) -> Result<Self::Value, E> {
+        Ok(v)
+    }
+
+    #[inline]
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        Ok(v.as_bytes().to_vec())
+    }
+
+    #[inline]
+    fn visit_string<E: de::Error>(self, v: String) -> Result<Self::Value, E> {
+        Ok(v.into_bytes())
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut buf = Vec::with_capacity(seq.size_hint().unwrap_or(0).min(4096));
+        while let Some(b) = seq.next_element()? {
+            buf.push(b);
+        }
+        Ok(buf)
+    }
+}
@@ -0,0 +1,144 @@
+//! Deserialize a binary into a `bytes::Bytes` that shares the storage of its input, without
+//! copying.
+//!
+//! Intended to be used with `#[serde(with = "rmp_serde::shared_bytes")]` on a `Bytes` field, and
+//! [`from_bytes`] to deserialize the value from a `Bytes` buffer. Requires the `bytes` feature.
+//!
+//! Binaries deserialized any other way, for example with [`from_slice`](crate::from_slice) or
+//! from a reader, are copied into a newly allocated `Bytes`.
+//!
+//! # Examples
+//!
+//! ```
+//! use bytes::Bytes;
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Debug, PartialEq, Deserialize, Serialize)]
+//! struct Packet {
+//!     id: u32,
+//!     #[serde(with = "rmp_serde::shared_bytes")]
+//!     payload: Bytes,
+//! }
+//!
+//! let val = Packet { id: 1, payload: Bytes::from_static(&[0xde, 0xad, 0xbe, 0xef]) };
+//! let buf = Bytes::from(rmp_serde::to_vec(&val).unwrap());
+//!
+//! let packet: Packet = rmp_serde::shared_bytes::from_bytes(&buf).unwrap();
+//! assert_eq!(val, packet);
+//! // The payload points into `buf`.
+//! assert_eq!(buf[4..].as_ptr(), packet.payload.as_ptr());
+//! ```
+
+use std::cell::RefCell;
+use std::fmt;
+
+use ::bytes::Bytes;
+use serde::de::{self, SeqAccess, Visitor};
+use serde::{Deserialize, Deserializer, Serializer};
+
+use crate::decode::Error;
+
+thread_local! {
+    /// The buffer being deserialized by [`from_bytes`] on this thread, if any.
+    static SOURCE: RefCell<Option<Bytes>> = const { RefCell::new(None) };
+}
+
+/// Makes the given buffer the current source until dropped, restoring the previous one, so that
+/// nested calls to [`from_bytes`] work as expected.
+struct SourceGuard(Option<Bytes>);
+
+impl SourceGuard {
+    fn set(source: Bytes) -> Self {
+        Self(SOURCE.with(|cell| cell.replace(Some(source))))
+    }
+}
+
+impl Drop for SourceGuard {
+    fn drop(&mut self) {
+        SOURCE.with(|cell| *cell.borrow_mut() = self.0.take());
+    }
+}
+
+/// Deserializes an instance of type `T` from the given buffer, so that the binaries deserialized
+/// with [`deserialize`] share its storage instead of being copied.
+///
+/// # Errors
+///
+/// This function returns the same errors as [`from_slice`](crate::from_slice).
+pub fn from_bytes<'a, T>(input: &'a Bytes) -> Result<T, Error>
+where
+    T: Deserialize<'a>,
+{
+    let _guard = SourceGuard::set(input.clone());
+    crate::from_slice(input)
+}
+
+/// Serializes the `Bytes` as a binary.
+///
+/// # Errors
+///
+/// Serialization can fail if the underlying serializer fails.
+#[inline]
+pub fn serialize<S: Serializer>(bytes: &Bytes, se: S) -> Result<S::Ok, S::Error> {
+    se.serialize_bytes(bytes)
+}
+
+/// Deserializes a binary into `Bytes`, sharing the storage of the buffer given to [`from_bytes`]
+/// when the binary is borrowed from it and copying it otherwise.
+///
+/// # Errors
+///
+/// Deserialization fails if the value is neither a binary, a string nor a sequence of bytes.
+#[inline]
+pub fn deserialize<'de, D: Deserializer<'de>>(de: D) -> Result<Bytes, D::Error> {
+    de.deserialize_bytes(BytesVisitor)
+}
+
+struct BytesVisitor;
+
+impl<'de> Visitor<'de> for BytesVisitor {
+    type Value = Bytes;
+
+    fn expecting(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.write_str("a binary")
+    }
+
+    fn visit_borrowed_bytes<E: de::Error>(self, v: &'de [u8]) -> Result<Bytes, E> {
+        let shared = SOURCE.with(|cell| {
+            cell.borrow().as_ref().filter(|source| contains(source, v)).map(|source| source.slice_ref(v))
+        });
+        Ok(shared.unwrap_or_else(|| Bytes::copy_from_slice(v)))
+    }
+
+    fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<Bytes, E> {
+        Ok(Bytes::copy_from_slice(v))
+    }
+
+    fn visit_byte_buf<E: de::Error>(self, v: Vec<u8>) -> Result<Bytes, E> {
+        Ok(Bytes::from(v))
+    }
+
+    fn visit_borrowed_str<E: de::Error>(self, v: &'de str) -> Result<Bytes, E> {
+        self.visit_borrowed_bytes(v.as_bytes())
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Bytes, E> {
+        self.visit_bytes(v.as_bytes())
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Bytes, A::Error> {
+        let mut buf = Vec::with_capacity(seq.size_hint().unwrap_or(0).min(4096));
+        while let Some(byte) = seq.next_element()? {
+            buf.push(byte);
+        }
+        Ok(Bytes::from(buf))
+    }
+}
+
+/// Returns `true` if the slice lies within the buffer.
+#[inline]
+fn contains(source: &Bytes, slice: &[u8]) -> bool {
+    let start = source.as_ptr() as usize;
+    let ptr = slice.as_ptr() as usize;
+    ptr >= start && ptr + slice.len() <= start + source.len()
+}
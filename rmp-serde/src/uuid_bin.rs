@@ -0,0 +1,73 @@
+//! Serialize a `Uuid` as a 16-byte MessagePack binary, rather than as a 36-character string.
+//!
+//! Intended to be used with `#[serde(with = "rmp_serde::uuid_bin")]`. Requires the `uuid`
+//! feature.
+//!
+//! For compatibility with data written without this helper, deserialization also accepts a `Uuid`
+//! encoded as a string.
+//!
+//! # Examples
+//!
+//! ```
+//! use serde::{Deserialize, Serialize};
+//! use uuid::Uuid;
+//!
+//! #[derive(Debug, PartialEq, Deserialize, Serialize)]
+//! struct Record {
+//!     #[serde(with = "rmp_serde::uuid_bin")]
+//!     id: Uuid,
+//! }
+//!
+//! let val = Record { id: Uuid::from_u128(0x936da01f_9abd_4d9d_80c7_02af85c822a8) };
+//! let buf = rmp_serde::to_vec(&val).unwrap();
+//!
+//! // [bin8(16) ...]
+//! assert_eq!([0x91, 0xc4, 0x10, 0x93, 0x6d], buf[..5]);
+//! assert_eq!(19, buf.len());
+//! assert_eq!(val, rmp_serde::from_slice(&buf).unwrap());
+//! ```
+
+use std::fmt;
+
+use serde::de::{self, Visitor};
+use serde::{Deserializer, Serializer};
+use uuid::Uuid;
+
+/// Serializes the `Uuid` as a 16-byte binary.
+///
+/// # Errors
+///
+/// Serialization can fail if the underlying serializer fails.
+#[inline]
+pub fn serialize<S: Serializer>(uuid: &Uuid, se: S) -> Result<S::Ok, S::Error> {
+    se.serialize_bytes(uuid.as_bytes())
+}
+
+/// Deserializes a `Uuid` from a 16-byte binary, or from its string representation.
+///
+/// # Errors
+///
+/// Deserialization fails if the binary is not exactly 16 bytes long, or the string is not a valid
+/// `Uuid`.
+#[inline]
+pub fn deserialize<'de, D: Deserializer<'de>>(de: D) -> Result<Uuid, D::Error> {
+    de.deserialize_bytes(UuidVisitor)
+}
+
+struct UuidVisitor;
+
+impl<'de> Visitor<'de> for UuidVisitor {
+    type Value = Uuid;
+
+    fn expecting(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.write_str("a 16-byte binary or a UUID string")
+    }
+
+    fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<Uuid, E> {
+        Uuid::from_slice(v).map_err(|_| E::invalid_length(v.len(), &self))
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Uuid, E> {
+        Uuid::try_parse(v).map_err(E::custom)
+    }
+}
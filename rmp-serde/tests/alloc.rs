@@ -0,0 +1,65 @@
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+use serde::{Deserialize, Serialize};
+
+/// Counts the allocations made by each thread, so that the tests running in parallel do not see
+/// each other's.
+struct CountingAlloc;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.with(|count| count.set(count.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+/// Returns the result of `f` and the number of allocations it made.
+fn count_allocations<T>(f: impl FnOnce() -> T) -> (T, usize) {
+    let before = ALLOCATIONS.with(Cell::get);
+    let res = f();
+    (res, ALLOCATIONS.with(Cell::get) - before)
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Record {
+    id: u64,
+    score: f64,
+    active: bool,
+    rank: u32,
+}
+
+#[test]
+fn pass_struct_field_names_without_allocation() {
+    let records: Vec<Record> = (0..100).map(|id| Record { id, score: 0.5, active: true, rank: 1 }).collect();
+    let buf = rmp_serde::to_vec_named(&records).unwrap();
+    let one = rmp_serde::to_vec_named(&records[0]).unwrap();
+
+    // Field names are matched against slices of the input.
+    let (record, count) = count_allocations(|| rmp_serde::from_slice::<Record>(&one).unwrap());
+    assert_eq!(records[0], record);
+    assert_eq!(0, count);
+
+    // The only allocation is the vector holding the records.
+    let (decoded, count) = count_allocations(|| rmp_serde::from_slice::<Vec<Record>>(&buf).unwrap());
+    assert_eq!(records, decoded);
+    assert_eq!(1, count);
+
+    // Field names are matched against a buffer which the reader allocates once and reuses.
+    let (record, single) = count_allocations(|| rmp_serde::from_read::<_, Record>(&one[..]).unwrap());
+    assert_eq!(records[0], record);
+    let (decoded, count) = count_allocations(|| rmp_serde::from_read::<_, Vec<Record>>(&buf[..]).unwrap());
+    assert_eq!(records, decoded);
+    assert_eq!(single + 1, count);
+}
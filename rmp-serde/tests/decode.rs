@@ -4,7 +4,6 @@ use std::io::Cursor;
 use serde::de;
 use serde::Deserialize;
 
-use rmp::Marker;
 use rmp_serde::decode::{self, Error};
 use rmp_serde::{Deserializer, Raw, RawRef};
 
@@ -22,7 +21,7 @@ fn fail_nil_from_reserved() {
 
     let res: Result<(), Error> = Deserialize::deserialize(&mut de);
     match res.err() {
-        Some(Error::TypeMismatch(Marker::Reserved)) => (),
+        Some(Error::ReservedMarker) => (),
         other => panic!("unexpected result: {other:?}"),
     }
 }
@@ -238,7 +237,6 @@ fn pass_tuple() {
     assert_eq!((42, 100500), actual);
 }
 
-#[ignore]
 #[test]
 fn fail_tuple_len_mismatch() {
     let buf = [0x92, 0x2a, 0xce, 0x0, 0x1, 0x88, 0x94];
@@ -251,6 +249,42 @@ fn fail_tuple_len_mismatch() {
         Error::LengthMismatch(1) => (),
         other => panic!("unexpected result: {other:?}"),
     }
+    // Nothing past the array header is read.
+    assert_eq!(1, de.get_ref().position());
+}
+
+#[test]
+fn pass_tuple_with_borrowed_fields() {
+    // ["le", 42, [0xde, 0xad]]
+    let buf = [0x93, 0xa2, 0x6c, 0x65, 0x2a, 0xc4, 0x02, 0xde, 0xad];
+
+    let (name, id, data): (&str, u32, &[u8]) = rmp_serde::from_slice(&buf).unwrap();
+
+    assert_eq!(("le", 42, &[0xde, 0xad][..]), (name, id, data));
+    // The string and the binary point into the input.
+    assert_eq!(buf[2..].as_ptr(), name.as_ptr());
+    assert_eq!(buf[7..].as_ptr(), data.as_ptr());
+
+    // Borrowing survives nesting into a sequence of tuples.
+    let buf = [0x91, 0x92, 0xa1, 0x61, 0xc4, 0x01, 0x00];
+    let records: Vec<(&str, &[u8])> = rmp_serde::from_slice(&buf).unwrap();
+    assert_eq!(vec![("a", &[0x00][..])], records);
+    assert_eq!(buf[3..].as_ptr(), records[0].0.as_ptr());
+    assert_eq!(buf[6..].as_ptr(), records[0].1.as_ptr());
+}
+
+#[test]
+fn fail_tuple_too_short() {
+    let buf = [0x92, 0x2a, 0xce, 0x0, 0x1, 0x88, 0x94];
+
+    match rmp_serde::from_slice::<(u32, u32, u32)>(&buf).unwrap_err() {
+        Error::LengthMismatch(3) => (),
+        other => panic!("unexpected result: {other:?}"),
+    }
+    match rmp_serde::from_slice::<[u32; 1]>(&buf).unwrap_err() {
+        Error::LengthMismatch(1) => (),
+        other => panic!("unexpected result: {other:?}"),
+    }
 }
 
 #[test]
@@ -297,7 +331,7 @@ fn fail_option_u8_from_reserved() {
     let mut de = Deserializer::new(cur);
     let actual: Result<Option<u8>, Error> = Deserialize::deserialize(&mut de);
     match actual.err() {
-        Some(Error::TypeMismatch(Marker::Reserved)) => (),
+        Some(Error::ReservedMarker) => (),
         other => panic!("unexpected result: {other:?}"),
     }
 }
@@ -567,3 +601,253 @@ fn fail_depth_limit() {
         other => panic!("unexpected result: {other:?}"),
     }
 }
+
+#[test]
+fn fail_depth_limit_by_default() {
+    #[derive(Debug, Deserialize)]
+    #[allow(dead_code)]
+    struct Nested(Vec<Nested>);
+
+    #[derive(Debug, Deserialize)]
+    #[allow(dead_code)]
+    enum Tree {
+        Leaf,
+        Node(Box<Tree>),
+    }
+
+    // Unoptimized builds need more than the default stack of a test thread for 1024 levels.
+    std::thread::Builder::new()
+        .stack_size(32 * 1024 * 1024)
+        .spawn(|| {
+            // Far deeper than the default limit, which must be hit before the stack overflows.
+            let data = vec![0x91; 100_000];
+            match rmp_serde::from_slice::<Nested>(&data) {
+                Err(decode::Error::DepthLimitExceeded) => (),
+                other => panic!("unexpected result: {other:?}"),
+            }
+
+            // {"Node": {"Node": ...}}
+            let data = [0x81, 0xa4, b'N', b'o', b'd', b'e'].repeat(100_000);
+            match rmp_serde::from_slice::<Tree>(&data) {
+                Err(decode::Error::DepthLimitExceeded) => (),
+                other => panic!("unexpected result: {other:?}"),
+            }
+        })
+        .unwrap()
+        .join()
+        .unwrap();
+
+    let data = [0x81, 0xa4, b'N', b'o', b'd', b'e'].repeat(100);
+    let mut de = Deserializer::new(&data[..]);
+    de.set_max_depth(3);
+    match Tree::deserialize(&mut de) {
+        Err(decode::Error::DepthLimitExceeded) => (),
+        other => panic!("unexpected result: {other:?}"),
+    }
+
+    // Nesting within the limit is fine.
+    let mut data = [0x81, 0xa4, b'N', b'o', b'd', b'e'].repeat(100);
+    data.extend_from_slice(&[0xa4, b'L', b'e', b'a', b'f']);
+    assert!(rmp_serde::from_slice::<Tree>(&data).is_ok());
+}
+
+#[test]
+fn fail_length_limit() {
+    // A str32 declaring 4 GiB of data, which is never read.
+    let buf = [0xdb, 0xff, 0xff, 0xff, 0xff];
+    let mut de = Deserializer::new(Cursor::new(&buf[..]));
+    de.set_max_string_len(1024);
+    match String::deserialize(&mut de) {
+        Err(Error::LengthLimitExceeded(0xffff_ffff)) => (),
+        other => panic!("unexpected result: {other:?}"),
+    }
+    assert_eq!(5, de.position());
+
+    // A bin8 of 3 bytes: [0xc4, 0x03, 1, 2, 3]
+    let buf = [0xc4, 0x03, 0x01, 0x02, 0x03];
+    let mut de = Deserializer::new(&buf[..]);
+    de.set_max_bin_len(2);
+    match serde_bytes::ByteBuf::deserialize(&mut de) {
+        Err(Error::LengthLimitExceeded(3)) => (),
+        other => panic!("unexpected result: {other:?}"),
+    }
+
+    // Lengths at the limit are accepted, and the string limit does not affect binaries.
+    let mut de = Deserializer::from_read_ref(&buf[..]);
+    de.set_max_bin_len(3);
+    de.set_max_string_len(0);
+    assert_eq!(vec![1, 2, 3], serde_bytes::ByteBuf::deserialize(&mut de).unwrap().into_vec());
+}
+
+#[test]
+fn fail_reserved_marker() {
+    let buf = [0xc1];
+
+    fn check<T: std::fmt::Debug + for<'de> Deserialize<'de>>(buf: &[u8]) {
+        match rmp_serde::from_slice::<T>(buf) {
+            Err(Error::ReservedMarker) => (),
+            other => panic!("unexpected result: {other:?}"),
+        }
+        match rmp_serde::from_read::<_, T>(buf) {
+            Err(Error::ReservedMarker) => (),
+            other => panic!("unexpected result: {other:?}"),
+        }
+    }
+
+    check::<u8>(&buf);
+    check::<i128>(&buf);
+    check::<f64>(&buf);
+    check::<bool>(&buf);
+    check::<String>(&buf);
+    check::<Option<u8>>(&buf);
+    check::<Vec<u8>>(&buf);
+    check::<()>(&buf);
+    check::<de::IgnoredAny>(&buf);
+    // Nested inside containers: [0xc1] and {"a": 0xc1}
+    check::<Vec<u8>>(&[0x91, 0xc1]);
+    check::<std::collections::BTreeMap<String, u8>>(&[0x81, 0xa1, 0x61, 0xc1]);
+    // While skipping a value of unexpected type: [[0xc1]] read as u8
+    check::<u8>(&[0x91, 0xc1]);
+}
+
+#[test]
+fn pass_struct_field_names_without_allocation() {
+    /// Records how the deserializer handed out a field name.
+    #[derive(Debug, PartialEq)]
+    enum Key {
+        Borrowed(&'static str),
+        Copied(String),
+        Owned(String),
+    }
+
+    struct KeyVisitor;
+
+    impl<'de> de::Visitor<'de> for KeyVisitor {
+        type Value = Key;
+
+        fn expecting(&self, fmt: &mut Formatter<'_>) -> fmt::Result {
+            fmt.write_str("a field name")
+        }
+
+        fn visit_borrowed_str<E>(self, _: &'de str) -> Result<Key, E> {
+            Ok(Key::Borrowed("borrowed"))
+        }
+
+        fn visit_str<E>(self, v: &str) -> Result<Key, E> {
+            Ok(Key::Copied(v.into()))
+        }
+
+        fn visit_string<E>(self, v: String) -> Result<Key, E> {
+            Ok(Key::Owned(v))
+        }
+    }
+
+    impl<'de> Deserialize<'de> for Key {
+        fn deserialize<D: de::Deserializer<'de>>(de: D) -> Result<Self, D::Error> {
+            de.deserialize_identifier(KeyVisitor)
+        }
+    }
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Struct {
+        name: u8,
+    }
+
+    // {"name": 1}
+    let buf = [0x81, 0xa4, 0x6e, 0x61, 0x6d, 0x65, 0x01];
+
+    // Field names are compared against a slice of the input, or against a buffer reused by the
+    // reader, and never collected into a `String`.
+    assert_eq!(Key::Borrowed("borrowed"), rmp_serde::from_slice(&buf[1..6]).unwrap());
+    assert_eq!(Key::Copied("name".into()), rmp_serde::from_read(&buf[1..6]).unwrap());
+    assert_eq!(Struct { name: 1 }, rmp_serde::from_slice(&buf).unwrap());
+    assert_eq!(Struct { name: 1 }, rmp_serde::from_read(&buf[..]).unwrap());
+}
+
+#[cfg(feature = "bytes")]
+#[test]
+fn pass_shared_bytes() {
+    use bytes::Bytes;
+
+    #[derive(Debug, Deserialize)]
+    struct Packet {
+        #[serde(with = "rmp_serde::shared_bytes")]
+        header: Bytes,
+        #[serde(with = "rmp_serde::shared_bytes")]
+        payload: Bytes,
+    }
+
+    // [bin8(2), bin8(3)]
+    let buf = Bytes::from(vec![0x92, 0xc4, 0x02, 0x01, 0x02, 0xc4, 0x03, 0x03, 0x04, 0x05]);
+
+    let packet: Packet = rmp_serde::shared_bytes::from_bytes(&buf).unwrap();
+    assert_eq!(&[0x01, 0x02], &packet.header[..]);
+    assert_eq!(&[0x03, 0x04, 0x05], &packet.payload[..]);
+    assert_eq!(buf[3..].as_ptr(), packet.header.as_ptr());
+    assert_eq!(buf[7..].as_ptr(), packet.payload.as_ptr());
+
+    // The shared storage outlives the original handle.
+    let ptr = buf.as_ptr();
+    drop(buf);
+    assert_eq!(ptr.wrapping_add(7), packet.payload.as_ptr());
+    assert_eq!(&[0x03, 0x04, 0x05], &packet.payload[..]);
+
+    // Without `from_bytes` the binaries are copied.
+    let buf = [0x92, 0xc4, 0x02, 0x01, 0x02, 0xc4, 0x00];
+    let packet: Packet = rmp_serde::from_slice(&buf).unwrap();
+    assert_eq!(&[0x01, 0x02], &packet.header[..]);
+    assert_ne!(buf[3..].as_ptr(), packet.header.as_ptr());
+    assert!(packet.payload.is_empty());
+}
+
+#[test]
+fn fail_ext_forbidden() {
+    #[derive(Debug, PartialEq, Deserialize)]
+    #[serde(rename = "_ExtStruct")]
+    struct ExtStruct((i8, serde_bytes::ByteBuf));
+
+    // fixext 1
+    let buf = [0xd4, 0x01, 0x2a];
+    let ext = ExtStruct((1, serde_bytes::ByteBuf::from(vec![0x2a])));
+    assert_eq!(ext, rmp_serde::from_slice(&buf).unwrap());
+
+    fn check<T: std::fmt::Debug + for<'de> Deserialize<'de>>(buf: &[u8]) {
+        let mut de = Deserializer::from_read_ref(buf).with_ext_forbidden().with_binary();
+        match T::deserialize(&mut de) {
+            Err(Error::ExtForbidden) => (),
+            other => panic!("unexpected result: {other:?}"),
+        }
+    }
+
+    check::<ExtStruct>(&buf);
+    check::<de::IgnoredAny>(&buf);
+    // Nested inside a container: [1, fixext 1]
+    check::<(u8, ExtStruct)>(&[0x92, 0x01, 0xd4, 0x01, 0x2a]);
+
+    let mut de = Deserializer::new(&[0x92, 0x01, 0xa1, 0x61][..]).with_ext_forbidden();
+    assert_eq!((1, "a".to_owned()), Deserialize::deserialize(&mut de).unwrap());
+}
+
+#[test]
+fn pass_from_buf_read() {
+    // ["le", "message", bin 8 [1, 2, 3, 4, 5, 6]], 42
+    let buf = [
+        0x93, 0xa2, 0x6c, 0x65, 0xa7, 0x6d, 0x65, 0x73, 0x73, 0x61, 0x67, 0x65, 0xc4, 0x06, 0x01, 0x02, 0x03, 0x04,
+        0x05, 0x06, 0x2a,
+    ];
+
+    // With a small buffer, the second string and the binary cross refills.
+    for capacity in [1, 4, 64] {
+        let mut de = Deserializer::from_buf_read(std::io::BufReader::with_capacity(capacity, &buf[..]));
+        let val: (String, String, serde_bytes::ByteBuf) = Deserialize::deserialize(&mut de).unwrap();
+        assert_eq!(("le".into(), "message".into(), serde_bytes::ByteBuf::from(vec![1, 2, 3, 4, 5, 6])), val);
+
+        let mut rd = de.into_inner();
+        let mut rest = Vec::new();
+        std::io::Read::read_to_end(&mut rd, &mut rest).unwrap();
+        assert_eq!(vec![0x2a], rest);
+    }
+
+    let val: u8 = rmp_serde::from_buf_read(&buf[20..]).unwrap();
+    assert_eq!(42, val);
+}
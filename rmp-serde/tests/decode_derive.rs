@@ -94,6 +94,300 @@ fn pass_struct_from_map() {
     assert_eq!(expected, actual);
 }
 
+#[test]
+fn pass_struct_from_map_in_any_order() {
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Struct {
+        id: u32,
+        name: String,
+        #[serde(default)]
+        tags: Vec<String>,
+        active: bool,
+    }
+
+    let buf = [
+        0x83, // 3 (size)
+        0xa6, 0x61, 0x63, 0x74, 0x69, 0x76, 0x65, // "active"
+        0xc3, // true
+        0xa4, 0x6e, 0x61, 0x6d, 0x65, // "name"
+        0xa2, 0x6c, 0x65, // "le"
+        0xa2, 0x69, 0x64, // "id"
+        0x2a, // 42
+    ];
+
+    // Fields are matched by name, so the order does not matter, and the missing field is defaulted.
+    let mut de = Deserializer::new(&buf[..]);
+    let actual = Struct::deserialize(&mut de).unwrap();
+    let expected = Struct { id: 42, name: "le".into(), tags: Vec::new(), active: true };
+
+    assert_eq!(expected, actual);
+    assert!(de.into_inner().is_empty());
+
+    // A missing field without a default is still an error.
+    let buf = [0x81, 0xa2, 0x69, 0x64, 0x2a];
+    match rmp_serde::from_slice::<Struct>(&buf) {
+        Err(Error::Syntax(msg)) => assert_eq!("missing field `name`", msg),
+        other => panic!("unexpected result: {other:?}"),
+    }
+}
+
+#[test]
+fn pass_struct_with_trailing_nils_padded() {
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Struct {
+        a: u8,
+        b: Option<u8>,
+        c: Option<u8>,
+    }
+
+    // [1, 2]
+    let buf = [0x92, 0x01, 0x02];
+    let mut de = Deserializer::new(&buf[..]).with_trailing_nils_padded();
+    assert_eq!(Struct { a: 1, b: Some(2), c: None }, Struct::deserialize(&mut de).unwrap());
+
+    // Without padding the short array is rejected.
+    let mut de = Deserializer::new(&buf[..]);
+    Struct::deserialize(&mut de).unwrap_err();
+
+    // A missing field which can not be read from nil is still an error.
+    let buf = [0x90];
+    let mut de = Deserializer::new(&buf[..]).with_trailing_nils_padded();
+    Struct::deserialize(&mut de).unwrap_err();
+
+    // Longer arrays are rejected as usual: [1, 2, 3, 4]
+    let buf = [0x94, 0x01, 0x02, 0x03, 0x04];
+    let mut de = Deserializer::new(&buf[..]).with_trailing_nils_padded();
+    match Struct::deserialize(&mut de) {
+        Err(Error::LengthMismatch(3)) => (),
+        other => panic!("unexpected result: {other:?}"),
+    }
+    // Tuple structs are padded as well: [1]
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Tuple(u8, Option<u8>, ());
+
+    let mut de = Deserializer::new(&[0x91, 0x01][..]).with_trailing_nils_padded();
+    assert_eq!(Tuple(1, None, ()), Tuple::deserialize(&mut de).unwrap());
+    rmp_serde::from_slice::<Tuple>(&[0x91, 0x01]).unwrap_err();
+
+    // [1, nil, nil, 4]
+    let mut de = Deserializer::new(&[0x94, 0x01, 0xc0, 0xc0, 0x04][..]).with_trailing_nils_padded();
+    match Tuple::deserialize(&mut de) {
+        Err(Error::LengthMismatch(3)) => (),
+        other => panic!("unexpected result: {other:?}"),
+    }
+}
+
+#[test]
+fn pass_struct_with_extra_elements_ignored() {
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Struct {
+        a: u8,
+        b: String,
+    }
+
+    // [[1, "le", [2, {"c": nil}], "d"], 3]
+    let buf = [0x92, 0x94, 0x01, 0xa2, 0x6c, 0x65, 0x92, 0x02, 0x81, 0xa1, 0x63, 0xc0, 0xa1, 0x64, 0x03];
+    let mut de = Deserializer::new(&buf[..]).with_extra_elements_ignored();
+    let actual = <(Struct, u8)>::deserialize(&mut de).unwrap();
+    assert_eq!((Struct { a: 1, b: "le".into() }, 3), actual);
+    assert!(de.into_inner().is_empty());
+
+    // Strict by default.
+    match rmp_serde::from_slice::<(Struct, u8)>(&buf) {
+        Err(Error::LengthMismatch(2)) => (),
+        other => panic!("unexpected result: {other:?}"),
+    }
+
+    // Tuples are not affected, and missing fields are still an error: [1, 2, 3] and [1]
+    let mut de = Deserializer::new(&[0x93, 0x01, 0x02, 0x03][..]).with_extra_elements_ignored();
+    <(u8, u8)>::deserialize(&mut de).unwrap_err();
+    let mut de = Deserializer::new(&[0x91, 0x01][..]).with_extra_elements_ignored();
+    Struct::deserialize(&mut de).unwrap_err();
+
+    // Combined with padding: [1] with an optional field, then [1, nil, 2].
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Optional {
+        a: u8,
+        b: Option<u8>,
+    }
+
+    let mut de = Deserializer::new(&[0x91, 0x01][..]).with_trailing_nils_padded().with_extra_elements_ignored();
+    assert_eq!(Optional { a: 1, b: None }, Optional::deserialize(&mut de).unwrap());
+    let mut de = Deserializer::new(&[0x93, 0x01, 0xc0, 0x02][..]).with_trailing_nils_padded().with_extra_elements_ignored();
+    assert_eq!(Optional { a: 1, b: None }, Optional::deserialize(&mut de).unwrap());
+}
+
+#[test]
+fn pass_nil_as_default() {
+    #[derive(Debug, Default, PartialEq, Deserialize)]
+    #[serde(default)]
+    struct Inner {
+        c: u8,
+    }
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Struct {
+        a: u32,
+        b: f64,
+        c: String,
+        d: bool,
+        e: Vec<u8>,
+        f: Option<u8>,
+        g: Inner,
+    }
+
+    // [nil, nil, nil, nil, nil, nil, nil]
+    let buf = [0x97, 0xc0, 0xc0, 0xc0, 0xc0, 0xc0, 0xc0, 0xc0];
+    let mut de = Deserializer::new(&buf[..]).with_nil_as_default();
+    let expected = Struct { a: 0, b: 0.0, c: String::new(), d: false, e: Vec::new(), f: None, g: Inner { c: 0 } };
+    assert_eq!(expected, Struct::deserialize(&mut de).unwrap());
+
+    // Strict by default.
+    rmp_serde::from_slice::<Struct>(&buf).unwrap_err();
+
+    // Structs encoded as maps, with a borrowed string: {"a": 1, "c": nil}
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Borrowed<'a> {
+        a: i8,
+        c: &'a str,
+    }
+
+    let buf = [0x82, 0xa1, 0x61, 0x01, 0xa1, 0x63, 0xc0];
+    let mut de = Deserializer::from_read_ref(&buf).with_nil_as_default();
+    assert_eq!(Borrowed { a: 1, c: "" }, Borrowed::deserialize(&mut de).unwrap());
+
+    // Structs without defaults still require their fields.
+    let mut de = Deserializer::new(&[0xc0][..]).with_nil_as_default();
+    Struct::deserialize(&mut de).unwrap_err();
+
+    // Combined with padding: [1]
+    let mut de = Deserializer::new(&[0x91, 0x01][..]).with_nil_as_default().with_trailing_nils_padded();
+    let expected = Struct { a: 1, b: 0.0, c: String::new(), d: false, e: Vec::new(), f: None, g: Inner { c: 0 } };
+    assert_eq!(expected, Struct::deserialize(&mut de).unwrap());
+    // Tuple structs, with every missing trailing field becoming its default: [1]
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Tuple(u8, u32, String, Option<u8>);
+
+    let mut de = Deserializer::new(&[0x91, 0x01][..]).with_nil_as_default().with_trailing_nils_padded();
+    assert_eq!(Tuple(1, 0, String::new(), None), Tuple::deserialize(&mut de).unwrap());
+
+    // nil is an empty array to a tuple struct, which needs defaults for its fields.
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Defaults(#[serde(default)] u8, #[serde(default)] String);
+
+    let mut de = Deserializer::new(&[0xc0][..]).with_nil_as_default();
+    assert_eq!(Defaults(0, String::new()), Defaults::deserialize(&mut de).unwrap());
+    let mut de = Deserializer::new(&[0xc0][..]).with_nil_as_default();
+    Tuple::deserialize(&mut de).unwrap_err();
+}
+
+#[test]
+fn pass_struct_with_defaulted_trailing_fields() {
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Tuple(u8, #[serde(default)] u32, #[serde(default)] String);
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Struct {
+        a: u8,
+        #[serde(default)]
+        b: u32,
+    }
+
+    #[derive(Debug, PartialEq, Default, Deserialize)]
+    #[serde(default)]
+    struct Defaults {
+        a: u8,
+        b: Vec<u8>,
+    }
+
+    // [1], as written before the trailing fields were added.
+    let buf = [0x91, 0x01];
+
+    assert_eq!(Tuple(1, 0, String::new()), rmp_serde::from_slice(&buf).unwrap());
+    assert_eq!(Struct { a: 1, b: 0 }, rmp_serde::from_slice(&buf).unwrap());
+    assert_eq!(Defaults { a: 1, b: vec![] }, rmp_serde::from_slice(&buf).unwrap());
+    assert_eq!(Defaults::default(), rmp_serde::from_slice(&[0x90]).unwrap());
+
+    // Fields that are present are read as usual.
+    assert_eq!(Tuple(1, 2, "a".into()), rmp_serde::from_slice(&[0x93, 0x01, 0x02, 0xa1, 0x61]).unwrap());
+
+    // Without a default, the missing field is still an error.
+    #[derive(Debug, Deserialize)]
+    struct Required(#[allow(unused)] u8, #[allow(unused)] u32);
+    rmp_serde::from_slice::<Required>(&buf).unwrap_err();
+}
+
+#[test]
+fn pass_internally_tagged_enum_with_tag_anywhere() {
+    #[derive(Debug, PartialEq, Deserialize)]
+    #[serde(tag = "type")]
+    enum Enum {
+        A { x: u8 },
+        B { x: u8, y: bool },
+    }
+
+    // {"x": 1, "type": "A"}
+    let buf = [0x82, 0xa1, 0x78, 0x01, 0xa4, 0x74, 0x79, 0x70, 0x65, 0xa1, 0x41];
+    assert_eq!(Enum::A { x: 1 }, rmp_serde::from_slice(&buf).unwrap());
+    assert_eq!(Enum::A { x: 1 }, rmp_serde::from_read(&buf[..]).unwrap());
+
+    // {"y": true, "type": "B", "x": 2}
+    let buf = [0x83, 0xa1, 0x79, 0xc3, 0xa4, 0x74, 0x79, 0x70, 0x65, 0xa1, 0x42, 0xa1, 0x78, 0x02];
+    assert_eq!(Enum::B { x: 2, y: true }, rmp_serde::from_slice(&buf).unwrap());
+
+    // ["B", 2, true]
+    let buf = [0x93, 0xa1, 0x42, 0x02, 0xc3];
+    assert_eq!(Enum::B { x: 2, y: true }, rmp_serde::from_slice(&buf).unwrap());
+
+    // {"x": 1}
+    let buf = [0x81, 0xa1, 0x78, 0x01];
+    rmp_serde::from_slice::<Enum>(&buf).unwrap_err();
+}
+
+#[test]
+fn pass_struct_from_array_or_map() {
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Inner {
+        a: u8,
+        b: bool,
+    }
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Outer {
+        inner: Inner,
+        items: Vec<Inner>,
+    }
+
+    let expected = Outer {
+        inner: Inner { a: 1, b: true },
+        items: vec![Inner { a: 2, b: false }, Inner { a: 3, b: true }],
+    };
+
+    // [[1, true], [[2, false], {"b": true, "a": 3}]]
+    let buf = [
+        0x92,
+        0x92, 0x01, 0xc3,
+        0x92,
+        0x92, 0x02, 0xc2,
+        0x82, 0xa1, 0x62, 0xc3, 0xa1, 0x61, 0x03,
+    ];
+    let mut de = Deserializer::new(&buf[..]);
+    assert_eq!(expected, Outer::deserialize(&mut de).unwrap());
+
+    // {"items": [{"a": 2, "b": false}, [3, true]], "inner": {"a": 1, "b": true}}
+    let buf = [
+        0x82,
+        0xa5, 0x69, 0x74, 0x65, 0x6d, 0x73,
+        0x92,
+        0x82, 0xa1, 0x61, 0x02, 0xa1, 0x62, 0xc2,
+        0x92, 0x03, 0xc3,
+        0xa5, 0x69, 0x6e, 0x6e, 0x65, 0x72,
+        0x82, 0xa1, 0x61, 0x01, 0xa1, 0x62, 0xc3,
+    ];
+    let mut de = Deserializer::new(&buf[..]);
+    assert_eq!(expected, Outer::deserialize(&mut de).unwrap());
+}
+
 #[test]
 fn pass_unit_variant() {
     // We expect enums to be encoded as a map {variant_idx => nil}
@@ -474,3 +768,99 @@ fn pass_from_ref() {
 
     assert_eq!(Dog { name: "Bobby", age: 8 }, rmp_serde::from_read_ref(&buf).unwrap());
 }
+
+#[test]
+fn pass_key_order_recorded() {
+    use serde::de;
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Inner {
+        x: u8,
+    }
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Outer {
+        a: u8,
+        inner: Inner,
+        b: u8,
+    }
+
+    // {"b": 1, "inner": {"x": 2}, "a": 3}, then {"a": 4, "inner": {"x": 5}, "b": 6}
+    let buf = [
+        0x83, 0xa1, b'b', 0x01, 0xa5, b'i', b'n', b'n', b'e', b'r', 0x81, 0xa1, b'x', 0x02, 0xa1, b'a', 0x03,
+        0x83, 0xa1, b'a', 0x04, 0xa5, b'i', b'n', b'n', b'e', b'r', 0x81, 0xa1, b'x', 0x05, 0xa1, b'b', 0x06,
+    ];
+    let mut de = Deserializer::from_read_ref(&buf).with_key_order_recorded();
+
+    let val = Outer::deserialize(&mut de).unwrap();
+    assert_eq!(Outer { a: 3, inner: Inner { x: 2 }, b: 1 }, val);
+    assert_eq!(["b", "inner", "a"], de.key_order());
+
+    // The keys of a single map are recorded until taken.
+    Outer::deserialize(&mut de).unwrap();
+    assert_eq!(vec!["b", "inner", "a"], de.take_key_order());
+    assert!(de.key_order().is_empty());
+
+    // Keys which are not strings are left out: {1: "a", "k": "b"}
+    let buf = [0x82, 0x01, 0xa1, b'a', 0xa1, b'k', 0xa1, b'b'];
+    let mut de = Deserializer::new(&buf[..]).with_key_order_recorded();
+    de::IgnoredAny::deserialize(&mut de).unwrap();
+    assert_eq!(["k"], de.key_order());
+
+    // Nothing is recorded by default.
+    let mut de = Deserializer::from_read_ref(&buf);
+    de::IgnoredAny::deserialize(&mut de).unwrap();
+    assert!(de.key_order().is_empty());
+}
+
+#[test]
+fn pass_on_unknown_field() {
+    use std::collections::BTreeMap;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Inner {
+        #[serde(alias = "y")]
+        x: u8,
+    }
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Outer {
+        inner: Inner,
+        map: BTreeMap<String, u8>,
+    }
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    #[serde(deny_unknown_fields)]
+    struct Strict {
+        a: u8,
+    }
+
+    let recorder = || {
+        let names = Arc::new(Mutex::new(Vec::new()));
+        let hook = Arc::clone(&names);
+        (names, move |name: &str| hook.lock().unwrap().push(name.to_owned()))
+    };
+
+    // {"inner": {"y": 1, "z": 2}, "map": {"k": 3}, "extra": [4]}
+    let buf = [
+        0x83, 0xa5, b'i', b'n', b'n', b'e', b'r', 0x82, 0xa1, b'y', 0x01, 0xa1, b'z', 0x02, 0xa3, b'm', b'a', b'p',
+        0x81, 0xa1, b'k', 0x03, 0xa5, b'e', b'x', b't', b'r', b'a', 0x91, 0x04,
+    ];
+    let (names, hook) = recorder();
+    let mut de = Deserializer::new(&buf[..]).on_unknown_field(hook);
+    let val = Outer::deserialize(&mut de).unwrap();
+    assert_eq!(Outer { inner: Inner { x: 1 }, map: vec![("k".into(), 3)].into_iter().collect() }, val);
+    // Neither aliases nor the keys of maps are unknown fields.
+    assert_eq!(vec!["z", "extra"], *names.lock().unwrap());
+
+    // The hook does not decide whether the field is rejected: {"b": 1, "a": 2}
+    let buf = [0x82, 0xa1, b'b', 0x01, 0xa1, b'a', 0x02];
+    let (names, hook) = recorder();
+    let mut de = Deserializer::from_read_ref(&buf).on_unknown_field(hook);
+    match Strict::deserialize(&mut de) {
+        Err(Error::Syntax(..)) => {}
+        other => panic!("unexpected result: {other:?}"),
+    }
+    assert_eq!(vec!["b"], *names.lock().unwrap());
+}
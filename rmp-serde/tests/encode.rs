@@ -300,6 +300,47 @@ fn pass_map() {
     assert_eq!(out, buf);
 }
 
+#[test]
+fn pass_map_streamed_with_serialize_entry() {
+    use serde::ser::{SerializeMap, Serializer as _};
+
+    /// Streams the squares of `0..n`, optionally without telling the length up front.
+    struct Squares {
+        n: u32,
+        known_len: bool,
+    }
+
+    impl Serialize for Squares {
+        fn serialize<S: serde::Serializer>(&self, se: S) -> Result<S::Ok, S::Error> {
+            let mut map = se.serialize_map(self.known_len.then_some(self.n as usize))?;
+            for i in 0..self.n {
+                map.serialize_entry(&i, &(i * i))?;
+            }
+            map.end()
+        }
+    }
+
+    for known_len in [true, false] {
+        let mut buf = Vec::new();
+        Squares { n: 2, known_len }.serialize(&mut Serializer::new(&mut buf)).unwrap();
+        assert_eq!(vec![0x82, 0x00, 0x00, 0x01, 0x01], buf);
+
+        let mut buf = Vec::new();
+        Squares { n: 20, known_len }.serialize(&mut Serializer::new(&mut buf)).unwrap();
+        assert_eq!([0xde, 0x00, 0x14, 0x00, 0x00], buf[..5]);
+        assert_eq!(&[0x13, 0xcd, 0x01, 0x69], &buf[buf.len() - 4..]);
+    }
+
+    // Maps of unknown length nested in each other.
+    let mut buf = Vec::new();
+    let mut se = Serializer::new(&mut buf);
+    let mut map = (&mut se).serialize_map(None).unwrap();
+    map.serialize_entry("a", &Squares { n: 1, known_len: false }).unwrap();
+    map.serialize_entry("b", &Squares { n: 0, known_len: false }).unwrap();
+    map.end().unwrap();
+    assert_eq!(vec![0x82, 0xa1, 0x61, 0x81, 0x00, 0x00, 0xa1, 0x62, 0x80], buf);
+}
+
 #[test]
 fn pass_empty_map() {
     use std::collections::BTreeMap;
@@ -347,6 +388,32 @@ fn pass_to_vec() {
     );
 }
 
+#[test]
+fn pass_to_writer() {
+    #[derive(Serialize)]
+    struct Struct {
+        a: u8,
+    }
+
+    let mut buf = Vec::new();
+    rmps::to_writer(&mut buf, &(42u8, "the Answer")).unwrap();
+    assert_eq!(vec![0x92, 0x2a, 0xaa, 0x74, 0x68, 0x65, 0x20, 0x41, 0x6e, 0x73, 0x77, 0x65, 0x72], buf);
+
+    let mut buf = Vec::new();
+    rmps::to_writer_named(&mut buf, &Struct { a: 42 }).unwrap();
+    assert_eq!(vec![0x81, 0xa1, 0x61, 0x2a], buf);
+}
+
+#[test]
+fn fail_to_writer_short_buffer() {
+    let mut buf = [0x00; 4];
+
+    match rmps::to_writer(&mut buf[..], "le message") {
+        Err(Error::InvalidValueWrite(..)) => (),
+        other => panic!("unexpected result: {other:?}"),
+    }
+}
+
 #[test]
 fn get_mut() {
     let mut se = Serializer::new(Vec::new());
@@ -409,3 +476,340 @@ fn pass_raw_ref_invalid_utf8() {
 fn serializer_one_type_arg() {
     let _s: rmp_serde::Serializer<&mut dyn std::io::Write>;
 }
+
+#[test]
+fn pass_whole_floats_compacted_only_when_exact() {
+    fn compact<T: Serialize>(val: T) -> Vec<u8> {
+        let mut se = Serializer::new(Vec::new()).with_whole_floats_compacted();
+        val.serialize(&mut se).unwrap();
+        se.into_inner()
+    }
+
+    assert_eq!(vec![0x00], compact(0.0f64));
+    assert_eq!(vec![0xd3, 0x80, 0, 0, 0, 0, 0, 0, 0], compact(i64::MIN as f64));
+    assert_eq!(vec![0xcf, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xf8, 0x00], compact(18_446_744_073_709_549_568.0f64));
+    // Out of range of the integers no larger than the float.
+    assert_eq!(vec![0xcb, 0x43, 0xf0, 0, 0, 0, 0, 0, 0], compact(u64::MAX as f64));
+    assert_eq!(vec![0xca, 0x4f, 0x80, 0x00, 0x00], compact(u32::MAX as f32));
+    assert_eq!(vec![0xd2, 0x80, 0x00, 0x00, 0x00], compact(i32::MIN as f32));
+    // Never whole.
+    assert_eq!(vec![0xcb, 0x80, 0, 0, 0, 0, 0, 0, 0], compact(-0.0f64));
+    assert_eq!(vec![0xca, 0x7f, 0x80, 0x00, 0x00], compact(f32::INFINITY));
+    assert_eq!(0xcb, compact(f64::NAN)[0]);
+}
+
+#[test]
+fn pass_map_keys_sorted() {
+    use std::collections::HashMap;
+
+    fn sorted<T: Serialize>(val: T) -> Vec<u8> {
+        let mut se = Serializer::new(Vec::new()).with_struct_map().with_map_keys_sorted();
+        val.serialize(&mut se).unwrap();
+        se.into_inner()
+    }
+
+    #[derive(Serialize)]
+    struct Record {
+        z: HashMap<u32, HashMap<&'static str, bool>>,
+        a: u8,
+    }
+
+    let inner: HashMap<_, _> = (0..20).map(|i| (["b", "a", "bb", "c"][i % 4], i % 2 == 0)).collect();
+    let outer: HashMap<_, _> = [300, 1, 70000, 2].into_iter().map(|k| (k, inner.clone())).collect();
+    let buf = sorted(Record { z: outer, a: 5 });
+
+    // {"a": false, "b": true, "c": false, "bb": true}
+    let inner = [0x84, 0xa1, b'a', 0xc2, 0xa1, b'b', 0xc3, 0xa1, b'c', 0xc2, 0xa2, b'b', b'b', 0xc3];
+    // Struct fields keep their order, map keys are sorted by their encoding.
+    let mut expected = vec![0x82, 0xa1, b'z', 0x84, 0x01];
+    expected.extend_from_slice(&inner);
+    expected.push(0x02);
+    expected.extend_from_slice(&inner);
+    expected.extend_from_slice(&[0xcd, 0x01, 0x2c]);
+    expected.extend_from_slice(&inner);
+    expected.extend_from_slice(&[0xce, 0x00, 0x01, 0x11, 0x70]);
+    expected.extend_from_slice(&inner);
+    expected.extend_from_slice(&[0xa1, b'a', 0x05]);
+    assert_eq!(expected, buf);
+
+    // Output is the same whatever the iteration order.
+    let map: HashMap<String, u32> = (0..100).map(|i| (i.to_string(), i)).collect();
+    let reordered: HashMap<String, u32> = (0..100).rev().map(|i| (i.to_string(), i)).collect();
+    assert_eq!(sorted(&map), sorted(&reordered));
+    assert_eq!(map, rmp_serde::from_slice::<HashMap<String, u32>>(&sorted(&map)).unwrap());
+}
+
+#[test]
+fn fail_output_too_large() {
+    use std::collections::{BTreeMap, HashMap};
+
+    use rmp_serde::encode::LimitedWriter;
+
+    // Exactly at the limit.
+    let mut se = Serializer::new(LimitedWriter::new(Vec::new(), 4));
+    [1u8, 2, 3].serialize(&mut se).unwrap();
+    assert_eq!(vec![0x93, 0x01, 0x02, 0x03], se.into_inner().into_inner());
+
+    // Serialization stops at the first write past the limit.
+    struct Endless;
+    impl Serialize for Endless {
+        fn serialize<S: serde::Serializer>(&self, se: S) -> Result<S::Ok, S::Error> {
+            use serde::ser::SerializeSeq;
+            let mut seq = se.serialize_seq(Some(u32::MAX as usize))?;
+            loop {
+                seq.serialize_element("chunk")?;
+            }
+        }
+    }
+
+    let mut se = Serializer::new(LimitedWriter::new(Vec::new(), 100));
+    match Endless.serialize(&mut se) {
+        Err(Error::OutputTooLarge(100)) => (),
+        other => panic!("unexpected result: {other:?}"),
+    }
+    assert!(se.get_ref().written() <= 100);
+
+    // Including writes of raw bytes and of named structs.
+    #[derive(Serialize)]
+    struct Named {
+        #[serde(with = "serde_bytes")]
+        data: Vec<u8>,
+    }
+    let val = Named { data: vec![0; 16] };
+    let err = val.serialize(&mut Serializer::new(LimitedWriter::new(Vec::new(), 16)).with_struct_map()).unwrap_err();
+    assert_eq!("output exceeds the limit of 16 bytes", err.to_string());
+    // Values which are buffered before being written stop at the limit too.
+    struct EndlessUnsized;
+    impl Serialize for EndlessUnsized {
+        fn serialize<S: serde::Serializer>(&self, se: S) -> Result<S::Ok, S::Error> {
+            use serde::ser::SerializeSeq;
+            let mut seq = se.serialize_seq(None)?;
+            loop {
+                seq.serialize_element("chunk")?;
+            }
+        }
+    }
+
+    let mut se = Serializer::new(Vec::new()).with_output_limit(100);
+    match EndlessUnsized.serialize(&mut se) {
+        Err(Error::OutputTooLarge(100)) => (),
+        other => panic!("unexpected result: {other:?}"),
+    }
+    assert_eq!(0, se.get_ref().written());
+
+    let map: BTreeMap<u32, u32> = (0..1000).map(|n| (n, n)).collect();
+    let mut se = Serializer::new(Vec::new()).with_map_keys_sorted().with_output_limit(100);
+    match map.serialize(&mut se) {
+        Err(Error::OutputTooLarge(100)) => (),
+        other => panic!("unexpected result: {other:?}"),
+    }
+    assert_eq!(0, se.get_ref().written());
+
+    let mut se = Serializer::new(Vec::new()).with_map_keys_sorted().with_output_limit(5);
+    [(2u8, 0u8), (1, 0)].into_iter().collect::<HashMap<_, _>>().serialize(&mut se).unwrap();
+    assert_eq!(vec![0x82, 0x01, 0x00, 0x02, 0x00], se.into_inner().into_inner());
+}
+
+#[test]
+fn pass_int_encoding() {
+    use rmp::Marker;
+    use rmps::config::{FixedWidthInt, IntEncoding, ShortestInt};
+
+    fn encode<T: Serialize>(val: T, encoding: &'static dyn IntEncoding) -> Vec<u8> {
+        let mut se = Serializer::new(Vec::new()).with_int_encoding(encoding);
+        val.serialize(&mut se).unwrap();
+        se.into_inner()
+    }
+
+    // The shortest form is the default.
+    let ints = (0u64, 127u8, 128u16, 65536u32, u64::MAX, -1i8, -33i16, -70000i32, i64::MIN, 5i64);
+    assert_eq!(rmps::to_vec(&ints).unwrap(), encode(ints, &ShortestInt));
+
+    let buf = encode((1u8, -1i8, 2i32), &FixedWidthInt);
+    assert_eq!(vec![
+        0x93,
+        0xcf, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01,
+        0xd3, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+        0xd3, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x02,
+    ], buf);
+    assert_eq!((1u8, -1i8, 2i32), rmps::from_slice(&buf).unwrap());
+
+    /// Chooses markers that can not hold the integers.
+    #[derive(Debug)]
+    struct Broken;
+
+    impl IntEncoding for Broken {
+        fn uint_marker(&self, _: u64) -> Marker {
+            Marker::U8
+        }
+
+        fn sint_marker(&self, _: i64) -> Marker {
+            Marker::FixPos(0)
+        }
+    }
+
+    let mut se = Serializer::new(Vec::new()).with_int_encoding(&Broken);
+    assert!(matches!(300u16.serialize(&mut se), Err(Error::InvalidDataModel(..))));
+    assert!(matches!((-1i8).serialize(&mut se), Err(Error::InvalidDataModel(..))));
+    assert!(se.into_inner().is_empty());
+    assert_eq!(vec![0xcc, 0x07], encode(7u8, &Broken));
+    assert_eq!(vec![0x00], encode(0i8, &Broken));
+}
+
+#[test]
+fn pass_minimal_markers() {
+    use std::collections::BTreeMap;
+
+    use rmp::decode::{validate_minimal, ValidateError};
+    use rmps::config::FixedWidthInt;
+    use serde_bytes::ByteBuf;
+
+    #[derive(Serialize)]
+    struct Boundaries {
+        uints: (u8, u8, u16, u16, u32, u64),
+        sints: (i8, i8, i8, i16, i32, i64, i64),
+        strs: Vec<String>,
+        bins: Vec<ByteBuf>,
+        arrays: Vec<Vec<()>>,
+        maps: Vec<BTreeMap<u32, ()>>,
+    }
+
+    let lens = [0, 15, 16, 31, 32, 255, 256, 65535, 65536];
+    let val = Boundaries {
+        uints: (127, 128, 255, 256, 65535, u64::from(u32::MAX) + 1),
+        sints: (5, -32, -33, -129, -32769, i64::from(i32::MIN) - 1, i64::MAX),
+        strs: lens.iter().map(|&len| "a".repeat(len)).collect(),
+        bins: lens.iter().map(|&len| ByteBuf::from(vec![0; len])).collect(),
+        arrays: lens.iter().map(|&len| vec![(); len]).collect(),
+        maps: lens.iter().map(|&len| (0..len as u32).map(|k| (k, ())).collect()).collect(),
+    };
+
+    let buf = rmps::to_vec(&val).unwrap();
+    assert_eq!(Ok(buf.len()), validate_minimal(&buf));
+    let buf = rmps::to_vec_named(&val).unwrap();
+    assert_eq!(Ok(buf.len()), validate_minimal(&buf));
+
+    let mut se = Serializer::new(Vec::new()).with_int_encoding(&FixedWidthInt);
+    (1u8,).serialize(&mut se).unwrap();
+    assert_eq!(Err(ValidateError::NonMinimal(1)), validate_minimal(&se.into_inner()));
+}
+
+#[test]
+fn pass_maps_as_structs() {
+    use serde::ser::{SerializeMap, Serializer as _};
+
+    /// Serializes `{"b": 1, "a": 2}` as a map of unknown length.
+    struct Unsized;
+
+    impl Serialize for Unsized {
+        fn serialize<S: serde::Serializer>(&self, se: S) -> Result<S::Ok, S::Error> {
+            let mut map = se.serialize_map(None)?;
+            map.serialize_entry("b", &1)?;
+            map.serialize_entry("a", &2)?;
+            map.end()
+        }
+    }
+
+    // Like structs written as maps, maps keep the order of their entries.
+    let mut se = Serializer::new(Vec::new()).with_maps_as_structs().with_map_keys_sorted().with_struct_map();
+    (Unsized, Unsized).serialize(&mut se).unwrap();
+    let map = [0x82, 0xa1, b'b', 0x01, 0xa1, b'a', 0x02];
+    assert_eq!([&[0x92][..], &map, &map].concat(), se.into_inner());
+
+    let mut se = Serializer::new(Vec::new()).with_map_keys_sorted().with_struct_map();
+    Unsized.serialize(&mut se).unwrap();
+    assert_eq!(vec![0x82, 0xa1, b'a', 0x02, 0xa1, b'b', 0x01], se.into_inner());
+
+    // With structs written as arrays, maps, including real ones, are still written as maps.
+    let mut se = Serializer::new(Vec::new()).with_maps_as_structs().with_map_keys_sorted();
+    Unsized.serialize(&mut se).unwrap();
+    assert_eq!(vec![0x82, 0xa1, b'a', 0x02, 0xa1, b'b', 0x01], se.into_inner());
+
+    let map: std::collections::HashMap<String, u8> = [("a".into(), 1), ("b".into(), 2)].into_iter().collect();
+    let mut se = Serializer::new(Vec::new()).with_maps_as_structs();
+    map.serialize(&mut se).unwrap();
+    assert_eq!(map, rmp_serde::from_slice::<std::collections::HashMap<String, u8>>(&se.into_inner()).unwrap());
+
+    let mut se = Serializer::new(Vec::new()).with_maps_as_structs();
+    (&mut se).serialize_map(Some(0)).unwrap().end().unwrap();
+    assert_eq!(vec![0x80], se.into_inner());
+}
+
+#[test]
+fn fail_ext_forbidden() {
+    #[derive(Serialize)]
+    #[serde(rename = "_ExtStruct")]
+    struct ExtStruct((i8, serde_bytes::ByteBuf));
+
+    let ext = ExtStruct((1, serde_bytes::ByteBuf::from(vec![0x2a])));
+
+    let mut se = Serializer::new(Vec::new()).with_ext_forbidden();
+    (1, "a").serialize(&mut se).unwrap();
+    assert_eq!(vec![0x92, 0x01, 0xa1, 0x61], se.into_inner());
+
+    let mut se = Serializer::new(Vec::new()).with_ext_forbidden().with_struct_map();
+    match (1, &ext).serialize(&mut se) {
+        Err(Error::ExtForbidden) => (),
+        other => panic!("unexpected result: {other:?}"),
+    }
+
+    let mut se = Serializer::new(Vec::new());
+    ext.serialize(&mut se).unwrap();
+    assert_eq!(vec![0xd4, 0x01, 0x2a], se.into_inner());
+}
+
+#[test]
+fn pass_collect_str() {
+    use std::cell::Cell;
+    use std::fmt;
+
+    /// Displays `count` times `piece`, through as many calls to the formatter.
+    struct Repeat {
+        piece: &'static str,
+        count: usize,
+    }
+
+    impl fmt::Display for Repeat {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            (0..self.count).try_for_each(|_| f.write_str(self.piece))
+        }
+    }
+
+    impl Serialize for Repeat {
+        fn serialize<S: serde::Serializer>(&self, se: S) -> Result<S::Ok, S::Error> {
+            se.collect_str(self)
+        }
+    }
+
+    for count in [0, 1, 10, 11, 100] {
+        let val = Repeat { piece: "abé", count };
+        let expected = rmp_serde::to_vec(&val.to_string()).unwrap();
+        assert_eq!(expected, rmp_serde::to_vec(&val).unwrap());
+        assert_eq!(expected, rmp_serde::to_vec(&(val,)).unwrap()[1..]);
+    }
+
+    /// Displays one more character on each call.
+    struct Growing(Cell<usize>);
+
+    impl fmt::Display for Growing {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            self.0.set(self.0.get() + 1);
+            f.write_str(&"a".repeat(self.0.get()))
+        }
+    }
+
+    let val = Growing(Cell::new(0));
+    let mut se = Serializer::new(Vec::new());
+    match serde::Serializer::collect_str(&mut se, &val) {
+        Err(Error::InvalidDataModel(..)) => (),
+        other => panic!("unexpected result: {other:?}"),
+    }
+
+    // Write errors are reported like those of `serialize_str`.
+    let val = Repeat { piece: "abc", count: 5 };
+    let mut se = Serializer::new(rmp_serde::encode::LimitedWriter::new(Vec::new(), 8));
+    match serde::Serializer::collect_str(&mut se, &val) {
+        Err(Error::OutputTooLarge(8)) => (),
+        other => panic!("unexpected result: {other:?}"),
+    }
+}
@@ -165,6 +165,87 @@ fn serialize_struct_variant_as_map() {
     );
 }
 
+#[test]
+fn pass_variant_as_index() {
+    use rmp_serde::config::DiscriminantMode;
+
+    #[derive(Serialize)]
+    enum Enum {
+        V1,
+        V2(u64),
+    }
+
+    let mut se = Serializer::new(Vec::new()).with_discriminant(DiscriminantMode::IndexU8);
+    Enum::V1.serialize(&mut se).unwrap();
+    Enum::V2(42).serialize(&mut se).unwrap();
+
+    // Expect: 0u8 {1u8 => 42}
+    assert_eq!(vec![0xcc, 0x00, 0x81, 0xcc, 0x01, 0x2a], se.into_inner());
+
+    let mut se = Serializer::new(Vec::new()).with_discriminant(DiscriminantMode::IndexU32);
+    Enum::V2(42).serialize(&mut se).unwrap();
+
+    // Expect: {1u32 => 42}
+    assert_eq!(vec![0x81, 0xce, 0x00, 0x00, 0x00, 0x01, 0x2a], se.into_inner());
+}
+
+#[test]
+fn pass_struct_with_trailing_nils_elided() {
+    #[derive(Serialize)]
+    struct Struct {
+        a: Option<u8>,
+        b: Option<u8>,
+        c: Option<u8>,
+    }
+
+    let mut se = Serializer::new(Vec::new()).with_trailing_nils_elided();
+    Struct { a: None, b: Some(42), c: None }.serialize(&mut se).unwrap();
+    Struct { a: None, b: None, c: None }.serialize(&mut se).unwrap();
+
+    // Expect: [nil, 42] []
+    assert_eq!(vec![0x92, 0xc0, 0x2a, 0x90], se.into_inner());
+
+    // Structs written as maps keep all of their fields.
+    let mut se = Serializer::new(Vec::new()).with_trailing_nils_elided().with_struct_map();
+    Struct { a: Some(1), b: None, c: None }.serialize(&mut se).unwrap();
+
+    // Expect: {"a": 1, "b": nil, "c": nil}
+    assert_eq!(vec![0x83, 0xa1, 0x61, 0x01, 0xa1, 0x62, 0xc0, 0xa1, 0x63, 0xc0], se.into_inner());
+}
+
+#[test]
+fn pass_struct_with_none_fields_skipped() {
+    #[derive(Serialize)]
+    struct Inner {
+        x: Option<u8>,
+    }
+
+    #[derive(Serialize)]
+    struct Struct {
+        a: Option<u8>,
+        b: (),
+        c: Option<Inner>,
+        d: Inner,
+    }
+
+    let mut se = Serializer::new(Vec::new()).with_struct_map().with_none_fields_skipped();
+    Struct { a: None, b: (), c: None, d: Inner { x: None } }.serialize(&mut se).unwrap();
+    Struct { a: Some(1), b: (), c: Some(Inner { x: Some(2) }), d: Inner { x: None } }.serialize(&mut se).unwrap();
+
+    // Expect: {"b": nil, "d": {}} {"a": 1, "b": nil, "c": {"x": 2}, "d": {}}
+    assert_eq!(vec![
+        0x82, 0xa1, 0x62, 0xc0, 0xa1, 0x64, 0x80,
+        0x84, 0xa1, 0x61, 0x01, 0xa1, 0x62, 0xc0, 0xa1, 0x63, 0x81, 0xa1, 0x78, 0x02, 0xa1, 0x64, 0x80,
+    ], se.into_inner());
+
+    // Structs written as tuples keep all of their fields.
+    let mut se = Serializer::new(Vec::new()).with_none_fields_skipped();
+    Inner { x: None }.serialize(&mut se).unwrap();
+
+    // Expect: [nil]
+    assert_eq!(vec![0x91, 0xc0], se.into_inner());
+}
+
 #[test]
 fn serialize_struct_with_flattened_map_field() {
     use std::collections::BTreeMap;
@@ -132,6 +132,31 @@ fn round_trip_option_cow() {
     assert_eq!(expected, Deserialize::deserialize(&mut de).unwrap());
 }
 
+#[test]
+fn round_char() {
+    for (val, encoded) in [
+        ('!', &[0xa1, 0x21][..]),
+        ('é', &[0xa2, 0xc3, 0xa9]),
+        ('€', &[0xa3, 0xe2, 0x82, 0xac]),
+        ('🦀', &[0xa4, 0xf0, 0x9f, 0xa6, 0x80]),
+    ] {
+        assert_eq!(encoded, rmp_serde::to_vec(&val).unwrap());
+        assert_eq!(val, rmp_serde::from_slice::<char>(encoded).unwrap());
+        assert_roundtrips(val);
+    }
+
+    // Only a string of exactly one character is a char.
+    assert!(rmp_serde::from_slice::<char>(&[0xa0]).is_err());
+    assert!(rmp_serde::from_slice::<char>(&[0xa2, 0x61, 0x62]).is_err());
+    assert!(rmp_serde::from_slice::<char>(&[0xc4, 0x01, 0x21]).is_err());
+    assert!(rmp_serde::from_slice::<char>(&[0x21]).is_err());
+
+    // Strings and binaries are interchangeable for text and bytes.
+    assert_eq!("!", rmp_serde::from_slice::<String>(&[0xc4, 0x01, 0x21]).unwrap());
+    assert_eq!("!", rmp_serde::from_slice::<&str>(&[0xc4, 0x01, 0x21]).unwrap());
+    assert_eq!(b"!", &rmp_serde::from_slice::<serde_bytes::ByteBuf>(&[0xa1, 0x21]).unwrap()[..]);
+}
+
 #[test]
 fn round_struct_like_enum() {
     use serde::Serialize;
@@ -281,6 +306,147 @@ fn round_struct_as_map() {
     assert_eq!(dog1, check);
 }
 
+#[test]
+fn round_struct_with_trailing_nils() {
+    #[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+    struct Inner {
+        a: u8,
+        b: Option<String>,
+    }
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+    enum Enum {
+        V { inner: Inner, c: Option<u8> },
+    }
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+    struct Outer {
+        inner: Inner,
+        items: Vec<Option<Inner>>,
+        e: Enum,
+        d: Option<Inner>,
+        unit: (),
+    }
+
+    let outer = Outer {
+        inner: Inner { a: 1, b: None },
+        items: vec![None, Some(Inner { a: 2, b: Some("b".into()) })],
+        e: Enum::V { inner: Inner { a: 3, b: None }, c: None },
+        d: None,
+        unit: (),
+    };
+
+    let mut se = Serializer::new(Vec::new()).with_trailing_nils_elided();
+    outer.serialize(&mut se).unwrap();
+    let buf = se.into_inner();
+
+    // [[1], [nil, [2, "b"]], {"V": [[3]]}]
+    assert_eq!(vec![
+        0x93,
+        0x91, 0x01,
+        0x92, 0xc0, 0x92, 0x02, 0xa1, 0x62,
+        0x81, 0xa1, 0x56, 0x91, 0x91, 0x03,
+    ], buf);
+
+    let mut de = Deserializer::new(&buf[..]).with_trailing_nils_padded();
+    assert_eq!(outer, Outer::deserialize(&mut de).unwrap());
+}
+
+#[test]
+fn round_struct_with_none_fields_skipped() {
+    #[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+    struct Inner {
+        a: u8,
+        b: Option<String>,
+    }
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+    enum Enum {
+        V { inner: Inner, c: Option<u8> },
+    }
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+    struct Outer {
+        inner: Inner,
+        e: Enum,
+        d: Option<Inner>,
+    }
+
+    let outer = Outer {
+        inner: Inner { a: 1, b: None },
+        e: Enum::V { inner: Inner { a: 2, b: Some("b".into()) }, c: None },
+        d: None,
+    };
+
+    let mut se = Serializer::new(Vec::new()).with_struct_map().with_none_fields_skipped();
+    outer.serialize(&mut se).unwrap();
+    let buf = se.into_inner();
+
+    // {"inner": {"a": 1}, "e": {"V": {"inner": {"a": 2, "b": "b"}}}}
+    assert_eq!(vec![
+        0x82,
+        0xa5, 0x69, 0x6e, 0x6e, 0x65, 0x72, 0x81, 0xa1, 0x61, 0x01,
+        0xa1, 0x65, 0x81, 0xa1, 0x56, 0x81,
+        0xa5, 0x69, 0x6e, 0x6e, 0x65, 0x72, 0x82, 0xa1, 0x61, 0x02, 0xa1, 0x62, 0xa1, 0x62,
+    ], buf);
+
+    assert_eq!(outer, rmp_serde::from_slice(&buf).unwrap());
+}
+
+#[test]
+fn round_whole_floats_compacted() {
+    #[derive(Debug, Serialize, Deserialize, PartialEq)]
+    struct Floats {
+        a: f64,
+        b: f32,
+        c: f64,
+        d: f64,
+    }
+
+    let val = Floats { a: 3.0, b: -2.0, c: 0.5, d: 4_294_967_296.0 };
+
+    let mut se = Serializer::new(Vec::new()).with_whole_floats_compacted();
+    val.serialize(&mut se).unwrap();
+    let buf = se.into_inner();
+
+    // [3, -2, 0.5, 4294967296]
+    assert_eq!(vec![
+        0x94, 0x03, 0xfe,
+        0xcb, 0x3f, 0xe0, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0xcf, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00,
+    ], buf);
+
+    assert_eq!(val, rmp_serde::from_slice(&buf).unwrap());
+}
+
+#[test]
+fn round_map_pairs_with_duplicate_keys() {
+    #[derive(Debug, Serialize, Deserialize, PartialEq)]
+    struct Record {
+        #[serde(with = "rmp_serde::map_pairs")]
+        pairs: Vec<(u8, String)>,
+    }
+
+    // {1: "a", 2: "b", 1: "c"}
+    let map = [0x83, 0x01, 0xa1, 0x61, 0x02, 0xa1, 0x62, 0x01, 0xa1, 0x63];
+    let mut buf = vec![0x81, 0xa5, 0x70, 0x61, 0x69, 0x72, 0x73];
+    buf.extend_from_slice(&map);
+
+    let val: Record = rmp_serde::from_slice(&buf).unwrap();
+    assert_eq!(vec![(1, "a".to_owned()), (2, "b".to_owned()), (1, "c".to_owned())], val.pairs);
+    assert_eq!(buf, rmp_serde::to_vec_named(&val).unwrap());
+
+    // Also read from a sequence of pairs, as written without the helper.
+    let buf = rmp_serde::to_vec(&(vec![(1u8, "a")],)).unwrap();
+    let val: Record = rmp_serde::from_slice(&buf).unwrap();
+    assert_eq!(vec![(1, "a".to_owned())], val.pairs);
+
+    // The helper can also be called on a `Deserializer` directly.
+    let mut de = Deserializer::new(&map[..]);
+    let pairs: Vec<(u8, String)> = rmp_serde::map_pairs::deserialize(&mut de).unwrap();
+    assert_eq!(3, pairs.len());
+}
+
 #[test]
 fn round_struct_as_map_in_vec() {
     // See: issue #205
@@ -385,6 +551,173 @@ fn round_trip_unit_struct_untagged_enum() {
     }
 }
 
+#[test]
+fn round_trip_internally_tagged_enum() {
+    #[derive(Debug, Serialize, Deserialize, PartialEq)]
+    struct Inner {
+        name: String,
+        #[serde(with = "serde_bytes")]
+        data: Vec<u8>,
+    }
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq)]
+    #[serde(tag = "type")]
+    enum Message {
+        Ping,
+        Data { id: u32, value: Option<f64>, inner: Inner },
+        Wrapped(Inner),
+    }
+
+    assert_roundtrips(Message::Ping);
+    assert_roundtrips(Message::Data {
+        id: 42,
+        value: None,
+        inner: Inner { name: "le message".into(), data: vec![1, 2, 3] },
+    });
+    assert_roundtrips(Message::Data {
+        id: 7,
+        value: Some(0.5),
+        inner: Inner { name: String::new(), data: vec![] },
+    });
+    assert_roundtrips(Message::Wrapped(Inner { name: "a".into(), data: vec![0xff] }));
+
+    // The tag comes first both in arrays and in maps.
+    assert_eq!(vec![0x91, 0xa4, 0x50, 0x69, 0x6e, 0x67], rmp_serde::to_vec(&Message::Ping).unwrap());
+    assert_eq!(
+        vec![0x81, 0xa4, 0x74, 0x79, 0x70, 0x65, 0xa4, 0x50, 0x69, 0x6e, 0x67],
+        rmp_serde::to_vec_named(&Message::Ping).unwrap()
+    );
+
+    // Like structs, they are only written as maps with `with_struct_map`, and read back from both.
+    let val = Message::Wrapped(Inner { name: "a".into(), data: vec![0xff] });
+    // ["Wrapped", "a", [0xff]]
+    let buf = rmp_serde::to_vec(&val).unwrap();
+    assert_eq!(vec![0x93, 0xa7, b'W', b'r', b'a', b'p', b'p', b'e', b'd', 0xa1, b'a', 0xc4, 0x01, 0xff], buf);
+    assert_eq!(val, rmp_serde::from_slice(&buf).unwrap());
+    let buf = rmp_serde::to_vec_named(&val).unwrap();
+    assert_eq!(0x83, buf[0]);
+    assert_eq!(val, rmp_serde::from_slice(&buf).unwrap());
+}
+
+#[test]
+fn round_trip_adjacently_tagged_enum() {
+    use std::collections::BTreeMap;
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq)]
+    struct Inner {
+        name: String,
+    }
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq)]
+    #[serde(tag = "t", content = "c")]
+    enum Message {
+        Ping,
+        Id(u32),
+        Pair(u8, String),
+        Data { id: u32, value: Option<f64>, inner: Inner },
+        Map(BTreeMap<String, u8>),
+    }
+
+    assert_roundtrips(Message::Ping);
+    assert_roundtrips(Message::Id(42));
+    assert_roundtrips(Message::Pair(1, "a".into()));
+    assert_roundtrips(Message::Data { id: 7, value: Some(0.5), inner: Inner { name: "le message".into() } });
+    assert_roundtrips(Message::Data { id: 7, value: None, inner: Inner { name: String::new() } });
+    assert_roundtrips(Message::Map([("k".to_owned(), 1)].into()));
+
+    // Written as maps even when structs are written as tuples.
+    // {"t": "Ping"}
+    assert_eq!(vec![0x81, 0xa1, 0x74, 0xa4, 0x50, 0x69, 0x6e, 0x67], rmp_serde::to_vec(&Message::Ping).unwrap());
+    // {"t": "Data", "c": {"id": 7, "value": nil, "inner": {"name": ""}}}
+    let val = Message::Data { id: 7, value: None, inner: Inner { name: String::new() } };
+    let buf = rmp_serde::to_vec(&val).unwrap();
+    assert_eq!(vec![
+        0x82, 0xa1, 0x74, 0xa4, 0x44, 0x61, 0x74, 0x61, 0xa1, 0x63,
+        0x83, 0xa2, 0x69, 0x64, 0x07, 0xa5, 0x76, 0x61, 0x6c, 0x75, 0x65, 0xc0,
+        0xa5, 0x69, 0x6e, 0x6e, 0x65, 0x72, 0x81, 0xa4, 0x6e, 0x61, 0x6d, 0x65, 0xa0,
+    ], buf);
+    assert_eq!(buf, rmp_serde::to_vec_named(&val).unwrap());
+
+    let mut se = Serializer::new(Vec::new()).with_trailing_nils_elided();
+    Message::Ping.serialize(&mut se).unwrap();
+    assert_eq!(vec![0x81, 0xa1, 0x74, 0xa4, 0x50, 0x69, 0x6e, 0x67], se.into_inner());
+
+    // Structs merely holding an enum keep being written as tuples.
+    #[derive(Debug, Serialize, Deserialize, PartialEq)]
+    enum Kind {
+        A,
+    }
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq)]
+    struct Kinded {
+        kind: Kind,
+    }
+
+    assert_eq!(vec![0x91, 0xa1, 0x41], rmp_serde::to_vec(&Kinded { kind: Kind::A }).unwrap());
+
+    // Serde gives no way to tell an adjacently tagged enum from a struct named after the enum of
+    // its first field, which is written as a map too.
+    #[derive(Debug, Serialize, Deserialize, PartialEq)]
+    #[serde(rename = "Kind")]
+    struct Lookalike {
+        kind: Kind,
+    }
+
+    // {"kind": "A"}
+    let buf = rmp_serde::to_vec(&Lookalike { kind: Kind::A }).unwrap();
+    assert_eq!(vec![0x81, 0xa4, b'k', b'i', b'n', b'd', 0xa1, 0x41], buf);
+    assert_eq!(Lookalike { kind: Kind::A }, rmp_serde::from_slice(&buf).unwrap());
+}
+
+#[test]
+fn round_trip_encoding_overrides() {
+    use rmp_serde::overrides::{AsBin, ForceU64};
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq)]
+    struct Record {
+        small: ForceU64,
+        name: AsBin<String>,
+        data: AsBin,
+        other: (u64, Vec<u8>),
+    }
+
+    let val = Record {
+        small: ForceU64(7),
+        name: AsBin("ab".into()),
+        data: AsBin(vec![1, 2]),
+        other: (7, vec![1, 2]),
+    };
+    assert_roundtrips(val);
+
+    let val = Record {
+        small: ForceU64(u64::MAX),
+        name: AsBin(String::new()),
+        data: AsBin(vec![]),
+        other: (0, vec![]),
+    };
+    let buf = rmp_serde::to_vec(&val).unwrap();
+    // [u64 MAX, bin8(0), bin8(0), [0, []]]
+    assert_eq!(vec![
+        0x94,
+        0xcf, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+        0xc4, 0x00,
+        0xc4, 0x00,
+        0x92, 0x00, 0x90,
+    ], buf);
+    assert_roundtrips(val);
+
+    // Data written without the wrappers is accepted: [7, "ab", [1, 2], [7, [1, 2]]]
+    let buf = [0x94, 0x07, 0xa2, 0x61, 0x62, 0x92, 0x01, 0x02, 0x92, 0x07, 0x92, 0x01, 0x02];
+    let val: Record = rmp_serde::from_slice(&buf).unwrap();
+    assert_eq!(ForceU64(7), val.small);
+    assert_eq!("ab", val.name.0);
+    assert_eq!(vec![1, 2], val.data.0);
+
+    // A binary which is not valid UTF-8 can not be read as `AsBin<String>`.
+    let buf = [0xc4, 0x01, 0xff];
+    rmp_serde::from_slice::<AsBin<String>>(&buf).unwrap_err();
+}
+
 #[test]
 fn round_trip_struct_with_flattened_map_field() {
     use std::collections::BTreeMap;
@@ -545,6 +878,22 @@ fn roundtrip_hashsets() {
     assert_roundtrips([0i8,1,11,111,-1,-11,-111].into_iter().collect::<HashSet<_>>());
 }
 
+#[test]
+fn roundtrip_btreesets() {
+    use std::collections::{BTreeSet, HashSet};
+
+    let set: BTreeSet<u32> = [3, 1, 70000, 2].into_iter().collect();
+    // A set is written as an array, in order.
+    assert_eq!(vec![0x94, 0x01, 0x02, 0x03, 0xce, 0x00, 0x01, 0x11, 0x70], rmp_serde::to_vec(&set).unwrap());
+    assert_roundtrips(set);
+    assert_roundtrips(BTreeSet::<u32>::new());
+
+    // Duplicate elements of the array collapse: [2, 1, 2, 1]
+    let buf = [0x94, 0x02, 0x01, 0x02, 0x01];
+    assert_eq!([1, 2].into_iter().collect::<BTreeSet<u32>>(), rmp_serde::from_slice(&buf).unwrap());
+    assert_eq!([1, 2].into_iter().collect::<HashSet<u32>>(), rmp_serde::from_slice(&buf).unwrap());
+}
+
 #[test]
 fn roundtrip_ipv4addr() {
     assert_roundtrips(Ipv4Addr::new(127, 0, 0, 1));
@@ -671,7 +1020,7 @@ fn roundtrip_some_failures() {
 #[cfg(test)]
 #[track_caller]
 fn assert_roundtrips<T: PartialEq + std::fmt::Debug + Serialize + for<'a> Deserialize<'a>>(val: T) {
-    use rmp_serde::config::BytesMode;
+    use rmp_serde::config::{BytesMode, DiscriminantMode};
 
     assert_roundtrips_config(&val, "default", |s| s, |d| d);
     assert_roundtrips_config(&val, ".with_struct_map()", |s| s.with_struct_map(), |d| d);
@@ -717,6 +1066,18 @@ fn assert_roundtrips<T: PartialEq + std::fmt::Debug + Serialize + for<'a> Deseri
         |s| s.with_bytes(BytesMode::ForceAll),
         |d| d,
     );
+    assert_roundtrips_config(
+        &val,
+        ".with_discriminant(IndexU8)",
+        |s| s.with_discriminant(DiscriminantMode::IndexU8),
+        |d| d,
+    );
+    assert_roundtrips_config(
+        &val,
+        ".with_discriminant(IndexU32).with_struct_map()",
+        |s| s.with_discriminant(DiscriminantMode::IndexU32).with_struct_map(),
+        |d| d,
+    );
 }
 
 #[cfg(test)]
@@ -761,3 +1122,341 @@ fn assert_roundtrips_config<T, CSF, SC, CDF, DC>(
 
     assert_eq!(val, &val2, "Config: {}", desc);
 }
+
+#[cfg(feature = "uuid")]
+#[test]
+fn round_uuid_as_bin() {
+    use uuid::Uuid;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Record {
+        #[serde(with = "rmp_serde::uuid_bin")]
+        id: Uuid,
+    }
+
+    for id in [Uuid::nil(), Uuid::max(), Uuid::from_u128(0x936da01f_9abd_4d9d_80c7_02af85c822a8)] {
+        let buf = rmp_serde::to_vec(&Record { id }).unwrap();
+
+        let mut expected = vec![0x91, 0xc4, 0x10];
+        expected.extend_from_slice(id.as_bytes());
+        assert_eq!(expected, buf);
+
+        assert_roundtrips(Record { id });
+    }
+
+    // A Uuid written as a string is accepted as well.
+    let id = Uuid::from_u128(0x936da01f_9abd_4d9d_80c7_02af85c822a8);
+    let buf = rmp_serde::to_vec(&(id.to_string(),)).unwrap();
+    assert_eq!(Record { id }, rmp_serde::from_slice(&buf).unwrap());
+
+    // [bin8(2)]
+    assert!(rmp_serde::from_slice::<Record>(&[0x91, 0xc4, 0x02, 0x00, 0x00]).is_err());
+}
+
+#[cfg(feature = "chrono")]
+#[test]
+fn round_chrono_timestamp() {
+    use chrono::{DateTime, NaiveDate, Utc};
+    use rmp_serde::chrono_timestamp::{from_ext_data, to_ext_data};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Event {
+        #[serde(with = "rmp_serde::chrono_timestamp")]
+        at: DateTime<Utc>,
+    }
+
+    let at = |secs, nanos| DateTime::from_timestamp(secs, nanos).unwrap();
+    let cases: [(DateTime<Utc>, &[u8]); 6] = [
+        // timestamp 32
+        (at(0, 0), &[0x91, 0xd6, 0xff, 0x00, 0x00, 0x00, 0x00]),
+        (at(u32::MAX.into(), 0), &[0x91, 0xd6, 0xff, 0xff, 0xff, 0xff, 0xff]),
+        // timestamp 64, with fractional seconds or more than 32 bits of seconds
+        (at(1, 500_000_000), &[0x91, 0xd7, 0xff, 0x77, 0x35, 0x94, 0x00, 0x00, 0x00, 0x00, 0x01]),
+        (at((1 << 34) - 1, 999_999_999), &[0x91, 0xd7, 0xff, 0xee, 0x6b, 0x27, 0xff, 0xff, 0xff, 0xff, 0xff]),
+        // timestamp 96, before the epoch or after 2514
+        (at(-2, 500_000_000), &[0x91, 0xc7, 0x0c, 0xff, 0x1d, 0xcd, 0x65, 0x00, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xfe]),
+        (at(1 << 34, 0), &[0x91, 0xc7, 0x0c, 0xff, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x04, 0x00, 0x00, 0x00, 0x00]),
+    ];
+    for (at, expected) in cases {
+        assert_eq!(expected, rmp_serde::to_vec(&Event { at }).unwrap());
+        assert_roundtrips(Event { at });
+        assert_eq!(Some(at), from_ext_data(&to_ext_data(&at)));
+    }
+    assert_roundtrips(Event { at: DateTime::<Utc>::MIN_UTC });
+    assert_roundtrips(Event { at: DateTime::<Utc>::MAX_UTC });
+
+    // A leap second is written as the start of the next second.
+    let leap = NaiveDate::from_ymd_opt(2016, 12, 31).unwrap().and_hms_nano_opt(23, 59, 59, 1_000_000_123).unwrap().and_utc();
+    assert_eq!(Some(at(1_483_228_800, 123)), from_ext_data(&to_ext_data(&leap)));
+
+    // Nanoseconds of a whole second, another length and another type.
+    assert_eq!(None, from_ext_data(&[0xee, 0x6b, 0x28, 0x00, 0x00, 0x00, 0x00, 0x00]));
+    assert_eq!(None, from_ext_data(&[0x00, 0x00]));
+    assert!(rmp_serde::from_slice::<Event>(&[0x91, 0xd6, 0x01, 0x00, 0x00, 0x00, 0x00]).is_err());
+}
+
+#[cfg(feature = "half")]
+#[test]
+fn round_half_ext() {
+    use half::f16;
+    use rmp_serde::half_ext::{from_ext_data, to_ext_data};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Sample {
+        #[serde(with = "rmp_serde::half_ext")]
+        val: f16,
+    }
+
+    let cases: [(f16, &[u8]); 7] = [
+        (f16::ZERO, &[0x91, 0xd5, 0x10, 0x00, 0x00]),
+        // The smallest and largest subnormals, and the smallest normal.
+        (f16::from_bits(0x0001), &[0x91, 0xd5, 0x10, 0x00, 0x01]),
+        (f16::from_bits(0x03ff), &[0x91, 0xd5, 0x10, 0x03, 0xff]),
+        (f16::MIN_POSITIVE, &[0x91, 0xd5, 0x10, 0x04, 0x00]),
+        // The largest finite value and the infinities.
+        (f16::MAX, &[0x91, 0xd5, 0x10, 0x7b, 0xff]),
+        (f16::INFINITY, &[0x91, 0xd5, 0x10, 0x7c, 0x00]),
+        (f16::NEG_INFINITY, &[0x91, 0xd5, 0x10, 0xfc, 0x00]),
+    ];
+    for (val, expected) in cases {
+        assert_eq!(expected, rmp_serde::to_vec(&Sample { val }).unwrap());
+        assert_roundtrips(Sample { val });
+        assert_eq!(Some(val.to_bits()), from_ext_data(&to_ext_data(val)).map(f16::to_bits));
+    }
+
+    // A NaN keeps its bits.
+    let buf = rmp_serde::to_vec(&Sample { val: f16::from_bits(0x7e01) }).unwrap();
+    assert_eq!(0x7e01, rmp_serde::from_slice::<Sample>(&buf).unwrap().val.to_bits());
+
+    // Floats are rounded, to a subnormal or past the largest finite value to infinity.
+    let from_f32 = |val: f32| rmp_serde::from_slice::<Sample>(&rmp_serde::to_vec(&(val,)).unwrap()).unwrap().val;
+    assert_eq!(f16::from_bits(0x0001), from_f32(6e-8));
+    assert_eq!(f16::INFINITY, from_f32(65520.0));
+    assert_eq!(f16::MAX, rmp_serde::from_slice::<Sample>(&rmp_serde::to_vec(&(65504.0f64,)).unwrap()).unwrap().val);
+
+    // Another length and another type.
+    assert_eq!(None, from_ext_data(&[0x00]));
+    assert!(rmp_serde::from_slice::<Sample>(&[0x91, 0xd5, 0x11, 0x00, 0x00]).is_err());
+    assert!(rmp_serde::from_slice::<Sample>(&[0x91, 0xd4, 0x10, 0x00]).is_err());
+}
+
+#[cfg(feature = "duration")]
+#[test]
+fn round_duration_as_array() {
+    use std::time::Duration;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Job {
+        #[serde(with = "rmp_serde::duration")]
+        timeout: Duration,
+    }
+
+    let cases: [(Duration, &[u8]); 4] = [
+        (Duration::ZERO, &[0x91, 0x92, 0x00, 0x00]),
+        (Duration::new(0, 999_999_999), &[0x91, 0x92, 0x00, 0xce, 0x3b, 0x9a, 0xc9, 0xff]),
+        (Duration::from_secs(u64::MAX), &[0x91, 0x92, 0xcf, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x00]),
+        (
+            Duration::MAX,
+            &[0x91, 0x92, 0xcf, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xce, 0x3b, 0x9a, 0xc9, 0xff],
+        ),
+    ];
+    for (timeout, expected) in cases {
+        assert_eq!(expected, rmp_serde::to_vec(&Job { timeout }).unwrap());
+        assert_roundtrips(Job { timeout });
+    }
+
+    // The struct form of serde's own implementation is accepted as well.
+    let buf = rmp_serde::to_vec_named(&(Duration::new(3, 4),)).unwrap();
+    assert_eq!(Job { timeout: Duration::new(3, 4) }, rmp_serde::from_slice(&buf).unwrap());
+
+    // Nanoseconds carried into seconds that overflow.
+    assert!(rmp_serde::from_slice::<Job>(&[0x91, 0x92, 0xcf, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xce, 0x3b, 0x9a, 0xca, 0x00]).is_err());
+}
+
+#[cfg(feature = "compression")]
+#[test]
+fn round_compressed_bin() {
+    use rmp_serde::compressed::{Compressed, Compressor};
+
+    /// Compresses payloads repeating a single byte into its count and the byte.
+    struct Fill;
+
+    impl Compressor for Fill {
+        const EXT_TYPE: i8 = 9;
+        const THRESHOLD: usize = 8;
+        type Error = &'static str;
+
+        fn compress(data: &[u8]) -> Result<Vec<u8>, Self::Error> {
+            if data.iter().all(|&b| b == data[0]) {
+                Ok([&(data.len() as u32).to_be_bytes()[..], &data[..1]].concat())
+            } else {
+                Ok(data.to_vec())
+            }
+        }
+
+        fn decompress(data: &[u8]) -> Result<Vec<u8>, Self::Error> {
+            match *data {
+                [a, b, c, d, byte] => Ok(vec![byte; u32::from_be_bytes([a, b, c, d]) as usize]),
+                _ => Err("invalid fill"),
+            }
+        }
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Blob {
+        payload: Compressed<Fill>,
+    }
+
+    // [ext8(5) type 9: 300 x 7]
+    let val = Blob { payload: Compressed::new(vec![7; 300]) };
+    let buf = rmp_serde::to_vec(&val).unwrap();
+    assert_eq!(vec![0x91, 0xc7, 0x05, 0x09, 0x00, 0x00, 0x01, 0x2c, 0x07], buf);
+    assert_roundtrips(val);
+
+    // At most the threshold, and not smaller once compressed.
+    for payload in [vec![7; 8], (0..100).collect()] {
+        let buf = rmp_serde::to_vec(&Blob { payload: Compressed::new(payload.clone()) }).unwrap();
+        assert_eq!(rmp_serde::to_vec(&(serde_bytes::ByteBuf::from(payload.clone()),)).unwrap(), buf);
+        assert_roundtrips(Blob { payload: Compressed::new(payload) });
+    }
+
+    // A payload written without the wrapper is accepted as well.
+    let buf = rmp_serde::to_vec(&(vec![1u8, 2, 3],)).unwrap();
+    assert_eq!(Blob { payload: Compressed::new(vec![1, 2, 3]) }, rmp_serde::from_slice(&buf).unwrap());
+
+    // An ext of another type, and invalid compressed data.
+    assert!(rmp_serde::from_slice::<Blob>(&[0x91, 0xc7, 0x05, 0x08, 0x00, 0x00, 0x01, 0x2c, 0x07]).is_err());
+    assert!(rmp_serde::from_slice::<Blob>(&[0x91, 0xd4, 0x09, 0x00]).is_err());
+}
+
+#[cfg(feature = "tokio-util")]
+#[test]
+fn round_trip_codec() {
+    use bytes::BytesMut;
+    use rmp_serde::codec::{Error, MsgpackCodec};
+    use tokio_util::codec::{Decoder, Encoder};
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq)]
+    struct Message {
+        id: u32,
+        #[serde(with = "serde_bytes")]
+        data: Vec<u8>,
+    }
+
+    let msg = || Message { id: 1, data: vec![0xab; 300] };
+
+    let mut codec = MsgpackCodec::<Message>::new().with_struct_map();
+    let mut buf = BytesMut::new();
+    codec.encode(msg(), &mut buf).unwrap();
+    codec.encode(&msg(), &mut buf).unwrap();
+    let frame_len = buf.len() / 2;
+    assert_eq!(&(frame_len as u32 - 4).to_be_bytes(), &buf[..4]);
+    assert_eq!(0x82, buf[4]);
+
+    // Feed the frames one byte at a time.
+    let mut src = BytesMut::new();
+    let mut decoded = Vec::new();
+    for byte in buf {
+        src.extend_from_slice(&[byte]);
+        if let Some(msg) = codec.decode(&mut src).unwrap() {
+            decoded.push(msg);
+        }
+    }
+    assert_eq!(vec![msg(), msg()], decoded);
+    assert!(src.is_empty());
+
+    // Frames are limited by the maximum frame length and by the width of the prefix.
+    let mut codec = MsgpackCodec::<Message>::new().with_length_width(1);
+    let mut buf = BytesMut::from(&b"keep"[..]);
+    match codec.encode(msg(), &mut buf) {
+        Err(Error::FrameTooLarge(len)) => assert!(len > 300),
+        other => panic!("unexpected result: {other:?}"),
+    }
+    assert_eq!(b"keep"[..], buf[..]);
+
+    let mut codec = MsgpackCodec::<Message>::new().with_length_width(8).with_max_frame_len(16);
+    let mut src = BytesMut::from(&[0, 0, 0, 0, 0, 0, 0, 17][..]);
+    assert!(matches!(codec.decode(&mut src), Err(Error::FrameTooLarge(17))));
+
+    let mut buf = BytesMut::new();
+    let small = Message { id: 2, data: vec![1] };
+    codec.encode(&small, &mut buf).unwrap();
+    assert_eq!(&[0, 0, 0, 0, 0, 0, 0, 5, 0x92, 0x02, 0xc4, 0x01, 0x01], &buf[..]);
+    assert_eq!(Some(small), codec.decode(&mut buf).unwrap());
+
+    // A frame holding invalid data fails to decode, but is consumed.
+    let mut src = BytesMut::from(&[0, 0, 0, 0, 0, 0, 0, 1, 0xc1][..]);
+    assert!(matches!(codec.decode(&mut src), Err(Error::Decode(..))));
+    assert!(src.is_empty());
+    // So does a frame with bytes after its message.
+    let mut src = BytesMut::from(&[0, 0, 0, 0, 0, 0, 0, 6, 0x92, 0x02, 0xc4, 0x01, 0x01, 0xc0, 0][..]);
+    assert!(matches!(codec.decode(&mut src), Err(Error::TrailingBytes(1))));
+    assert_eq!([0][..], src[..]);
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+async fn round_trip_async_writer_over_duplex() {
+    use tokio::io::AsyncReadExt;
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq)]
+    struct Message {
+        id: u32,
+        #[serde(with = "serde_bytes")]
+        data: Vec<u8>,
+    }
+
+    let msg = || Message { id: 1, data: vec![0xab; 100_000] };
+
+    let (mut tx, mut rx) = tokio::io::duplex(64);
+    let write = async move {
+        rmp_serde::to_writer_async(&mut tx, &msg()).await.unwrap();
+        rmp_serde::to_writer_named_async(&mut tx, &msg()).await.unwrap();
+    };
+    let read = async move {
+        let mut buf = Vec::new();
+        rx.read_to_end(&mut buf).await.unwrap();
+        buf
+    };
+    let ((), buf) = tokio::join!(write, read);
+
+    let mut rd = &buf[..];
+    let mut de = Deserializer::new(&mut rd);
+    assert_eq!(msg(), Message::deserialize(&mut de).unwrap());
+    assert_eq!(msg(), Message::deserialize(&mut de).unwrap());
+    assert!(rd.is_empty());
+}
+
+#[test]
+fn round_trip_shared_pointers() {
+    use std::rc::Rc;
+    use std::sync::Arc;
+
+    let val = Arc::new("le message".to_owned());
+    let buf = rmp_serde::to_vec(&val).unwrap();
+    assert_eq!(rmp_serde::to_vec("le message").unwrap(), buf);
+    assert_eq!(val, rmp_serde::from_slice::<Arc<String>>(&buf).unwrap());
+    assert_eq!(&*val, &*rmp_serde::from_slice::<Arc<str>>(&buf).unwrap());
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Inner {
+        id: u32,
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Outer {
+        a: Rc<Inner>,
+        b: Rc<Inner>,
+    }
+
+    // The shared value is written once for each pointer, and read back into two copies.
+    let inner = Rc::new(Inner { id: 7 });
+    let val = Outer { a: inner.clone(), b: inner };
+    let buf = rmp_serde::to_vec_named(&val).unwrap();
+    // {"a": {"id": 7}, "b": {"id": 7}}
+    assert_eq!(vec![0x82, 0xa1, b'a', 0x81, 0xa2, b'i', b'd', 0x07, 0xa1, b'b', 0x81, 0xa2, b'i', b'd', 0x07], buf);
+
+    let copy: Outer = rmp_serde::from_slice(&buf).unwrap();
+    assert_eq!(val, copy);
+    assert!(!Rc::ptr_eq(&copy.a, &copy.b));
+}
@@ -0,0 +1,76 @@
+use alloc::vec::Vec;
+
+use super::validate::{len_size, smallest_marker, walk};
+use super::ValidateError;
+use crate::Marker;
+
+/// A value using a larger marker than needed, as reported by [`audit_canonical`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CanonicalWarning {
+    /// The offset of the marker in the buffer.
+    pub offset: usize,
+    /// The marker the value was written with.
+    pub marker: Marker,
+    /// The smallest marker able to hold the value.
+    pub smallest: Marker,
+}
+
+impl CanonicalWarning {
+    /// Returns the number of bytes saved by writing the value with the smallest marker, which is 0
+    /// for a non-negative integer only written with a signed marker of the right size.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmp::decode::CanonicalWarning;
+    /// use rmp::Marker;
+    ///
+    /// // uint 32 1, instead of a positive fixint
+    /// let warning = CanonicalWarning { offset: 0, marker: Marker::U32, smallest: Marker::FixPos(1) };
+    /// assert_eq!(4, warning.wasted_bytes());
+    /// ```
+    #[must_use]
+    pub fn wasted_bytes(&self) -> usize {
+        (len_size(self.marker) - len_size(self.smallest)) as usize
+    }
+}
+
+/// Walks the MessagePack value in the given buffer, reporting every value which could be written
+/// with a smaller marker, in the order in which they appear.
+///
+/// The rules are those of [`validate_minimal`](super::validate_minimal), which stops at the first
+/// such value: an integer in more bytes than needed or with a signed marker while non-negative,
+/// a string, binary, array, map or ext whose length fits in a smaller marker, including the fix
+/// ones. Like [`validate`](super::validate), the buffer must contain exactly one well-formed
+/// value. No value is built, so the only allocation is the returned list.
+///
+/// # Errors
+///
+/// Returns the same errors as [`validate`](super::validate). No warning is returned for a buffer
+/// which is not well-formed, as the values following the error can not be found.
+///
+/// # Examples
+///
+/// ```
+/// use rmp::decode::{audit_canonical, CanonicalWarning};
+/// use rmp::Marker;
+///
+/// // [uint 16 1, str 8 "a", 2]
+/// let buf = [0x93, 0xcd, 0x00, 0x01, 0xd9, 0x01, 0x61, 0x02];
+///
+/// assert_eq!(vec![
+///     CanonicalWarning { offset: 1, marker: Marker::U16, smallest: Marker::FixPos(1) },
+///     CanonicalWarning { offset: 4, marker: Marker::Str8, smallest: Marker::FixStr(1) },
+/// ], audit_canonical(&buf).unwrap());
+/// ```
+pub fn audit_canonical(buf: &[u8]) -> Result<Vec<CanonicalWarning>, ValidateError> {
+    let mut warnings = Vec::new();
+    walk(buf, |offset, marker| {
+        let smallest = smallest_marker(buf, offset + 1, marker);
+        if smallest != marker {
+            warnings.push(CanonicalWarning { offset, marker, smallest });
+        }
+        Ok(())
+    })?;
+    Ok(warnings)
+}
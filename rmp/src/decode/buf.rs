@@ -0,0 +1,97 @@
+//! Implementation of the [`BytesReader`] type
+
+use super::RmpRead;
+use crate::decode::bytes::BytesReadError;
+
+/// An adapter which reads from a [`bytes::Buf`], advancing it as values are decoded.
+///
+/// The data is read directly out of the chunks of the buffer, so a non-contiguous buffer, such
+/// as a chain of network frames, can be decoded without first being copied into a single slice.
+///
+/// Available with the `bytes` feature, which also works on `#[no_std]`.
+///
+/// # Examples
+///
+/// ```
+/// use rmp::decode::{read_array_len, read_int, BytesReader};
+///
+/// let mut rd = BytesReader::new(&[0x92, 0x01, 0xcd, 0x01, 0x00][..]);
+///
+/// assert_eq!(2, read_array_len(&mut rd).unwrap());
+/// assert_eq!(1, read_int::<u16, _>(&mut rd).unwrap());
+/// assert_eq!(256, read_int::<u16, _>(&mut rd).unwrap());
+/// assert_eq!(5, rd.position());
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct BytesReader<B> {
+    buf: B,
+    /// The number of bytes read so far, only used for error reporting.
+    current_position: u64,
+}
+
+impl<B: bytes::Buf> BytesReader<B> {
+    /// Wraps the given buffer, setting the internal position to zero.
+    #[inline]
+    pub const fn new(buf: B) -> Self {
+        Self { buf, current_position: 0 }
+    }
+
+    /// Returns the number of bytes read so far.
+    #[inline]
+    #[must_use]
+    pub const fn position(&self) -> u64 {
+        self.current_position
+    }
+
+    /// Gets a reference to the underlying buffer.
+    #[inline]
+    pub const fn get_ref(&self) -> &B {
+        &self.buf
+    }
+
+    /// Gets a mutable reference to the underlying buffer.
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut B {
+        &mut self.buf
+    }
+
+    /// Consumes this adapter, returning the underlying buffer with the remaining data.
+    #[inline]
+    pub fn into_inner(self) -> B {
+        self.buf
+    }
+}
+
+impl<B: bytes::Buf> RmpRead for BytesReader<B> {
+    type Error = BytesReadError;
+
+    #[inline]
+    fn read_u8(&mut self) -> Result<u8, Self::Error> {
+        if self.buf.has_remaining() {
+            self.current_position += 1;
+            Ok(self.buf.get_u8())
+        } else {
+            Err(BytesReadError::InsufficientBytes {
+                expected: 1,
+                actual: 0,
+                position: self.current_position,
+            })
+        }
+    }
+
+    #[inline]
+    fn read_exact_buf(&mut self, buf: &mut [u8]) -> Result<(), Self::Error> {
+        let to_read = buf.len();
+        if to_read <= self.buf.remaining() {
+            self.buf.copy_to_slice(buf);
+            self.current_position += to_read as u64;
+            Ok(())
+        } else {
+            Err(BytesReadError::InsufficientBytes {
+                expected: to_read,
+                actual: self.buf.remaining(),
+                position: self.current_position,
+            })
+        }
+    }
+}
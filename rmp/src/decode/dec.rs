@@ -5,13 +5,17 @@ use crate::Marker;
 ///
 /// The first byte should be the marker and the others should represent the data itself.
 ///
+/// The exact IEEE-754 bit pattern is read, so NaN payloads and the sign of zero are preserved.
+///
 /// # Errors
 ///
 /// This function will return `ValueReadError` on any I/O error while reading either the marker or
 /// the data.
 ///
 /// It also returns `ValueReadError::TypeMismatch` if the actual type is not equal with the
-/// expected one, indicating you with the actual type.
+/// expected one, indicating you with the actual type. Only the `F32` marker is accepted:
+/// integers and `f64` values are rejected rather than converted, which makes this function suitable
+/// for enforcing a float of this exact width.
 ///
 /// # Note
 ///
@@ -28,13 +32,17 @@ pub fn read_f32<R: RmpRead>(rd: &mut R) -> Result<f32, ValueReadError<R::Error>>
 ///
 /// The first byte should be the marker and the others should represent the data itself.
 ///
+/// The exact IEEE-754 bit pattern is read, so NaN payloads and the sign of zero are preserved.
+///
 /// # Errors
 ///
 /// This function will return `ValueReadError` on any I/O error while reading either the marker or
 /// the data.
 ///
 /// It also returns `ValueReadError::TypeMismatch` if the actual type is not equal with the
-/// expected one, indicating you with the actual type.
+/// expected one, indicating you with the actual type. Only the `F64` marker is accepted:
+/// integers and `f32` values are rejected rather than converted, which makes this function suitable
+/// for enforcing a float of this exact width.
 ///
 /// # Note
 ///
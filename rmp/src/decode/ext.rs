@@ -166,3 +166,97 @@ pub fn read_ext_meta<R: RmpRead>(rd: &mut R) -> Result<ExtMeta, ValueReadError<R
 
     Ok(meta)
 }
+
+/// A reader of the data of an ext value, limited to the size given by its [`ExtMeta`].
+///
+/// This allows processing large ext data, such as embedded images, in chunks instead of copying it
+/// into a buffer at once. Reads never go past the end of the data, so the underlying reader is
+/// positioned right after the ext value once all of its data has been read or skipped.
+///
+/// With the `std` feature, it implements `std::io::Read` for readers failing with
+/// `std::io::Error`, returning `Ok(0)` at the end of the data.
+///
+/// # Examples
+///
+/// ```
+/// use rmp::decode::{read_ext_meta, read_pfix, ExtDataReader};
+///
+/// // ext8(1, [1, 2, 3, 4, 5]), followed by 42
+/// let buf = [0xc7, 0x05, 0x01, 0x01, 0x02, 0x03, 0x04, 0x05, 0x2a];
+/// let mut rd = &buf[..];
+///
+/// let meta = read_ext_meta(&mut rd).unwrap();
+/// let mut data = ExtDataReader::new(&mut rd, &meta);
+///
+/// let mut chunk = [0; 2];
+/// assert_eq!(2, data.read(&mut chunk).unwrap());
+/// assert_eq!([0x01, 0x02], chunk);
+/// assert_eq!(3, data.remaining());
+///
+/// data.skip().unwrap();
+/// assert_eq!(0, data.read(&mut chunk).unwrap());
+/// assert_eq!(42, read_pfix(&mut rd).unwrap());
+/// ```
+#[derive(Debug)]
+pub struct ExtDataReader<'a, R> {
+    rd: &'a mut R,
+    remaining: u32,
+}
+
+impl<'a, R: RmpRead> ExtDataReader<'a, R> {
+    /// Creates a reader of the data of the ext value whose meta information was just read from
+    /// the given reader with [`read_ext_meta`].
+    #[inline]
+    pub fn new(rd: &'a mut R, meta: &ExtMeta) -> Self {
+        Self { rd, remaining: meta.size }
+    }
+
+    /// Returns the number of bytes of the data left to read.
+    #[inline]
+    #[must_use]
+    pub const fn remaining(&self) -> u32 {
+        self.remaining
+    }
+
+    /// Reads the next bytes of the data into the buffer, returning how many were read.
+    ///
+    /// The buffer is filled completely unless the data ends first, in which case only the rest of
+    /// the data is read. Returns 0 at the end of the data.
+    ///
+    /// # Errors
+    ///
+    /// This function returns the error of the underlying reader, such as reaching the end of the
+    /// input before the end of the data.
+    pub fn read(&mut self, buf: &mut [u8]) -> Result<usize, R::Error> {
+        let len = buf.len().min(self.remaining as usize);
+        self.rd.read_exact_buf(&mut buf[..len])?;
+        self.remaining -= len as u32;
+        Ok(len)
+    }
+
+    /// Reads and discards the rest of the data, leaving the underlying reader after the ext value.
+    ///
+    /// # Errors
+    ///
+    /// This function returns the error of the underlying reader, such as reaching the end of the
+    /// input before the end of the data.
+    pub fn skip(&mut self) -> Result<(), R::Error> {
+        let mut buf = [0; 256];
+        while self.read(&mut buf)? != 0 {}
+        Ok(())
+    }
+
+    /// Returns the underlying reader, positioned after the data read so far.
+    #[inline]
+    pub fn into_inner(self) -> &'a mut R {
+        self.rd
+    }
+}
+
+#[cfg(feature = "std")]
+impl<R: RmpRead<Error = std::io::Error>> std::io::Read for ExtDataReader<'_, R> {
+    #[inline]
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        Self::read(self, buf)
+    }
+}
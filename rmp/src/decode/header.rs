@@ -0,0 +1,64 @@
+use super::{read_marker, RmpRead, ValueReadError};
+use crate::Marker;
+
+/// The marker of a MessagePack value and, if it has one, its length, as returned by
+/// [`read_header`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Header {
+    /// The marker of the value.
+    pub marker: Marker,
+    /// The number of bytes of a string, binary or ext, the number of elements of an array or the
+    /// number of entries of a map. `None` for every other marker, whose payload has a fixed size.
+    pub len: Option<u32>,
+}
+
+/// Reads the marker of a value and, for strings, binaries, exts, arrays and maps, its length,
+/// without reading anything else.
+///
+/// This is the building block of custom parsers: the reader is left at the start of the payload,
+/// which can then be read or skipped as needed. For an ext the payload starts with the type, which
+/// is not counted in the length, and for an array or a map it is made of its elements or of the
+/// keys and values of its entries, each one a value with its own header.
+///
+/// # Errors
+///
+/// This function returns `ValueReadError::InvalidMarkerRead` or
+/// `ValueReadError::InvalidDataRead` on any I/O error while reading the marker or the length.
+/// The reserved marker is returned like any other one.
+///
+/// # Examples
+///
+/// ```
+/// use rmp::decode::{read_header, Header};
+/// use rmp::Marker;
+///
+/// // str 8 "le", uint 16 300
+/// let buf = [0xd9, 0x02, 0x6c, 0x65, 0xcd, 0x01, 0x2c];
+/// let mut rd = &buf[..];
+///
+/// assert_eq!(Header { marker: Marker::Str8, len: Some(2) }, read_header(&mut rd).unwrap());
+/// assert_eq!([0x6c, 0x65], rd[..2]);
+/// rd = &rd[2..];
+/// assert_eq!(Header { marker: Marker::U16, len: None }, read_header(&mut rd).unwrap());
+/// assert_eq!([0x01, 0x2c], rd);
+/// ```
+pub fn read_header<R: RmpRead>(rd: &mut R) -> Result<Header, ValueReadError<R::Error>> {
+    let marker = read_marker(rd)?;
+    let len = match marker {
+        Marker::FixStr(len) | Marker::FixArray(len) | Marker::FixMap(len) => Some(len.into()),
+        Marker::FixExt1 => Some(1),
+        Marker::FixExt2 => Some(2),
+        Marker::FixExt4 => Some(4),
+        Marker::FixExt8 => Some(8),
+        Marker::FixExt16 => Some(16),
+        Marker::Str8 | Marker::Bin8 | Marker::Ext8 => Some(rd.read_data_u8()?.into()),
+        Marker::Str16 | Marker::Bin16 | Marker::Ext16 | Marker::Array16 | Marker::Map16 => {
+            Some(rd.read_data_u16()?.into())
+        }
+        Marker::Str32 | Marker::Bin32 | Marker::Ext32 | Marker::Array32 | Marker::Map32 => {
+            Some(rd.read_data_u32()?)
+        }
+        _ => None,
+    };
+    Ok(Header { marker, len })
+}
@@ -0,0 +1,139 @@
+use alloc::vec::Vec;
+use core::cmp::min;
+use core::fmt::{self, Display, Formatter};
+#[cfg(feature = "std")]
+use std::error;
+
+#[allow(deprecated)]
+use super::Error;
+use super::{read_array_len, read_bool, read_f32, read_f64, read_int, NumValueReadError, RmpRead, RmpReadErr, ValueReadError};
+
+/// The number of elements allocated up front by [`read_homogeneous_array`], whatever the length
+/// of the array, so that a forged length can not exhaust memory before the elements are read.
+const PREALLOC_MAX: usize = 64 * 1024;
+
+/// A type which the elements of an array can be read as, with [`read_homogeneous_array`].
+///
+/// This is implemented for `bool`, the integer types, which accept any integer marker whose value
+/// is in range, and `f32` and `f64`, which only accept their own marker, like [`read_f32`] and
+/// [`read_f64`].
+pub trait ArrayElement: Sized {
+    /// Reads a single value, marker included.
+    ///
+    /// # Errors
+    ///
+    /// Returns `NumValueReadError::TypeMismatch` if the marker is not one of this type, and
+    /// `NumValueReadError::OutOfRange` if the value does not fit it.
+    fn read_element<R: RmpRead>(rd: &mut R) -> Result<Self, NumValueReadError<R::Error>>;
+}
+
+impl ArrayElement for bool {
+    #[inline]
+    fn read_element<R: RmpRead>(rd: &mut R) -> Result<Self, NumValueReadError<R::Error>> {
+        Ok(read_bool(rd)?)
+    }
+}
+
+impl ArrayElement for f32 {
+    #[inline]
+    fn read_element<R: RmpRead>(rd: &mut R) -> Result<Self, NumValueReadError<R::Error>> {
+        Ok(read_f32(rd)?)
+    }
+}
+
+impl ArrayElement for f64 {
+    #[inline]
+    fn read_element<R: RmpRead>(rd: &mut R) -> Result<Self, NumValueReadError<R::Error>> {
+        Ok(read_f64(rd)?)
+    }
+}
+
+macro_rules! impl_array_element_for_int {
+    ($($ty:ty),*) => {
+        $(
+            impl ArrayElement for $ty {
+                #[inline]
+                fn read_element<R: RmpRead>(rd: &mut R) -> Result<Self, NumValueReadError<R::Error>> {
+                    read_int(rd)
+                }
+            }
+        )*
+    };
+}
+
+impl_array_element_for_int!(u8, u16, u32, u64, usize, i8, i16, i32, i64, isize);
+
+/// An error which can occur when reading an array with [`read_homogeneous_array`].
+#[derive(Debug)]
+#[allow(deprecated)] // Used for compatibility
+pub enum HomogeneousArrayError<E: RmpReadErr = Error> {
+    /// Failed to read the marker or the length of the array, or the value is not an array.
+    Len(ValueReadError<E>),
+    /// Failed to read the element at the given index.
+    Element(usize, NumValueReadError<E>),
+}
+
+#[cfg(feature = "std")]
+impl<E: RmpReadErr> error::Error for HomogeneousArrayError<E> {
+    #[cold]
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            Self::Len(ref err) => Some(err),
+            Self::Element(_, ref err) => Some(err),
+        }
+    }
+}
+
+impl<E: RmpReadErr> Display for HomogeneousArrayError<E> {
+    #[cold]
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), fmt::Error> {
+        match *self {
+            Self::Len(ref err) => write!(f, "failed to read MessagePack array: {err}"),
+            Self::Element(idx, ref err) => write!(f, "invalid MessagePack array element at index {idx}: {err}"),
+        }
+    }
+}
+
+/// Reads an array whose elements all have the same type, such as a column of integers, straight
+/// into a `Vec`.
+///
+/// The length of the array is read, then each element as `T`, stopping at the first one which
+/// can not be read. This avoids decoding each element into a generic value, and checking and
+/// converting it afterwards.
+///
+/// # Errors
+///
+/// Returns `HomogeneousArrayError::Len` if the value is not an array or its length can not be
+/// read, and `HomogeneousArrayError::Element` with the index and the error of the first element
+/// which is not of type `T`, is out of its range or can not be read. The reader is then left in
+/// the middle of the array.
+///
+/// # Examples
+///
+/// ```
+/// use rmp::decode::{read_homogeneous_array, HomogeneousArrayError, NumValueReadError};
+/// use rmp::Marker;
+///
+/// // [1, -2, 300]
+/// let buf = [0x93, 0x01, 0xfe, 0xcd, 0x01, 0x2c];
+/// assert_eq!(vec![1, -2, 300], read_homogeneous_array::<i64, _>(&mut &buf[..]).unwrap());
+///
+/// // [1, "a"]
+/// let buf = [0x92, 0x01, 0xa1, 0x61];
+/// match read_homogeneous_array::<i64, _>(&mut &buf[..]) {
+///     Err(HomogeneousArrayError::Element(1, NumValueReadError::TypeMismatch(Marker::FixStr(1)))) => {}
+///     other => panic!("unexpected result: {other:?}"),
+/// }
+/// ```
+pub fn read_homogeneous_array<T, R>(rd: &mut R) -> Result<Vec<T>, HomogeneousArrayError<R::Error>>
+where
+    T: ArrayElement,
+    R: RmpRead,
+{
+    let len = read_array_len(rd).map_err(HomogeneousArrayError::Len)? as usize;
+    let mut vec = Vec::with_capacity(min(len, PREALLOC_MAX));
+    for idx in 0..len {
+        vec.push(T::read_element(rd).map_err(|err| HomogeneousArrayError::Element(idx, err))?);
+    }
+    Ok(vec)
+}
@@ -8,27 +8,72 @@
 //! I/O error and simultaneously be a recoverable state (for example, when reading from
 //! non-blocking socket and it returns EWOULDBLOCK) be sure that you buffer the data externally
 //! to avoid data loss (using `BufRead` readers with manual consuming or some other way).
-
+//!
+//! # Errors
+//!
+//! The error types of this module are generic over the error of the underlying [`RmpRead`]
+//! reader, which is kept as it is in the `InvalidMarkerRead` and `InvalidDataRead` variants. With
+//! the `std` feature, the errors of any reader implement `std::error::Error` and, for readers
+//! failing with `std::io::Error`, convert into `std::io::Error` with `?`, so that they fit into
+//! functions returning `io::Result`, `Box<dyn Error>` or an application error type implementing
+//! `From<io::Error>`.
+//!
+//! A reader with its own error type implements `std::io::Read`, wrapping its errors with
+//! `io::Error::other`; the original error can then be recovered with
+//! `io::Error::get_ref` and `downcast_ref`.
+//!
+//! ```
+//! use std::io;
+//!
+//! fn read_point(rd: &mut &[u8]) -> io::Result<(i32, i32)> {
+//!     if rmp::decode::read_array_len(rd)? != 2 {
+//!         return Err(io::Error::new(io::ErrorKind::InvalidData, "expected a pair"));
+//!     }
+//!     Ok((rmp::decode::read_int(rd)?, rmp::decode::read_int(rd)?))
+//! }
+//!
+//! assert_eq!((1, -2), read_point(&mut &[0x92, 0x01, 0xfe][..]).unwrap());
+//! // A type mismatch is reported as invalid data.
+//! let err = read_point(&mut &[0x92, 0xc0, 0x01][..]).unwrap_err();
+//! assert_eq!(io::ErrorKind::InvalidData, err.kind());
+//! // A truncated input is reported as the original I/O error.
+//! let err = read_point(&mut &[0x92, 0x01][..]).unwrap_err();
+//! assert_eq!(io::ErrorKind::UnexpectedEof, err.kind());
+//! ```
+
+mod audit;
 mod dec;
 mod ext;
+mod header;
+mod homogeneous;
+mod scalar;
 mod sint;
+mod stats;
 mod str;
 mod uint;
+mod validate;
 
 #[cfg(feature = "std")]
 mod est;
 #[cfg(feature = "std")]
 pub use est::{MessageLen, LenError};
 
+pub use self::audit::{audit_canonical, CanonicalWarning};
 pub use self::dec::{read_f32, read_f64};
 pub use self::ext::{
-    read_ext_meta, read_fixext1, read_fixext16, read_fixext2, read_fixext4, read_fixext8, ExtMeta,
+    read_ext_meta, read_fixext1, read_fixext16, read_fixext2, read_fixext4, read_fixext8, ExtDataReader,
+    ExtMeta,
 };
+pub use self::header::{read_header, Header};
+pub use self::homogeneous::{read_homogeneous_array, ArrayElement, HomogeneousArrayError};
+pub use self::scalar::{read_scalar, Scalar, ScalarReadError};
 pub use self::sint::{read_i16, read_i32, read_i64, read_i8, read_nfix};
+pub use self::stats::{collect_stats, Stats};
 #[allow(deprecated)]
 // While we re-export deprecated items, we don't want to trigger warnings while compiling this crate
 pub use self::str::{read_str, read_str_from_slice, read_str_len, read_str_ref, DecodeStringError};
 pub use self::uint::{read_pfix, read_u16, read_u32, read_u64, read_u8};
+pub use self::validate::{validate, validate_minimal, ValidateError};
 
 use core::fmt::{self, Debug, Display, Formatter};
 #[cfg(feature = "std")]
@@ -41,6 +86,11 @@ use crate::Marker;
 pub mod bytes;
 pub use bytes::Bytes;
 
+#[cfg(feature = "bytes")]
+mod buf;
+#[cfg(feature = "bytes")]
+pub use buf::BytesReader;
+
 #[doc(inline)]
 #[allow(deprecated)]
 pub use crate::errors::Error;
@@ -76,6 +126,8 @@ mod sealed {
     #[cfg(not(feature = "std"))]
     impl<'a> Sealed for &'a [u8] {}
     impl Sealed for super::Bytes<'_> {}
+    #[cfg(feature = "bytes")]
+    impl<B: bytes::Buf> Sealed for super::BytesReader<B> {}
 }
 
 /// A type that `rmp` supports reading from.
@@ -202,7 +254,7 @@ pub enum ValueReadError<E: RmpReadErr = Error> {
 }
 
 #[cfg(feature = "std")]
-impl error::Error for ValueReadError {
+impl<E: RmpReadErr> error::Error for ValueReadError<E> {
     #[cold]
     fn source(&self) -> Option<&(dyn error::Error + 'static)> {
         match *self {
@@ -213,7 +265,7 @@ impl error::Error for ValueReadError {
     }
 }
 
-impl Display for ValueReadError {
+impl<E: RmpReadErr> Display for ValueReadError<E> {
     #[cold]
     fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), fmt::Error> {
         // TODO: This should probably use formatting
@@ -225,6 +277,42 @@ impl Display for ValueReadError {
     }
 }
 
+#[cfg(feature = "std")]
+impl<E: RmpReadErr> error::Error for MarkerReadError<E> {
+    #[cold]
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        Some(&self.0)
+    }
+}
+
+impl<E: RmpReadErr> Display for MarkerReadError<E> {
+    #[cold]
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), fmt::Error> {
+        f.write_str("failed to read MessagePack marker")
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<MarkerReadError<Self>> for std::io::Error {
+    #[cold]
+    fn from(err: MarkerReadError<Self>) -> Self {
+        err.0
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<ValueReadError<Self>> for std::io::Error {
+    /// Returns the I/O error, or an error of kind `InvalidData` for a type mismatch.
+    #[cold]
+    fn from(err: ValueReadError<Self>) -> Self {
+        match err {
+            ValueReadError::InvalidMarkerRead(err) |
+            ValueReadError::InvalidDataRead(err) => err,
+            ValueReadError::TypeMismatch(..) => Self::new(std::io::ErrorKind::InvalidData, err),
+        }
+    }
+}
+
 impl<E: RmpReadErr> From<MarkerReadError<E>> for ValueReadError<E> {
     #[cold]
     fn from(err: MarkerReadError<E>) -> Self {
@@ -247,6 +335,51 @@ pub fn read_marker<R: RmpRead>(rd: &mut R) -> Result<Marker, MarkerReadError<R::
     Ok(Marker::from_u8(rd.read_u8()?))
 }
 
+/// Decodes the first byte of the given buffer as a MessagePack marker without consuming it, or
+/// returns `None` if the buffer is empty.
+#[inline]
+#[must_use]
+pub fn peek_marker(buf: &[u8]) -> Option<Marker> {
+    buf.first().copied().map(Marker::from_u8)
+}
+
+/// How a struct is encoded, as returned by [`peek_struct_encoding`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StructEncoding {
+    /// A map from field names to values, as written by named serializers.
+    Map,
+    /// An array of field values in declaration order, as written by compact serializers.
+    Array,
+    /// Neither a map nor an array, or an empty buffer.
+    Other,
+}
+
+/// Tells whether the value at the start of the given buffer is a map or an array, that is whether
+/// a struct was encoded with its field names or as a tuple.
+///
+/// Only the marker is inspected, so the rest of the value is not checked to be well-formed.
+///
+/// # Examples
+///
+/// ```
+/// use rmp::decode::{peek_struct_encoding, StructEncoding};
+///
+/// // {"a": 1}
+/// assert_eq!(StructEncoding::Map, peek_struct_encoding(&[0x81, 0xa1, 0x61, 0x01]));
+/// // [1]
+/// assert_eq!(StructEncoding::Array, peek_struct_encoding(&[0x91, 0x01]));
+/// assert_eq!(StructEncoding::Other, peek_struct_encoding(&[0xc0]));
+/// assert_eq!(StructEncoding::Other, peek_struct_encoding(&[]));
+/// ```
+#[must_use]
+pub fn peek_struct_encoding(buf: &[u8]) -> StructEncoding {
+    match peek_marker(buf) {
+        Some(Marker::FixMap(..) | Marker::Map16 | Marker::Map32) => StructEncoding::Map,
+        Some(Marker::FixArray(..) | Marker::Array16 | Marker::Array32) => StructEncoding::Array,
+        _ => StructEncoding::Other,
+    }
+}
+
 /// Attempts to read a single byte from the given reader and to decode it as a nil value.
 ///
 /// According to the MessagePack specification, a nil value is represented as a single `0xc0` byte.
@@ -310,7 +443,7 @@ pub enum NumValueReadError<E: RmpReadErr = Error> {
 }
 
 #[cfg(feature = "std")]
-impl error::Error for NumValueReadError {
+impl<E: RmpReadErr> error::Error for NumValueReadError<E> {
     fn source(&self) -> Option<&(dyn error::Error + 'static)> {
         match *self {
             Self::InvalidMarkerRead(ref err) |
@@ -341,6 +474,21 @@ impl<E: RmpReadErr> From<MarkerReadError<E>> for NumValueReadError<E> {
     }
 }
 
+#[cfg(feature = "std")]
+impl From<NumValueReadError<Self>> for std::io::Error {
+    /// Returns the I/O error, or an error of kind `InvalidData` for a type mismatch or an out of
+    /// range value.
+    #[cold]
+    fn from(err: NumValueReadError<Self>) -> Self {
+        match err {
+            NumValueReadError::InvalidMarkerRead(err) |
+            NumValueReadError::InvalidDataRead(err) => err,
+            NumValueReadError::TypeMismatch(..) |
+            NumValueReadError::OutOfRange => Self::new(std::io::ErrorKind::InvalidData, err),
+        }
+    }
+}
+
 impl<E: RmpReadErr> From<ValueReadError<E>> for NumValueReadError<E> {
     #[cold]
     fn from(err: ValueReadError<E>) -> Self {
@@ -0,0 +1,191 @@
+use core::fmt::{self, Display, Formatter};
+use core::str::{from_utf8, Utf8Error};
+#[cfg(feature = "std")]
+use std::error;
+
+use super::validate::{read_len, skip_bytes};
+use crate::Marker;
+
+/// A single MessagePack value which is not an array or a map, borrowing its data from the buffer
+/// it was read from, as returned by [`read_scalar`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Scalar<'a> {
+    /// Nil.
+    Nil,
+    /// A boolean.
+    Bool(bool),
+    /// A non-negative integer, whichever marker it was encoded with.
+    UInt(u64),
+    /// A negative integer, whichever marker it was encoded with.
+    Int(i64),
+    /// A 32-bit float.
+    F32(f32),
+    /// A 64-bit float.
+    F64(f64),
+    /// A string.
+    Str(&'a str),
+    /// A binary.
+    Bin(&'a [u8]),
+    /// An ext, with its type and data.
+    Ext(i8, &'a [u8]),
+}
+
+/// An error which can occur when reading a value with [`read_scalar`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ScalarReadError {
+    /// The buffer ends before the value is complete.
+    Truncated,
+    /// The never used `0xc1` marker was found.
+    ReservedMarker,
+    /// The value is an array or a map, whose marker is given.
+    Container(Marker),
+    /// The string is not valid UTF-8.
+    InvalidUtf8(Utf8Error),
+}
+
+#[cfg(feature = "std")]
+impl error::Error for ScalarReadError {
+    #[cold]
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            Self::InvalidUtf8(ref err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl Display for ScalarReadError {
+    #[cold]
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), fmt::Error> {
+        match *self {
+            Self::Truncated => f.write_str("MessagePack value is truncated"),
+            Self::ReservedMarker => f.write_str("reserved MessagePack marker"),
+            Self::Container(marker) => write!(f, "expected a scalar MessagePack value, found {marker:?}"),
+            Self::InvalidUtf8(ref err) => write!(f, "MessagePack string is not valid UTF-8: {err}"),
+        }
+    }
+}
+
+/// Reads a single value which is not an array or a map from the buffer, returning it along with
+/// the rest of the buffer.
+///
+/// This covers every other marker, and is lighter than decoding a complete value when a leaf is
+/// expected: nothing is allocated, strings, binaries and exts borrow their data from the buffer.
+///
+/// Only slices are supported, rather than any [`RmpRead`](super::RmpRead) reader, because that
+/// borrowing is the point of a `Scalar`: a reader can not lend out its data, so a reader based
+/// variant would have to copy every string, binary and ext into a buffer. To read from a reader,
+/// use [`read_header`](super::read_header) to find the kind of the value and then the typed
+/// functions such as [`read_int`](super::read_int) and [`read_str`](super::read_str).
+///
+/// # Errors
+///
+/// Returns `ScalarReadError::Container` if the value is an array or a map, without consuming it,
+/// `ScalarReadError::Truncated` if the buffer ends before the value is complete,
+/// `ScalarReadError::ReservedMarker` if the never used marker is found and
+/// `ScalarReadError::InvalidUtf8` if a string is not valid UTF-8.
+///
+/// # Examples
+///
+/// ```
+/// use rmp::decode::{read_scalar, Scalar, ScalarReadError};
+/// use rmp::Marker;
+///
+/// // uint 16 300, "a", fixarray
+/// let buf = [0xcd, 0x01, 0x2c, 0xa1, 0x61, 0x90];
+///
+/// let (val, rest) = read_scalar(&buf).unwrap();
+/// assert_eq!(Scalar::UInt(300), val);
+/// let (val, rest) = read_scalar(rest).unwrap();
+/// assert_eq!(Scalar::Str("a"), val);
+/// assert_eq!(Err(ScalarReadError::Container(Marker::FixArray(0))), read_scalar(rest));
+/// ```
+pub fn read_scalar(buf: &[u8]) -> Result<(Scalar<'_>, &[u8]), ScalarReadError> {
+    let marker = Marker::from_u8(*buf.first().ok_or(ScalarReadError::Truncated)?);
+    let mut pos = 1;
+
+    let val = match marker {
+        Marker::Null => Scalar::Nil,
+        Marker::True => Scalar::Bool(true),
+        Marker::False => Scalar::Bool(false),
+        Marker::FixPos(val) => Scalar::UInt(val.into()),
+        Marker::FixNeg(val) => Scalar::Int(val.into()),
+        Marker::U8 => Scalar::UInt(data_at(buf, &mut pos, 1)?[0].into()),
+        Marker::U16 => Scalar::UInt(u16::from_be_bytes(array(data_at(buf, &mut pos, 2)?)).into()),
+        Marker::U32 => Scalar::UInt(u32::from_be_bytes(array(data_at(buf, &mut pos, 4)?)).into()),
+        Marker::U64 => Scalar::UInt(u64::from_be_bytes(array(data_at(buf, &mut pos, 8)?))),
+        Marker::I8 => int(i8::from_be_bytes(array(data_at(buf, &mut pos, 1)?)).into()),
+        Marker::I16 => int(i16::from_be_bytes(array(data_at(buf, &mut pos, 2)?)).into()),
+        Marker::I32 => int(i32::from_be_bytes(array(data_at(buf, &mut pos, 4)?)).into()),
+        Marker::I64 => int(i64::from_be_bytes(array(data_at(buf, &mut pos, 8)?))),
+        Marker::F32 => Scalar::F32(f32::from_be_bytes(array(data_at(buf, &mut pos, 4)?))),
+        Marker::F64 => Scalar::F64(f64::from_be_bytes(array(data_at(buf, &mut pos, 8)?))),
+        Marker::FixStr(..) | Marker::Str8 | Marker::Str16 | Marker::Str32 => {
+            let len = len(buf, &mut pos, marker)?;
+            let s = from_utf8(data_at(buf, &mut pos, len)?).map_err(ScalarReadError::InvalidUtf8)?;
+            Scalar::Str(s)
+        }
+        Marker::Bin8 | Marker::Bin16 | Marker::Bin32 => {
+            let len = len(buf, &mut pos, marker)?;
+            Scalar::Bin(data_at(buf, &mut pos, len)?)
+        }
+        Marker::FixExt1 |
+        Marker::FixExt2 |
+        Marker::FixExt4 |
+        Marker::FixExt8 |
+        Marker::FixExt16 |
+        Marker::Ext8 |
+        Marker::Ext16 |
+        Marker::Ext32 => {
+            let len = len(buf, &mut pos, marker)?;
+            let ty = data_at(buf, &mut pos, 1)?[0] as i8;
+            Scalar::Ext(ty, data_at(buf, &mut pos, len)?)
+        }
+        Marker::FixArray(..) |
+        Marker::Array16 |
+        Marker::Array32 |
+        Marker::FixMap(..) |
+        Marker::Map16 |
+        Marker::Map32 => return Err(ScalarReadError::Container(marker)),
+        Marker::Reserved => return Err(ScalarReadError::ReservedMarker),
+    };
+    Ok((val, &buf[pos..]))
+}
+
+/// Returns the integer as `UInt` if it is non-negative, so that each integer has a single
+/// representation.
+#[inline]
+fn int(val: i64) -> Scalar<'static> {
+    match u64::try_from(val) {
+        Ok(val) => Scalar::UInt(val),
+        Err(..) => Scalar::Int(val),
+    }
+}
+
+#[inline]
+fn array<const N: usize>(data: &[u8]) -> [u8; N] {
+    data.try_into().unwrap()
+}
+
+/// Reads the length of a string, binary or ext with the given marker, following it.
+fn len(buf: &[u8], pos: &mut usize, marker: Marker) -> Result<u64, ScalarReadError> {
+    let size = match marker {
+        Marker::FixStr(len) => return Ok(len.into()),
+        Marker::FixExt1 => return Ok(1),
+        Marker::FixExt2 => return Ok(2),
+        Marker::FixExt4 => return Ok(4),
+        Marker::FixExt8 => return Ok(8),
+        Marker::FixExt16 => return Ok(16),
+        Marker::Str8 | Marker::Bin8 | Marker::Ext8 => 1,
+        Marker::Str16 | Marker::Bin16 | Marker::Ext16 => 2,
+        _ => 4,
+    };
+    read_len(buf, pos, size).map_err(|_| ScalarReadError::Truncated)
+}
+
+#[inline]
+fn data_at<'a>(buf: &'a [u8], pos: &mut usize, len: u64) -> Result<&'a [u8], ScalarReadError> {
+    let start = *pos;
+    *pos = skip_bytes(buf, start, len).map_err(|_| ScalarReadError::Truncated)?;
+    Ok(&buf[start..*pos])
+}
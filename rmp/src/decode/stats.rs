@@ -0,0 +1,210 @@
+use alloc::vec::Vec;
+
+use super::validate::{len_size, read_len, walk};
+use super::ValidateError;
+use crate::Marker;
+
+/// Statistics about the shape of a MessagePack value, as returned by [`collect_stats`].
+///
+/// Values are counted by the family of their marker, so for example `uints` counts positive
+/// fixints along with all of the `uint 8` to `uint 64` markers, and by the marker itself, see
+/// [`marker_count`](Self::marker_count). Arrays and maps are counted themselves, in addition to
+/// the values they contain.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Stats {
+    /// The number of nils.
+    pub nils: usize,
+    /// The number of booleans.
+    pub bools: usize,
+    /// The number of positive fixints and unsigned integers.
+    pub uints: usize,
+    /// The number of negative fixints and signed integers.
+    pub sints: usize,
+    /// The number of `f32` and `f64` floats.
+    pub floats: usize,
+    /// The number of strings.
+    pub strs: usize,
+    /// The number of binaries.
+    pub bins: usize,
+    /// The number of arrays.
+    pub arrays: usize,
+    /// The number of maps.
+    pub maps: usize,
+    /// The number of ext values.
+    pub exts: usize,
+    /// The deepest nesting of arrays and maps, which is 0 for a scalar and 1 for a flat array.
+    pub max_depth: usize,
+    /// The length of the encoded value in bytes.
+    pub total_bytes: usize,
+    /// The length in bytes of the longest string, or 0 if there are none.
+    pub max_str_len: usize,
+    /// The length in bytes of the longest binary, or 0 if there are none.
+    pub max_bin_len: usize,
+    /// The number of values written with each marker, indexed by its byte.
+    markers: [usize; 256],
+}
+
+impl Default for Stats {
+    fn default() -> Self {
+        Self {
+            nils: 0,
+            bools: 0,
+            uints: 0,
+            sints: 0,
+            floats: 0,
+            strs: 0,
+            bins: 0,
+            arrays: 0,
+            maps: 0,
+            exts: 0,
+            max_depth: 0,
+            total_bytes: 0,
+            max_str_len: 0,
+            max_bin_len: 0,
+            markers: [0; 256],
+        }
+    }
+}
+
+impl Stats {
+    /// Returns the number of values written with the given marker.
+    ///
+    /// The fix markers are counted for each value they hold, so `Marker::FixStr(3)` counts the
+    /// strings of 3 bytes written as fixstrs. [`marker_counts`](Self::marker_counts) lists every
+    /// marker found.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmp::decode::collect_stats;
+    /// use rmp::Marker;
+    ///
+    /// // [1, uint 16 1, uint 16 2]
+    /// let stats = collect_stats(&[0x93, 0x01, 0xcd, 0x00, 0x01, 0xcd, 0x00, 0x02]).unwrap();
+    ///
+    /// assert_eq!(2, stats.marker_count(Marker::U16));
+    /// assert_eq!(1, stats.marker_count(Marker::FixPos(1)));
+    /// assert_eq!(0, stats.marker_count(Marker::U8));
+    /// ```
+    #[must_use]
+    pub fn marker_count(&self, marker: Marker) -> usize {
+        self.markers[usize::from(marker.to_u8())]
+    }
+
+    /// Returns every marker found, with the number of values written with it, in the order of
+    /// their bytes.
+    pub fn marker_counts(&self) -> impl Iterator<Item = (Marker, usize)> + '_ {
+        (0..=u8::MAX)
+            .map(|byte| (Marker::from_u8(byte), self.markers[usize::from(byte)]))
+            .filter(|&(_, count)| count > 0)
+    }
+}
+
+/// Walks the MessagePack value in the given buffer, counting its values by type and by marker
+/// along with its depth and the length of its strings and binaries.
+///
+/// Like [`validate`](super::validate), the buffer must contain exactly one well-formed value, and
+/// the data of strings, binaries and exts is skipped without being decoded. No value is built, so
+/// the only allocation is the stack of open arrays and maps, which holds a single count per level.
+///
+/// # Errors
+///
+/// Returns the same errors as [`validate`](super::validate).
+///
+/// # Examples
+///
+/// ```
+/// use rmp::decode::collect_stats;
+///
+/// // [1, -1, ["abc", nil]]
+/// let stats = collect_stats(&[0x93, 0x01, 0xff, 0x92, 0xa3, 0x61, 0x62, 0x63, 0xc0]).unwrap();
+///
+/// assert_eq!(2, stats.arrays);
+/// assert_eq!(1, stats.uints);
+/// assert_eq!(1, stats.sints);
+/// assert_eq!(1, stats.nils);
+/// assert_eq!(2, stats.max_depth);
+/// assert_eq!(3, stats.max_str_len);
+/// assert_eq!(9, stats.total_bytes);
+/// ```
+pub fn collect_stats(buf: &[u8]) -> Result<Stats, ValidateError> {
+    let mut stats = Stats::default();
+    // Number of values that still need to be read by each open array and map, innermost last.
+    let mut open: Vec<u64> = Vec::new();
+
+    let total_bytes = walk(buf, |offset, marker| {
+        stats.markers[usize::from(marker.to_u8())] += 1;
+        // The walk has already checked that the length is in the buffer.
+        let len = match marker {
+            Marker::FixStr(len) | Marker::FixArray(len) | Marker::FixMap(len) => len.into(),
+            _ => read_len(buf, &mut (offset + 1), len_size(marker)).unwrap_or(0),
+        };
+
+        let items = match marker {
+            Marker::Null => {
+                stats.nils += 1;
+                None
+            }
+            Marker::True | Marker::False => {
+                stats.bools += 1;
+                None
+            }
+            Marker::FixPos(..) | Marker::U8 | Marker::U16 | Marker::U32 | Marker::U64 => {
+                stats.uints += 1;
+                None
+            }
+            Marker::FixNeg(..) | Marker::I8 | Marker::I16 | Marker::I32 | Marker::I64 => {
+                stats.sints += 1;
+                None
+            }
+            Marker::F32 | Marker::F64 => {
+                stats.floats += 1;
+                None
+            }
+            Marker::FixStr(..) | Marker::Str8 | Marker::Str16 | Marker::Str32 => {
+                stats.strs += 1;
+                stats.max_str_len = stats.max_str_len.max(len as usize);
+                None
+            }
+            Marker::Bin8 | Marker::Bin16 | Marker::Bin32 => {
+                stats.bins += 1;
+                stats.max_bin_len = stats.max_bin_len.max(len as usize);
+                None
+            }
+            Marker::FixArray(..) | Marker::Array16 | Marker::Array32 => {
+                stats.arrays += 1;
+                Some(len)
+            }
+            Marker::FixMap(..) | Marker::Map16 | Marker::Map32 => {
+                stats.maps += 1;
+                Some(len * 2)
+            }
+            _ => {
+                stats.exts += 1;
+                None
+            }
+        };
+
+        if let Some(items) = items {
+            stats.max_depth = stats.max_depth.max(open.len() + 1);
+            if items > 0 {
+                open.push(items);
+                return Ok(());
+            }
+        }
+
+        // The value is complete, and so is every container it was the last value of.
+        while let Some(left) = open.last_mut() {
+            *left -= 1;
+            if *left > 0 {
+                break;
+            }
+            open.pop();
+        }
+        Ok(())
+    })?;
+
+    stats.total_bytes = total_bytes;
+    Ok(stats)
+}
@@ -0,0 +1,268 @@
+use core::fmt::{self, Display, Formatter};
+#[cfg(feature = "std")]
+use std::error;
+
+use crate::Marker;
+
+/// An error which can occur when validating a MessagePack buffer with [`validate`] or
+/// [`validate_minimal`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ValidateError {
+    /// The buffer ends before the value is complete.
+    Truncated,
+    /// The never used `0xc1` marker was found at the given offset.
+    ReservedMarker(usize),
+    /// The buffer contains a complete value, followed by trailing bytes starting at the given
+    /// offset.
+    TrailingBytes(usize),
+    /// The value starting at the given offset could be written with a smaller marker, as
+    /// reported by [`validate_minimal`] only.
+    NonMinimal(usize),
+}
+
+#[cfg(feature = "std")]
+impl error::Error for ValidateError {}
+
+impl Display for ValidateError {
+    #[cold]
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), fmt::Error> {
+        match *self {
+            Self::Truncated => f.write_str("MessagePack value is truncated"),
+            Self::ReservedMarker(pos) => write!(f, "reserved MessagePack marker at offset {pos}"),
+            Self::TrailingBytes(pos) => write!(f, "trailing bytes after MessagePack value at offset {pos}"),
+            Self::NonMinimal(pos) => write!(f, "MessagePack value at offset {pos} does not use its smallest marker"),
+        }
+    }
+}
+
+/// Checks that the given buffer contains exactly one well-formed MessagePack value and nothing
+/// else, returning the number of bytes consumed.
+///
+/// The structure of the value is walked verifying markers and lengths, without decoding the data
+/// itself, so for example the contents of strings are not checked to be valid UTF-8. This function
+/// neither allocates nor recurses, so it is safe to use on untrusted input of any nesting depth.
+///
+/// # Errors
+///
+/// Returns `ValidateError::Truncated` if the buffer ends before the value is complete,
+/// `ValidateError::ReservedMarker` if the never used marker is found and
+/// `ValidateError::TrailingBytes` if there are bytes left after the value.
+///
+/// # Examples
+///
+/// ```
+/// use rmp::decode::{validate, ValidateError};
+///
+/// assert_eq!(Ok(3), validate(&[0x92, 0x01, 0xc0]));
+///
+/// assert_eq!(Err(ValidateError::Truncated), validate(&[0x92, 0x01]));
+/// assert_eq!(Err(ValidateError::TrailingBytes(1)), validate(&[0xc0, 0xc0]));
+/// ```
+pub fn validate(buf: &[u8]) -> Result<usize, ValidateError> {
+    walk(buf, |_, _| Ok(()))
+}
+
+/// Checks like [`validate`] that the given buffer contains exactly one well-formed MessagePack
+/// value, and that every integer, string, binary, array, map and ext in it uses the smallest
+/// marker able to hold it, returning the number of bytes consumed.
+///
+/// This is the representation written by the `write_sint`, `write_uint`, `write_str_len`,
+/// `write_bin_len`, `write_array_len`, `write_map_len` and `write_ext_meta` functions of the
+/// [`encode`](crate::encode) module, so it checks that the output of a serializer is canonical,
+/// for example in tests comparing it byte for byte with another implementation. Non-negative
+/// integers must use the unsigned markers, and fixext markers are required for the lengths they
+/// cover. Floats are not checked, as their marker depends on their type rather than their value.
+///
+/// # Errors
+///
+/// Returns the errors of [`validate`], and `ValidateError::NonMinimal` with the offset of the
+/// first value which could be written with a smaller marker.
+///
+/// # Examples
+///
+/// ```
+/// use rmp::decode::{validate_minimal, ValidateError};
+///
+/// // [1, "a"]
+/// assert_eq!(Ok(4), validate_minimal(&[0x92, 0x01, 0xa1, 0x61]));
+///
+/// // [1, uint 8 1]
+/// assert_eq!(Err(ValidateError::NonMinimal(2)), validate_minimal(&[0x92, 0x01, 0xcc, 0x01]));
+/// // str 8 "a"
+/// assert_eq!(Err(ValidateError::NonMinimal(0)), validate_minimal(&[0xd9, 0x01, 0x61]));
+/// ```
+pub fn validate_minimal(buf: &[u8]) -> Result<usize, ValidateError> {
+    walk(buf, |offset, marker| {
+        if smallest_marker(buf, offset + 1, marker) == marker {
+            Ok(())
+        } else {
+            Err(ValidateError::NonMinimal(offset))
+        }
+    })
+}
+
+/// Walks the value in the buffer, calling `visit` with the offset and the marker of each value
+/// once its bytes are known to be in the buffer, and returns the number of bytes consumed.
+pub(super) fn walk<F>(buf: &[u8], mut visit: F) -> Result<usize, ValidateError>
+where
+    F: FnMut(usize, Marker) -> Result<(), ValidateError>,
+{
+    let mut pos = 0;
+    // Number of values that still need to be read. Every value takes at least one byte, so this
+    // never has to exceed the buffer length.
+    let mut pending: u64 = 1;
+
+    while pending > 0 {
+        pending -= 1;
+
+        let marker = Marker::from_u8(*buf.get(pos).ok_or(ValidateError::Truncated)?);
+        let offset = pos;
+        pos += 1;
+
+        let (skip, items) = match marker {
+            Marker::FixPos(..) |
+            Marker::FixNeg(..) |
+            Marker::Null |
+            Marker::True |
+            Marker::False => (0, 0),
+            Marker::Reserved => return Err(ValidateError::ReservedMarker(offset)),
+            Marker::U8 | Marker::I8 => (1, 0),
+            Marker::U16 | Marker::I16 => (2, 0),
+            Marker::U32 | Marker::I32 | Marker::F32 => (4, 0),
+            Marker::U64 | Marker::I64 | Marker::F64 => (8, 0),
+            Marker::FixStr(len) => (len.into(), 0),
+            Marker::Str8 | Marker::Bin8 => (read_len(buf, &mut pos, 1)?, 0),
+            Marker::Str16 | Marker::Bin16 => (read_len(buf, &mut pos, 2)?, 0),
+            Marker::Str32 | Marker::Bin32 => (read_len(buf, &mut pos, 4)?, 0),
+            Marker::FixArray(len) => (0, len.into()),
+            Marker::Array16 => (0, read_len(buf, &mut pos, 2)?),
+            Marker::Array32 => (0, read_len(buf, &mut pos, 4)?),
+            Marker::FixMap(len) => (0, u64::from(len) * 2),
+            Marker::Map16 => (0, read_len(buf, &mut pos, 2)? * 2),
+            Marker::Map32 => (0, read_len(buf, &mut pos, 4)? * 2),
+            // The type byte is skipped along with the data.
+            Marker::FixExt1 => (1 + 1, 0),
+            Marker::FixExt2 => (1 + 2, 0),
+            Marker::FixExt4 => (1 + 4, 0),
+            Marker::FixExt8 => (1 + 8, 0),
+            Marker::FixExt16 => (1 + 16, 0),
+            Marker::Ext8 => (1 + read_len(buf, &mut pos, 1)?, 0),
+            Marker::Ext16 => (1 + read_len(buf, &mut pos, 2)?, 0),
+            Marker::Ext32 => (1 + read_len(buf, &mut pos, 4)?, 0),
+        };
+
+        pos = skip_bytes(buf, pos, skip)?;
+        visit(offset, marker)?;
+        pending += items;
+        if pending > (buf.len() - pos) as u64 {
+            return Err(ValidateError::Truncated);
+        }
+    }
+
+    if pos == buf.len() {
+        Ok(pos)
+    } else {
+        Err(ValidateError::TrailingBytes(pos))
+    }
+}
+
+/// Returns the smallest marker able to hold the value with the given marker, followed by its
+/// length or integer data at `pos`, which is the marker itself if it is already the smallest. The
+/// bytes must already be checked to exist.
+pub(super) fn smallest_marker(buf: &[u8], mut pos: usize, marker: Marker) -> Marker {
+    let mut field = |size| read_len(buf, &mut pos, size).unwrap_or(0);
+    match marker {
+        Marker::U8 => uint_marker(field(1)),
+        Marker::U16 => uint_marker(field(2)),
+        Marker::U32 => uint_marker(field(4)),
+        Marker::U64 => uint_marker(field(8)),
+        Marker::I8 => sint_marker((field(1) as u8 as i8).into()),
+        Marker::I16 => sint_marker((field(2) as u16 as i16).into()),
+        Marker::I32 => sint_marker((field(4) as u32 as i32).into()),
+        Marker::I64 => sint_marker(field(8) as i64),
+        Marker::Str8 | Marker::Str16 | Marker::Str32 => match field(len_size(marker)) {
+            len @ 0..=31 => Marker::FixStr(len as u8),
+            0x20..=0xff => Marker::Str8,
+            0x100..=0xffff => Marker::Str16,
+            _ => Marker::Str32,
+        },
+        Marker::Bin16 | Marker::Bin32 => match field(len_size(marker)) {
+            0..=0xff => Marker::Bin8,
+            0x100..=0xffff => Marker::Bin16,
+            _ => Marker::Bin32,
+        },
+        Marker::Array16 | Marker::Array32 => match field(len_size(marker)) {
+            len @ 0..=15 => Marker::FixArray(len as u8),
+            0x10..=0xffff => Marker::Array16,
+            _ => Marker::Array32,
+        },
+        Marker::Map16 | Marker::Map32 => match field(len_size(marker)) {
+            len @ 0..=15 => Marker::FixMap(len as u8),
+            0x10..=0xffff => Marker::Map16,
+            _ => Marker::Map32,
+        },
+        Marker::Ext8 | Marker::Ext16 | Marker::Ext32 => match field(len_size(marker)) {
+            1 => Marker::FixExt1,
+            2 => Marker::FixExt2,
+            4 => Marker::FixExt4,
+            8 => Marker::FixExt8,
+            16 => Marker::FixExt16,
+            0..=0xff => Marker::Ext8,
+            0x100..=0xffff => Marker::Ext16,
+            _ => Marker::Ext32,
+        },
+        marker => marker,
+    }
+}
+
+fn uint_marker(val: u64) -> Marker {
+    match val {
+        0..=0x7f => Marker::FixPos(val as u8),
+        0x80..=0xff => Marker::U8,
+        0x100..=0xffff => Marker::U16,
+        0x1_0000..=0xffff_ffff => Marker::U32,
+        _ => Marker::U64,
+    }
+}
+
+fn sint_marker(val: i64) -> Marker {
+    match val {
+        0.. => uint_marker(val as u64),
+        -32..=-1 => Marker::FixNeg(val as i8),
+        -128..=-33 => Marker::I8,
+        -32768..=-129 => Marker::I16,
+        -2147483648..=-32769 => Marker::I32,
+        _ => Marker::I64,
+    }
+}
+
+/// Returns the number of bytes of the length or integer data following the marker.
+pub(super) fn len_size(marker: Marker) -> u64 {
+    match marker {
+        Marker::U8 | Marker::I8 | Marker::Str8 | Marker::Bin8 | Marker::Ext8 => 1,
+        Marker::U16 | Marker::I16 | Marker::Str16 | Marker::Bin16 | Marker::Ext16 => 2,
+        Marker::U32 | Marker::I32 | Marker::Str32 | Marker::Bin32 | Marker::Ext32 => 4,
+        Marker::Array16 | Marker::Map16 => 2,
+        Marker::Array32 | Marker::Map32 => 4,
+        Marker::U64 | Marker::I64 => 8,
+        _ => 0,
+    }
+}
+
+/// Reads a big-endian length of `size` bytes.
+#[inline]
+pub(super) fn read_len(buf: &[u8], pos: &mut usize, size: u64) -> Result<u64, ValidateError> {
+    let end = skip_bytes(buf, *pos, size)?;
+    let len = buf[*pos..end].iter().fold(0, |len, &b| len << 8 | u64::from(b));
+    *pos = end;
+    Ok(len)
+}
+
+#[inline]
+pub(super) fn skip_bytes(buf: &[u8], pos: usize, len: u64) -> Result<usize, ValidateError> {
+    usize::try_from(len)
+        .ok()
+        .and_then(|len| pos.checked_add(len))
+        .filter(|&end| end <= buf.len())
+        .ok_or(ValidateError::Truncated)
+}
@@ -6,7 +6,8 @@ use crate::Marker;
 /// write, returning the marker used.
 ///
 /// This function is useful when you want to get full control for writing the data itself, for
-/// example, when using non-blocking socket.
+/// example, when using non-blocking socket. The header must be followed by exactly `len` bytes,
+/// see [Headers](crate::encode#headers).
 ///
 /// # Errors
 ///
@@ -31,6 +32,31 @@ pub fn write_bin_len<W: RmpWrite>(wr: &mut W, len: u32) -> Result<Marker, ValueW
     Ok(marker)
 }
 
+/// Returns the number of bytes taken by a binary of `len` bytes, header included, as written by
+/// [`write_bin_len`] followed by the data.
+///
+/// See [`encoded_str_len`](super::encoded_str_len) to compare it with a string.
+///
+/// # Examples
+///
+/// ```
+/// use rmp::encode::encoded_bin_len;
+///
+/// assert_eq!(2 + 255, encoded_bin_len(255));
+/// assert_eq!(3 + 256, encoded_bin_len(256));
+/// ```
+#[must_use]
+pub const fn encoded_bin_len(len: u32) -> u64 {
+    let header = if len < 256 {
+        2
+    } else if len <= u16::MAX as u32 {
+        3
+    } else {
+        5
+    };
+    header + len as u64
+}
+
 /// Encodes and attempts to write the most efficient binary implementation to the given `Write`.
 ///
 /// # Errors
@@ -43,3 +69,40 @@ pub fn write_bin<W: RmpWrite>(wr: &mut W, data: &[u8]) -> Result<(), ValueWriteE
     wr.write_bytes(data)
         .map_err(ValueWriteError::InvalidDataWrite)
 }
+
+/// Encodes and attempts to write the most efficient binary implementation to the given `Write`,
+/// writing the data in chunks of at most `chunk_len` bytes and flushing the writer after each one.
+///
+/// This bounds the amount of data handed to the writer at once, which helps when the writer is a
+/// socket with backpressure or buffers its input, such as a `BufWriter`: each chunk is flushed
+/// before the next one is written, instead of the whole payload being queued downstream.
+///
+/// # Errors
+///
+/// This function will return `ValueWriteError` on any I/O error occurred while writing either the
+/// marker or the data, or while flushing. The data may then have been partially written.
+///
+/// # Panics
+///
+/// Panics if `chunk_len` is 0.
+///
+/// # Examples
+///
+/// ```
+/// use rmp::encode::write_bin_chunked;
+///
+/// let mut buf = Vec::new();
+/// write_bin_chunked(&mut buf, &[0xde, 0xad, 0xbe, 0xef], 3).unwrap();
+///
+/// assert_eq!(vec![0xc4, 0x04, 0xde, 0xad, 0xbe, 0xef], buf);
+/// ```
+#[cfg(feature = "std")]
+pub fn write_bin_chunked<W: std::io::Write>(wr: &mut W, data: &[u8], chunk_len: usize) -> Result<(), ValueWriteError> {
+    assert!(chunk_len > 0, "chunk length must not be zero");
+    write_bin_len(wr, data.len() as u32)?;
+    for chunk in data.chunks(chunk_len) {
+        wr.write_all(chunk).map_err(ValueWriteError::InvalidDataWrite)?;
+        wr.flush().map_err(ValueWriteError::InvalidDataWrite)?;
+    }
+    Ok(())
+}
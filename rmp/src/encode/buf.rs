@@ -0,0 +1,105 @@
+//! Implementation of the [`BytesWriter`] type
+
+use super::{RmpWrite, RmpWriteErr};
+use core::fmt::{self, Display, Formatter};
+
+/// Indicates that a [`BytesWriter`] ran out of capacity.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum BytesWriteError {
+    /// Indicates that the buffer could not hold the bytes being written.
+    InsufficientCapacity {
+        expected: usize,
+        actual: usize,
+    },
+}
+
+impl Display for BytesWriteError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::InsufficientCapacity { expected, actual } => {
+                write!(f, "Expected capacity for at least {expected} bytes, but only got {actual}")
+            }
+        }
+    }
+}
+#[cfg(feature = "std")]
+impl std::error::Error for BytesWriteError {}
+impl RmpWriteErr for BytesWriteError {}
+
+/// An adapter which writes into a [`bytes::BufMut`].
+///
+/// Bytes are put directly into the buffer, so for example a `BytesMut` can be filled in place and
+/// then split off and frozen without any extra copies. Writing fails instead of panicking when a
+/// fixed-capacity buffer runs full, while growable buffers such as `BytesMut` or `Vec<u8>` never
+/// fail.
+///
+/// Available with the `bytes` feature, which also works on `#[no_std]`.
+///
+/// # Examples
+///
+/// ```
+/// use bytes::BytesMut;
+/// use rmp::encode::{write_array_len, write_str, BytesWriter};
+///
+/// let mut wr = BytesWriter::new(BytesMut::new());
+///
+/// write_array_len(&mut wr, 1).unwrap();
+/// write_str(&mut wr, "le message").unwrap();
+///
+/// let frame = wr.into_inner().freeze();
+/// assert_eq!(b"\x91\xaale message"[..], frame[..]);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct BytesWriter<B> {
+    buf: B,
+}
+
+impl<B: bytes::BufMut> BytesWriter<B> {
+    /// Wraps the given buffer.
+    #[inline]
+    pub const fn new(buf: B) -> Self {
+        Self { buf }
+    }
+
+    /// Gets a reference to the underlying buffer.
+    #[inline]
+    pub const fn get_ref(&self) -> &B {
+        &self.buf
+    }
+
+    /// Gets a mutable reference to the underlying buffer.
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut B {
+        &mut self.buf
+    }
+
+    /// Consumes this adapter, returning the underlying buffer.
+    #[inline]
+    pub fn into_inner(self) -> B {
+        self.buf
+    }
+}
+
+impl<B: bytes::BufMut> RmpWrite for BytesWriter<B> {
+    type Error = BytesWriteError;
+
+    #[inline]
+    fn write_u8(&mut self, val: u8) -> Result<(), Self::Error> {
+        self.write_bytes(&[val])
+    }
+
+    #[inline]
+    fn write_bytes(&mut self, buf: &[u8]) -> Result<(), Self::Error> {
+        let remaining = self.buf.remaining_mut();
+        if buf.len() <= remaining {
+            self.buf.put_slice(buf);
+            Ok(())
+        } else {
+            Err(BytesWriteError::InsufficientCapacity {
+                expected: buf.len(),
+                actual: remaining,
+            })
+        }
+    }
+}
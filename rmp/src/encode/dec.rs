@@ -6,6 +6,8 @@ use crate::Marker;
 ///
 /// The first byte becomes the `f32` marker and the others will represent the data itself.
 ///
+/// The exact IEEE-754 bit pattern is written, so NaN payloads and the sign of zero are preserved.
+///
 /// # Errors
 ///
 /// This function will return `ValueWriteError` on any I/O error occurred while writing either the
@@ -20,6 +22,8 @@ pub fn write_f32<W: RmpWrite>(wr: &mut W, val: f32) -> Result<(), ValueWriteErro
 ///
 /// The first byte becomes the `f64` marker and the others will represent the data itself.
 ///
+/// The exact IEEE-754 bit pattern is written, so NaN payloads and the sign of zero are preserved.
+///
 /// # Errors
 ///
 /// This function will return `ValueWriteError` on any I/O error occurred while writing either the
@@ -1 +1,113 @@
+use super::{write_map_len, RmpWrite};
+use crate::encode::ValueWriteError;
 
+/// A guard for manually encoding a map, which checks that the number of written key-value pairs
+/// matches the declared length.
+///
+/// The map header is written on construction and each pair is then written using
+/// [`write_pair`](MapWriter::write_pair). In debug builds the guard panics when more pairs than
+/// declared are written or, on drop, when fewer pairs were written. In release builds all checks
+/// compile away, leaving a plain reference to the writer.
+///
+/// If a pair fails to be written the check on drop is skipped, so that error propagation with `?`
+/// does not turn into a panic.
+///
+/// # Examples
+///
+/// ```
+/// use rmp::encode::{write_str, write_uint, MapWriter};
+///
+/// let mut buf = Vec::new();
+///
+/// let mut map = MapWriter::new(&mut buf, 1).unwrap();
+/// map.write_pair(|wr| {
+///     write_str(wr, "a")?;
+///     write_uint(wr, 42)?;
+///     Ok::<(), rmp::encode::ValueWriteError>(())
+/// }).unwrap();
+/// drop(map);
+///
+/// assert_eq!(vec![0x81, 0xa1, 0x61, 0x2a], buf);
+/// ```
+pub struct MapWriter<'a, W: RmpWrite> {
+    wr: &'a mut W,
+    #[cfg(debug_assertions)]
+    remaining: u32,
+    #[cfg(debug_assertions)]
+    failed: bool,
+}
+
+impl<'a, W: RmpWrite> MapWriter<'a, W> {
+    /// Writes the header of a map with `len` key-value pairs and returns a guard for writing them.
+    ///
+    /// # Errors
+    ///
+    /// This function will return `ValueWriteError` on any I/O error occurred while writing the map
+    /// header.
+    pub fn new(wr: &'a mut W, len: u32) -> Result<Self, ValueWriteError<W::Error>> {
+        write_map_len(wr, len)?;
+        Ok(Self {
+            wr,
+            #[cfg(debug_assertions)]
+            remaining: len,
+            #[cfg(debug_assertions)]
+            failed: false,
+        })
+    }
+
+    /// Writes a single key-value pair using the given function, which must write exactly one key
+    /// followed by exactly one value.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of the given function.
+    ///
+    /// # Panics
+    ///
+    /// In debug builds, panics if all the declared pairs were already written.
+    pub fn write_pair<F, E>(&mut self, f: F) -> Result<(), E>
+    where
+        F: FnOnce(&mut W) -> Result<(), E>,
+    {
+        #[cfg(debug_assertions)]
+        {
+            assert!(self.remaining > 0, "more map pairs written than declared by the map header");
+            self.remaining -= 1;
+        }
+        let res = f(self.wr);
+        #[cfg(debug_assertions)]
+        {
+            self.failed |= res.is_err();
+        }
+        res
+    }
+}
+
+impl<W: RmpWrite> Drop for MapWriter<'_, W> {
+    fn drop(&mut self) {
+        #[cfg(debug_assertions)]
+        {
+            #[cfg(feature = "std")]
+            let panicking = std::thread::panicking();
+            #[cfg(not(feature = "std"))]
+            let panicking = false;
+
+            if !self.failed && !panicking {
+                assert!(
+                    self.remaining == 0,
+                    "{} map pair(s) declared by the map header were not written",
+                    self.remaining
+                );
+            }
+        }
+    }
+}
+
+impl<W: RmpWrite> core::fmt::Debug for MapWriter<'_, W> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let mut s = f.debug_struct("MapWriter");
+        #[cfg(debug_assertions)]
+        s.field("remaining", &self.remaining);
+        s.finish_non_exhaustive()
+    }
+}
@@ -1,18 +1,58 @@
 //! Provides various functions and structs for MessagePack encoding.
+//!
+//! # Headers
+//!
+//! Arrays, maps, strings, binaries and exts can be written in two parts: a header holding the
+//! marker and the length, then a body written separately, possibly over many calls. This is what
+//! streaming encoders build on, since the body does not need to be in memory at once.
+//!
+//! | Header                | Must be followed by                                     |
+//! |-----------------------|---------------------------------------------------------|
+//! | [`write_array_len`]   | exactly `len` encoded values                            |
+//! | [`write_map_len`]     | exactly `len` encoded keys, each followed by its value  |
+//! | [`write_str_len`]     | exactly `len` bytes of UTF-8                            |
+//! | [`write_bin_len`]     | exactly `len` bytes                                     |
+//! | [`write_ext_meta`]    | exactly `len` bytes, the type being part of the header  |
+//!
+//! Nothing checks that the body matches its header. A body that is shorter or longer makes the
+//! rest of the stream be read as something else.
+//!
+//! ```
+//! use rmp::encode::{write_array_len, write_bin_len, write_uint};
+//!
+//! let mut buf = Vec::new();
+//! write_array_len(&mut buf, 2).unwrap();
+//! write_uint(&mut buf, 1).unwrap();
+//! write_bin_len(&mut buf, 4).unwrap();
+//! for chunk in [&[1, 2][..], &[3, 4]] {
+//!     buf.extend_from_slice(chunk);
+//! }
+//!
+//! assert_eq!(vec![0x92, 0x01, 0xc4, 0x04, 0x01, 0x02, 0x03, 0x04], buf);
+//! ```
 
 mod bin;
 mod dec;
 mod ext;
 mod map;
+mod seq;
 mod sint;
+mod slice;
 mod str;
 mod uint;
 mod vec;
 
-pub use self::bin::{write_bin, write_bin_len};
+pub use self::bin::{encoded_bin_len, write_bin, write_bin_len};
+#[cfg(feature = "std")]
+pub use self::bin::write_bin_chunked;
 pub use self::dec::{write_f32, write_f64};
+pub use self::map::MapWriter;
+#[cfg(feature = "std")]
+pub use self::seq::SeekableSeqWriter;
+pub use self::seq::SeqWriter;
 pub use self::sint::{write_i16, write_i32, write_i64, write_i8, write_nfix, write_sint};
-pub use self::str::{write_str, write_str_len};
+pub use self::slice::{SliceWriteError, SliceWriter};
+pub use self::str::{encoded_str_len, write_str, write_str_len};
 pub use self::uint::{write_pfix, write_u16, write_u32, write_u64, write_u8, write_uint, write_uint8};
 
 use core::fmt::{self, Debug, Display, Formatter};
@@ -24,6 +64,11 @@ use crate::Marker;
 pub mod buffer;
 pub use buffer::ByteBuf;
 
+#[cfg(feature = "bytes")]
+mod buf;
+#[cfg(feature = "bytes")]
+pub use buf::{BytesWriteError, BytesWriter};
+
 #[doc(inline)]
 #[allow(deprecated)]
 pub use crate::errors::Error;
@@ -111,6 +156,9 @@ mod sealed {
     #[cfg(not(feature = "std"))]
     impl Sealed for alloc::vec::Vec<u8> {}
     impl Sealed for super::ByteBuf {}
+    impl Sealed for super::SliceWriter<'_> {}
+    #[cfg(feature = "bytes")]
+    impl<B: bytes::BufMut> Sealed for super::BytesWriter<B> {}
 }
 
 macro_rules! write_byteorder_utils {
@@ -251,6 +299,8 @@ impl<E: RmpWriteErr> Display for ValueWriteError<E> {
 /// Encodes and attempts to write the most efficient array length implementation to the given write,
 /// returning the marker used.
 ///
+/// The header must be followed by exactly `len` values, see [Headers](self#headers).
+///
 /// # Errors
 ///
 /// This function will return `ValueWriteError` on any I/O error occurred while writing either the
@@ -276,6 +326,9 @@ pub fn write_array_len<W: RmpWrite>(wr: &mut W, len: u32) -> Result<Marker, Valu
 /// Encodes and attempts to write the most efficient map length implementation to the given write,
 /// returning the marker used.
 ///
+/// The header must be followed by exactly `len` keys, each followed by its value, see
+/// [Headers](self#headers).
+///
 /// # Errors
 ///
 /// This function will return `ValueWriteError` on any I/O error occurred while writing either the
@@ -301,15 +354,14 @@ pub fn write_map_len<W: RmpWrite>(wr: &mut W, len: u32) -> Result<Marker, ValueW
 /// Encodes and attempts to write the most efficient ext metadata implementation to the given
 /// write, returning the marker used.
 ///
+/// The header, which includes the type, must be followed by exactly `len` bytes of data, see
+/// [Headers](self#headers). Negative types are reserved for the types predefined by MessagePack,
+/// such as `-1` for timestamps.
+///
 /// # Errors
 ///
 /// This function will return `ValueWriteError` on any I/O error occurred while writing either the
 /// marker or the data.
-///
-/// # Panics
-///
-/// Panics if `ty` is negative, because it is reserved for future MessagePack extension including
-/// 2-byte type information.
 pub fn write_ext_meta<W: RmpWrite>(wr: &mut W, len: u32, ty: i8) -> Result<Marker, ValueWriteError<W::Error>> {
     let marker = match len {
         1 => Marker::FixExt1,
@@ -0,0 +1,184 @@
+use super::{write_array_len, ByteBuf, RmpWrite};
+use crate::encode::ValueWriteError;
+#[cfg(feature = "std")]
+use crate::Marker;
+#[cfg(feature = "std")]
+use std::io::{self, Seek, SeekFrom, Write};
+
+/// A writer for arrays whose length is not known until all of their elements were written.
+///
+/// MessagePack requires the length of an array up front, so the elements are encoded into an
+/// internal buffer and written out, after the array header, by [`finish`](SeqWriter::finish).
+/// This works with any writer, at the cost of holding the encoded elements in memory. Writers
+/// that implement [`Seek`](std::io::Seek) can avoid the buffer with [`SeekableSeqWriter`].
+///
+/// Nothing is written if the `SeqWriter` is dropped without calling `finish`.
+///
+/// # Examples
+///
+/// ```
+/// use rmp::encode::{write_uint, SeqWriter};
+///
+/// let mut buf = Vec::new();
+///
+/// let mut seq = SeqWriter::new(&mut buf);
+/// for val in (0..10).filter(|val| val % 4 == 0) {
+///     seq.write_element(|wr| write_uint(wr, val).map(|_| ()))?;
+/// }
+/// seq.finish()?;
+///
+/// assert_eq!(vec![0x93, 0x00, 0x04, 0x08], buf);
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[must_use = "the array is only written by `finish`"]
+#[derive(Debug)]
+pub struct SeqWriter<'a, W: RmpWrite> {
+    wr: &'a mut W,
+    buf: ByteBuf,
+    len: u32,
+}
+
+impl<'a, W: RmpWrite> SeqWriter<'a, W> {
+    /// Creates a writer for an array to be written into `wr`.
+    pub fn new(wr: &'a mut W) -> Self {
+        Self { wr, buf: ByteBuf::new(), len: 0 }
+    }
+
+    /// Returns the number of elements written so far.
+    #[inline]
+    #[must_use]
+    pub fn len(&self) -> u32 {
+        self.len
+    }
+
+    /// Returns true if no elements were written so far.
+    #[inline]
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Writes a single element into the buffer using the given function, which must write
+    /// exactly one value.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of the given function.
+    ///
+    /// # Panics
+    ///
+    /// Panics if more than `u32::MAX` elements are written.
+    pub fn write_element<F, E>(&mut self, f: F) -> Result<(), E>
+    where
+        F: FnOnce(&mut ByteBuf) -> Result<(), E>,
+    {
+        f(&mut self.buf)?;
+        self.len = self.len.checked_add(1).expect("more than u32::MAX array elements written");
+        Ok(())
+    }
+
+    /// Writes the array header followed by the buffered elements.
+    ///
+    /// # Errors
+    ///
+    /// This function will return `ValueWriteError` on any I/O error occurred while writing.
+    pub fn finish(self) -> Result<(), ValueWriteError<W::Error>> {
+        write_array_len(self.wr, self.len)?;
+        self.wr.write_bytes(self.buf.as_slice()).map_err(ValueWriteError::InvalidDataWrite)
+    }
+}
+
+/// A writer for arrays whose length is not known until all of their elements were written, which
+/// writes the elements directly and then goes back to fill in the length.
+///
+/// A placeholder `array32` header is written on construction and patched by
+/// [`finish`](SeekableSeqWriter::finish), so no memory is needed for the elements. The tradeoff is
+/// that the header always takes 5 bytes, where [`SeqWriter`] uses the smallest header for the
+/// final length, and that the writer must support seeking, which rules out sockets and pipes. If
+/// the `SeekableSeqWriter` is dropped without calling `finish`, the placeholder header declares an
+/// empty array followed by the elements, so the output is not well-formed.
+///
+/// # Examples
+///
+/// ```
+/// use std::io::Cursor;
+/// use rmp::encode::{write_str, SeekableSeqWriter};
+///
+/// let mut cur = Cursor::new(Vec::new());
+///
+/// let mut seq = SeekableSeqWriter::new(&mut cur)?;
+/// seq.write_element(|wr| write_str(wr, "a"))?;
+/// seq.finish()?;
+///
+/// assert_eq!(vec![0xdd, 0x00, 0x00, 0x00, 0x01, 0xa1, 0x61], cur.into_inner());
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[cfg(feature = "std")]
+#[must_use = "the array length is only written by `finish`"]
+#[derive(Debug)]
+pub struct SeekableSeqWriter<'a, W: Write + Seek> {
+    wr: &'a mut W,
+    start: u64,
+    len: u32,
+}
+
+#[cfg(feature = "std")]
+impl<'a, W: Write + Seek> SeekableSeqWriter<'a, W> {
+    /// Writes a placeholder array header at the current position of `wr` and returns a writer for
+    /// the elements.
+    ///
+    /// # Errors
+    ///
+    /// Returns any I/O error occurred while writing the header.
+    pub fn new(wr: &'a mut W) -> io::Result<Self> {
+        let start = wr.stream_position()?;
+        wr.write_all(&[Marker::Array32.to_u8(), 0, 0, 0, 0])?;
+        Ok(Self { wr, start, len: 0 })
+    }
+
+    /// Returns the number of elements written so far.
+    #[inline]
+    #[must_use]
+    pub fn len(&self) -> u32 {
+        self.len
+    }
+
+    /// Returns true if no elements were written so far.
+    #[inline]
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Writes a single element using the given function, which must write exactly one value.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of the given function.
+    ///
+    /// # Panics
+    ///
+    /// Panics if more than `u32::MAX` elements are written.
+    pub fn write_element<F, E>(&mut self, f: F) -> Result<(), E>
+    where
+        F: FnOnce(&mut W) -> Result<(), E>,
+    {
+        f(self.wr)?;
+        self.len = self.len.checked_add(1).expect("more than u32::MAX array elements written");
+        Ok(())
+    }
+
+    /// Fills in the length in the array header, leaving the writer positioned after the last
+    /// element.
+    ///
+    /// # Errors
+    ///
+    /// Returns any I/O error occurred while seeking or writing.
+    pub fn finish(self) -> io::Result<()> {
+        let end = self.wr.stream_position()?;
+        self.wr.seek(SeekFrom::Start(self.start + 1))?;
+        self.wr.write_all(&self.len.to_be_bytes())?;
+        self.wr.seek(SeekFrom::Start(end))?;
+        Ok(())
+    }
+}
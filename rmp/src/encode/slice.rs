@@ -0,0 +1,105 @@
+//! Implementation of the [`SliceWriter`] type
+
+use super::{RmpWrite, RmpWriteErr};
+use core::fmt::{self, Display, Formatter};
+
+/// Indicates that a [`SliceWriter`] ran out of space.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum SliceWriteError {
+    /// Indicates that the remaining space of the buffer could not hold the bytes being written.
+    BufferFull,
+}
+
+impl Display for SliceWriteError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::BufferFull => f.write_str("fixed-size buffer is full"),
+        }
+    }
+}
+#[cfg(feature = "std")]
+impl std::error::Error for SliceWriteError {}
+impl RmpWriteErr for SliceWriteError {}
+
+/// A writer into a fixed-size byte slice, such as an array on the stack, which keeps track of the
+/// number of bytes written.
+///
+/// Writing never allocates, and fails with [`SliceWriteError::BufferFull`] when the bytes do not
+/// fit into the rest of the buffer. The bytes of a failed write are not written at all, but a value
+/// made of several writes, such as a string header and its data, may be left partially written.
+/// Unlike writing through `&mut [u8]` directly, the same error is returned with and without the
+/// `std` feature.
+///
+/// # Examples
+///
+/// ```
+/// use rmp::encode::{write_array_len, write_str, write_uint, SliceWriter};
+///
+/// let mut buf = [0x00; 8];
+/// let mut wr = SliceWriter::new(&mut buf);
+///
+/// write_array_len(&mut wr, 2).unwrap();
+/// write_uint(&mut wr, 300).unwrap();
+/// write_str(&mut wr, "a").unwrap();
+/// assert_eq!(&[0x92, 0xcd, 0x01, 0x2c, 0xa1, 0x61], wr.written());
+///
+/// // The string header still fits, but its data does not.
+/// assert!(write_str(&mut wr, "too long").is_err());
+/// assert_eq!(7, wr.position());
+/// assert_eq!(1, wr.remaining());
+/// ```
+#[derive(Debug)]
+pub struct SliceWriter<'a> {
+    buf: &'a mut [u8],
+    pos: usize,
+}
+
+impl<'a> SliceWriter<'a> {
+    /// Creates a writer starting at the beginning of the given buffer.
+    #[inline]
+    pub fn new(buf: &'a mut [u8]) -> Self {
+        Self { buf, pos: 0 }
+    }
+
+    /// Returns the number of bytes written so far.
+    #[inline]
+    #[must_use]
+    pub const fn position(&self) -> usize {
+        self.pos
+    }
+
+    /// Returns the number of bytes that can still be written.
+    #[inline]
+    #[must_use]
+    pub const fn remaining(&self) -> usize {
+        self.buf.len() - self.pos
+    }
+
+    /// Returns the bytes written so far.
+    #[inline]
+    #[must_use]
+    pub fn written(&self) -> &[u8] {
+        &self.buf[..self.pos]
+    }
+
+    /// Consumes this writer, returning the bytes written.
+    #[inline]
+    #[must_use]
+    pub fn into_written(self) -> &'a mut [u8] {
+        &mut self.buf[..self.pos]
+    }
+}
+
+impl RmpWrite for SliceWriter<'_> {
+    type Error = SliceWriteError;
+
+    #[inline]
+    fn write_bytes(&mut self, buf: &[u8]) -> Result<(), Self::Error> {
+        let end = self.pos + buf.len();
+        let dst = self.buf.get_mut(self.pos..end).ok_or(SliceWriteError::BufferFull)?;
+        dst.copy_from_slice(buf);
+        self.pos = end;
+        Ok(())
+    }
+}
@@ -5,6 +5,9 @@ use crate::Marker;
 /// Encodes and attempts to write the most efficient string length implementation to the given
 /// write, returning the marker used.
 ///
+/// The header must be followed by exactly `len` bytes of UTF-8, see
+/// [Headers](crate::encode#headers).
+///
 /// # Errors
 ///
 /// This function will return `ValueWriteError` on any I/O error occurred while writing either the
@@ -33,6 +36,36 @@ pub fn write_str_len<W: RmpWrite>(wr: &mut W, len: u32) -> Result<Marker, ValueW
     Ok(marker)
 }
 
+/// Returns the number of bytes taken by a string of `len` bytes, header included, as written by
+/// [`write_str_len`] followed by the data.
+///
+/// Together with [`encoded_bin_len`](super::encoded_bin_len), this tells which of the two
+/// representations is smaller for some data, without writing it: a string is never larger, and
+/// is one byte smaller below 32 bytes, where its length fits in the marker.
+///
+/// # Examples
+///
+/// ```
+/// use rmp::encode::{encoded_bin_len, encoded_str_len};
+///
+/// assert_eq!(1 + 31, encoded_str_len(31));
+/// assert_eq!(2 + 32, encoded_str_len(32));
+/// assert_eq!(encoded_bin_len(32), encoded_str_len(32));
+/// ```
+#[must_use]
+pub const fn encoded_str_len(len: u32) -> u64 {
+    let header = if len < 32 {
+        1
+    } else if len < 256 {
+        2
+    } else if len <= u16::MAX as u32 {
+        3
+    } else {
+        5
+    };
+    header + len as u64
+}
+
 /// Encodes and attempts to write the most efficient string binary representation to the
 /// given `Write`.
 ///
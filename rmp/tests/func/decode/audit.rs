@@ -0,0 +1,61 @@
+use rmp::decode::{audit_canonical, CanonicalWarning, ValidateError};
+use rmp::encode::*;
+use rmp::Marker;
+
+#[test]
+fn pass_audit_canonical_minimal() {
+    let mut buf = Vec::new();
+    write_map_len(&mut buf, 2).unwrap();
+    write_str(&mut buf, "ints").unwrap();
+    write_array_len(&mut buf, 300).unwrap();
+    for i in 0..300 {
+        write_sint(&mut buf, i - 150).unwrap();
+    }
+    write_str(&mut buf, "ext").unwrap();
+    write_ext_meta(&mut buf, 3, 1).unwrap();
+    buf.extend_from_slice(&[0; 3]);
+
+    assert_eq!(Ok(Vec::new()), audit_canonical(&buf));
+}
+
+#[test]
+fn pass_audit_canonical_reports_every_value() {
+    let buf = [
+        0xdc, 0x00, 0x06, // array 16 of 6 elements
+        0xcf, 0, 0, 0, 0, 0, 0, 0x01, 0x00, // uint 64 256
+        0xd1, 0x00, 0x05, // int 16 5
+        0xd0, 0xff, // int 8 -1
+        0xda, 0x00, 0x01, 0x61, // str 16 "a"
+        0xc5, 0x00, 0x00, // bin 16 of 0 bytes
+        0xde, 0x00, 0x01, 0xc0, // map 16 of 1 entry, with a nil key and as value:
+        0xc7, 0x04, 0x01, 0, 0, 0, 0, // ext 8 of 4 bytes
+    ];
+
+    let warnings = audit_canonical(&buf).unwrap();
+    assert_eq!(vec![
+        CanonicalWarning { offset: 0, marker: Marker::Array16, smallest: Marker::FixArray(6) },
+        CanonicalWarning { offset: 3, marker: Marker::U64, smallest: Marker::U16 },
+        CanonicalWarning { offset: 12, marker: Marker::I16, smallest: Marker::FixPos(5) },
+        CanonicalWarning { offset: 15, marker: Marker::I8, smallest: Marker::FixNeg(-1) },
+        CanonicalWarning { offset: 17, marker: Marker::Str16, smallest: Marker::FixStr(1) },
+        CanonicalWarning { offset: 21, marker: Marker::Bin16, smallest: Marker::Bin8 },
+        CanonicalWarning { offset: 24, marker: Marker::Map16, smallest: Marker::FixMap(1) },
+        CanonicalWarning { offset: 28, marker: Marker::Ext8, smallest: Marker::FixExt4 },
+    ], warnings);
+    assert_eq!(vec![2, 6, 2, 1, 2, 1, 2, 1], warnings.iter().map(CanonicalWarning::wasted_bytes).collect::<Vec<_>>());
+}
+
+#[test]
+fn pass_audit_canonical_signed_marker_of_same_width() {
+    // int 16 300
+    let warning = audit_canonical(&[0xd1, 0x01, 0x2c]).unwrap();
+    assert_eq!(vec![CanonicalWarning { offset: 0, marker: Marker::I16, smallest: Marker::U16 }], warning);
+    assert_eq!(0, warning[0].wasted_bytes());
+}
+
+#[test]
+fn fail_audit_canonical_invalid() {
+    assert_eq!(Err(ValidateError::Truncated), audit_canonical(&[0xdc, 0x00, 0x02, 0xcd]));
+    assert_eq!(Err(ValidateError::ReservedMarker(1)), audit_canonical(&[0x91, 0xc1]));
+    assert_eq!(Err(ValidateError::TrailingBytes(2)), audit_canonical(&[0xcc, 0x01, 0xc0]));
+}
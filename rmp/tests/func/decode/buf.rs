@@ -0,0 +1,55 @@
+use ::bytes::Buf;
+use rmp::decode::bytes::BytesReadError;
+use rmp::decode::*;
+
+#[test]
+fn pass_read_from_chained_buf() {
+    // The second chunk starts in the middle of the `u16`.
+    let buf = (&[0x93, 0xc3, 0xcd, 0x01][..]).chain(&[0x00, 0xa2, 0x68, 0x69][..]);
+    let mut rd = BytesReader::new(buf);
+
+    assert_eq!(3, read_array_len(&mut rd).unwrap());
+    assert!(read_bool(&mut rd).unwrap());
+    assert_eq!(256, read_int::<u16, _>(&mut rd).unwrap());
+    assert_eq!(2, read_str_len(&mut rd).unwrap());
+    assert_eq!(6, rd.position());
+
+    let mut rest = [0; 2];
+    rd.get_mut().copy_to_slice(&mut rest);
+    assert_eq!(*b"hi", rest);
+    assert!(!rd.into_inner().has_remaining());
+}
+
+#[test]
+fn pass_read_containers_from_buf() {
+    let buf: &[u8] = &[
+        0x82, // {
+        0xa1, 0x61, 0xcb, 0x40, 0x09, 0x21, 0xfb, 0x54, 0x44, 0x2d, 0x18, // "a": pi,
+        0xa1, 0x62, 0xd4, 0x01, 0x02, // "b": ext(1, [2])
+    ];
+    let mut rd = BytesReader::new(buf);
+
+    assert_eq!(2, read_map_len(&mut rd).unwrap());
+    assert_eq!(1, read_str_len(&mut rd).unwrap());
+    assert_eq!(b'a', rd.get_mut().get_u8());
+    assert_eq!(std::f64::consts::PI, read_f64(&mut rd).unwrap());
+    assert_eq!(1, read_str_len(&mut rd).unwrap());
+    assert_eq!(b'b', rd.get_mut().get_u8());
+    assert_eq!((1, 2), read_fixext1(&mut rd).unwrap());
+}
+
+#[test]
+fn fail_read_from_truncated_buf() {
+    let mut rd = BytesReader::new(&[0xcd, 0x01][..]);
+
+    match read_u16(&mut rd) {
+        Err(ValueReadError::InvalidDataRead(BytesReadError::InsufficientBytes { expected: 2, actual: 1, position: 1 })) => (),
+        other => panic!("unexpected result: {other:?}"),
+    }
+
+    let mut rd = BytesReader::new(&[][..]);
+    match read_marker(&mut rd) {
+        Err(MarkerReadError(BytesReadError::InsufficientBytes { expected: 1, actual: 0, position: 0 })) => (),
+        other => panic!("unexpected result: {other:?}"),
+    }
+}
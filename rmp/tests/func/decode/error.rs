@@ -0,0 +1,79 @@
+use std::error::Error;
+use std::fmt;
+use std::io::{self, ErrorKind, Read};
+
+use rmp::decode::*;
+
+#[test]
+fn from_value_read_error_into_io_error() {
+    fn read(buf: &[u8]) -> io::Result<u32> {
+        Ok(read_array_len(&mut &buf[..])?)
+    }
+
+    assert_eq!(2, read(&[0x92]).unwrap());
+    assert_eq!(ErrorKind::InvalidData, read(&[0xc0]).unwrap_err().kind());
+    assert_eq!(ErrorKind::UnexpectedEof, read(&[0xdc, 0x00]).unwrap_err().kind());
+}
+
+#[test]
+fn from_num_value_read_error_into_io_error() {
+    fn read(buf: &[u8]) -> io::Result<u8> {
+        Ok(read_int(&mut &buf[..])?)
+    }
+
+    assert_eq!(42, read(&[0x2a]).unwrap());
+    assert_eq!(ErrorKind::InvalidData, read(&[0xcd, 0x01, 0x00]).unwrap_err().kind());
+    assert_eq!(ErrorKind::InvalidData, read(&[0xc3]).unwrap_err().kind());
+    assert_eq!(ErrorKind::UnexpectedEof, read(&[0xcd, 0x01]).unwrap_err().kind());
+}
+
+#[test]
+fn from_marker_read_error_into_io_error() {
+    fn read(buf: &[u8]) -> io::Result<rmp::Marker> {
+        Ok(read_marker(&mut &buf[..])?)
+    }
+
+    assert_eq!(rmp::Marker::Null, read(&[0xc0]).unwrap());
+    assert_eq!(ErrorKind::UnexpectedEof, read(&[]).unwrap_err().kind());
+}
+
+#[test]
+fn from_read_errors_into_boxed_error() {
+    fn read(buf: &[u8]) -> Result<(u32, i8), Box<dyn Error>> {
+        let mut rd = buf;
+        Ok((read_map_len(&mut rd)?, read_int(&mut rd)?))
+    }
+
+    assert_eq!((1, -1), read(&[0x81, 0xff]).unwrap());
+    assert!(read(&[0x90]).is_err());
+    let err = read(&[0x81, 0xcc, 0xff]).unwrap_err();
+    assert_eq!("out of range integral type conversion attempted", err.to_string());
+}
+
+#[derive(Debug)]
+struct ClosedError;
+
+impl fmt::Display for ClosedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("channel closed")
+    }
+}
+
+impl Error for ClosedError {}
+
+struct ClosedReader;
+
+impl Read for ClosedReader {
+    fn read(&mut self, _buf: &mut [u8]) -> io::Result<usize> {
+        Err(io::Error::other(ClosedError))
+    }
+}
+
+#[test]
+fn recover_custom_reader_error() {
+    let err = read_u32(&mut ClosedReader).unwrap_err();
+    assert!(err.source().is_some());
+
+    let err = io::Error::from(err);
+    assert!(err.get_ref().unwrap().downcast_ref::<ClosedError>().is_some());
+}
@@ -96,6 +96,30 @@ fn from_fixext4_read_ext_meta() {
     assert_eq!(2, cur.position());
 }
 
+#[test]
+fn from_timestamp32_read_ext_meta() {
+    let buf: &[u8] = &[0xd6, 0xff];
+    let mut cur = Cursor::new(buf);
+
+    assert_eq!(
+        ExtMeta { typeid: -1, size: 4 },
+        read_ext_meta(&mut cur).unwrap()
+    );
+    assert_eq!(2, cur.position());
+}
+
+#[test]
+fn from_timestamp64_read_ext_meta() {
+    let buf: &[u8] = &[0xd7, 0xff];
+    let mut cur = Cursor::new(buf);
+
+    assert_eq!(
+        ExtMeta { typeid: -1, size: 8 },
+        read_ext_meta(&mut cur).unwrap()
+    );
+    assert_eq!(2, cur.position());
+}
+
 #[test]
 fn from_fixext8_read_ext_meta() {
     let buf: &[u8] = &[0xd7, 0x01];
@@ -143,3 +167,70 @@ fn from_ext32_read_ext_meta() {
     assert_eq!(ExtMeta { typeid: 1, size: 4294967295 }, read_ext_meta(&mut cur).unwrap());
     assert_eq!(6, cur.position());
 }
+
+#[test]
+fn from_ext8_read_data_in_chunks() {
+    // ext8(5, [0..10]), nil
+    let mut buf = vec![0xc7, 0x0a, 0x05];
+    buf.extend(0..10);
+    buf.push(0xc0);
+    let mut cur = Cursor::new(&buf[..]);
+
+    let meta = read_ext_meta(&mut cur).unwrap();
+    let mut data = ExtDataReader::new(&mut cur, &meta);
+    let mut chunk = [0xff; 4];
+    let mut read = Vec::new();
+    loop {
+        let len = data.read(&mut chunk).unwrap();
+        if len == 0 {
+            break;
+        }
+        read.extend_from_slice(&chunk[..len]);
+    }
+    assert_eq!((0..10).collect::<Vec<u8>>(), read);
+    assert_eq!(0, data.remaining());
+
+    // The reader stops at the end of the data.
+    read_nil(data.into_inner()).unwrap();
+}
+
+#[test]
+fn from_ext8_skip_data() {
+    let mut buf = vec![0xc8, 0x03, 0x00, 0x01];
+    buf.resize(buf.len() + 0x300, 0xab);
+    buf.push(0xc3);
+    let mut cur = Cursor::new(&buf[..]);
+
+    let meta = read_ext_meta(&mut cur).unwrap();
+    assert_eq!(ExtMeta { typeid: 1, size: 0x300 }, meta);
+    let mut data = ExtDataReader::new(&mut cur, &meta);
+    data.skip().unwrap();
+    assert!(read_bool(data.into_inner()).unwrap());
+}
+
+#[test]
+fn from_truncated_ext8_read_data() {
+    let buf = [0xc7, 0x04, 0x01, 0x00, 0x01];
+    let mut cur = Cursor::new(&buf[..]);
+
+    let meta = read_ext_meta(&mut cur).unwrap();
+    let mut data = ExtDataReader::new(&mut cur, &meta);
+    let mut chunk = [0; 4];
+    data.read(&mut chunk).unwrap_err();
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn from_ext8_copy_data_as_io_read() {
+    use std::io::Read;
+
+    // ext8(2, "hello"), "x"
+    let buf = [0xc7, 0x05, 0x02, 0x68, 0x65, 0x6c, 0x6c, 0x6f, 0xa1, 0x78];
+    let mut rd = &buf[..];
+
+    let meta = read_ext_meta(&mut rd).unwrap();
+    let mut out = String::new();
+    ExtDataReader::new(&mut rd, &meta).read_to_string(&mut out).unwrap();
+    assert_eq!("hello", out);
+    assert_eq!(&[0xa1, 0x78], rd);
+}
@@ -106,3 +106,29 @@ fn from_null_read_f64() {
     }
     assert_eq!(1, cur.position());
 }
+
+#[test]
+fn from_int_or_other_width_read_float_strictly() {
+    // 1, 255 and -0.0 as an f32
+    let cases: [(&[u8], Marker); 3] = [
+        (&[0x01], Marker::FixPos(1)),
+        (&[0xcc, 0xff], Marker::U8),
+        (&[0xca, 0x80, 0x00, 0x00, 0x00], Marker::F32),
+    ];
+    for (buf, marker) in cases {
+        match read_f64(&mut Cursor::new(buf)) {
+            Err(ValueReadError::TypeMismatch(actual)) => assert_eq!(marker, actual),
+            other => panic!("unexpected result: {other:?}"),
+        }
+    }
+
+    let buf: &[u8] = &[0xcb, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00];
+    match read_f32(&mut Cursor::new(buf)) {
+        Err(ValueReadError::TypeMismatch(Marker::F64)) => (),
+        other => panic!("unexpected result: {other:?}"),
+    }
+    match read_f32(&mut Cursor::new(&[0xd0, 0x80][..])) {
+        Err(ValueReadError::TypeMismatch(Marker::I8)) => (),
+        other => panic!("unexpected result: {other:?}"),
+    }
+}
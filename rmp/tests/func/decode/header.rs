@@ -0,0 +1,50 @@
+use super::Cursor;
+
+use rmp::decode::*;
+use rmp::Marker;
+
+#[test]
+fn from_each_marker_read_header() {
+    let cases: &[(&[u8], Marker, Option<u32>)] = &[
+        (&[0xc0], Marker::Null, None),
+        (&[0x2a], Marker::FixPos(42), None),
+        (&[0xcd], Marker::U16, None),
+        (&[0xcb], Marker::F64, None),
+        (&[0xc1], Marker::Reserved, None),
+        (&[0xa3], Marker::FixStr(3), Some(3)),
+        (&[0xd9, 0xff], Marker::Str8, Some(255)),
+        (&[0xda, 0x01, 0x00], Marker::Str16, Some(256)),
+        (&[0xdb, 0x00, 0x01, 0x00, 0x00], Marker::Str32, Some(65536)),
+        (&[0xc4, 0x02], Marker::Bin8, Some(2)),
+        (&[0xc5, 0xff, 0xff], Marker::Bin16, Some(65535)),
+        (&[0xc6, 0xff, 0xff, 0xff, 0xff], Marker::Bin32, Some(u32::MAX)),
+        (&[0xd4], Marker::FixExt1, Some(1)),
+        (&[0xd8], Marker::FixExt16, Some(16)),
+        (&[0xc7, 0x03], Marker::Ext8, Some(3)),
+        (&[0xc8, 0x01, 0x00], Marker::Ext16, Some(256)),
+        (&[0xc9, 0x00, 0x00, 0x00, 0x05], Marker::Ext32, Some(5)),
+        (&[0x9f], Marker::FixArray(15), Some(15)),
+        (&[0xdc, 0x00, 0x10], Marker::Array16, Some(16)),
+        (&[0xdd, 0x00, 0x00, 0x00, 0x00], Marker::Array32, Some(0)),
+        (&[0x81], Marker::FixMap(1), Some(1)),
+        (&[0xde, 0x00, 0x10], Marker::Map16, Some(16)),
+        (&[0xdf, 0x00, 0x01, 0x00, 0x00], Marker::Map32, Some(65536)),
+    ];
+
+    for &(buf, marker, len) in cases {
+        // The payload is left unread.
+        let buf = [buf, &[0xc0]].concat();
+        let mut cur = Cursor::new(&buf);
+        assert_eq!(Header { marker, len }, read_header(&mut cur).unwrap(), "{buf:x?}");
+        assert_eq!(buf.len() as u64 - 1, cur.position());
+    }
+}
+
+#[test]
+fn from_truncated_read_header() {
+    let mut cur = Cursor::new(&[]);
+    assert!(matches!(read_header(&mut cur), Err(ValueReadError::InvalidMarkerRead(..))));
+
+    let mut cur = Cursor::new(&[0xda, 0x01]);
+    assert!(matches!(read_header(&mut cur), Err(ValueReadError::InvalidDataRead(..))));
+}
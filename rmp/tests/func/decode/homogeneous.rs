@@ -0,0 +1,52 @@
+use rmp::decode::{read_homogeneous_array, HomogeneousArrayError, NumValueReadError, ValueReadError};
+use rmp::encode::*;
+use rmp::Marker;
+
+#[test]
+fn pass_read_homogeneous_array() {
+    let mut buf = Vec::new();
+    write_array_len(&mut buf, 300).unwrap();
+    for i in 0..300 {
+        write_sint(&mut buf, i * 1000 - 150_000).unwrap();
+    }
+    let expected: Vec<i64> = (0..300).map(|i| i * 1000 - 150_000).collect();
+    assert_eq!(expected, read_homogeneous_array::<i64, _>(&mut &buf[..]).unwrap());
+
+    let buf = [0x92, 0xc3, 0xc2];
+    assert_eq!(vec![true, false], read_homogeneous_array::<bool, _>(&mut &buf[..]).unwrap());
+
+    let mut buf = Vec::new();
+    write_array_len(&mut buf, 2).unwrap();
+    write_f64(&mut buf, 1.5).unwrap();
+    write_f64(&mut buf, -0.0).unwrap();
+    assert_eq!(vec![1.5, -0.0], read_homogeneous_array::<f64, _>(&mut &buf[..]).unwrap());
+
+    assert_eq!(Vec::<u8>::new(), read_homogeneous_array::<u8, _>(&mut &[0x90][..]).unwrap());
+}
+
+#[test]
+fn fail_read_homogeneous_array() {
+    // [1, 256] as u8
+    let buf = [0x92, 0x01, 0xcd, 0x01, 0x00];
+    match read_homogeneous_array::<u8, _>(&mut &buf[..]) {
+        Err(HomogeneousArrayError::Element(1, NumValueReadError::OutOfRange)) => {}
+        other => panic!("unexpected result: {other:?}"),
+    }
+
+    // [1.5f64, 1.5f32] as f64
+    let buf = [0x92, 0xcb, 0x3f, 0xf8, 0, 0, 0, 0, 0, 0, 0xca, 0x3f, 0xc0, 0, 0];
+    match read_homogeneous_array::<f64, _>(&mut &buf[..]) {
+        Err(HomogeneousArrayError::Element(1, NumValueReadError::TypeMismatch(Marker::F32))) => {}
+        other => panic!("unexpected result: {other:?}"),
+    }
+
+    // A map, and a truncated array
+    match read_homogeneous_array::<u8, _>(&mut &[0x80][..]) {
+        Err(HomogeneousArrayError::Len(ValueReadError::TypeMismatch(Marker::FixMap(0)))) => {}
+        other => panic!("unexpected result: {other:?}"),
+    }
+    match read_homogeneous_array::<u8, _>(&mut &[0xdd, 0xff, 0xff, 0xff, 0xff, 0x01][..]) {
+        Err(HomogeneousArrayError::Element(1, NumValueReadError::InvalidMarkerRead(..))) => {}
+        other => panic!("unexpected result: {other:?}"),
+    }
+}
@@ -59,3 +59,18 @@ fn from_null_read_map_len() {
     }
     assert_eq!(1, cur.position());
 }
+
+#[test]
+fn from_map_or_array_peek_struct_encoding() {
+    assert_eq!(Some(Marker::FixMap(0)), peek_marker(&[0x80]));
+    assert_eq!(None, peek_marker(&[]));
+
+    assert_eq!(StructEncoding::Map, peek_struct_encoding(&[0x80]));
+    assert_eq!(StructEncoding::Map, peek_struct_encoding(&[0xde, 0x00, 0x01]));
+    assert_eq!(StructEncoding::Map, peek_struct_encoding(&[0xdf, 0x00, 0x00, 0x00, 0x01]));
+    assert_eq!(StructEncoding::Array, peek_struct_encoding(&[0x9f]));
+    assert_eq!(StructEncoding::Array, peek_struct_encoding(&[0xdc, 0x00, 0x01]));
+    assert_eq!(StructEncoding::Array, peek_struct_encoding(&[0xdd, 0x00, 0x00, 0x00, 0x01]));
+    assert_eq!(StructEncoding::Other, peek_struct_encoding(&[0xa1, 0x61]));
+    assert_eq!(StructEncoding::Other, peek_struct_encoding(&[0xc1]));
+}
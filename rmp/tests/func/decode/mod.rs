@@ -1,13 +1,23 @@
 mod array;
+mod audit;
 mod bin;
 mod bool;
+#[cfg(feature = "std")]
+mod error;
+#[cfg(feature = "bytes")]
+mod buf;
 mod ext;
 mod float;
+mod header;
+mod homogeneous;
 mod map;
 mod null;
+mod scalar;
 mod sint;
+mod stats;
 mod string;
 mod uint;
+mod validate;
 
 #[cfg(feature = "std")]
 pub type Cursor<'a> = std::io::Cursor<&'a [u8]>;
@@ -0,0 +1,95 @@
+use rmp::decode::*;
+use rmp::Marker;
+
+#[test]
+fn from_each_scalar_read_scalar() {
+    let cases: &[(&[u8], Scalar<'_>)] = &[
+        (&[0xc0], Scalar::Nil),
+        (&[0xc3], Scalar::Bool(true)),
+        (&[0xc2], Scalar::Bool(false)),
+        (&[0x7f], Scalar::UInt(127)),
+        (&[0xe0], Scalar::Int(-32)),
+        (&[0xcc, 0xff], Scalar::UInt(255)),
+        (&[0xcd, 0x01, 0x2c], Scalar::UInt(300)),
+        (&[0xce, 0xff, 0xff, 0xff, 0xff], Scalar::UInt(u32::MAX.into())),
+        (&[0xcf, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff], Scalar::UInt(u64::MAX)),
+        (&[0xd0, 0x80], Scalar::Int(-128)),
+        (&[0xd1, 0x80, 0x00], Scalar::Int(i16::MIN.into())),
+        (&[0xd2, 0x80, 0x00, 0x00, 0x00], Scalar::Int(i32::MIN.into())),
+        (&[0xd3, 0x80, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], Scalar::Int(i64::MIN)),
+        (&[0xca, 0x3f, 0xc0, 0x00, 0x00], Scalar::F32(1.5)),
+        (&[0xcb, 0x3f, 0xf8, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], Scalar::F64(1.5)),
+        (&[0xa2, 0x6c, 0x65], Scalar::Str("le")),
+        (&[0xd9, 0x00], Scalar::Str("")),
+        (&[0xda, 0x00, 0x01, 0x61], Scalar::Str("a")),
+        (&[0xdb, 0x00, 0x00, 0x00, 0x01, 0x61], Scalar::Str("a")),
+        (&[0xc4, 0x02, 0x01, 0x02], Scalar::Bin(&[1, 2])),
+        (&[0xc5, 0x00, 0x01, 0x03], Scalar::Bin(&[3])),
+        (&[0xc6, 0x00, 0x00, 0x00, 0x00], Scalar::Bin(&[])),
+        (&[0xd4, 0x01, 0x02], Scalar::Ext(1, &[2])),
+        (&[0xd5, 0xff, 0x01, 0x02], Scalar::Ext(-1, &[1, 2])),
+        (&[0xd6, 0x05, 0x01, 0x02, 0x03, 0x04], Scalar::Ext(5, &[1, 2, 3, 4])),
+        (&[0xd7, 0x05, 0, 0, 0, 0, 0, 0, 0, 0], Scalar::Ext(5, &[0; 8])),
+        (&[0xd8, 0x05, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0], Scalar::Ext(5, &[0; 16])),
+        (&[0xc7, 0x01, 0x02, 0x03], Scalar::Ext(2, &[3])),
+        (&[0xc8, 0x00, 0x00, 0x02], Scalar::Ext(2, &[])),
+        (&[0xc9, 0x00, 0x00, 0x00, 0x01, 0x02, 0x03], Scalar::Ext(2, &[3])),
+    ];
+
+    for &(buf, expected) in cases {
+        let mut input = buf.to_vec();
+        input.push(0x00);
+
+        let (val, rest) = read_scalar(&input).unwrap();
+        assert_eq!(expected, val, "{buf:x?}");
+        assert_eq!(&[0x00], rest, "{buf:x?}");
+    }
+}
+
+#[test]
+fn from_signed_positive_read_scalar() {
+    // Non-negative integers are `UInt`, even with a signed marker.
+    assert_eq!(Scalar::UInt(1), read_scalar(&[0xd0, 0x01]).unwrap().0);
+    assert_eq!(Scalar::UInt(i64::MAX as u64), read_scalar(&[0xd3, 0x7f, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff]).unwrap().0);
+}
+
+#[test]
+fn from_container_read_scalar() {
+    assert_eq!(Err(ScalarReadError::Container(Marker::FixArray(1))), read_scalar(&[0x91, 0x01]));
+    assert_eq!(Err(ScalarReadError::Container(Marker::Array16)), read_scalar(&[0xdc, 0x00, 0x00]));
+    assert_eq!(Err(ScalarReadError::Container(Marker::FixMap(0))), read_scalar(&[0x80]));
+    assert_eq!(Err(ScalarReadError::Container(Marker::Map32)), read_scalar(&[0xdf]));
+}
+
+#[test]
+fn from_truncated_read_scalar() {
+    let cases: &[&[u8]] = &[
+        &[],
+        &[0xcd, 0x01],
+        &[0xd3, 0x00, 0x00],
+        &[0xcb, 0x00],
+        &[0xa2, 0x61],
+        &[0xda, 0x00],
+        &[0xc6, 0x00, 0x00, 0x00, 0x02, 0x01],
+        &[0xd4, 0x01],
+        &[0xc7, 0x01],
+        &[0xc9, 0xff, 0xff, 0xff, 0xff, 0x01],
+    ];
+
+    for &buf in cases {
+        assert_eq!(Err(ScalarReadError::Truncated), read_scalar(buf), "{buf:x?}");
+    }
+}
+
+#[test]
+fn from_reserved_read_scalar() {
+    assert_eq!(Err(ScalarReadError::ReservedMarker), read_scalar(&[0xc1]));
+}
+
+#[test]
+fn from_invalid_utf8_read_scalar() {
+    match read_scalar(&[0xa2, 0xc3, 0x28]) {
+        Err(ScalarReadError::InvalidUtf8(err)) => assert_eq!(0, err.valid_up_to()),
+        res => panic!("unexpected result: {res:?}"),
+    }
+}
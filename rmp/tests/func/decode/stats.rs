@@ -0,0 +1,83 @@
+use rmp::decode::{collect_stats, ValidateError};
+use rmp::encode::*;
+use rmp::Marker;
+
+#[test]
+fn pass_collect_stats_scalar() {
+    let stats = collect_stats(&[0xcd, 0x01, 0x2c]).unwrap();
+    assert_eq!(1, stats.uints);
+    assert_eq!(0, stats.sints);
+    assert_eq!(0, stats.max_depth);
+    assert_eq!(3, stats.total_bytes);
+}
+
+#[test]
+fn pass_collect_stats_nested() {
+    let mut buf = Vec::new();
+    write_map_len(&mut buf, 3).unwrap();
+    write_str(&mut buf, "ints").unwrap();
+    write_array_len(&mut buf, 300).unwrap();
+    for i in 0..300 {
+        write_sint(&mut buf, i - 150).unwrap();
+    }
+    write_str(&mut buf, "blob").unwrap();
+    write_bin(&mut buf, &[0; 1000]).unwrap();
+    write_str(&mut buf, "deep").unwrap();
+    write_array_len(&mut buf, 2).unwrap();
+    write_array_len(&mut buf, 0).unwrap();
+    write_array_len(&mut buf, 1).unwrap();
+    write_map_len(&mut buf, 1).unwrap();
+    write_nil(&mut buf).unwrap();
+    write_ext_meta(&mut buf, 1, 3).unwrap();
+    buf.push(0x00);
+
+    let stats = collect_stats(&buf).unwrap();
+    assert_eq!(1, stats.nils);
+    assert_eq!(150, stats.uints);
+    assert_eq!(150, stats.sints);
+    assert_eq!(3, stats.strs);
+    assert_eq!(1, stats.bins);
+    assert_eq!(4, stats.arrays);
+    assert_eq!(2, stats.maps);
+    assert_eq!(1, stats.exts);
+    assert_eq!(4, stats.max_depth);
+    assert_eq!(4, stats.max_str_len);
+    assert_eq!(1000, stats.max_bin_len);
+    assert_eq!(buf.len(), stats.total_bytes);
+
+    assert_eq!(1, stats.marker_count(Marker::Map16) + stats.marker_count(Marker::FixMap(3)));
+    assert_eq!(1, stats.marker_count(Marker::Array16));
+    assert_eq!(1, stats.marker_count(Marker::Bin16));
+    assert_eq!(1, stats.marker_count(Marker::FixExt1) + stats.marker_count(Marker::Ext8));
+    assert_eq!(3, stats.marker_count(Marker::FixStr(4)));
+    // -150 to -129 need an int 16, -128 to -33 an int 8 and 128 to 149 a uint 8.
+    assert_eq!(22, stats.marker_count(Marker::I16));
+    assert_eq!(96, stats.marker_count(Marker::I8));
+    assert_eq!(1, stats.marker_count(Marker::FixNeg(-1)));
+    assert_eq!(1, stats.marker_count(Marker::FixPos(0)));
+    assert_eq!(22, stats.marker_count(Marker::U8));
+
+    let is_fixint = |marker: &Marker| matches!(marker, Marker::FixPos(..) | Marker::FixNeg(..));
+    let fixints = stats.marker_counts().filter(|(marker, _)| is_fixint(marker));
+    assert_eq!(160, fixints.map(|(_, count)| count).sum::<usize>());
+    let values = stats.nils + stats.uints + stats.sints + stats.strs + stats.bins + stats.arrays + stats.maps + stats.exts;
+    assert_eq!(values, stats.marker_counts().map(|(_, count)| count).sum::<usize>());
+}
+
+#[test]
+fn pass_collect_stats_deep_nesting() {
+    let mut buf = vec![0x91; 100_000];
+    buf.push(0xc0);
+
+    let stats = collect_stats(&buf).unwrap();
+    assert_eq!(100_000, stats.arrays);
+    assert_eq!(100_000, stats.max_depth);
+}
+
+#[test]
+fn fail_collect_stats_invalid() {
+    assert_eq!(Err(ValidateError::Truncated), collect_stats(&[0x92, 0x01]));
+    assert_eq!(Err(ValidateError::Truncated), collect_stats(&[0xdd, 0xff, 0xff, 0xff, 0xff]));
+    assert_eq!(Err(ValidateError::ReservedMarker(1)), collect_stats(&[0x91, 0xc1]));
+    assert_eq!(Err(ValidateError::TrailingBytes(1)), collect_stats(&[0xc0, 0xc0]));
+}
@@ -0,0 +1,139 @@
+use rmp::decode::{validate, validate_minimal, ValidateError};
+use rmp::encode::*;
+
+#[test]
+fn pass_validate_scalars() {
+    assert_eq!(Ok(1), validate(&[0xc0]));
+    assert_eq!(Ok(1), validate(&[0x2a]));
+    assert_eq!(Ok(3), validate(&[0xcd, 0x01, 0x2c]));
+    assert_eq!(Ok(9), validate(&[0xcb, 0x40, 0x45, 0, 0, 0, 0, 0, 0]));
+    assert_eq!(Ok(4), validate(&[0xa3, 0x61, 0x62, 0x63]));
+    assert_eq!(Ok(4), validate(&[0xc4, 0x02, 0xcc, 0x80]));
+}
+
+#[test]
+fn pass_validate_ext() {
+    assert_eq!(Ok(3), validate(&[0xd4, 0x01, 0x02]));
+    assert_eq!(Ok(6), validate(&[0xd6, 0xff, 0x00, 0x00, 0x00, 0x01]));
+    assert_eq!(Ok(18), validate(&[&[0xd8, 0x01][..], &[0; 16]].concat()));
+    assert_eq!(Ok(5), validate(&[0xc7, 0x02, 0x01, 0x00, 0x00]));
+}
+
+#[test]
+fn pass_validate_nested() {
+    let mut buf = Vec::new();
+    write_map_len(&mut buf, 2).unwrap();
+    write_str(&mut buf, "a").unwrap();
+    write_array_len(&mut buf, 300).unwrap();
+    for i in 0..300 {
+        write_uint(&mut buf, i).unwrap();
+    }
+    write_str(&mut buf, "b").unwrap();
+    write_map_len(&mut buf, 0).unwrap();
+
+    assert_eq!(Ok(buf.len()), validate(&buf));
+}
+
+#[test]
+fn pass_validate_deep_nesting() {
+    let mut buf = vec![0x91; 100_000];
+    buf.push(0xc0);
+
+    assert_eq!(Ok(buf.len()), validate(&buf));
+}
+
+#[test]
+fn fail_validate_truncated() {
+    assert_eq!(Err(ValidateError::Truncated), validate(&[]));
+    assert_eq!(Err(ValidateError::Truncated), validate(&[0xcd, 0x01]));
+    assert_eq!(Err(ValidateError::Truncated), validate(&[0xa3, 0x61]));
+    assert_eq!(Err(ValidateError::Truncated), validate(&[0xda, 0x00]));
+    assert_eq!(Err(ValidateError::Truncated), validate(&[0x82, 0xc0, 0xc0, 0xc0]));
+    assert_eq!(Err(ValidateError::Truncated), validate(&[0xdd, 0xff, 0xff, 0xff, 0xff, 0xc0]));
+    assert_eq!(Err(ValidateError::Truncated), validate(&[0xc6, 0xff, 0xff, 0xff, 0xff]));
+}
+
+#[test]
+fn fail_validate_reserved_marker() {
+    assert_eq!(Err(ValidateError::ReservedMarker(1)), validate(&[0x91, 0xc1]));
+}
+
+#[test]
+fn fail_validate_trailing_bytes() {
+    assert_eq!(Err(ValidateError::TrailingBytes(2)), validate(&[0x91, 0xc0, 0xc0]));
+}
+
+#[test]
+fn pass_validate_minimal_boundaries() {
+    let mut buf = Vec::new();
+    let ints = [0, 127, 128, 255, 256, 65535, 65536, u64::from(u32::MAX), u64::from(u32::MAX) + 1, u64::MAX];
+    let sints = [-1, -32, -33, -128, -129, -32768, -32769, i64::from(i32::MIN), i64::from(i32::MIN) - 1, i64::MIN];
+    let lens = [0, 1, 2, 4, 8, 15, 16, 31, 32, 255, 256, 65535, 65536];
+    write_array_len(&mut buf, (ints.len() + sints.len() + 3 * lens.len()) as u32).unwrap();
+    for val in ints {
+        write_uint(&mut buf, val).unwrap();
+    }
+    for val in sints {
+        write_sint(&mut buf, val).unwrap();
+    }
+    for len in lens {
+        write_str_len(&mut buf, len).unwrap();
+        buf.resize(buf.len() + len as usize, b'a');
+        write_bin_len(&mut buf, len).unwrap();
+        buf.resize(buf.len() + len as usize, 0);
+        write_ext_meta(&mut buf, len, 1).unwrap();
+        buf.resize(buf.len() + len as usize, 0);
+    }
+
+    assert_eq!(Ok(buf.len()), validate_minimal(&buf));
+
+    for len in [15, 16, 65535, 65536] {
+        let mut buf = Vec::new();
+        write_array_len(&mut buf, 2).unwrap();
+        write_array_len(&mut buf, len).unwrap();
+        buf.resize(buf.len() + len as usize, 0xc0);
+        write_map_len(&mut buf, len).unwrap();
+        buf.resize(buf.len() + 2 * len as usize, 0xc0);
+        assert_eq!(Ok(buf.len()), validate_minimal(&buf));
+    }
+}
+
+#[test]
+fn fail_validate_minimal() {
+    let cases: &[&[u8]] = &[
+        &[0xcc, 0x7f],
+        &[0xcd, 0x00, 0xff],
+        &[0xce, 0x00, 0x00, 0xff, 0xff],
+        &[0xcf, 0x00, 0x00, 0x00, 0x00, 0xff, 0xff, 0xff, 0xff],
+        &[0xd0, 0xe0],
+        &[0xd0, 0x01],
+        &[0xd1, 0xff, 0x80],
+        &[0xd2, 0xff, 0xff, 0x80, 0x00],
+        &[0xd3, 0xff, 0xff, 0xff, 0xff, 0x80, 0x00, 0x00, 0x00],
+        &[0xd3, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00],
+        &[0xd9, 0x00],
+        &[0xda, 0x00, 0x00],
+        &[0xdb, 0x00, 0x00, 0x00, 0x00],
+        &[0xc5, 0x00, 0x00],
+        &[0xc6, 0x00, 0x00, 0x00, 0x00],
+        &[0xdc, 0x00, 0x00],
+        &[0xdd, 0x00, 0x00, 0x00, 0x00],
+        &[0xde, 0x00, 0x00],
+        &[0xdf, 0x00, 0x00, 0x00, 0x00],
+        &[0xc7, 0x01, 0x01, 0x00],
+        &[0xc8, 0x00, 0x00, 0x01],
+        &[0xc9, 0x00, 0x00, 0x00, 0x00, 0x01],
+    ];
+    for &case in cases {
+        // Accepted by the plain validation.
+        assert_eq!(Ok(case.len()), validate(case), "{case:x?}");
+        assert_eq!(Err(ValidateError::NonMinimal(0)), validate_minimal(case), "{case:x?}");
+    }
+
+    // Reports the offset of the nested value: {"a": [uint 8 1]}
+    let buf = [0x81, 0xa1, 0x61, 0x91, 0xcc, 0x01];
+    assert_eq!(Err(ValidateError::NonMinimal(4)), validate_minimal(&buf));
+    // Structural errors are still reported.
+    assert_eq!(Err(ValidateError::Truncated), validate_minimal(&[0xcc]));
+    assert_eq!(Err(ValidateError::TrailingBytes(1)), validate_minimal(&[0xc0, 0xc0]));
+}
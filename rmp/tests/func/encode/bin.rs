@@ -33,3 +33,56 @@ fn pass_pack_len_u32() {
 
     assert_eq!([0xc6, 0xff, 0xff, 0xff, 0xff], buf);
 }
+
+#[cfg(feature = "std")]
+#[test]
+fn pass_pack_bin_chunked() {
+    use std::io::{self, Write};
+
+    /// Records the size of every write and the number of flushes.
+    #[derive(Default)]
+    struct Recorder {
+        buf: Vec<u8>,
+        writes: Vec<usize>,
+        flushes: usize,
+    }
+
+    impl Write for Recorder {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.writes.push(buf.len());
+            self.buf.extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            self.flushes += 1;
+            Ok(())
+        }
+    }
+
+    let data: Vec<u8> = (0..=255).cycle().take(300).collect();
+    let mut wr = Recorder::default();
+    write_bin_chunked(&mut wr, &data, 128).unwrap();
+
+    let mut expected = Vec::new();
+    write_bin(&mut expected, &data).unwrap();
+    assert_eq!(expected, wr.buf);
+    // The marker and the length, followed by the data in chunks.
+    assert_eq!(vec![1, 2, 128, 128, 44], wr.writes);
+    assert_eq!(3, wr.flushes);
+
+    // Empty data is written without any chunk.
+    let mut wr = Recorder::default();
+    write_bin_chunked(&mut wr, &[], 128).unwrap();
+    assert_eq!(vec![0xc4, 0x00], wr.buf);
+    assert_eq!(0, wr.flushes);
+}
+
+#[test]
+fn pass_encoded_bin_len_matches_header() {
+    for len in [0, 1, 255, 256, 65535, 65536, u32::MAX] {
+        let mut buf = Vec::new();
+        write_bin_len(&mut buf, len).unwrap();
+        assert_eq!(buf.len() as u64 + u64::from(len), encoded_bin_len(len));
+    }
+}
@@ -0,0 +1,40 @@
+use ::bytes::BytesMut;
+use rmp::encode::*;
+
+#[test]
+fn pass_write_into_bytes_mut() {
+    let mut wr = BytesWriter::new(BytesMut::new());
+
+    write_map_len(&mut wr, 2).unwrap();
+    write_str(&mut wr, "a").unwrap();
+    write_array_len(&mut wr, 2).unwrap();
+    write_uint(&mut wr, 300).unwrap();
+    write_f32(&mut wr, 1.5).unwrap();
+    write_str(&mut wr, "b").unwrap();
+    write_bin(&mut wr, &[0xff]).unwrap();
+    write_ext_meta(&mut wr, 1, 7).unwrap();
+
+    let buf = wr.into_inner().freeze();
+    assert_eq!(
+        [
+            0x82, 0xa1, 0x61, 0x92, 0xcd, 0x01, 0x2c, 0xca, 0x3f, 0xc0, 0x00, 0x00,
+            0xa1, 0x62, 0xc4, 0x01, 0xff, 0xd4, 0x07,
+        ][..],
+        buf[..]
+    );
+}
+
+#[test]
+fn fail_write_into_full_buf() {
+    let mut buf = [0x00; 2];
+    let mut wr = BytesWriter::new(&mut buf[..]);
+
+    write_nil(&mut wr).unwrap();
+    match write_u16(&mut wr, 1) {
+        Err(ValueWriteError::InvalidMarkerWrite(..)) => panic!("the marker should fit"),
+        Err(ValueWriteError::InvalidDataWrite(BytesWriteError::InsufficientCapacity { expected: 2, actual: 0 })) => (),
+        other => panic!("unexpected result: {other:?}"),
+    }
+
+    assert_eq!([0xc0, 0xcd], buf);
+}
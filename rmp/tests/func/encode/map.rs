@@ -27,3 +27,74 @@ fn pass_pack_len_u32() {
 
     assert_eq!([0xdf, 0xff, 0xff, 0xff, 0xff], buf);
 }
+
+#[test]
+fn pass_map_writer() {
+    let mut buf = Vec::new();
+
+    let mut map = MapWriter::new(&mut buf, 2).unwrap();
+    for (key, val) in [("a", 1), ("b", 2)] {
+        map.write_pair(|wr| {
+            write_str(wr, key)?;
+            write_uint(wr, val)?;
+            Ok::<(), ValueWriteError>(())
+        }).unwrap();
+    }
+    drop(map);
+
+    assert_eq!(vec![0x82, 0xa1, 0x61, 0x01, 0xa1, 0x62, 0x02], buf);
+}
+
+#[test]
+fn pass_map_writer_failed_pair_skips_check() {
+    let mut buf = [0x00; 2];
+    let mut wr = &mut buf[..];
+
+    let mut map = MapWriter::new(&mut wr, 3).unwrap();
+    assert!(map.write_pair(|wr| write_str(wr, "le message")).is_err());
+}
+
+#[cfg(debug_assertions)]
+#[test]
+#[should_panic(expected = "were not written")]
+fn fail_map_writer_too_few_pairs() {
+    let mut buf = Vec::new();
+
+    let mut map = MapWriter::new(&mut buf, 2).unwrap();
+    map.write_pair(|wr| write_nil(wr).and_then(|()| write_nil(wr))).unwrap();
+}
+
+#[cfg(debug_assertions)]
+#[test]
+#[should_panic(expected = "more map pairs written")]
+fn fail_map_writer_too_many_pairs() {
+    let mut buf = Vec::new();
+
+    let mut map = MapWriter::new(&mut buf, 0).unwrap();
+    map.write_pair(|wr| write_nil(wr).and_then(|()| write_nil(wr))).unwrap();
+}
+
+#[test]
+fn pass_pack_headers_with_streamed_bodies() {
+    use std::io::Write;
+
+    // {"s": "abc", "e": ext 7 [1; 20]}, with every body written byte by byte.
+    let mut buf = Vec::new();
+    write_map_len(&mut buf, 2).unwrap();
+    write_str_len(&mut buf, 1).unwrap();
+    buf.write_all(b"s").unwrap();
+    write_str_len(&mut buf, 3).unwrap();
+    for b in b"abc" {
+        buf.write_all(&[*b]).unwrap();
+    }
+    write_str(&mut buf, "e").unwrap();
+    write_ext_meta(&mut buf, 20, 7).unwrap();
+    for _ in 0..20 {
+        buf.write_all(&[1]).unwrap();
+    }
+
+    let mut expected = vec![0x82, 0xa1, b's', 0xa3, b'a', b'b', b'c', 0xa1, b'e', 0xc7, 20, 7];
+    expected.extend_from_slice(&[1; 20]);
+    assert_eq!(expected, buf);
+    rmp::decode::validate(&buf).unwrap();
+}
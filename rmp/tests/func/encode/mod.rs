@@ -1,9 +1,13 @@
 mod array;
 mod bin;
 mod bool;
+#[cfg(feature = "bytes")]
+mod buf;
 mod ext;
 mod float;
 mod int;
 mod map;
 mod null;
+mod seq;
+mod slice;
 mod string;
@@ -0,0 +1,105 @@
+use rmp::encode::*;
+
+#[test]
+fn pass_seq_writer_buffered() {
+    let mut buf = Vec::new();
+
+    let mut seq = SeqWriter::new(&mut buf);
+    assert!(seq.is_empty());
+    for i in 0..16 {
+        seq.write_element(|wr| write_uint(wr, i).map(|_| ())).unwrap();
+    }
+    assert_eq!(16, seq.len());
+    seq.finish().unwrap();
+
+    // An array16 header, as 16 elements do not fit into a fixarray.
+    let mut expected = vec![0xdc, 0x00, 0x10];
+    expected.extend(0..16);
+    assert_eq!(expected, buf);
+}
+
+#[test]
+fn pass_seq_writer_empty() {
+    let mut buf = Vec::new();
+
+    SeqWriter::new(&mut buf).finish().unwrap();
+
+    assert_eq!(vec![0x90], buf);
+}
+
+#[test]
+fn pass_seq_writer_nested() {
+    let mut buf = Vec::new();
+
+    let mut seq = SeqWriter::new(&mut buf);
+    seq.write_element(|wr| {
+        let mut inner = SeqWriter::new(wr);
+        inner.write_element(|wr| write_str(wr, "a"))?;
+        inner.finish().map(|_| ())
+    }).unwrap();
+    seq.write_element(write_nil).unwrap();
+    seq.finish().unwrap();
+
+    assert_eq!(vec![0x92, 0x91, 0xa1, 0x61, 0xc0], buf);
+}
+
+#[test]
+fn pass_seq_writer_dropped_writes_nothing() {
+    let mut buf = Vec::new();
+
+    let mut seq = SeqWriter::new(&mut buf);
+    seq.write_element(write_nil).unwrap();
+    drop(seq);
+
+    assert!(buf.is_empty());
+}
+
+#[test]
+fn fail_seq_writer_short_buffer() {
+    let mut buf = [0x00; 2];
+
+    let mut wr = &mut buf[..];
+    let mut seq = SeqWriter::new(&mut wr);
+    seq.write_element(|wr| write_str(wr, "abc")).unwrap();
+    match seq.finish() {
+        Err(ValueWriteError::InvalidDataWrite(..)) => (),
+        other => panic!("unexpected result: {other:?}"),
+    }
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn pass_seekable_seq_writer_backpatches_length() {
+    use std::io::Cursor;
+
+    // Start in the middle of the stream, after an unrelated value.
+    let mut cur = Cursor::new(Vec::new());
+    write_bool(&mut cur, true).unwrap();
+
+    let mut seq = SeekableSeqWriter::new(&mut cur).unwrap();
+    for i in 0..3 {
+        seq.write_element(|wr| write_uint(wr, i).map(|_| ())).unwrap();
+    }
+    assert_eq!(3, seq.len());
+    seq.finish().unwrap();
+
+    // Writing continues after the array.
+    write_nil(&mut cur).unwrap();
+
+    assert_eq!(vec![0xc3, 0xdd, 0x00, 0x00, 0x00, 0x03, 0x00, 0x01, 0x02, 0xc0], cur.into_inner());
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn pass_seekable_seq_writer_decodes() {
+    use std::io::Cursor;
+
+    let mut cur = Cursor::new(Vec::new());
+    let mut seq = SeekableSeqWriter::new(&mut cur).unwrap();
+    seq.write_element(|wr| write_str(wr, "le message")).unwrap();
+    seq.finish().unwrap();
+
+    let buf = cur.into_inner();
+    assert_eq!(1, rmp::decode::read_array_len(&mut &buf[..]).unwrap());
+    assert_eq!(Ok(buf.len()), rmp::decode::validate(&buf));
+}
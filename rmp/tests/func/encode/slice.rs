@@ -0,0 +1,39 @@
+use rmp::encode::*;
+
+#[test]
+fn pass_write_into_stack_buffer() {
+    let mut buf = [0x00; 16];
+    let mut wr = SliceWriter::new(&mut buf);
+
+    write_map_len(&mut wr, 1).unwrap();
+    write_str(&mut wr, "a").unwrap();
+    write_f64(&mut wr, 1.5).unwrap();
+
+    assert_eq!(12, wr.position());
+    assert_eq!(4, wr.remaining());
+    assert_eq!(
+        &[0x81, 0xa1, 0x61, 0xcb, 0x3f, 0xf8, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00],
+        wr.into_written()
+    );
+}
+
+#[test]
+fn fail_write_into_full_stack_buffer() {
+    let mut buf = [0x00; 3];
+    let mut wr = SliceWriter::new(&mut buf);
+
+    write_u8(&mut wr, 42).unwrap();
+    match write_u32(&mut wr, 42) {
+        Err(ValueWriteError::InvalidDataWrite(SliceWriteError::BufferFull)) => {}
+        other => panic!("unexpected result: {other:?}"),
+    }
+    // The marker fit, but the data did not.
+    assert_eq!(&[0xcc, 0x2a, 0xce], wr.written());
+
+    assert_eq!(Err(SliceWriteError::BufferFull), wr.write_bytes(&[0x00]));
+    assert_eq!(3, wr.position());
+
+    let mut empty = SliceWriter::new(&mut []);
+    assert!(write_nil(&mut empty).is_err());
+    assert_eq!(0, empty.position());
+}
@@ -36,3 +36,13 @@ fn pass_pack_len_u32() {
 
     assert_eq!([0xdb, 0xff, 0xff, 0xff, 0xff], buf);
 }
+
+#[test]
+fn pass_encoded_str_len_matches_header() {
+    for len in [0, 31, 32, 255, 256, 65535, 65536, u32::MAX] {
+        let mut buf = Vec::new();
+        write_str_len(&mut buf, len).unwrap();
+        assert_eq!(buf.len() as u64 + u64::from(len), encoded_str_len(len));
+        assert!(encoded_str_len(len) <= encoded_bin_len(len));
+    }
+}
@@ -32,4 +32,18 @@ quickcheck! {
         let res = decode::read_f64(&mut Bytes::new(buf.as_slice())).expect("read");
         true || xs == res || (xs.is_nan() && res.is_nan())
     }
+
+    fn mirror_f32_bits(bits: u32) -> bool {
+        let mut buf = ByteBuf::new();
+        encode::write_f32(&mut buf, f32::from_bits(bits)).unwrap();
+
+        bits == decode::read_f32(&mut Bytes::new(buf.as_slice())).unwrap().to_bits()
+    }
+
+    fn mirror_f64_bits(bits: u64) -> bool {
+        let mut buf = ByteBuf::new();
+        encode::write_f64(&mut buf, f64::from_bits(bits)).unwrap();
+
+        bits == decode::read_f64(&mut Bytes::new(buf.as_slice())).unwrap().to_bits()
+    }
 }
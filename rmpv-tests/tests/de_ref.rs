@@ -280,6 +280,20 @@ fn pass_enum_from_value() {
         deserialize_from(ValueRef::Array(vec![ValueRef::from(3), ValueRef::Array(vec![ValueRef::from("John"), ValueRef::from(42)])])).unwrap());
 }
 
+#[test]
+fn pass_unit_variant_from_bare_string() {
+    #[derive(Debug, PartialEq, Deserialize)]
+    enum Enum<'a> {
+        Unit,
+        Newtype(&'a str),
+    }
+
+    assert_eq!(Enum::Unit, deserialize_from(ValueRef::from("Unit")).unwrap());
+    let val = ValueRef::from("Unit");
+    assert_eq!(Enum::Unit, deserialize_from::<Enum<'_>, &ValueRef<'_>>(&val).unwrap());
+    deserialize_from::<Enum<'_>, _>(ValueRef::from("Newtype")).unwrap_err();
+}
+
 #[test]
 fn pass_from_slice() {
     let buf = [0x93, 0xa4, 0x4a, 0x6f, 0x68, 0x6e, 0xa5, 0x53, 0x6d, 0x69, 0x74, 0x68, 0x2a];
@@ -108,6 +108,21 @@ fn pass_value_map() {
     test_decode(&[0x82, 0x00, 0xa2, 0x6c, 0x65, 0x01, 0xa4, 0x73, 0x68, 0x69, 0x74], val);
 }
 
+#[test]
+fn pass_vec_of_values() {
+    // [1, "a", bin [2, 3], fixext1 type 5 [4]]
+    let buf = [0x94, 0x01, 0xa1, 0x61, 0xc4, 0x02, 0x02, 0x03, 0xd4, 0x05, 0x04];
+
+    let vec: Vec<Value> = rmp_serde::from_slice(&buf).unwrap();
+    assert_eq!(
+        vec![Value::from(1), Value::from("a"), Value::Binary(vec![2, 3]), Value::Ext(5, vec![4])],
+        vec
+    );
+
+    let mut rd = &buf[..];
+    assert_eq!(vec, Vec::<Value>::deserialize(&mut rmp_serde::Deserializer::new(&mut rd)).unwrap());
+}
+
 #[test]
 fn pass_uint_from_value() {
     assert_eq!(i8::min_value(), from_value(Value::from(i8::min_value())).unwrap());
@@ -264,6 +279,21 @@ fn pass_enum_from_value() {
         from_value(Value::Array(vec![Value::from(3), Value::Array(vec![Value::from("John"), Value::from(42)])])).unwrap());
 }
 
+#[test]
+fn pass_unit_variant_from_bare_string() {
+    #[derive(Debug, PartialEq, Deserialize)]
+    enum Enum {
+        Unit,
+        Newtype(String),
+    }
+
+    assert_eq!(Enum::Unit, from_value(Value::from("Unit")).unwrap());
+    assert_eq!(Enum::Unit, from_value(Value::Array(vec![Value::from(0), Value::Array(vec![])])).unwrap());
+    // Only unit variants can be selected by a bare string.
+    from_value::<Enum>(Value::from("Newtype")).unwrap_err();
+    from_value::<Enum>(Value::from("Other")).unwrap_err();
+}
+
 #[test]
 fn pass_tuple_struct_from_ext() {
     #[derive(Debug, PartialEq)]
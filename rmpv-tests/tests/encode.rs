@@ -269,3 +269,50 @@ fn pass_ext_struct_to_value() {
         to_value(ExtStruct((5, ByteBuf::from(vec![10])))).unwrap()
     );
 }
+
+#[test]
+fn pass_value_map_as_struct() {
+    use rmp_serde::config::FixedWidthInt;
+
+    #[derive(Serialize)]
+    struct Inner {
+        x: u8,
+    }
+
+    #[derive(Serialize)]
+    struct Outer {
+        a: String,
+        b: Inner,
+        c: Vec<i32>,
+    }
+
+    let var = Outer { a: "le".into(), b: Inner { x: 42 }, c: vec![-1, -300] };
+    let val = Value::Map(vec![
+        (Value::from("a"), Value::from("le")),
+        (Value::from("b"), Value::Map(vec![(Value::from("x"), Value::from(42))])),
+        (Value::from("c"), Value::Array(vec![Value::from(-1), Value::from(-300)])),
+    ]);
+
+    fn encode<T: Serialize>(val: &T, named: bool) -> Vec<u8> {
+        let mut buf = Vec::new();
+        let se = Serializer::new(&mut buf)
+            .with_int_encoding(&FixedWidthInt)
+            .with_map_keys_sorted()
+            .with_maps_as_structs();
+        if named {
+            val.serialize(&mut se.with_struct_map()).unwrap();
+        } else {
+            val.serialize(&mut se.with_struct_tuple()).unwrap();
+        }
+        buf
+    }
+
+    let buf = encode(&var, true);
+    // {"a": "le", "b": {"x": uint 64 42}, ...}
+    assert_eq!([0x83, 0xa1, b'a', 0xa2, b'l', b'e', 0xa1, b'b', 0x81, 0xa1, b'x', 0xcf], buf[..12]);
+    assert_eq!(buf, encode(&val, true));
+    assert_eq!(rmp_serde::to_vec_named(&var).unwrap(), rmp_serde::to_vec(&val).unwrap());
+    // Maps are never written as arrays, which would lose their keys.
+    assert_ne!(encode(&var, false), encode(&val, false));
+    assert_eq!(buf, encode(&val, false));
+}
@@ -72,6 +72,20 @@ fn pass_sint() {
     test_round(i64::MAX, Value::from(i64::MAX));
 }
 
+#[test]
+fn pass_128_bit_ints_from_value_read_by_rmp_serde() {
+    for v in [0, u128::from(u64::MAX), u128::MAX] {
+        let mut buf = Vec::new();
+        rmpv::encode::write_value(&mut buf, &Value::from(v)).unwrap();
+        assert_eq!(v, rmp_serde::from_slice::<u128>(&buf).unwrap());
+    }
+    for v in [-1, i128::from(i64::MIN), i128::MIN, i128::MAX] {
+        let mut buf = Vec::new();
+        rmpv::encode::write_value(&mut buf, &Value::from(v)).unwrap();
+        assert_eq!(v, rmp_serde::from_slice::<i128>(&buf).unwrap());
+    }
+}
+
 #[test]
 fn pass_f32() {
     test_round(std::f32::MAX, Value::from(std::f32::MAX));
@@ -197,3 +211,77 @@ fn pass_derive_serde_ext_struct() {
         Value::Ext(2, vec![5]),
     );
 }
+
+#[test]
+fn pass_struct_with_bin_and_ext_to_value() {
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    #[serde(rename = "_ExtStruct")]
+    struct ExtStruct((i8, serde_bytes::ByteBuf));
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Record {
+        name: String,
+        #[serde(with = "serde_bytes")]
+        data: Vec<u8>,
+        ext: ExtStruct,
+        items: Vec<Option<ExtStruct>>,
+    }
+
+    let record = Record {
+        name: "le message".into(),
+        data: vec![0xde, 0xad],
+        ext: ExtStruct((1, ByteBuf::from(vec![0x01]))),
+        items: vec![None, Some(ExtStruct((-2, ByteBuf::from(vec![0x02, 0x03]))))],
+    };
+
+    let val = rmpv::to_value(&record).unwrap();
+    assert_eq!(Value::Array(vec![
+        Value::from("le message"),
+        Value::Binary(vec![0xde, 0xad]),
+        Value::Ext(1, vec![0x01]),
+        Value::Array(vec![Value::Nil, Value::Ext(-2, vec![0x02, 0x03])]),
+    ]), val);
+
+    assert_eq!(record, rmpv::from_value::<Record>(val).unwrap());
+}
+
+#[test]
+fn pass_json_large_integers() {
+    use rmpv::ValueRef;
+
+    let json = r#"{"id":18446744073709551615,"neg":-9223372036854775808,"f":1.5}"#;
+
+    // JSON -> `Value` keeps every integer that fits into 64 bits exactly.
+    let val: Value = serde_json::from_str(json).unwrap();
+    assert_eq!(Value::Map(vec![
+        (Value::from("id"), Value::from(u64::MAX)),
+        (Value::from("neg"), Value::from(i64::MIN)),
+        (Value::from("f"), Value::F64(1.5)),
+    ]), val);
+
+    // Numbers which would lose precision or become infinite are an error.
+    for json in ["18446744073709551616", "-9223372036854775809", "1e400"] {
+        assert!(serde_json::from_str::<Value>(json).is_err(), "{json}");
+        assert!(serde_json::from_str::<ValueRef<'_>>(json).is_err(), "{json}");
+    }
+
+    let mut buf = Vec::new();
+    rmpv::encode::write_value(&mut buf, &val["id"]).unwrap();
+    assert_eq!(vec![0xcf, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff], buf);
+
+    // `Value` -> `serde_json::Value` -> JSON and back.
+    let val = Value::Array(vec![Value::from(u64::MAX), Value::from(i64::MIN), Value::from(9_007_199_254_740_993u64)]);
+    let json = serde_json::to_value(&val).unwrap();
+    assert_eq!("[18446744073709551615,-9223372036854775808,9007199254740993]", json.to_string());
+    assert_eq!(val, serde_json::from_value::<Value>(json.clone()).unwrap());
+
+    let text = json.to_string();
+    let val_ref: ValueRef<'_> = serde_json::from_str(&text).unwrap();
+    assert_eq!(val.as_ref(), val_ref);
+
+    // A MessagePack map with the key `serde_json` uses for such numbers is kept as it is.
+    let map = Value::Map(vec![(Value::from("$serde_json::private::Number"), Value::from("5"))]);
+    let buf = rmp_serde::to_vec(&map).unwrap();
+    assert_eq!(map, rmp_serde::from_slice::<Value>(&buf).unwrap());
+    assert_eq!(map.as_ref(), rmp_serde::from_slice::<ValueRef<'_>>(&buf).unwrap());
+}
@@ -0,0 +1,228 @@
+//! Decoding of [`Value`]s from a [`tokio::io::AsyncRead`].
+//!
+//! Requires the `tokio` feature.
+//!
+//! MessagePack values do not carry their total length, so [`read_value_async`] reads a value node
+//! by node, each marker followed by the bytes it announces, into an internal buffer which is
+//! decoded once the value is complete. Nothing past the end of the value
+//! is read, so consecutive values can be read from the same stream.
+//!
+//! For streams of values framed by their length, [`FrameReader`] reads frames in the format of
+//! `rmp_serde::codec::MsgpackCodec` with its default settings: each value is preceded by its length
+//! in bytes as a 4-byte big-endian integer. Such frames are written by
+//! [`FrameWriter`](crate::async_encode::FrameWriter).
+//!
+//! # Examples
+//!
+//! ```
+//! # tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(async {
+//! use rmpv::Value;
+//! use rmpv::async_decode::read_value_async;
+//!
+//! // 1, [2, "a"]
+//! let buf = [0x01, 0x92, 0x02, 0xa1, 0x61];
+//! let mut rd = &buf[..];
+//!
+//! assert_eq!(Value::from(1), read_value_async(&mut rd).await.unwrap());
+//! assert_eq!(Value::Array(vec![Value::from(2), Value::from("a")]), read_value_async(&mut rd).await.unwrap());
+//! assert!(rd.is_empty());
+//! # });
+//! ```
+
+use std::io::{self, ErrorKind};
+
+use rmp::Marker;
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+use crate::decode::{self, Error};
+use crate::Value;
+
+/// The largest number of bytes read in a single step, so that the length announced by the input is
+/// not trusted for allocating the buffer in one go.
+const READ_CHUNK: usize = 64 * 1024;
+
+/// Attempts to read a single [`Value`] from the given asynchronous reader.
+///
+/// # Errors
+///
+/// This function returns the same errors as [`read_value`](decode::read_value).
+pub async fn read_value_async<R>(rd: &mut R) -> Result<Value, Error>
+where
+    R: AsyncRead + Unpin,
+{
+    let mut buf = Vec::new();
+    // The number of values still to be read at each level of nesting.
+    let mut pending = vec![1_u64];
+
+    while let Some(left) = pending.last_mut() {
+        if *left == 0 {
+            pending.pop();
+            continue;
+        }
+        *left -= 1;
+        // Deeper values are rejected by decoding, as is a reserved marker, so there is no need to
+        // read any further.
+        if pending.len() > decode::MAX_DEPTH {
+            break;
+        }
+
+        let marker = Marker::from_u8(read_marker(rd, &mut buf).await?);
+        let (len_size, extra) = match marker {
+            Marker::FixArray(len) => {
+                pending.push(len.into());
+                continue;
+            }
+            Marker::FixMap(len) => {
+                pending.push(u64::from(len) * 2);
+                continue;
+            }
+            Marker::FixStr(len) => {
+                read_data(rd, &mut buf, len.into()).await?;
+                continue;
+            }
+            Marker::Reserved => break,
+            Marker::Str8 | Marker::Bin8 => (1, 0),
+            Marker::Str16 | Marker::Bin16 | Marker::Array16 | Marker::Map16 => (2, 0),
+            Marker::Str32 | Marker::Bin32 | Marker::Array32 | Marker::Map32 => (4, 0),
+            Marker::Ext8 => (1, 1),
+            Marker::Ext16 => (2, 1),
+            Marker::Ext32 => (4, 1),
+            marker => {
+                read_data(rd, &mut buf, fixed_size(marker)).await?;
+                continue;
+            }
+        };
+
+        let start = buf.len();
+        read_data(rd, &mut buf, len_size).await?;
+        let len = buf[start..].iter().fold(0, |len, &b| len << 8 | u64::from(b));
+        match marker {
+            Marker::Array16 | Marker::Array32 => pending.push(len),
+            Marker::Map16 | Marker::Map32 => pending.push(len * 2),
+            _ => read_data(rd, &mut buf, len + extra).await?,
+        }
+    }
+
+    decode::read_value(&mut &buf[..])
+}
+
+/// Returns the number of bytes following a marker of a fixed-size value.
+fn fixed_size(marker: Marker) -> u64 {
+    match marker {
+        Marker::U8 | Marker::I8 => 1,
+        Marker::U16 | Marker::I16 => 2,
+        Marker::U32 | Marker::I32 | Marker::F32 => 4,
+        Marker::U64 | Marker::I64 | Marker::F64 => 8,
+        Marker::FixExt1 => 1 + 1,
+        Marker::FixExt2 => 1 + 2,
+        Marker::FixExt4 => 1 + 4,
+        Marker::FixExt8 => 1 + 8,
+        Marker::FixExt16 => 1 + 16,
+        _ => 0,
+    }
+}
+
+async fn read_marker<R: AsyncRead + Unpin>(rd: &mut R, buf: &mut Vec<u8>) -> Result<u8, Error> {
+    let byte = rd.read_u8().await.map_err(Error::InvalidMarkerRead)?;
+    buf.push(byte);
+    Ok(byte)
+}
+
+async fn read_data<R: AsyncRead + Unpin>(rd: &mut R, buf: &mut Vec<u8>, len: u64) -> Result<(), Error> {
+    let mut left = len;
+    while left > 0 {
+        let start = buf.len();
+        let step = left.min(READ_CHUNK as u64) as usize;
+        buf.resize(start + step, 0);
+        rd.read_exact(&mut buf[start..]).await.map_err(Error::InvalidDataRead)?;
+        left -= step as u64;
+    }
+    Ok(())
+}
+
+/// Reads [`Value`]s framed by their length from an asynchronous reader, see the
+/// [module documentation](self).
+#[derive(Debug)]
+pub struct FrameReader<R> {
+    rd: R,
+    max_frame_len: usize,
+}
+
+impl<R: AsyncRead + Unpin> FrameReader<R> {
+    /// The default maximum frame length, the same as the one of `rmp_serde::codec::MsgpackCodec`.
+    pub const DEFAULT_MAX_FRAME_LEN: usize = 8 * 1024 * 1024;
+
+    /// Creates a frame reader with the default maximum frame length.
+    #[inline]
+    pub fn new(rd: R) -> Self {
+        Self { rd, max_frame_len: Self::DEFAULT_MAX_FRAME_LEN }
+    }
+
+    /// Sets the maximum length of a frame in bytes, excluding its length prefix.
+    #[inline]
+    #[must_use]
+    pub fn with_max_frame_len(mut self, len: usize) -> Self {
+        self.max_frame_len = len;
+        self
+    }
+
+    /// Returns a reference to the underlying reader.
+    #[inline]
+    pub fn get_ref(&self) -> &R {
+        &self.rd
+    }
+
+    /// Returns a mutable reference to the underlying reader.
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.rd
+    }
+
+    /// Consumes this frame reader, returning the underlying reader.
+    #[inline]
+    pub fn into_inner(self) -> R {
+        self.rd
+    }
+
+    /// Reads the next frame, returning `None` if the input ends before it starts.
+    ///
+    /// # Errors
+    ///
+    /// This function returns [`Error`] on any I/O error, and on a frame that is truncated, longer
+    /// than the maximum frame length, or not exactly one valid value. A frame that is too long is
+    /// rejected before any of it is read.
+    pub async fn read_frame(&mut self) -> Result<Option<Value>, Error> {
+        let mut prefix = [0; 4];
+        let mut filled = 0;
+        while filled < prefix.len() {
+            match self.rd.read(&mut prefix[filled..]).await {
+                Ok(0) if filled == 0 => return Ok(None),
+                Ok(0) => return Err(Error::InvalidMarkerRead(ErrorKind::UnexpectedEof.into())),
+                Ok(n) => filled += n,
+                Err(err) if err.kind() == ErrorKind::Interrupted => {}
+                Err(err) => return Err(Error::InvalidMarkerRead(err)),
+            }
+        }
+
+        let len = u32::from_be_bytes(prefix) as usize;
+        if len > self.max_frame_len {
+            return Err(Error::InvalidDataRead(io::Error::new(
+                ErrorKind::InvalidData,
+                format!("frame of {len} bytes is too large"),
+            )));
+        }
+
+        let mut frame = vec![0; len];
+        self.rd.read_exact(&mut frame).await.map_err(Error::InvalidDataRead)?;
+
+        let mut rd = &frame[..];
+        let val = decode::read_value(&mut rd)?;
+        if !rd.is_empty() {
+            return Err(Error::InvalidDataRead(io::Error::new(
+                ErrorKind::InvalidData,
+                format!("{} trailing bytes after the value in a frame", rd.len()),
+            )));
+        }
+        Ok(Some(val))
+    }
+}
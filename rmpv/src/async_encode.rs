@@ -0,0 +1,102 @@
+//! Encoding of [`Value`]s into a [`tokio::io::AsyncWrite`].
+//!
+//! Requires the `tokio` feature.
+//!
+//! Each value is encoded into a buffer first, and then written with a single
+//! [`write_all`](tokio::io::AsyncWriteExt::write_all), rather than with one awaited write per
+//! marker. The writer is not flushed, so a buffered writer has to be flushed by the caller.
+//!
+//! [`FrameWriter`] writes the frames read by [`FrameReader`](crate::async_decode::FrameReader).
+//!
+//! # Examples
+//!
+//! ```
+//! # tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(async {
+//! use rmpv::Value;
+//! use rmpv::async_encode::write_value_async;
+//!
+//! let mut buf = Vec::new();
+//! write_value_async(&mut buf, &Value::Array(vec![Value::from(2), Value::from("a")])).await.unwrap();
+//!
+//! assert_eq!(vec![0x92, 0x02, 0xa1, 0x61], buf);
+//! # });
+//! ```
+
+use std::io::{self, ErrorKind};
+
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+
+use crate::encode::{self, Error};
+use crate::Value;
+
+/// Encodes the value and writes it to the given asynchronous writer.
+///
+/// # Errors
+///
+/// This function returns [`Error::InvalidDataWrite`] on any I/O error occurred while writing.
+pub async fn write_value_async<W>(wr: &mut W, val: &Value) -> Result<(), Error>
+where
+    W: AsyncWrite + Unpin,
+{
+    let mut buf = Vec::new();
+    encode::write_value(&mut buf, val)?;
+    wr.write_all(&buf).await.map_err(Error::InvalidDataWrite)
+}
+
+/// Writes [`Value`]s framed by their length to an asynchronous writer, in the format read by
+/// [`FrameReader`](crate::async_decode::FrameReader).
+#[derive(Debug)]
+pub struct FrameWriter<W> {
+    wr: W,
+}
+
+impl<W: AsyncWrite + Unpin> FrameWriter<W> {
+    /// Creates a frame writer.
+    #[inline]
+    pub fn new(wr: W) -> Self {
+        Self { wr }
+    }
+
+    /// Returns a reference to the underlying writer.
+    #[inline]
+    pub fn get_ref(&self) -> &W {
+        &self.wr
+    }
+
+    /// Returns a mutable reference to the underlying writer.
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.wr
+    }
+
+    /// Consumes this frame writer, returning the underlying writer.
+    #[inline]
+    pub fn into_inner(self) -> W {
+        self.wr
+    }
+
+    /// Writes the value as a frame, preceded by its length.
+    ///
+    /// # Errors
+    ///
+    /// This function returns [`Error::InvalidDataWrite`] on any I/O error, and if the encoded value
+    /// is longer than the length prefix can represent.
+    pub async fn write_frame(&mut self, val: &Value) -> Result<(), Error> {
+        let mut buf = vec![0; 4];
+        encode::write_value(&mut buf, val)?;
+        let len = u32::try_from(buf.len() - 4).map_err(|_| {
+            Error::InvalidDataWrite(io::Error::new(ErrorKind::InvalidInput, "frame is too large"))
+        })?;
+        buf[..4].copy_from_slice(&len.to_be_bytes());
+        self.wr.write_all(&buf).await.map_err(Error::InvalidDataWrite)
+    }
+
+    /// Flushes the underlying writer.
+    ///
+    /// # Errors
+    ///
+    /// This function returns [`Error::InvalidDataWrite`] on any I/O error.
+    pub async fn flush(&mut self) -> Result<(), Error> {
+        self.wr.flush().await.map_err(Error::InvalidDataWrite)
+    }
+}
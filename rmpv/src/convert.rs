@@ -0,0 +1,143 @@
+//! Conversions of a [`Value`] into collections of other types, see [`Value::try_into_vec`],
+//! [`Value::try_into_option`] and the `TryFrom<Value>` implementation of `HashMap`.
+
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::fmt::{self, Debug, Display};
+use std::hash::{BuildHasher, Hash};
+
+use crate::Value;
+
+/// An error returned when converting a [`Value`] into a collection, telling which part of it
+/// failed.
+///
+/// `E` is the error of the elements of an array or of the values of a map, and `K` the one of
+/// the keys of a map.
+///
+/// # Examples
+///
+/// ```
+/// use rmpv::{CollectionError, Value};
+///
+/// let val = Value::Array(vec![Value::from(1), Value::from("two")]);
+///
+/// assert_eq!(Err(CollectionError::Element(1, Value::from("two"))), val.try_into_vec::<u64>());
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub enum CollectionError<E, K = E> {
+    /// The value is not an array, or not a map, and is returned.
+    Type(Value),
+    /// The array element, or the value of the map entry, at the given index failed to convert.
+    Element(usize, E),
+    /// The key of the map entry at the given index failed to convert.
+    Key(usize, K),
+}
+
+impl<E: Display, K: Display> Display for CollectionError<E, K> {
+    #[cold]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        match *self {
+            Self::Type(ref val) => write!(f, "expected a collection, found {}", val.type_name()),
+            Self::Element(idx, ref err) => write!(f, "invalid element at index {idx}: {err}"),
+            Self::Key(idx, ref err) => write!(f, "invalid key at index {idx}: {err}"),
+        }
+    }
+}
+
+impl<E: Debug + Display, K: Debug + Display> std::error::Error for CollectionError<E, K> {}
+
+impl Value {
+    /// If the `Value` is an Array, converts each of its elements into `T`.
+    ///
+    /// This is a method rather than a `TryFrom` implementation, as it would overlap with the
+    /// existing conversions into `Vec<Value>`, `Vec<u8>` and `Vec<(Value, Value)>`, which take
+    /// arrays, binaries and maps respectively. Elements can be of any type converted from a
+    /// `Value` with `TryFrom`, including `HashMap` and `Vec<Value>`, but not of the types only
+    /// converted by this method or [`try_into_option`](Self::try_into_option).
+    ///
+    /// # Errors
+    ///
+    /// Returns `CollectionError::Type` with the original `Value` if it is not an Array, and
+    /// `CollectionError::Element` with the index and the error of the first element which can not
+    /// be converted.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmpv::Value;
+    ///
+    /// let val = Value::Array(vec![Value::from("a"), Value::from("b")]);
+    ///
+    /// assert_eq!(vec!["a".to_owned(), "b".to_owned()], val.try_into_vec::<String>().unwrap());
+    /// ```
+    pub fn try_into_vec<T: TryFrom<Self>>(self) -> Result<Vec<T>, CollectionError<T::Error>> {
+        let vec = self.into_array().map_err(CollectionError::Type)?;
+        vec.into_iter()
+            .enumerate()
+            .map(|(idx, val)| T::try_from(val).map_err(|err| CollectionError::Element(idx, err)))
+            .collect()
+    }
+
+    /// Converts the `Value` into `None` if it is Nil, and into `T` otherwise.
+    ///
+    /// This is a method rather than a `TryFrom` implementation, as it would overlap with the
+    /// standard conversion of a `Value` into an `Option<Value>`, which is always `Some`.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of the conversion into `T`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmpv::Value;
+    ///
+    /// assert_eq!(Ok(None), Value::Nil.try_into_option::<u64>());
+    /// assert_eq!(Ok(Some(42)), Value::from(42).try_into_option::<u64>());
+    /// assert_eq!(Err(Value::from("a")), Value::from("a").try_into_option::<u64>());
+    /// ```
+    pub fn try_into_option<T: TryFrom<Self>>(self) -> Result<Option<T>, T::Error> {
+        match self {
+            Self::Nil => Ok(None),
+            val => T::try_from(val).map(Some),
+        }
+    }
+}
+
+/// Converts a Map, keeping the last of the entries with equal keys.
+///
+/// # Examples
+///
+/// ```
+/// use std::collections::HashMap;
+/// use std::convert::TryFrom;
+///
+/// use rmpv::{CollectionError, Value};
+///
+/// let val = Value::Map(vec![(Value::from("a"), Value::from(1)), (Value::from("b"), Value::from(2))]);
+/// let map = HashMap::<String, u64>::try_from(val).unwrap();
+/// assert_eq!(Some(&2), map.get("b"));
+///
+/// let val = Value::Map(vec![(Value::from("a"), Value::from(1)), (Value::from(2), Value::from(2))]);
+/// assert_eq!(Err(CollectionError::Key(1, Value::from(2))), HashMap::<String, u64>::try_from(val));
+/// ```
+impl<K, V, S> TryFrom<Value> for HashMap<K, V, S>
+where
+    K: TryFrom<Value> + Eq + Hash,
+    V: TryFrom<Value>,
+    S: BuildHasher + Default,
+{
+    type Error = CollectionError<V::Error, K::Error>;
+
+    fn try_from(val: Value) -> Result<Self, Self::Error> {
+        let map = val.into_map().map_err(CollectionError::Type)?;
+        map.into_iter()
+            .enumerate()
+            .map(|(idx, (key, val))| {
+                let key = K::try_from(key).map_err(|err| CollectionError::Key(idx, err))?;
+                let val = V::try_from(val).map_err(|err| CollectionError::Element(idx, err))?;
+                Ok((key, val))
+            })
+            .collect()
+    }
+}
@@ -1,3 +1,16 @@
+//! Decoding of MessagePack data into [`Value`](crate::Value) and [`ValueRef`](crate::ValueRef).
+//!
+//! # Allocation
+//!
+//! A decoded [`Value`](crate::Value) tree always allocates its strings, binaries, arrays and maps
+//! with the global allocator. `Value` is an owned type built on the standard `String` and `Vec`,
+//! so decoding into an arena, such as the collections of `bumpalo`, would need a separate tree
+//! type with arena-backed nodes, which this crate does not provide.
+//!
+//! When the input is already in memory, [`read_value_ref`] is the cheaper alternative: strings,
+//! binaries and ext data are borrowed from the input buffer, so the only allocations left are the
+//! vectors backing arrays and maps.
+
 use std::error;
 use std::fmt::{self, Display, Formatter};
 use std::io::{self, ErrorKind};
@@ -7,14 +20,19 @@ use rmp::decode::{MarkerReadError, ValueReadError};
 pub mod value;
 pub mod value_ref;
 
-pub use self::value::{read_value, read_value_with_max_depth};
+pub use self::value::{
+    read_array_iter, read_array_prefix, read_map_iter, read_value, read_value_opt, read_value_with,
+    read_value_with_max_depth, ArrayIter, ExtHook, MapIter, ReadOptions,
+};
 pub use self::value_ref::{read_value_ref, read_value_ref_with_max_depth};
+pub use crate::preserve::read_value_preserving;
 
 /// The maximum recursion depth before [`Error::DepthLimitExceeded`] is returned.
 pub const MAX_DEPTH: usize = 1024;
 
 /// This type represents all possible errors that can occur when deserializing a value.
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum Error {
     /// Error while reading marker byte.
     InvalidMarkerRead(io::Error),
@@ -22,6 +40,10 @@ pub enum Error {
     InvalidDataRead(io::Error),
     /// The depth limit [`MAX_DEPTH`] was exceeded.
     DepthLimitExceeded,
+    /// The never used marker `0xc1` was found, which means that the data is corrupt.
+    ReservedMarker,
+    /// The value is encoded in more bytes than [`ReadOptions::max_value_bytes`] allows.
+    ByteLimitExceeded(usize),
 }
 
 #[inline]
@@ -36,7 +58,9 @@ impl Error {
         match *self {
             Self::InvalidMarkerRead(ref err) => err.kind(),
             Self::InvalidDataRead(ref err) => err.kind(),
-            Self::DepthLimitExceeded => ErrorKind::Unsupported,
+            Self::DepthLimitExceeded |
+            Self::ByteLimitExceeded(..) => ErrorKind::Unsupported,
+            Self::ReservedMarker => ErrorKind::InvalidData,
         }
     }
 }
@@ -47,7 +71,9 @@ impl error::Error for Error {
         match *self {
             Self::InvalidMarkerRead(ref err) => Some(err),
             Self::InvalidDataRead(ref err) => Some(err),
-            Self::DepthLimitExceeded => None,
+            Self::DepthLimitExceeded |
+            Self::ReservedMarker |
+            Self::ByteLimitExceeded(..) => None,
         }
     }
 }
@@ -65,6 +91,12 @@ impl Display for Error {
             Self::DepthLimitExceeded => {
                 write!(fmt, "depth limit exceeded")
             }
+            Self::ReservedMarker => {
+                write!(fmt, "reserved marker 0xc1 found")
+            }
+            Self::ByteLimitExceeded(limit) => {
+                write!(fmt, "value exceeds the limit of {limit} bytes")
+            }
         }
     }
 }
@@ -95,7 +127,9 @@ impl From<Error> for io::Error {
         match val {
             Error::InvalidMarkerRead(err) |
             Error::InvalidDataRead(err) => err,
-            Error::DepthLimitExceeded => Self::new(val.kind(), val),
+            Error::DepthLimitExceeded |
+            Error::ReservedMarker |
+            Error::ByteLimitExceeded(..) => Self::new(val.kind(), val),
         }
     }
 }
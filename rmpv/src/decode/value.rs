@@ -7,10 +7,13 @@ use rmp::Marker;
 use super::Error;
 use crate::{Utf8String, Value};
 
+/// The ext hook threaded through the decoding functions, either an [`ExtHook`] or a closure.
+pub(crate) type ExtDecoder<'a> = &'a dyn Fn(i8, &[u8]) -> Option<Value>;
+
 // See https://github.com/3Hren/msgpack-rust/issues/151
 const PREALLOC_MAX: usize = 64 * 1024; // 64 KiB
 
-fn read_array_data<R: Read>(rd: &mut R, mut len: usize, depth: u16) -> Result<Vec<Value>, Error> {
+fn read_array_data<R: Read>(rd: &mut R, mut len: usize, depth: u16, ext_hook: Option<ExtDecoder<'_>>) -> Result<Vec<Value>, Error> {
     let depth = super::decrement_depth(depth)?;
 
     // Note: Do not preallocate a Vec of size `len`.
@@ -18,14 +21,14 @@ fn read_array_data<R: Read>(rd: &mut R, mut len: usize, depth: u16) -> Result<Ve
     let mut vec = Vec::new();
 
     while len > 0 {
-        vec.push(read_value_inner(rd, depth)?);
+        vec.push(read_value_inner(rd, depth, ext_hook)?);
         len -= 1;
     }
 
     Ok(vec)
 }
 
-fn read_map_data<R: Read>(rd: &mut R, mut len: usize, depth: u16) -> Result<Vec<(Value, Value)>, Error> {
+fn read_map_data<R: Read>(rd: &mut R, mut len: usize, depth: u16, ext_hook: Option<ExtDecoder<'_>>) -> Result<Vec<(Value, Value)>, Error> {
     let depth = super::decrement_depth(depth)?;
 
     // Note: Do not preallocate a Vec of size `len`.
@@ -33,7 +36,7 @@ fn read_map_data<R: Read>(rd: &mut R, mut len: usize, depth: u16) -> Result<Vec<
     let mut vec = Vec::new();
 
     while len > 0 {
-        vec.push((read_value_inner(rd, depth)?, read_value_inner(rd, depth)?));
+        vec.push((read_value_inner(rd, depth, ext_hook)?, read_value_inner(rd, depth, ext_hook)?));
         len -= 1;
     }
 
@@ -79,8 +82,16 @@ fn read_ext_body<R: Read>(rd: &mut R, len: usize, depth: u16) -> Result<(i8, Vec
     Ok((ty, vec))
 }
 
+#[inline]
+fn ext_value(ty: i8, data: Vec<u8>, ext_hook: Option<ExtDecoder<'_>>) -> Value {
+    match ext_hook.and_then(|hook| hook(ty, &data)) {
+        Some(val) => val,
+        None => Value::Ext(ty, data),
+    }
+}
+
 #[inline(never)]
-fn read_value_inner<R>(rd: &mut R, depth: u16) -> Result<Value, Error> where R: Read {
+pub(crate) fn read_value_inner<R>(rd: &mut R, depth: u16, ext_hook: Option<ExtDecoder<'_>>) -> Result<Value, Error> where R: Read {
     let depth = super::decrement_depth(depth)?;
     let val = match read_marker(rd)? {
         Marker::Null => Value::Nil,
@@ -118,31 +129,31 @@ fn read_value_inner<R>(rd: &mut R, depth: u16) -> Result<Value, Error> where R:
             Value::String(res)
         }
         Marker::FixArray(len) => {
-            let vec = read_array_data(rd, len as usize, depth)?;
+            let vec = read_array_data(rd, len as usize, depth, ext_hook)?;
             Value::Array(vec)
         }
         Marker::Array16 => {
             let len = rd.read_data_u16()?;
-            let vec = read_array_data(rd, len as usize, depth)?;
+            let vec = read_array_data(rd, len as usize, depth, ext_hook)?;
             Value::Array(vec)
         }
         Marker::Array32 => {
             let len = rd.read_data_u32()?;
-            let vec = read_array_data(rd, len as usize, depth)?;
+            let vec = read_array_data(rd, len as usize, depth, ext_hook)?;
             Value::Array(vec)
         }
         Marker::FixMap(len) => {
-            let map = read_map_data(rd, len as usize, depth)?;
+            let map = read_map_data(rd, len as usize, depth, ext_hook)?;
             Value::Map(map)
         }
         Marker::Map16 => {
             let len = rd.read_data_u16()?;
-            let map = read_map_data(rd, len as usize, depth)?;
+            let map = read_map_data(rd, len as usize, depth, ext_hook)?;
             Value::Map(map)
         }
         Marker::Map32 => {
             let len = rd.read_data_u32()?;
-            let map = read_map_data(rd, len as usize, depth)?;
+            let map = read_map_data(rd, len as usize, depth, ext_hook)?;
             Value::Map(map)
         }
         Marker::Bin8 => {
@@ -163,44 +174,44 @@ fn read_value_inner<R>(rd: &mut R, depth: u16) -> Result<Value, Error> where R:
         Marker::FixExt1 => {
             let len = 1_usize;
             let (ty, vec) = read_ext_body(rd, len, depth)?;
-            Value::Ext(ty, vec)
+            ext_value(ty, vec, ext_hook)
         }
         Marker::FixExt2 => {
             let len = 2_usize;
             let (ty, vec) = read_ext_body(rd, len, depth)?;
-            Value::Ext(ty, vec)
+            ext_value(ty, vec, ext_hook)
         }
         Marker::FixExt4 => {
             let len = 4_usize;
             let (ty, vec) = read_ext_body(rd, len, depth)?;
-            Value::Ext(ty, vec)
+            ext_value(ty, vec, ext_hook)
         }
         Marker::FixExt8 => {
             let len = 8_usize;
             let (ty, vec) = read_ext_body(rd, len, depth)?;
-            Value::Ext(ty, vec)
+            ext_value(ty, vec, ext_hook)
         }
         Marker::FixExt16 => {
             let len = 16_usize;
             let (ty, vec) = read_ext_body(rd, len, depth)?;
-            Value::Ext(ty, vec)
+            ext_value(ty, vec, ext_hook)
         }
         Marker::Ext8 => {
             let len = rd.read_data_u8()? as usize;
             let (ty, vec) = read_ext_body(rd, len, depth)?;
-            Value::Ext(ty, vec)
+            ext_value(ty, vec, ext_hook)
         }
         Marker::Ext16 => {
             let len = rd.read_data_u16()? as usize;
             let (ty, vec) = read_ext_body(rd, len, depth)?;
-            Value::Ext(ty, vec)
+            ext_value(ty, vec, ext_hook)
         }
         Marker::Ext32 => {
             let len = rd.read_data_u32()? as usize;
             let (ty, vec) = read_ext_body(rd, len, depth)?;
-            Value::Ext(ty, vec)
+            ext_value(ty, vec, ext_hook)
         }
-        Marker::Reserved => Value::Nil,
+        Marker::Reserved => return Err(Error::ReservedMarker),
     };
 
     Ok(val)
@@ -221,7 +232,51 @@ fn read_value_inner<R>(rd: &mut R, depth: u16) -> Result<Value, Error> where R:
 pub fn read_value<R>(rd: &mut R) -> Result<Value, Error>
     where R: Read
 {
-    read_value_inner(rd, super::MAX_DEPTH as _)
+    read_value_inner(rd, super::MAX_DEPTH as _, None)
+}
+
+/// Attempts to read a [`Value`] from the given reader, returning `None` if the reader is at the
+/// end of the input.
+///
+/// This is meant for reading a stream of consecutive values: the end of the input before the
+/// first byte of a value means that the stream ended normally, while the end of the input in the
+/// middle of a value means that the last value was truncated, and is reported as an error.
+///
+/// # Errors
+///
+/// This function returns [`Error`] on any I/O error while either reading or decoding a [`Value`],
+/// like [`read_value`] does. A value cut short by the end of the input is reported as an error of
+/// kind [`ErrorKind::UnexpectedEof`](io::ErrorKind).
+///
+/// # Examples
+///
+/// ```
+/// use rmpv::Value;
+/// use rmpv::decode::read_value_opt;
+///
+/// // 1, "a", followed by a truncated [2, 3]
+/// let buf = [0x01, 0xa1, 0x61, 0x92, 0x02];
+/// let mut rd = &buf[..];
+///
+/// assert_eq!(Some(Value::from(1)), read_value_opt(&mut rd).unwrap());
+/// assert_eq!(Some(Value::from("a")), read_value_opt(&mut rd).unwrap());
+/// assert!(read_value_opt(&mut rd).is_err());
+/// assert_eq!(None, read_value_opt(&mut &[][..]).unwrap());
+/// ```
+pub fn read_value_opt<R>(rd: &mut R) -> Result<Option<Value>, Error>
+    where R: Read
+{
+    let mut marker = [0; 1];
+    loop {
+        match rd.read(&mut marker) {
+            Ok(0) => return Ok(None),
+            Ok(..) => break,
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+            Err(err) => return Err(Error::InvalidMarkerRead(err)),
+        }
+    }
+
+    read_value_inner(&mut (&marker[..]).chain(rd), super::MAX_DEPTH as _, None).map(Some)
 }
 
 /// Attempts to read bytes from the given reader and interpret them as a [`Value`].
@@ -239,5 +294,317 @@ pub fn read_value<R>(rd: &mut R) -> Result<Value, Error>
 pub fn read_value_with_max_depth<R>(rd: &mut R, max_depth: usize) -> Result<Value, Error>
     where R: Read
 {
-    read_value_inner(rd, max_depth.min(u16::MAX as usize) as u16)
+    read_value_inner(rd, max_depth.min(u16::MAX as usize) as u16, None)
+}
+
+/// Attempts to read an array from the given reader, decoding at most its first `n` elements.
+///
+/// This is meant for previewing large arrays: decoding stops after `n` elements, without reading
+/// the rest of the array.
+///
+/// On success the reader is positioned right after the last decoded element. If the array has
+/// more than `n` elements, the remaining ones are left unread, so the reader is in the middle of
+/// the array and the next value read from it is element `n`. If the array has `n` elements or
+/// less, all of them are returned and the reader is positioned after the whole array. Use
+/// [`rmp::decode::read_array_len`] and [`read_value`] directly when the remaining elements have to
+/// be read or skipped afterwards.
+///
+/// # Errors
+///
+/// This function returns [`Error`] if the value is not an array, or on any I/O error while reading
+/// or decoding the elements, including an unexpected end of input before `n` elements or the end
+/// of the array have been read.
+///
+/// # Examples
+///
+/// ```
+/// use rmpv::Value;
+/// use rmpv::decode::{read_array_prefix, read_value};
+///
+/// // [1, 2, 3]
+/// let buf = [0x93, 0x01, 0x02, 0x03];
+/// let mut rd = &buf[..];
+///
+/// assert_eq!(vec![Value::from(1), Value::from(2)], read_array_prefix(&mut rd, 2).unwrap());
+/// assert_eq!(Value::from(3), read_value(&mut rd).unwrap());
+/// ```
+pub fn read_array_prefix<R>(rd: &mut R, n: usize) -> Result<Vec<Value>, Error>
+    where R: Read
+{
+    let len = rmp::decode::read_array_len(rd)? as usize;
+    read_array_data(rd, min(len, n), super::MAX_DEPTH as _, None)
+}
+
+/// Attempts to read the header of an array from the given reader, returning an iterator which
+/// decodes its elements one at a time.
+///
+/// This is meant for processing huge arrays in constant memory: each element is read from the
+/// reader only when the iterator is advanced, and can be dropped before the next one is read.
+/// Each element is decoded like [`read_value`] does.
+///
+/// # Errors
+///
+/// This function returns [`Error`] if the value is not an array, or on any I/O error while
+/// reading its header. The iterator yields an [`Error`] for an element which can not be read, for
+/// example because the input ends before it is complete, and stops after it.
+///
+/// # Examples
+///
+/// ```
+/// use rmpv::Value;
+/// use rmpv::decode::read_array_iter;
+///
+/// // [1, "a"]
+/// let buf = [0x92, 0x01, 0xa1, 0x61];
+/// let mut rd = &buf[..];
+/// let mut iter = read_array_iter(&mut rd).unwrap();
+///
+/// assert_eq!(Value::from(1), iter.next().unwrap().unwrap());
+/// assert_eq!(Value::from("a"), iter.next().unwrap().unwrap());
+/// assert!(iter.next().is_none());
+/// ```
+pub fn read_array_iter<R>(rd: &mut R) -> Result<ArrayIter<'_, R>, Error>
+    where R: Read
+{
+    let left = rmp::decode::read_array_len(rd)?;
+    Ok(ArrayIter { rd, left })
+}
+
+/// Attempts to read the header of a map from the given reader, returning an iterator which
+/// decodes its entries one at a time.
+///
+/// This is the equivalent of [`read_array_iter`] for maps, each key and value being decoded like
+/// [`read_value`] does.
+///
+/// # Errors
+///
+/// This function returns [`Error`] if the value is not a map, or on any I/O error while reading
+/// its header. The iterator yields an [`Error`] for an entry which can not be read, including one
+/// whose key is complete but whose value is not, and stops after it.
+///
+/// # Examples
+///
+/// ```
+/// use rmpv::Value;
+/// use rmpv::decode::read_map_iter;
+///
+/// // {"a": 1}, followed by a truncated {"b": 2}
+/// let buf = [0x81, 0xa1, 0x61, 0x01, 0x81, 0xa1, 0x62];
+/// let mut rd = &buf[..];
+///
+/// let entries: Vec<_> = read_map_iter(&mut rd).unwrap().collect::<Result<_, _>>().unwrap();
+/// assert_eq!(vec![(Value::from("a"), Value::from(1))], entries);
+///
+/// let mut iter = read_map_iter(&mut rd).unwrap();
+/// assert!(iter.next().unwrap().is_err());
+/// assert!(iter.next().is_none());
+/// ```
+pub fn read_map_iter<R>(rd: &mut R) -> Result<MapIter<'_, R>, Error>
+    where R: Read
+{
+    let left = rmp::decode::read_map_len(rd)?;
+    Ok(MapIter { rd, left })
+}
+
+/// An iterator over the elements of an array read from a reader, see [`read_array_iter`].
+#[derive(Debug)]
+pub struct ArrayIter<'a, R> {
+    rd: &'a mut R,
+    left: u32,
+}
+
+impl<R: Read> Iterator for ArrayIter<'_, R> {
+    type Item = Result<Value, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.left == 0 {
+            return None;
+        }
+        self.left -= 1;
+        let res = read_value(self.rd);
+        if res.is_err() {
+            self.left = 0;
+        }
+        Some(res)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.left as usize))
+    }
+}
+
+/// An iterator over the entries of a map read from a reader, see [`read_map_iter`].
+#[derive(Debug)]
+pub struct MapIter<'a, R> {
+    rd: &'a mut R,
+    left: u32,
+}
+
+impl<R: Read> Iterator for MapIter<'_, R> {
+    type Item = Result<(Value, Value), Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.left == 0 {
+            return None;
+        }
+        self.left -= 1;
+        let res = read_value(self.rd).and_then(|key| Ok((key, read_value(self.rd)?)));
+        if res.is_err() {
+            self.left = 0;
+        }
+        Some(res)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.left as usize))
+    }
+}
+
+/// A function that interprets the payload of an ext value, see [`ReadOptions::ext_hook`].
+pub type ExtHook = fn(i8, &[u8]) -> Option<Value>;
+
+/// Options controlling how [`read_value_with`] decodes a [`Value`].
+///
+/// The default options decode a [`Value`] exactly like [`read_value`] does.
+#[derive(Clone, Copy, Debug)]
+#[non_exhaustive]
+pub struct ReadOptions {
+    /// The maximum recursion depth, see [`read_value_with_max_depth`].
+    pub max_depth: usize,
+    /// When set, this function is called with the type and the data of every ext value, including
+    /// nested ones. If it returns a [`Value`], that value is used in place of the ext, otherwise the
+    /// ext is decoded as [`Value::Ext`].
+    pub ext_hook: Option<ExtHook>,
+    /// When set, the maximum number of bytes the value may be encoded in, markers, lengths and
+    /// nested values included. Reading fails with [`Error::ByteLimitExceeded`] as soon as more
+    /// bytes are needed, without reading them, so a value made of many small elements is stopped
+    /// in the middle just like a long string.
+    ///
+    /// This complements [`max_depth`](Self::max_depth) and the limited preallocation with a bound
+    /// on the total work done for each value, whatever the size of the input.
+    pub max_value_bytes: Option<usize>,
+}
+
+impl ReadOptions {
+    /// Sets [`max_depth`](Self::max_depth).
+    #[inline]
+    #[must_use]
+    pub const fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// Sets [`ext_hook`](Self::ext_hook).
+    #[inline]
+    #[must_use]
+    pub const fn with_ext_hook(mut self, ext_hook: ExtHook) -> Self {
+        self.ext_hook = Some(ext_hook);
+        self
+    }
+
+    /// Sets [`max_value_bytes`](Self::max_value_bytes).
+    #[inline]
+    #[must_use]
+    pub const fn with_max_value_bytes(mut self, max_value_bytes: usize) -> Self {
+        self.max_value_bytes = Some(max_value_bytes);
+        self
+    }
+}
+
+impl Default for ReadOptions {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            max_depth: super::MAX_DEPTH,
+            ext_hook: None,
+            max_value_bytes: None,
+        }
+    }
+}
+
+/// Attempts to read bytes from the given reader and interpret them as a [`Value`], using the
+/// given options.
+///
+/// # Errors
+///
+/// This function will return [`Error`] on any I/O error while either reading or decoding a [`Value`].
+/// All instances of [`ErrorKind::Interrupted`](io::ErrorKind) are handled by this function and the
+/// underlying operation is retried.
+///
+/// [`Error::DepthLimitExceeded`] is returned if this function recurses
+/// [`ReadOptions::max_depth`] times, and [`Error::ByteLimitExceeded`] if the value needs more
+/// than [`ReadOptions::max_value_bytes`] bytes.
+///
+/// # Examples
+///
+/// ```
+/// use rmpv::Value;
+/// use rmpv::decode::{read_value_with, ReadOptions};
+///
+/// // Ext type 5 carries a big-endian u16.
+/// fn parse_ext(ty: i8, data: &[u8]) -> Option<Value> {
+///     match (ty, data) {
+///         (5, &[hi, lo]) => Some(Value::from(u16::from_be_bytes([hi, lo]))),
+///         _ => None,
+///     }
+/// }
+///
+/// // [ext(5, [0x01, 0x2c]), ext(6, [0x00])]
+/// let buf = [0x92, 0xd5, 0x05, 0x01, 0x2c, 0xd4, 0x06, 0x00];
+/// let options = ReadOptions::default().with_ext_hook(parse_ext);
+///
+/// assert_eq!(
+///     Value::Array(vec![Value::from(300), Value::Ext(6, vec![0x00])]),
+///     read_value_with(&mut &buf[..], options).unwrap()
+/// );
+/// ```
+#[inline]
+pub fn read_value_with<R>(rd: &mut R, options: ReadOptions) -> Result<Value, Error>
+    where R: Read
+{
+    let ext_hook = options.ext_hook.as_ref().map(|hook| hook as ExtDecoder<'_>);
+    read_value_with_decoder(rd, options, ext_hook)
+}
+
+/// Reads a value like [`read_value_with`] does, with the given ext decoder in place of
+/// [`ReadOptions::ext_hook`].
+pub(crate) fn read_value_with_decoder<R>(rd: &mut R, options: ReadOptions, ext_hook: Option<ExtDecoder<'_>>) -> Result<Value, Error>
+    where R: Read
+{
+    let depth = options.max_depth.min(u16::MAX as usize) as u16;
+    let Some(limit) = options.max_value_bytes else {
+        return read_value_inner(rd, depth, ext_hook);
+    };
+
+    let mut rd = LimitedReader { rd, left: limit, exceeded: false };
+    match read_value_inner(&mut rd, depth, ext_hook) {
+        Err(..) if rd.exceeded => Err(Error::ByteLimitExceeded(limit)),
+        res => res,
+    }
+}
+
+/// A reader failing once more than a given number of bytes are requested from it.
+struct LimitedReader<'a, R> {
+    rd: &'a mut R,
+    left: usize,
+    /// Whether a read failed because of the limit, rather than because of the inner reader.
+    exceeded: bool,
+}
+
+impl<R: Read> Read for LimitedReader<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        if self.left == 0 {
+            self.exceeded = true;
+            return Err(io::Error::new(io::ErrorKind::Other, "byte limit exceeded"));
+        }
+        let len = min(buf.len(), self.left);
+        let read = self.rd.read(&mut buf[..len])?;
+        self.left -= read;
+        Ok(read)
+    }
 }
@@ -255,7 +255,7 @@ fn read_value_ref_inner<'a, R>(rd: &mut R, depth: u16) -> Result<ValueRef<'a>, E
             let (ty, vec) = read_ext_body(rd, len as usize, depth)?;
             ValueRef::Ext(ty, vec)
         }
-        Marker::Reserved => ValueRef::Nil,
+        Marker::Reserved => return Err(Error::ReservedMarker),
     };
 
     Ok(val)
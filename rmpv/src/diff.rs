@@ -0,0 +1,111 @@
+//! Structural comparison of two [`Value`] trees, see [`diff`].
+
+use std::fmt::Write;
+
+use crate::Value;
+
+/// A single difference between two [`Value`] trees, as reported by [`diff`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct Change {
+    /// The location of the difference as a JSON Pointer (RFC 6901), such as `/users/0/name`. The
+    /// whole value is the empty string.
+    ///
+    /// String map keys are used as they are, other keys are written in their [`Display`]
+    /// form, for example `1` or `true`. In both cases `~` is escaped to `~0` and `/` to `~1`.
+    /// Array elements are identified by their index.
+    ///
+    /// [`Display`]: std::fmt::Display
+    pub path: String,
+    /// The value before the change, or `None` if it was added.
+    pub old: Option<Value>,
+    /// The value after the change, or `None` if it was removed.
+    pub new: Option<Value>,
+}
+
+/// Compares two values, returning every location at which they differ.
+///
+/// Maps are compared entry by entry, matching their entries by key, and arrays element by element,
+/// matching their elements by index: entries and elements only present in `new` are reported as
+/// added, and those only present in `old` as removed. Any other pair of values that are not equal,
+/// including values of different types, is reported as a single change of the whole value.
+///
+/// Changes are listed in the order of `old`, followed by the additions in the order of `new`.
+/// Comparing equal values returns an empty list.
+///
+/// # Examples
+///
+/// ```
+/// use rmpv::{diff, Change, Value};
+///
+/// let old = Value::Map(vec![
+///     (Value::from("name"), Value::from("le message")),
+///     (Value::from("tags"), Value::Array(vec![Value::from("a"), Value::from("b")])),
+/// ]);
+/// let new = Value::Map(vec![
+///     (Value::from("name"), Value::from("le message")),
+///     (Value::from("tags"), Value::Array(vec![Value::from("c")])),
+///     (Value::from("a/b"), Value::from(42)),
+/// ]);
+///
+/// assert_eq!(vec![
+///     Change { path: "/tags/0".into(), old: Some(Value::from("a")), new: Some(Value::from("c")) },
+///     Change { path: "/tags/1".into(), old: Some(Value::from("b")), new: None },
+///     Change { path: "/a~1b".into(), old: None, new: Some(Value::from(42)) },
+/// ], diff(&old, &new));
+/// ```
+#[must_use]
+pub fn diff(old: &Value, new: &Value) -> Vec<Change> {
+    let mut changes = Vec::new();
+    diff_into(&mut String::new(), old, new, &mut changes);
+    changes
+}
+
+fn diff_into(path: &mut String, old: &Value, new: &Value, changes: &mut Vec<Change>) {
+    match (old, new) {
+        (Value::Map(old), Value::Map(new)) => {
+            for (key, old) in old {
+                let len = push_key(path, key);
+                match new.iter().find(|(k, _)| k == key) {
+                    Some((_, new)) => diff_into(path, old, new, changes),
+                    None => changes.push(Change { path: path.clone(), old: Some(old.clone()), new: None }),
+                }
+                path.truncate(len);
+            }
+            for (key, new) in new {
+                if !old.iter().any(|(k, _)| k == key) {
+                    let len = push_key(path, key);
+                    changes.push(Change { path: path.clone(), old: None, new: Some(new.clone()) });
+                    path.truncate(len);
+                }
+            }
+        }
+        (Value::Array(old), Value::Array(new)) => {
+            for idx in 0..old.len().max(new.len()) {
+                let len = path.len();
+                write!(path, "/{idx}").unwrap();
+                match (old.get(idx), new.get(idx)) {
+                    (Some(old), Some(new)) => diff_into(path, old, new, changes),
+                    (old, new) => changes.push(Change { path: path.clone(), old: old.cloned(), new: new.cloned() }),
+                }
+                path.truncate(len);
+            }
+        }
+        (old, new) => {
+            if old != new {
+                changes.push(Change { path: path.clone(), old: Some(old.clone()), new: Some(new.clone()) });
+            }
+        }
+    }
+}
+
+/// Appends the escaped key to the path, returning the length of the path before.
+pub(crate) fn push_key(path: &mut String, key: &Value) -> usize {
+    let len = path.len();
+    let key = match key.as_str() {
+        Some(key) => key.to_owned(),
+        None => key.to_string(),
+    };
+    path.push('/');
+    path.push_str(&key.replace('~', "~0").replace('/', "~1"));
+    len
+}
@@ -3,5 +3,9 @@ pub use rmp::encode::ValueWriteError as Error;
 mod value;
 mod value_ref;
 
-pub use self::value::write_value;
+pub use self::value::{
+    write_value, write_value_canonical, write_value_minimal, write_value_with, KeyComparator, WriteOptions,
+};
+pub(crate) use self::value::write_value_inner;
 pub use self::value_ref::write_value_ref;
+pub use crate::preserve::write_value_preserving;
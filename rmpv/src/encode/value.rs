@@ -1,3 +1,4 @@
+use std::cmp::Ordering;
 use std::io::Write;
 
 use rmp::encode::{
@@ -16,7 +17,186 @@ use crate::{IntPriv, Integer, Utf8String, Value};
 /// operation is retried.
 pub fn write_value<W>(wr: &mut W, val: &Value) -> Result<(), Error>
     where W: Write
+{
+    write_value_with(wr, val, WriteOptions::default())
+}
+
+/// A function that orders two map keys, see [`WriteOptions::key_order`].
+pub type KeyComparator = fn(&Value, &Value) -> Ordering;
+
+/// Options controlling how [`write_value_with`] encodes a Value.
+///
+/// The default options encode a Value exactly like [`write_value`] does.
+#[derive(Clone, Copy, Debug, Default)]
+#[non_exhaustive]
+pub struct WriteOptions {
+    /// When set, the entries of every map, including nested ones, are written sorted by their
+    /// keys using this comparator instead of in their stored order. The sort is stable, so entries
+    /// with equal keys keep their relative order.
+    pub key_order: Option<KeyComparator>,
+    /// When set, every `F64` which can be converted to an `F32` and back without changing its
+    /// bits, such as `1.5`, is written as an `F32`, in 5 bytes instead of 9, see
+    /// [`write_value_minimal`].
+    pub shrink_floats: bool,
+}
+
+impl WriteOptions {
+    /// Sets [`key_order`](Self::key_order).
+    #[inline]
+    #[must_use]
+    pub const fn with_key_order(mut self, key_order: KeyComparator) -> Self {
+        self.key_order = Some(key_order);
+        self
+    }
+
+    /// Enables [`shrink_floats`](Self::shrink_floats).
+    #[inline]
+    #[must_use]
+    pub const fn with_floats_shrunk(mut self) -> Self {
+        self.shrink_floats = true;
+        self
+    }
+}
+
+/// Encodes and attempts to write the most efficient representation of the given Value, using the
+/// given options.
+///
+/// # Note
+///
+/// All instances of `ErrorKind::Interrupted` are handled by this function and the underlying
+/// operation is retried.
+///
+/// # Examples
+///
+/// ```
+/// use rmpv::Value;
+/// use rmpv::encode::{write_value_with, WriteOptions};
+///
+/// let val = Value::Map(vec![(Value::from(2), Value::Nil), (Value::from(1), Value::Nil)]);
+///
+/// let mut buf = Vec::new();
+/// let options = WriteOptions::default().with_key_order(|a, b| a.as_u64().cmp(&b.as_u64()));
+/// write_value_with(&mut buf, &val, options).unwrap();
+///
+/// assert_eq!(vec![0x82, 0x01, 0xc0, 0x02, 0xc0], buf);
+/// ```
+pub fn write_value_with<W>(wr: &mut W, val: &Value, options: WriteOptions) -> Result<(), Error>
+    where W: Write
+{
+    write_value_inner(wr, val, options, None)
+}
+
+/// Encodes and attempts to write the canonical representation of the given Value.
+///
+/// This is the most efficient representation, as written by [`write_value`], with the entries of
+/// every map, including nested ones, sorted by the encoding of their keys compared byte by byte.
+/// Values which only differ by the order of their map entries therefore have the same canonical
+/// encoding, which makes it suitable for hashing and signing. The sort is stable, so entries with
+/// equal keys keep their relative order. The keys of each map are encoded once, then sorted.
+///
+/// Negative zeros are written as positive zeros, as they are equal to them, so that equal values
+/// always have the same canonical encoding. Every other float is written bit for bit, and NaNs,
+/// which are never equal, keep their payloads.
+///
+/// # Note
+///
+/// All instances of `ErrorKind::Interrupted` are handled by this function and the underlying
+/// operation is retried.
+///
+/// # Examples
+///
+/// ```
+/// use rmpv::Value;
+/// use rmpv::encode::write_value_canonical;
+///
+/// let val = Value::Map(vec![(Value::from("b"), Value::Nil), (Value::from(1), Value::Nil)]);
+///
+/// let mut buf = Vec::new();
+/// write_value_canonical(&mut buf, &val).unwrap();
+///
+/// assert_eq!(vec![0x82, 0x01, 0xc0, 0xa1, 0x62, 0xc0], buf);
+/// ```
+pub fn write_value_canonical<W>(wr: &mut W, val: &Value) -> Result<(), Error>
+    where W: Write
 {
+    match *val {
+        // Float patterns compare like `==`, so these match negative zeros too.
+        Value::F32(0.0) => write_f32(wr, 0.0)?,
+        Value::F64(0.0) => write_f64(wr, 0.0)?,
+        Value::Array(ref vec) => {
+            write_array_len(wr, vec.len() as u32)?;
+            for v in vec {
+                write_value_canonical(wr, v)?;
+            }
+        }
+        Value::Map(ref map) => {
+            write_map_len(wr, map.len() as u32)?;
+            let mut entries = map
+                .iter()
+                .map(|(key, val)| {
+                    let mut buf = Vec::new();
+                    write_value_canonical(&mut buf, key)?;
+                    Ok((buf, val))
+                })
+                .collect::<Result<Vec<_>, Error>>()?;
+            entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+            for (key, val) in entries {
+                wr.write_all(&key).map_err(Error::InvalidDataWrite)?;
+                write_value_canonical(wr, val)?;
+            }
+        }
+        _ => write_value(wr, val)?,
+    }
+    Ok(())
+}
+
+/// Encodes and attempts to write the shortest representation of the given Value, keeping the
+/// order of its map entries.
+///
+/// [`write_value`] already writes every integer, and the length of every string, binary, array,
+/// map and ext, with the smallest marker able to hold it, whatever marker it was read with. This
+/// also writes every `F64` as an `F32` when the conversion is exact, which [`write_value`] does
+/// not do, as it keeps the float types apart. NaNs are kept as they are unless their payload
+/// fits, and no other value changes: the result decodes to the same numbers, only with `F32`
+/// instead of `F64` for the shrunk floats. Unlike [`write_value_canonical`], maps are not sorted.
+///
+/// # Note
+///
+/// All instances of `ErrorKind::Interrupted` are handled by this function and the underlying
+/// operation is retried.
+///
+/// # Examples
+///
+/// ```
+/// use rmpv::Value;
+/// use rmpv::encode::write_value_minimal;
+///
+/// let val = Value::Array(vec![Value::F64(1.5), Value::F64(0.1)]);
+///
+/// let mut buf = Vec::new();
+/// write_value_minimal(&mut buf, &val).unwrap();
+///
+/// assert_eq!(1 + 5 + 9, buf.len());
+/// assert_eq!(0xca, buf[1]);
+/// ```
+pub fn write_value_minimal<W>(wr: &mut W, val: &Value) -> Result<(), Error>
+    where W: Write
+{
+    write_value_with(wr, val, WriteOptions::default().with_floats_shrunk())
+}
+
+/// A function that may encode a value as an ext, returning its type and data.
+pub(crate) type ExtEncoder<'a> = &'a dyn Fn(&Value) -> Option<(i8, Vec<u8>)>;
+
+pub(crate) fn write_value_inner<W>(wr: &mut W, val: &Value, options: WriteOptions, ext_encoder: Option<ExtEncoder<'_>>) -> Result<(), Error>
+    where W: Write
+{
+    if let Some((ty, data)) = ext_encoder.and_then(|encoder| encoder(val)) {
+        write_ext_meta(wr, data.len() as u32, ty)?;
+        wr.write_all(&data).map_err(Error::InvalidDataWrite)?;
+        return Ok(());
+    }
+
     match *val {
         Value::Nil => {
             write_nil(wr).map_err(Error::InvalidMarkerWrite)?;
@@ -36,7 +216,12 @@ pub fn write_value<W>(wr: &mut W, val: &Value) -> Result<(), Error>
             write_f32(wr, val)?;
         }
         Value::F64(val) => {
-            write_f64(wr, val)?;
+            let short = val as f32;
+            if options.shrink_floats && f64::from(short).to_bits() == val.to_bits() {
+                write_f32(wr, short)?;
+            } else {
+                write_f64(wr, val)?;
+            }
         }
         Value::String(Utf8String { ref s }) => match *s {
             Ok(ref val) => write_str(wr, val)?,
@@ -48,14 +233,17 @@ pub fn write_value<W>(wr: &mut W, val: &Value) -> Result<(), Error>
         Value::Array(ref vec) => {
             write_array_len(wr, vec.len() as u32)?;
             for v in vec {
-                write_value(wr, v)?;
+                write_value_inner(wr, v, options, ext_encoder)?;
             }
         }
         Value::Map(ref map) => {
             write_map_len(wr, map.len() as u32)?;
-            for (key, val) in map {
-                write_value(wr, key)?;
-                write_value(wr, val)?;
+            if let Some(key_order) = options.key_order {
+                let mut entries: Vec<_> = map.iter().collect();
+                entries.sort_by(|(a, _), (b, _)| key_order(a, b));
+                write_map_entries(wr, entries, options, ext_encoder)?;
+            } else {
+                write_map_entries(wr, map, options, ext_encoder)?;
             }
         }
         Value::Ext(ty, ref data) => {
@@ -66,3 +254,14 @@ pub fn write_value<W>(wr: &mut W, val: &Value) -> Result<(), Error>
 
     Ok(())
 }
+
+fn write_map_entries<'a, W, I>(wr: &mut W, entries: I, options: WriteOptions, ext_encoder: Option<ExtEncoder<'_>>) -> Result<(), Error>
+    where W: Write,
+          I: IntoIterator<Item = &'a (Value, Value)>
+{
+    for (key, val) in entries {
+        write_value_inner(wr, key, options, ext_encoder)?;
+        write_value_inner(wr, val, options, ext_encoder)?;
+    }
+    Ok(())
+}
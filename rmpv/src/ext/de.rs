@@ -4,6 +4,7 @@ use std::iter::ExactSizeIterator;
 use std::slice::Iter;
 use std::vec::IntoIter;
 
+use serde::de::value::BorrowedStrDeserializer;
 use serde::de::{self, DeserializeSeed, IntoDeserializer, SeqAccess, Unexpected, Visitor};
 use serde::forward_to_deserialize_any;
 use serde::{self, Deserialize, Deserializer};
@@ -28,6 +29,32 @@ pub fn deserialize_from<'de, T, D>(val: D) -> Result<T, Error>
     Deserialize::deserialize(val)
 }
 
+/// The single key of the map by which `serde_json` passes numbers to `deserialize_any` when its
+/// `arbitrary_precision` feature is enabled.
+///
+/// Such maps are only recognized from human-readable deserializers, so that a MessagePack map which
+/// happens to have this key is kept as it is.
+const JSON_NUMBER_TOKEN: &str = "$serde_json::private::Number";
+
+/// Parses the digits of a `serde_json` arbitrary precision number into an integer if it is in the
+/// range of `u64` or `i64`, and into an `f64` if it has a fraction or an exponent.
+///
+/// Integers out of the range of `u64` and `i64`, and numbers too large for a finite `f64`, are an
+/// error rather than being rounded.
+fn parse_json_number<E: de::Error>(num: &str) -> Result<Value, E> {
+    if let Ok(n) = num.parse::<u64>() {
+        return Ok(Value::from(n));
+    }
+    if let Ok(n) = num.parse::<i64>() {
+        return Ok(Value::from(n));
+    }
+    let is_float = num.contains(['.', 'e', 'E']);
+    match num.parse::<f64>() {
+        Ok(n) if is_float && n.is_finite() => Ok(Value::F64(n)),
+        _ => Err(E::custom(format_args!("number {num} does not fit into a u64, an i64 or a finite f64"))),
+    }
+}
+
 impl de::Error for Error {
     #[cold]
     fn custom<T: Display>(msg: T) -> Self {
@@ -40,7 +67,8 @@ impl<'de> Deserialize<'de> for Value {
     fn deserialize<D>(de: D) -> Result<Self, D::Error>
         where D: de::Deserializer<'de>
     {
-        struct ValueVisitor;
+        /// Whether the deserializer is human-readable, see [`JSON_NUMBER_TOKEN`].
+        struct ValueVisitor(bool);
 
         impl<'de> serde::de::Visitor<'de> for ValueVisitor {
             type Value = Value;
@@ -140,6 +168,12 @@ impl<'de> Deserialize<'de> for Value {
                     pairs.push((key, val));
                 }
 
+                if let [(Value::String(key), Value::String(num))] = &pairs[..] {
+                    if let (true, Some(JSON_NUMBER_TOKEN), Some(num)) = (self.0, key.as_str(), num.as_str()) {
+                        return parse_json_number(num);
+                    }
+                }
+
                 Ok(Value::Map(pairs))
             }
 
@@ -172,7 +206,8 @@ impl<'de> Deserialize<'de> for Value {
             }
         }
 
-        de.deserialize_any(ValueVisitor)
+        let is_human_readable = de.is_human_readable();
+        de.deserialize_any(ValueVisitor(is_human_readable))
     }
 }
 
@@ -181,7 +216,8 @@ impl<'de> Deserialize<'de> for ValueRef<'de> {
     fn deserialize<D>(de: D) -> Result<Self, D::Error>
         where D: Deserializer<'de>
     {
-        struct ValueVisitor;
+        /// Whether the deserializer is human-readable, see [`JSON_NUMBER_TOKEN`].
+        struct ValueVisitor(bool);
 
         impl<'de> de::Visitor<'de> for ValueVisitor {
             type Value = ValueRef<'de>;
@@ -271,6 +307,16 @@ impl<'de> Deserialize<'de> for ValueRef<'de> {
                     vec.push((key, val));
                 }
 
+                if let [(ValueRef::String(key), ValueRef::String(num))] = &vec[..] {
+                    if let (true, Some(JSON_NUMBER_TOKEN), Some(num)) = (self.0, key.as_str(), num.as_str()) {
+                        return match parse_json_number(num)? {
+                            Value::Integer(n) => Ok(ValueRef::Integer(n)),
+                            Value::F64(n) => Ok(ValueRef::F64(n)),
+                            _ => unreachable!("JSON numbers are parsed into integers and floats"),
+                        };
+                    }
+                }
+
                 Ok(ValueRef::Map(vec))
             }
 
@@ -302,7 +348,8 @@ impl<'de> Deserialize<'de> for ValueRef<'de> {
             }
         }
 
-        de.deserialize_any(ValueVisitor)
+        let is_human_readable = de.is_human_readable();
+        de.deserialize_any(ValueVisitor(is_human_readable))
     }
 }
 
@@ -364,7 +411,11 @@ impl<'de> Deserializer<'de> for Value {
     fn deserialize_enum<V>(self, _name: &str, _variants: &'static [&'static str], visitor: V) -> Result<V::Value, Self::Error>
         where V: Visitor<'de>
     {
-        ValueBase::deserialize_enum(self, visitor)
+        match self {
+            // A bare string selects a unit variant by its name.
+            Self::String(Utf8String { s: Ok(s) }) => visitor.visit_enum(s.into_deserializer()),
+            other => ValueBase::deserialize_enum(other, visitor),
+        }
     }
 
     #[inline]
@@ -457,7 +508,11 @@ impl<'de> Deserializer<'de> for ValueRef<'de> {
     fn deserialize_enum<V>(self, _name: &str, _variants: &'static [&'static str], visitor: V) -> Result<V::Value, Self::Error>
         where V: Visitor<'de>
     {
-        ValueBase::deserialize_enum(self, visitor)
+        match self {
+            // A bare string selects a unit variant by its name.
+            ValueRef::String(Utf8StringRef { s: Ok(s) }) => visitor.visit_enum(BorrowedStrDeserializer::new(s)),
+            other => ValueBase::deserialize_enum(other, visitor),
+        }
     }
 
     #[inline]
@@ -571,6 +626,8 @@ impl<'de> Deserializer<'de> for &'de ValueRef<'de> {
 
                 visitor.visit_enum(EnumRefDeserializer::new(id, iter.next()))
             }
+            // A bare string selects a unit variant by its name.
+            ValueRef::String(Utf8StringRef { s: Ok(s) }) => visitor.visit_enum(BorrowedStrDeserializer::new(s)),
             other => Err(de::Error::invalid_type(other.unexpected(), &"array, map or int")),
         }
     }
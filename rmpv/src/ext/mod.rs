@@ -1,3 +1,34 @@
+//! Conversions between [`Value`] and Rust types through Serde, with the `with-serde` feature.
+//!
+//! [`to_value`] converts any `Serialize` type into a [`Value`] tree and [`from_value`] converts it
+//! back, without encoding to MessagePack in between, much like `serde_json::to_value` and
+//! `serde_json::from_value`. Both are also available at the root of the crate. Byte buffers
+//! serialized as bytes, such as with `serde_bytes`, become [`Value::Binary`], and the ext types
+//! of `rmp_serde` become [`Value::Ext`], like they would when encoded and decoded again.
+//!
+//! ```
+//! use rmpv::Value;
+//!
+//! let val = rmpv::to_value((42, "le message")).unwrap();
+//! assert_eq!(Value::Array(vec![Value::from(42), Value::from("le message")]), val);
+//!
+//! let (n, s): (u8, String) = rmpv::from_value(val).unwrap();
+//! assert_eq!((42, "le message".to_owned()), (n, s));
+//! ```
+//!
+//! [`Value`] and [`ValueRef`] implement `Serialize` and `Deserialize`, so they also convert to and
+//! from other Serde formats. For example, `serde_json::to_value` and `serde_json::from_value`
+//! bridge a [`Value`] and a `serde_json::Value`.
+//!
+//! Integers of up to 64 bits keep their exact value in both directions, because `serde_json`
+//! stores them as `u64` or `i64` rather than as `f64`. They also survive `serde_json`'s
+//! `arbitrary_precision` feature, which passes numbers around as strings: those are parsed back
+//! into an integer when they are in the range of `u64` or `i64`, and into an `f64` when they have a
+//! fraction or an exponent, so that the most compact integer marker is written again. Larger
+//! integers, and numbers too large for a finite `f64`, can not be represented in MessagePack and
+//! fail to deserialize. This only applies to human-readable deserializers, so MessagePack data read
+//! with `rmp-serde` is never rewritten.
+
 use std::error;
 use std::fmt::{self, Display, Formatter};
 
@@ -0,0 +1,177 @@
+//! A registry of application-defined ext types, see [`ExtRegistry`].
+
+use std::fmt::{self, Debug, Formatter};
+use std::io::{Read, Write};
+
+use crate::decode::{self, ReadOptions};
+use crate::encode::{self, WriteOptions};
+use crate::Value;
+
+type EncodeFn = dyn Fn(&Value) -> Option<Vec<u8>> + Send + Sync;
+type DecodeFn = dyn Fn(&[u8]) -> Option<Value> + Send + Sync;
+
+struct Entry {
+    ty: i8,
+    encode: Box<EncodeFn>,
+    decode: Box<DecodeFn>,
+}
+
+/// A set of application-defined ext types, each registered once with both its encoder and its
+/// decoder, so that writing and reading values agree on how the types are represented.
+///
+/// On decoding, the data of every ext value with a registered type is passed to the matching
+/// decoder, and the returned [`Value`] is used in place of the ext. On encoding, every value,
+/// including nested ones, is offered to the encoders in registration order; the first one that
+/// returns data has the value written as an ext of its type. Values that no encoder claims and
+/// ext values that no decoder accepts are handled as usual.
+///
+/// # Examples
+///
+/// A point type, represented as a `{"x": i32, "y": i32}` map and encoded as ext type 1 holding the
+/// two big-endian coordinates:
+///
+/// ```
+/// use rmpv::{ExtRegistry, Value};
+///
+/// fn encode_point(val: &Value) -> Option<Vec<u8>> {
+///     match val.as_map()?.as_slice() {
+///         [(kx, x), (ky, y)] if kx.as_str() == Some("x") && ky.as_str() == Some("y") => {
+///             let x = i32::try_from(x.as_i64()?).ok()?;
+///             let y = i32::try_from(y.as_i64()?).ok()?;
+///             Some([x.to_be_bytes(), y.to_be_bytes()].concat())
+///         }
+///         _ => None,
+///     }
+/// }
+///
+/// fn decode_point(data: &[u8]) -> Option<Value> {
+///     let x = i32::from_be_bytes(data.get(0..4)?.try_into().ok()?);
+///     let y = i32::from_be_bytes(data.get(4..8)?.try_into().ok()?);
+///     Some(Value::Map(vec![(Value::from("x"), Value::from(x)), (Value::from("y"), Value::from(y))]))
+/// }
+///
+/// let mut registry = ExtRegistry::new();
+/// registry.register(1, encode_point, decode_point);
+///
+/// let point = Value::Map(vec![(Value::from("x"), Value::from(3)), (Value::from("y"), Value::from(-4))]);
+/// let val = Value::Array(vec![point, Value::from("label")]);
+///
+/// let mut buf = Vec::new();
+/// registry.write_value(&mut buf, &val).unwrap();
+/// assert_eq!(&[0x92, 0xd7, 0x01, 0, 0, 0, 3, 0xff, 0xff, 0xff, 0xfc], &buf[..11]);
+///
+/// assert_eq!(val, registry.read_value(&mut &buf[..]).unwrap());
+/// ```
+#[derive(Default)]
+pub struct ExtRegistry {
+    entries: Vec<Entry>,
+}
+
+impl ExtRegistry {
+    /// Creates an empty registry.
+    #[inline]
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers the encoder and the decoder of the given ext type, replacing any previously
+    /// registered for it.
+    ///
+    /// The encoder returns the ext data when given a value of the custom type and `None` for any
+    /// other value. The decoder returns the value represented by the ext data, or `None` to keep
+    /// the data as a plain [`Value::Ext`].
+    pub fn register<E, D>(&mut self, ty: i8, encode: E, decode: D) -> &mut Self
+    where
+        E: Fn(&Value) -> Option<Vec<u8>> + Send + Sync + 'static,
+        D: Fn(&[u8]) -> Option<Value> + Send + Sync + 'static,
+    {
+        let entry = Entry { ty, encode: Box::new(encode), decode: Box::new(decode) };
+        match self.entries.iter_mut().find(|entry| entry.ty == ty) {
+            Some(slot) => *slot = entry,
+            None => self.entries.push(entry),
+        }
+        self
+    }
+
+    /// Returns `true` if an encoder and a decoder are registered for the given ext type.
+    #[must_use]
+    pub fn contains(&self, ty: i8) -> bool {
+        self.entries.iter().any(|entry| entry.ty == ty)
+    }
+
+    /// Offers the value to the registered encoders, returning the ext type and data produced by
+    /// the first one that claims it.
+    #[must_use]
+    pub fn encode(&self, val: &Value) -> Option<(i8, Vec<u8>)> {
+        self.entries
+            .iter()
+            .find_map(|entry| (entry.encode)(val).map(|data| (entry.ty, data)))
+    }
+
+    /// Decodes the ext data using the decoder registered for its type, if any.
+    #[must_use]
+    pub fn decode(&self, ty: i8, data: &[u8]) -> Option<Value> {
+        self.entries
+            .iter()
+            .find(|entry| entry.ty == ty)
+            .and_then(|entry| (entry.decode)(data))
+    }
+
+    /// Encodes the value like [`write_value`](encode::write_value) does, writing values claimed by
+    /// the registered encoders as exts.
+    ///
+    /// # Errors
+    ///
+    /// This function will return [`encode::Error`] on any I/O error occurred while writing.
+    #[inline]
+    pub fn write_value<W: Write>(&self, wr: &mut W, val: &Value) -> Result<(), encode::Error> {
+        self.write_value_with(wr, val, WriteOptions::default())
+    }
+
+    /// Encodes the value like [`write_value_with`](encode::write_value_with) does, writing values
+    /// claimed by the registered encoders as exts.
+    ///
+    /// # Errors
+    ///
+    /// This function will return [`encode::Error`] on any I/O error occurred while writing.
+    pub fn write_value_with<W: Write>(&self, wr: &mut W, val: &Value, options: WriteOptions) -> Result<(), encode::Error> {
+        encode::write_value_inner(wr, val, options, Some(&|val: &Value| self.encode(val)))
+    }
+
+    /// Decodes a value like [`read_value`](decode::read_value) does, passing ext values to the
+    /// registered decoders.
+    ///
+    /// # Errors
+    ///
+    /// This function will return [`decode::Error`] on any I/O error while either reading or
+    /// decoding a [`Value`].
+    #[inline]
+    pub fn read_value<R: Read>(&self, rd: &mut R) -> Result<Value, decode::Error> {
+        self.read_value_with(rd, ReadOptions::default())
+    }
+
+    /// Decodes a value like [`read_value_with`](decode::read_value_with) does, passing ext values
+    /// to the registered decoders. The [`ReadOptions::ext_hook`] is only called for ext values that
+    /// no registered decoder accepts.
+    ///
+    /// # Errors
+    ///
+    /// This function will return [`decode::Error`] on any I/O error while either reading or
+    /// decoding a [`Value`].
+    pub fn read_value_with<R: Read>(&self, rd: &mut R, options: ReadOptions) -> Result<Value, decode::Error> {
+        let ext_hook = |ty: i8, data: &[u8]| {
+            self.decode(ty, data)
+                .or_else(|| options.ext_hook.and_then(|hook| hook(ty, data)))
+        };
+        decode::value::read_value_with_decoder(rd, options, Some(&ext_hook))
+    }
+}
+
+impl Debug for ExtRegistry {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ExtRegistry")
+            .field("types", &self.entries.iter().map(|entry| entry.ty).collect::<Vec<_>>())
+            .finish()
+    }
+}
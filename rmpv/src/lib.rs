@@ -2,17 +2,46 @@
 #![forbid(unsafe_code)]
 
 use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap};
 use std::convert::TryFrom;
 use std::fmt::{self, Debug, Display};
+use std::hash::Hasher;
+use std::io::{self, Write};
 use std::iter::FromIterator;
 use std::ops::Index;
 use std::str::Utf8Error;
 
+mod convert;
 pub mod decode;
+mod diff;
 pub mod encode;
+mod ext_registry;
+mod pointer;
+mod preserve;
+mod schema;
+mod size;
+mod test_vectors;
+mod walk;
+
+pub use crate::convert::CollectionError;
+pub use crate::diff::{diff, Change};
+pub use crate::ext_registry::ExtRegistry;
+pub use crate::pointer::PointerError;
+pub use crate::preserve::PreservedValue;
+pub use crate::schema::{Schema, ValidationError, ValidationErrorKind};
+pub use crate::size::TRUNCATION_MARKER;
+pub use crate::test_vectors::test_vectors;
+pub use crate::walk::PathSegment;
 
 #[cfg(feature = "with-serde")]
 pub mod ext;
+#[cfg(feature = "with-serde")]
+pub use crate::ext::{from_value, to_value};
+
+#[cfg(feature = "tokio")]
+pub mod async_decode;
+#[cfg(feature = "tokio")]
+pub mod async_encode;
 
 #[derive(Copy, Clone, Debug, PartialEq)]
 enum IntPriv {
@@ -40,6 +69,11 @@ pub const MSGPACK_EXT_STRUCT_NAME: &str = "_ExtStruct";
 /// Represents a MessagePack integer, whether signed or unsigned.
 ///
 /// A `Value` or `ValueRef` that contains integer can be constructed using `From` trait.
+///
+/// Integers are always normalized: a non-negative one is held as unsigned and a negative one as
+/// signed, whatever type it is constructed or decoded from. So `Integer::from(1i64)` and
+/// `Integer::from(1u8)` are equal, and are both encoded as a positive fixint, and comparing,
+/// hashing or canonically encoding values never depends on how their integers were produced.
 #[derive(Copy, Clone, PartialEq)]
 pub struct Integer {
     n: IntPriv,
@@ -87,6 +121,9 @@ impl Integer {
     }
 
     /// Returns the integer represented as `f64` if possible, or else `None`.
+    ///
+    /// The integer is rounded to the nearest `f64`, so this currently always returns `Some`, with
+    /// a loss of precision beyond 2<sup>53</sup> in magnitude.
     #[inline]
     #[must_use]
     pub fn as_f64(&self) -> Option<f64> {
@@ -427,6 +464,81 @@ impl<'a> From<Utf8StringRef<'a>> for Utf8String {
     }
 }
 
+/// An error returned by the `try_as_*` accessors of a `Value` when it holds a value of another
+/// type.
+///
+/// # Examples
+///
+/// ```
+/// use rmpv::Value;
+///
+/// let err = Value::Boolean(true).try_as_str().unwrap_err();
+///
+/// assert_eq!("str", err.expected());
+/// assert_eq!("Boolean", err.actual());
+/// assert_eq!("expected str, found Boolean", err.to_string());
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct TypeError {
+    expected: &'static str,
+    actual: &'static str,
+}
+
+impl TypeError {
+    #[cold]
+    fn new(expected: &'static str, actual: &Value) -> Self {
+        Self { expected, actual: actual.type_name() }
+    }
+
+    /// Returns a description of the type that was requested.
+    #[inline]
+    #[must_use]
+    pub fn expected(&self) -> &'static str {
+        self.expected
+    }
+
+    /// Returns the name of the `Value` variant that was actually found.
+    #[inline]
+    #[must_use]
+    pub fn actual(&self) -> &'static str {
+        self.actual
+    }
+}
+
+impl Display for TypeError {
+    #[cold]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        write!(f, "expected {}, found {}", self.expected, self.actual)
+    }
+}
+
+impl std::error::Error for TypeError {}
+
+/// An error returned by [`Value::ext`] and [`Value::timestamp`] when the ext can not be encoded
+/// as MessagePack.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ExtError {
+    /// The data, whose length is given, is longer than an ext can hold.
+    TooLong(usize),
+    /// The data of a timestamp ext, of type -1, has the given length instead of 4, 8 or 12 bytes.
+    InvalidTimestampLen(usize),
+    /// The nanoseconds of a timestamp are not below one second.
+    InvalidNanos(u32),
+}
+
+impl Display for ExtError {
+    #[cold]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        match *self {
+            Self::TooLong(len) => write!(f, "ext data of {len} bytes does not fit a 32-bit length"),
+            Self::InvalidTimestampLen(len) => write!(f, "timestamp ext data of {len} bytes, expected 4, 8 or 12"),
+            Self::InvalidNanos(nanos) => write!(f, "timestamp nanoseconds {nanos} are not below one second"),
+        }
+    }
+}
+
+impl std::error::Error for ExtError {}
+
 /// Represents any valid MessagePack value.
 #[derive(Clone, Debug, PartialEq)]
 pub enum Value {
@@ -447,8 +559,29 @@ pub enum Value {
     /// ```
     Integer(Integer),
     /// A 32-bit floating point number.
+    ///
+    /// See [`Value::F64`] for the notes about comparing floats.
     F32(f32),
     /// A 64-bit floating point number.
+    ///
+    /// # Note
+    ///
+    /// Encoding and decoding preserve the exact bit pattern of a float, including NaN payloads
+    /// and the sign of zero. However `PartialEq` for `Value` compares floats numerically, so
+    /// `NaN != NaN` and `0.0 == -0.0`. Compare the results of `f64::to_bits` to check that two
+    /// floats are bit-identical.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmpv::Value;
+    ///
+    /// assert_ne!(Value::F64(f64::NAN), Value::F64(f64::NAN));
+    ///
+    /// assert!(matches!(Value::from(-0.0), Value::F64(..)));
+    /// let v = Value::from(-0.0).as_f64().unwrap();
+    /// assert_eq!((-0.0f64).to_bits(), v.to_bits());
+    /// ```
     F64(f64),
     /// String extending Raw type represents a UTF-8 string.
     ///
@@ -776,6 +909,10 @@ impl Value {
     /// If the `Value` is a number, return or cast it to a f64.
     /// Returns None otherwise.
     ///
+    /// Integers are promoted to the nearest `f64`, so those beyond 2<sup>53</sup> in magnitude
+    /// may lose precision. Use [`as_i64`](Self::as_i64) or [`as_u64`](Self::as_u64) first when
+    /// integers need to be told apart from floats.
+    ///
     /// # Examples
     ///
     /// ```
@@ -786,6 +923,7 @@ impl Value {
     /// assert_eq!(Some(42.0), Value::F64(42.0f64).as_f64());
     ///
     /// assert_eq!(Some(2147483647.0), Value::from(i32::MAX as i64).as_f64());
+    /// assert_eq!(Some(9007199254740992.0), Value::from(9007199254740993u64).as_f64());
     ///
     /// assert_eq!(None, Value::Nil.as_f64());
     /// ```
@@ -896,6 +1034,36 @@ impl Value {
         }
     }
 
+    /// If the `Value` is a Map, returns the value of its first entry whose key is a String equal
+    /// to the given key. Returns None otherwise.
+    ///
+    /// Maps are scanned in their stored order, so when a key appears more than once the first
+    /// entry wins, like with indexing by `&str`. Combine it with the `try_as_*` accessors to read
+    /// the value as a specific type.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmpv::Value;
+    ///
+    /// let val = Value::Map(vec![
+    ///     (Value::from("id"), Value::from(42)),
+    ///     (Value::from("id"), Value::from(43)),
+    /// ]);
+    ///
+    /// assert_eq!(Some(&Value::from(42)), val.map_get("id"));
+    /// assert_eq!(42, val.map_get("id").unwrap().try_as_u64().unwrap());
+    /// assert_eq!(None, val.map_get("name"));
+    /// assert_eq!(None, Value::Nil.map_get("id"));
+    /// ```
+    #[must_use]
+    pub fn map_get(&self, key: &str) -> Option<&Self> {
+        self.as_map()?
+            .iter()
+            .find(|(k, _)| k.as_str() == Some(key))
+            .map(|(_, val)| val)
+    }
+
     /// If the `Value` is an Ext, returns the associated tuple with a ty and slice.
     /// Returns None otherwise.
     ///
@@ -917,8 +1085,689 @@ impl Value {
             None
         }
     }
+
+    /// Constructs an Ext, checking that it can be encoded.
+    ///
+    /// Unlike building `Value::Ext` directly, this rejects data which does not fit the 32-bit
+    /// length of an ext, and checks that data of type -1 is a valid timestamp, as described in
+    /// [`timestamp`](Self::timestamp). Other negative types are reserved by the MessagePack
+    /// specification but not checked, as their meaning is not defined yet.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ExtError::TooLong` if the data is too long, and `ExtError::InvalidTimestampLen` or
+    /// `ExtError::InvalidNanos` for an invalid timestamp.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmpv::{ExtError, Value};
+    ///
+    /// assert_eq!(Ok(Value::Ext(42, vec![1, 2])), Value::ext(42, [1, 2]));
+    /// assert_eq!(Err(ExtError::InvalidTimestampLen(2)), Value::ext(-1, [1, 2]));
+    /// ```
+    pub fn ext(ty: i8, data: impl Into<Vec<u8>>) -> Result<Self, ExtError> {
+        let data = data.into();
+        if u32::try_from(data.len()).is_err() {
+            return Err(ExtError::TooLong(data.len()));
+        }
+        if ty == TIMESTAMP_EXT_TYPE {
+            let nanos = match *data {
+                [_, _, _, _] => 0,
+                [a, b, c, d, ..] if data.len() == 8 => u32::from_be_bytes([a, b, c, d]) >> 2,
+                [a, b, c, d, ..] if data.len() == 12 => u32::from_be_bytes([a, b, c, d]),
+                _ => return Err(ExtError::InvalidTimestampLen(data.len())),
+            };
+            if nanos >= NANOS_PER_SEC {
+                return Err(ExtError::InvalidNanos(nanos));
+            }
+        }
+        Ok(Self::Ext(ty, data))
+    }
+
+    /// Constructs the timestamp ext, of type -1, of the time `secs` seconds and `nanos`
+    /// nanoseconds after the epoch, using the smallest of its three formats.
+    ///
+    /// The nanoseconds are counted forward from the seconds, so 1.5 seconds before the epoch is
+    /// -2 seconds and 500 000 000 nanoseconds.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ExtError::InvalidNanos` if `nanos` is not below one second.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmpv::{ExtError, Value};
+    ///
+    /// assert_eq!(Ok(Value::Ext(-1, vec![0x65, 0x53, 0xf1, 0x00])), Value::timestamp(1_700_000_000, 0));
+    /// assert_eq!(12, Value::timestamp(-1, 0).unwrap().as_ext().unwrap().1.len());
+    /// assert_eq!(Err(ExtError::InvalidNanos(1_000_000_000)), Value::timestamp(0, 1_000_000_000));
+    /// ```
+    pub fn timestamp(secs: i64, nanos: u32) -> Result<Self, ExtError> {
+        if nanos >= NANOS_PER_SEC {
+            return Err(ExtError::InvalidNanos(nanos));
+        }
+        let data = match u64::try_from(secs) {
+            Ok(secs) if secs >> 32 == 0 && nanos == 0 => (secs as u32).to_be_bytes().to_vec(),
+            Ok(secs) if secs >> 34 == 0 => (u64::from(nanos) << 34 | secs).to_be_bytes().to_vec(),
+            _ => [&nanos.to_be_bytes()[..], &secs.to_be_bytes()].concat(),
+        };
+        Ok(Self::Ext(TIMESTAMP_EXT_TYPE, data))
+    }
+
+    /// Collapses nested single-element arrays, removing at most `max_depth` levels of nesting.
+    ///
+    /// While this value is an array containing exactly one element, which is itself an array, it
+    /// is replaced with that inner array. The outermost array is always kept, so `[[x]]` becomes
+    /// `[x]` and never `x`. Only this value is affected: arrays nested inside other elements,
+    /// or inside maps, are left untouched.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmpv::Value;
+    ///
+    /// let mut val = Value::Array(vec![Value::Array(vec![Value::Array(vec![Value::from(42)])])]);
+    /// val.flatten_arrays(1);
+    /// assert_eq!(Value::Array(vec![Value::Array(vec![Value::from(42)])]), val);
+    ///
+    /// val.flatten_arrays(usize::MAX);
+    /// assert_eq!(Value::Array(vec![Value::from(42)]), val);
+    /// ```
+    pub fn flatten_arrays(&mut self, max_depth: usize) {
+        for _ in 0..max_depth {
+            match self {
+                Self::Array(array) if array.len() == 1 && array[0].is_array() => {
+                    let inner = array.pop().unwrap_or(Self::Nil);
+                    *self = inner;
+                }
+                _ => break,
+            }
+        }
+    }
+
+    /// Applies the given patch to this value with the semantics of JSON Merge Patch
+    /// ([RFC 7386](https://tools.ietf.org/html/rfc7386)), treating maps as JSON objects and `Nil`
+    /// as `null`.
+    ///
+    /// If the patch is a map, this value is turned into an empty map unless it already is one,
+    /// and then for each entry of the patch: a `Nil` value removes all the entries with an equal
+    /// key, and any other value is merged recursively into the entry with that key, which is
+    /// appended if missing. Any other patch, arrays included, replaces this value entirely.
+    ///
+    /// Keys may be of any type and are compared with `==`. New entries are appended in the order
+    /// in which they appear in the patch.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmpv::Value;
+    ///
+    /// let mut val = Value::Map(vec![
+    ///     (Value::from("name"), Value::from("le message")),
+    ///     (Value::from("tags"), Value::Array(vec![Value::from("a")])),
+    /// ]);
+    /// let patch = Value::Map(vec![
+    ///     (Value::from("name"), Value::Nil),
+    ///     (Value::from("size"), Value::from(42)),
+    /// ]);
+    ///
+    /// val.apply_merge_patch(&patch);
+    ///
+    /// assert_eq!(Value::Map(vec![
+    ///     (Value::from("tags"), Value::Array(vec![Value::from("a")])),
+    ///     (Value::from("size"), Value::from(42)),
+    /// ]), val);
+    /// ```
+    pub fn apply_merge_patch(&mut self, patch: &Self) {
+        let Self::Map(patch) = patch else {
+            *self = patch.clone();
+            return;
+        };
+        if !self.is_map() {
+            *self = Self::Map(Vec::new());
+        }
+        let Self::Map(map) = self else {
+            unreachable!()
+        };
+
+        for (key, value) in patch {
+            if value.is_nil() {
+                map.retain(|(k, _)| k != key);
+            } else if let Some((_, old)) = map.iter_mut().find(|(k, _)| k == key) {
+                old.apply_merge_patch(value);
+            } else {
+                let mut new = Self::Nil;
+                new.apply_merge_patch(value);
+                map.push((key.clone(), new));
+            }
+        }
+    }
+
+    /// Converts the keys of every map, including nested ones, to strings, so that the value can be
+    /// represented in formats such as JSON that only allow string keys.
+    ///
+    /// Keys are converted as follows:
+    ///
+    /// - valid UTF-8 strings are kept as they are;
+    /// - `Nil` becomes `"nil"`, and booleans `"true"` or `"false"`;
+    /// - integers become their decimal representation, such as `"-42"`;
+    /// - floats become their decimal representation without an exponent, as written by `Display`,
+    ///   such as `"1.5"` or `"1"`, and `"NaN"`, `"inf"` or `"-inf"` for the special values.
+    ///
+    /// Keys that are strings with invalid UTF-8, binaries, arrays, maps or exts can not be converted.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `TypeError` describing the first key that can not be converted. In that case the
+    /// value is left unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmpv::Value;
+    ///
+    /// let mut val = Value::Map(vec![
+    ///     (Value::from(1), Value::Map(vec![(Value::from(true), Value::Nil)])),
+    ///     (Value::from("a"), Value::Array(vec![Value::Map(vec![(Value::from(1.5), Value::Nil)])])),
+    /// ]);
+    ///
+    /// val.coerce_keys_to_string().unwrap();
+    ///
+    /// assert_eq!(Value::Map(vec![
+    ///     (Value::from("1"), Value::Map(vec![(Value::from("true"), Value::Nil)])),
+    ///     (Value::from("a"), Value::Array(vec![Value::Map(vec![(Value::from("1.5"), Value::Nil)])])),
+    /// ]), val);
+    ///
+    /// let mut val = Value::Map(vec![(Value::Array(vec![]), Value::Nil)]);
+    /// assert_eq!("Array", val.coerce_keys_to_string().unwrap_err().actual());
+    /// ```
+    pub fn coerce_keys_to_string(&mut self) -> Result<(), TypeError> {
+        self.check_keys_coercible()?;
+        self.coerce_keys_unchecked();
+        Ok(())
+    }
+
+    fn check_keys_coercible(&self) -> Result<(), TypeError> {
+        match *self {
+            Self::Array(ref vec) => vec.iter().try_for_each(Self::check_keys_coercible),
+            Self::Map(ref map) => map.iter().try_for_each(|(key, val)| {
+                if key.key_to_string().is_none() {
+                    return Err(TypeError::new("a key convertible to a string", key));
+                }
+                val.check_keys_coercible()
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    fn coerce_keys_unchecked(&mut self) {
+        match *self {
+            Self::Array(ref mut vec) => vec.iter_mut().for_each(Self::coerce_keys_unchecked),
+            Self::Map(ref mut map) => {
+                for (key, val) in map {
+                    if !key.is_str() {
+                        if let Some(s) = key.key_to_string() {
+                            *key = Self::from(s);
+                        }
+                    }
+                    val.coerce_keys_unchecked();
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Returns the string form of a map key, see [`coerce_keys_to_string`](Self::coerce_keys_to_string).
+    fn key_to_string(&self) -> Option<String> {
+        match *self {
+            Self::Nil => Some("nil".into()),
+            Self::Boolean(val) => Some(val.to_string()),
+            Self::Integer(ref val) => Some(val.to_string()),
+            Self::F32(val) => Some(val.to_string()),
+            Self::F64(val) => Some(val.to_string()),
+            Self::String(ref val) => val.as_str().map(str::to_owned),
+            Self::Binary(..) | Self::Array(..) | Self::Map(..) | Self::Ext(..) => None,
+        }
+    }
+
+    /// Replaces the type of every ext, including ones nested in arrays and maps, map keys too, with
+    /// the type returned by the given function.
+    ///
+    /// The function is called once for each ext, in the order in which they would be encoded. The
+    /// ext data is left unchanged. Like [`walk`](Self::walk), this does not recurse, so arbitrarily
+    /// deep values can not overflow the call stack.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmpv::Value;
+    ///
+    /// let mut val = Value::Array(vec![
+    ///     Value::Ext(3, vec![0x01]),
+    ///     Value::Map(vec![(Value::from("at"), Value::Ext(4, vec![0x02]))]),
+    /// ]);
+    ///
+    /// val.remap_ext_tags(|ty| if ty == 3 { 7 } else { ty });
+    ///
+    /// assert_eq!(Value::Array(vec![
+    ///     Value::Ext(7, vec![0x01]),
+    ///     Value::Map(vec![(Value::from("at"), Value::Ext(4, vec![0x02]))]),
+    /// ]), val);
+    /// ```
+    pub fn remap_ext_tags<F>(&mut self, mut f: F)
+    where
+        F: FnMut(i8) -> i8,
+    {
+        let mut stack = vec![self];
+
+        while let Some(node) = stack.pop() {
+            match node {
+                Self::Ext(ty, _) => *ty = f(*ty),
+                Self::Array(vec) => stack.extend(vec.iter_mut().rev()),
+                Self::Map(map) => {
+                    for (key, val) in map.iter_mut().rev() {
+                        stack.push(val);
+                        stack.push(key);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Shrinks the capacity of every string, binary, ext, array and map in this value, nested ones
+    /// included, to its length.
+    ///
+    /// Decoding may leave spare capacity, for example in an array whose announced length was too
+    /// large to allocate up front. Shrinking reduces the memory held by values that are kept
+    /// around, such as cached ones. There is no need for a separate compacting clone: [`Clone`]
+    /// already allocates every nested buffer with exactly the length it holds. Like
+    /// [`walk`](Self::walk), this does not recurse.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmpv::Value;
+    ///
+    /// let mut vec = Vec::with_capacity(100);
+    /// vec.push(Value::from(1));
+    /// let mut val = Value::Array(vec);
+    ///
+    /// val.shrink_to_fit();
+    /// assert_eq!(1, val.as_array().unwrap().capacity());
+    /// ```
+    pub fn shrink_to_fit(&mut self) {
+        let mut stack = vec![self];
+
+        while let Some(node) = stack.pop() {
+            match node {
+                Self::String(s) => match s.s {
+                    Ok(ref mut s) => s.shrink_to_fit(),
+                    Err((ref mut buf, _)) => buf.shrink_to_fit(),
+                },
+                Self::Binary(data) | Self::Ext(_, data) => data.shrink_to_fit(),
+                Self::Array(vec) => {
+                    vec.shrink_to_fit();
+                    stack.extend(vec.iter_mut());
+                }
+                Self::Map(map) => {
+                    map.shrink_to_fit();
+                    for (key, val) in map.iter_mut() {
+                        stack.push(key);
+                        stack.push(val);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Returns the name of the variant of this `Value`, for example `"Integer"` or `"Map"`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmpv::Value;
+    ///
+    /// assert_eq!("Nil", Value::Nil.type_name());
+    /// assert_eq!("Array", Value::Array(vec![]).type_name());
+    /// ```
+    #[must_use]
+    pub const fn type_name(&self) -> &'static str {
+        match *self {
+            Self::Nil => "Nil",
+            Self::Boolean(..) => "Boolean",
+            Self::Integer(..) => "Integer",
+            Self::F32(..) => "F32",
+            Self::F64(..) => "F64",
+            Self::String(..) => "String",
+            Self::Binary(..) => "Binary",
+            Self::Array(..) => "Array",
+            Self::Map(..) => "Map",
+            Self::Ext(..) => "Ext",
+        }
+    }
+
+    /// If the `Value` is a Boolean, returns the associated bool.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `TypeError` describing the actual variant otherwise.
+    #[inline]
+    pub fn try_as_bool(&self) -> Result<bool, TypeError> {
+        self.as_bool().ok_or_else(|| TypeError::new("bool", self))
+    }
+
+    /// If the `Value` is an integer, return or cast it to a i64.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `TypeError` describing the actual variant otherwise, including integers that do
+    /// not fit into an i64.
+    #[inline]
+    pub fn try_as_i64(&self) -> Result<i64, TypeError> {
+        self.as_i64().ok_or_else(|| TypeError::new("i64", self))
+    }
+
+    /// If the `Value` is an integer, return or cast it to a u64.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `TypeError` describing the actual variant otherwise, including negative
+    /// integers.
+    #[inline]
+    pub fn try_as_u64(&self) -> Result<u64, TypeError> {
+        self.as_u64().ok_or_else(|| TypeError::new("u64", self))
+    }
+
+    /// If the `Value` is a number, return or cast it to a f64.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `TypeError` describing the actual variant otherwise.
+    #[inline]
+    pub fn try_as_f64(&self) -> Result<f64, TypeError> {
+        self.as_f64().ok_or_else(|| TypeError::new("f64", self))
+    }
+
+    /// If the `Value` is a String, returns the associated str.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `TypeError` describing the actual variant otherwise, including strings that
+    /// are not valid UTF-8.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmpv::Value;
+    ///
+    /// assert_eq!(Ok("le message"), Value::String("le message".into()).try_as_str());
+    ///
+    /// assert!(Value::Nil.try_as_str().is_err());
+    /// ```
+    #[inline]
+    pub fn try_as_str(&self) -> Result<&str, TypeError> {
+        self.as_str().ok_or_else(|| TypeError::new("str", self))
+    }
+
+    /// If the `Value` is a Binary or a String, returns the associated slice.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `TypeError` describing the actual variant otherwise.
+    #[inline]
+    pub fn try_as_slice(&self) -> Result<&[u8], TypeError> {
+        self.as_slice().ok_or_else(|| TypeError::new("binary", self))
+    }
+
+    /// If the `Value` is an Array, returns the associated vector.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `TypeError` describing the actual variant otherwise.
+    #[inline]
+    pub fn try_as_array(&self) -> Result<&Vec<Self>, TypeError> {
+        self.as_array().ok_or_else(|| TypeError::new("array", self))
+    }
+
+    /// If the `Value` is a Map, returns the associated vector of key-value tuples.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `TypeError` describing the actual variant otherwise.
+    #[inline]
+    pub fn try_as_map(&self) -> Result<&Vec<(Self, Self)>, TypeError> {
+        self.as_map().ok_or_else(|| TypeError::new("map", self))
+    }
+
+    /// If the `Value` is an Ext, returns the associated tuple with a ty and slice.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `TypeError` describing the actual variant otherwise.
+    #[inline]
+    pub fn try_as_ext(&self) -> Result<(i8, &[u8]), TypeError> {
+        self.as_ext().ok_or_else(|| TypeError::new("ext", self))
+    }
+
+    /// If the `Value` is a valid UTF-8 String, returns the owned string.
+    ///
+    /// # Errors
+    ///
+    /// Returns the original `Value` otherwise.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmpv::Value;
+    ///
+    /// assert_eq!(Ok("le message".to_string()), Value::from("le message").into_str());
+    ///
+    /// assert_eq!(Err(Value::Nil), Value::Nil.into_str());
+    /// ```
+    #[inline]
+    pub fn into_str(self) -> Result<String, Self> {
+        String::try_from(self)
+    }
+
+    /// If the `Value` is a Binary, returns the owned byte vector.
+    ///
+    /// # Errors
+    ///
+    /// Returns the original `Value` otherwise.
+    #[inline]
+    pub fn into_bin(self) -> Result<Vec<u8>, Self> {
+        Vec::<u8>::try_from(self)
+    }
+
+    /// If the `Value` is an Array, returns the owned vector.
+    ///
+    /// # Errors
+    ///
+    /// Returns the original `Value` otherwise.
+    #[inline]
+    pub fn into_array(self) -> Result<Vec<Self>, Self> {
+        Vec::<Self>::try_from(self)
+    }
+
+    /// If the `Value` is a Map, returns the owned vector of key-value tuples.
+    ///
+    /// # Errors
+    ///
+    /// Returns the original `Value` otherwise.
+    #[inline]
+    pub fn into_map(self) -> Result<Vec<(Self, Self)>, Self> {
+        Vec::<(Self, Self)>::try_from(self)
+    }
+
+    /// If the `Value` is an Array, returns an iterator over its owned elements.
+    ///
+    /// `Value` does not implement `IntoIterator` itself, as arrays and maps yield different
+    /// items.
+    ///
+    /// # Errors
+    ///
+    /// Returns the original `Value` otherwise.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmpv::Value;
+    ///
+    /// let val = Value::Array(vec![Value::from(1), Value::from(2)]);
+    ///
+    /// let mut sum = 0;
+    /// for item in val.into_array_iter().unwrap() {
+    ///     sum += item.as_u64().unwrap();
+    /// }
+    /// assert_eq!(3, sum);
+    ///
+    /// assert_eq!(Err(Value::Nil), Value::Nil.into_array_iter().map(|_| ()));
+    /// ```
+    #[inline]
+    pub fn into_array_iter(self) -> Result<std::vec::IntoIter<Self>, Self> {
+        self.into_array().map(IntoIterator::into_iter)
+    }
+
+    /// If the `Value` is a Map, returns an iterator over its owned key-value tuples.
+    ///
+    /// # Errors
+    ///
+    /// Returns the original `Value` otherwise.
+    #[inline]
+    pub fn into_map_iter(self) -> Result<std::vec::IntoIter<(Self, Self)>, Self> {
+        self.into_map().map(IntoIterator::into_iter)
+    }
+
+    /// If the `Value` is an Array, returns an iterator over its elements. Returns None otherwise.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmpv::Value;
+    ///
+    /// let val = Value::Array(vec![Value::from("a"), Value::from("b")]);
+    /// let strs: Vec<_> = val.iter_array().unwrap().filter_map(Value::as_str).collect();
+    /// assert_eq!(vec!["a", "b"], strs);
+    ///
+    /// assert!(Value::Nil.iter_array().is_none());
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn iter_array(&self) -> Option<std::slice::Iter<'_, Self>> {
+        self.as_array().map(|vec| vec.iter())
+    }
+
+    /// If the `Value` is a Map, returns an iterator over its key-value tuples. Returns None
+    /// otherwise.
+    #[inline]
+    #[must_use]
+    pub fn iter_map(&self) -> Option<std::slice::Iter<'_, (Self, Self)>> {
+        self.as_map().map(|map| map.iter())
+    }
+
+    /// If the `Value` is an Ext, returns the associated tuple with a ty and the owned data.
+    ///
+    /// # Errors
+    ///
+    /// Returns the original `Value` otherwise.
+    #[inline]
+    pub fn into_ext(self) -> Result<(i8, Vec<u8>), Self> {
+        match self {
+            Self::Ext(ty, buf) => Ok((ty, buf)),
+            v => Err(v),
+        }
+    }
+
+    /// Encodes this value into the given writer, like [`encode::write_value`] does.
+    ///
+    /// # Errors
+    ///
+    /// Returns any I/O error occurred while writing.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmpv::Value;
+    ///
+    /// let mut buf = Vec::new();
+    /// Value::from("a").write_to(&mut buf).unwrap();
+    /// Value::from(1).write_to(&mut buf).unwrap();
+    ///
+    /// assert_eq!(vec![0xa1, 0x61, 0x01], buf);
+    /// ```
+    #[inline]
+    pub fn write_to<W: Write>(&self, wr: &mut W) -> io::Result<()> {
+        Ok(encode::write_value(wr, self)?)
+    }
+
+    /// Encodes this value into the given writer using the given options, like
+    /// [`encode::write_value_with`] does.
+    ///
+    /// # Errors
+    ///
+    /// Returns any I/O error occurred while writing.
+    #[inline]
+    pub fn write_to_with<W: Write>(&self, wr: &mut W, options: encode::WriteOptions) -> io::Result<()> {
+        Ok(encode::write_value_with(wr, self, options)?)
+    }
+
+    /// Feeds the canonical encoding of this value, as written by
+    /// [`encode::write_value_canonical`], into the given hasher, without collecting it into a
+    /// buffer first.
+    ///
+    /// The bytes are fed in pieces, as they are written, so the hash is only guaranteed to match
+    /// hashing the whole encoding at once for hashers whose result does not depend on how their
+    /// input is split. Equal values, and values which only differ by the
+    /// order of their map entries, always produce the same hash.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::collections::hash_map::DefaultHasher;
+    /// use std::hash::Hasher;
+    ///
+    /// use rmpv::Value;
+    ///
+    /// let hash = |val: &Value| {
+    ///     let mut hasher = DefaultHasher::new();
+    ///     val.canonical_hash(&mut hasher);
+    ///     hasher.finish()
+    /// };
+    ///
+    /// let a = Value::Map(vec![(Value::from("a"), Value::from(1)), (Value::from("b"), Value::Nil)]);
+    /// let b = Value::Map(vec![(Value::from("b"), Value::Nil), (Value::from("a"), Value::from(1))]);
+    /// assert_eq!(hash(&a), hash(&b));
+    /// ```
+    pub fn canonical_hash<H: Hasher>(&self, hasher: &mut H) {
+        // Writing into a hasher can not fail.
+        let _ = encode::write_value_canonical(&mut HashWriter(hasher), self);
+    }
+}
+
+/// Feeds everything written into a hasher.
+struct HashWriter<'a, H>(&'a mut H);
+
+impl<H: Hasher> Write for HashWriter<'_, H> {
+    #[inline]
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.write(buf);
+        Ok(buf.len())
+    }
+
+    #[inline]
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
 }
 
+/// The ext type of MessagePack timestamps.
+const TIMESTAMP_EXT_TYPE: i8 = -1;
+const NANOS_PER_SEC: u32 = 1_000_000_000;
+
 static NIL: Value = Value::Nil;
 static NIL_REF: ValueRef<'static> = ValueRef::Nil;
 
@@ -933,19 +1782,7 @@ impl Index<usize> for Value {
 impl Index<&str> for Value {
     type Output = Self;
     fn index(&self, index: &str) -> &Self {
-        if let Self::Map(ref map) = *self {
-            if let Some(found) = map.iter().find(|(key, _val)| {
-                if let Self::String(ref strval) = *key {
-                    if let Some(s) = strval.as_str() {
-                        if s == index { return true; }
-                    }
-                }
-                false
-            }) {
-                return &found.1;
-            }
-        }
-        &NIL
+        self.map_get(index).unwrap_or(&NIL)
     }
 }
 
@@ -1026,6 +1863,42 @@ impl From<isize> for Value {
     }
 }
 
+/// Converts to an [`Integer`](crate::Value::Integer) if the value fits into a `u64` or an `i64`,
+/// and otherwise to a 16-byte [`Binary`](crate::Value::Binary) holding the value in big-endian.
+///
+/// This is the layout `rmp-serde` uses for 128-bit integers, but `rmp-serde` writes every `u128` as
+/// such a binary, even the small ones, so only values beyond the range of a `u64` are converted to
+/// the same encoding.
+impl From<u128> for Value {
+    #[inline]
+    fn from(v: u128) -> Self {
+        match u64::try_from(v) {
+            Ok(v) => Self::from(v),
+            Err(..) => Self::Binary(v.to_be_bytes().into()),
+        }
+    }
+}
+
+/// Converts to an [`Integer`](crate::Value::Integer) if the value fits into a `u64` or an `i64`,
+/// and otherwise to a 16-byte [`Binary`](crate::Value::Binary) holding the value in big-endian
+/// two's complement.
+///
+/// This is the layout `rmp-serde` uses for 128-bit integers, but `rmp-serde` writes every `i128` as
+/// such a binary, even the small ones, so only values beyond the range of a `u64` and an `i64` are
+/// converted to the same encoding.
+impl From<i128> for Value {
+    #[inline]
+    fn from(v: i128) -> Self {
+        if let Ok(v) = u64::try_from(v) {
+            Self::from(v)
+        } else if let Ok(v) = i64::try_from(v) {
+            Self::from(v)
+        } else {
+            Self::Binary(v.to_be_bytes().into())
+        }
+    }
+}
+
 impl From<f32> for Value {
     #[inline]
     fn from(v: f32) -> Self {
@@ -1096,6 +1969,37 @@ impl From<Vec<(Self, Self)>> for Value {
     }
 }
 
+/// Converts to [`Nil`](crate::Value::Nil) for `None`, and to the converted value otherwise.
+impl<T: Into<Self>> From<Option<T>> for Value {
+    #[inline]
+    fn from(v: Option<T>) -> Self {
+        v.map_or(Self::Nil, Into::into)
+    }
+}
+
+/// Converts to a [`Map`](crate::Value::Map) with the entries in the iteration order of the
+/// `HashMap`, which is unspecified.
+impl<K, V, S> From<HashMap<K, V, S>> for Value
+where
+    K: Into<Self>,
+    V: Into<Self>,
+{
+    fn from(v: HashMap<K, V, S>) -> Self {
+        Self::Map(v.into_iter().map(|(k, v)| (k.into(), v.into())).collect())
+    }
+}
+
+/// Converts to a [`Map`](crate::Value::Map) with the entries in ascending order of their keys.
+impl<K, V> From<BTreeMap<K, V>> for Value
+where
+    K: Into<Self>,
+    V: Into<Self>,
+{
+    fn from(v: BTreeMap<K, V>) -> Self {
+        Self::Map(v.into_iter().map(|(k, v)| (k.into(), v.into())).collect())
+    }
+}
+
 /// Note that an `Iterator<Item = u8>` will be collected into an
 /// [`Array`](crate::Value::Array), rather than a
 /// [`Binary`](crate::Value::Binary)
@@ -1109,6 +2013,46 @@ where
     }
 }
 
+/// Collects key-value pairs into a [`Map`](crate::Value::Map), keeping their order and any
+/// duplicate keys.
+impl FromIterator<(Value, Value)> for Value {
+    fn from_iter<I: IntoIterator<Item = (Value, Value)>>(iter: I) -> Self {
+        Self::Map(iter.into_iter().collect())
+    }
+}
+
+/// Appends elements to an [`Array`](crate::Value::Array).
+///
+/// # Panics
+///
+/// Panics if the value is not an `Array`.
+impl<V> Extend<V> for Value
+where
+    V: Into<Self>,
+{
+    fn extend<I: IntoIterator<Item = V>>(&mut self, iter: I) {
+        match self {
+            Self::Array(array) => array.extend(iter.into_iter().map(Into::into)),
+            other => panic!("can not extend {} with array elements", other.type_name()),
+        }
+    }
+}
+
+/// Appends key-value pairs to a [`Map`](crate::Value::Map), without replacing existing entries
+/// with equal keys.
+///
+/// # Panics
+///
+/// Panics if the value is not a `Map`.
+impl Extend<(Value, Value)> for Value {
+    fn extend<I: IntoIterator<Item = (Value, Value)>>(&mut self, iter: I) {
+        match self {
+            Self::Map(map) => map.extend(iter),
+            other => panic!("can not extend {} with map entries", other.type_name()),
+        }
+    }
+}
+
 impl TryFrom<Value> for u64 {
     type Error = Value;
 
@@ -1273,7 +2217,7 @@ pub enum ValueRef<'a> {
     Ext(i8, &'a [u8]),
 }
 
-impl ValueRef<'_> {
+impl<'a> ValueRef<'a> {
     /// Converts the current non-owning value to an owned Value.
     ///
     /// This is achieved by deep copying all underlying structures and borrowed buffers.
@@ -1378,6 +2322,38 @@ impl ValueRef<'_> {
             None
         }
     }
+
+    /// Returns true if the `ValueRef` is an Ext. Returns false otherwise.
+    #[inline]
+    #[must_use]
+    pub fn is_ext(&self) -> bool {
+        self.as_ext().is_some()
+    }
+
+    /// If the `ValueRef` is an Ext, returns the associated tuple with a ty and slice.
+    /// Returns None otherwise.
+    ///
+    /// The slice borrows from the original buffer rather than from this `ValueRef`, so it may
+    /// outlive it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmpv::ValueRef;
+    ///
+    /// assert_eq!(Some((42, &[1, 2, 3][..])), ValueRef::Ext(42, &[1, 2, 3]).as_ext());
+    ///
+    /// assert_eq!(None, ValueRef::Boolean(true).as_ext());
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn as_ext(&self) -> Option<(i8, &'a [u8])> {
+        if let ValueRef::Ext(ty, buf) = *self {
+            Some((ty, buf))
+        } else {
+            None
+        }
+    }
 }
 
 impl From<u8> for ValueRef<'_> {
@@ -0,0 +1,210 @@
+//! Access to the nodes of a [`Value`] tree by JSON Pointer, see [`Value::pointer`],
+//! [`Value::pointer_mut`] and [`Value::set_at`].
+
+use std::fmt::{self, Display};
+
+use crate::Value;
+
+/// An error returned by [`Value::set_at`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PointerError {
+    /// The pointer is neither empty nor starts with `/`, or has a `~` not followed by `0` or `1`.
+    Syntax,
+    /// The node at the given pointer, a prefix of the one being set, is neither an array nor a
+    /// map, so it can not have children.
+    NotAContainer(String),
+    /// The last segment of the given pointer, a prefix of the one being set, is neither `-` nor
+    /// the index of an element of the array, or of the end of the array.
+    InvalidIndex(String),
+}
+
+impl Display for PointerError {
+    #[cold]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        match *self {
+            Self::Syntax => f.write_str("invalid JSON pointer"),
+            Self::NotAContainer(ref pointer) => write!(f, "value at \"{pointer}\" is neither an array nor a map"),
+            Self::InvalidIndex(ref pointer) => write!(f, "\"{pointer}\" is not a valid array index"),
+        }
+    }
+}
+
+impl std::error::Error for PointerError {}
+
+impl Value {
+    /// Returns the node of this value at the given JSON Pointer, as defined by RFC 6901, or
+    /// `None` if there is none or the pointer is not valid.
+    ///
+    /// The empty pointer is this value itself, and each segment of the form `/segment` steps into
+    /// the array element at that index, or the value of the first map entry with that string key.
+    /// In segments, `~1` stands for `/` and `~0` for `~`. Keys of other types can not be reached.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmpv::Value;
+    ///
+    /// let val = Value::Map(vec![
+    ///     (Value::from("servers"), Value::Array(vec![Value::Map(vec![
+    ///         (Value::from("host"), Value::from("localhost")),
+    ///     ])])),
+    ///     (Value::from("a/b"), Value::from(42)),
+    /// ]);
+    ///
+    /// assert_eq!(Some(&Value::from("localhost")), val.pointer("/servers/0/host"));
+    /// assert_eq!(Some(&Value::from(42)), val.pointer("/a~1b"));
+    /// assert_eq!(Some(&val), val.pointer(""));
+    /// assert_eq!(None, val.pointer("/servers/1"));
+    /// ```
+    #[must_use]
+    pub fn pointer(&self, pointer: &str) -> Option<&Self> {
+        let mut node = self;
+        for (segment, _) in parse(pointer).ok()? {
+            node = match *node {
+                Self::Map(ref map) => &map.iter().find(|(key, _)| key.as_str() == Some(&*segment))?.1,
+                Self::Array(ref vec) => vec.get(index(&segment)?)?,
+                _ => return None,
+            };
+        }
+        Some(node)
+    }
+
+    /// Returns a mutable reference to the node of this value at the given JSON Pointer, see
+    /// [`pointer`](Self::pointer).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmpv::Value;
+    ///
+    /// let mut val = Value::Array(vec![Value::from(1), Value::from(2)]);
+    ///
+    /// *val.pointer_mut("/1").unwrap() = Value::from(3);
+    ///
+    /// assert_eq!(Value::Array(vec![Value::from(1), Value::from(3)]), val);
+    /// ```
+    #[must_use]
+    pub fn pointer_mut(&mut self, pointer: &str) -> Option<&mut Self> {
+        let mut node = self;
+        for (segment, _) in parse(pointer).ok()? {
+            node = match *node {
+                Self::Map(ref mut map) => &mut map.iter_mut().find(|(key, _)| key.as_str() == Some(&*segment))?.1,
+                Self::Array(ref mut vec) => vec.get_mut(index(&segment)?)?,
+                _ => return None,
+            };
+        }
+        Some(node)
+    }
+
+    /// Sets the node of this value at the given JSON Pointer, see [`pointer`](Self::pointer),
+    /// creating it and its missing parents as needed.
+    ///
+    /// A missing map entry is appended to the map, with the segment as its key. In an array, the
+    /// segment `-` or the index of the end of the array appends an element. A missing parent is
+    /// created as an empty array if the segment stepping into it is `-` or `0`, and as an empty
+    /// map otherwise.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PointerError::Syntax` if the pointer is not valid, `PointerError::NotAContainer`
+    /// if one of the existing nodes along the path, including `Nil`, is neither an array nor a
+    /// map, and `PointerError::InvalidIndex` if a segment stepping into an existing array is not
+    /// an index of it, of its end, or `-`. In every case this value is left unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmpv::{PointerError, Value};
+    ///
+    /// let mut val = Value::Map(vec![(Value::from("name"), Value::from("le message"))]);
+    ///
+    /// val.set_at("/name", Value::from("hello")).unwrap();
+    /// val.set_at("/servers/-/host", Value::from("localhost")).unwrap();
+    ///
+    /// assert_eq!(Value::Map(vec![
+    ///     (Value::from("name"), Value::from("hello")),
+    ///     (Value::from("servers"), Value::Array(vec![Value::Map(vec![
+    ///         (Value::from("host"), Value::from("localhost")),
+    ///     ])])),
+    /// ]), val);
+    ///
+    /// let err = val.set_at("/name/first", Value::from("le")).unwrap_err();
+    /// assert_eq!(PointerError::NotAContainer("/name".into()), err);
+    /// ```
+    pub fn set_at(&mut self, pointer: &str, value: Self) -> Result<(), PointerError> {
+        let segments = parse(pointer)?;
+        let mut node = self;
+        let mut start = 0;
+        for (idx, (segment, end)) in segments.iter().enumerate() {
+            // The parent of a node being created is the only one to check its segment, so the
+            // created node already holds the right container for the next one.
+            let child = match segments.get(idx + 1) {
+                Some((next, _)) if next == "-" || next == "0" => Self::Array(Vec::new()),
+                Some(..) => Self::Map(Vec::new()),
+                None => Self::Nil,
+            };
+            node = match *node {
+                Self::Map(ref mut map) => match map.iter().position(|(key, _)| key.as_str() == Some(&**segment)) {
+                    Some(pos) => &mut map[pos].1,
+                    None => {
+                        map.push((Self::from(segment.as_str()), child));
+                        &mut map.last_mut().unwrap().1
+                    }
+                },
+                Self::Array(ref mut vec) => {
+                    let pos = match &**segment {
+                        "-" => vec.len(),
+                        segment => index(segment)
+                            .filter(|&pos| pos <= vec.len())
+                            .ok_or_else(|| PointerError::InvalidIndex(pointer[..*end].into()))?,
+                    };
+                    if pos == vec.len() {
+                        vec.push(child);
+                    }
+                    &mut vec[pos]
+                }
+                _ => return Err(PointerError::NotAContainer(pointer[..start].into())),
+            };
+            start = *end;
+        }
+        *node = value;
+        Ok(())
+    }
+}
+
+/// Splits the pointer into its unescaped segments, each with the offset of its end in the
+/// pointer.
+fn parse(pointer: &str) -> Result<Vec<(String, usize)>, PointerError> {
+    if pointer.is_empty() {
+        return Ok(Vec::new());
+    }
+    let rest = pointer.strip_prefix('/').ok_or(PointerError::Syntax)?;
+
+    let mut end = 0;
+    rest.split('/')
+        .map(|raw| {
+            end += 1 + raw.len();
+            let mut segment = String::with_capacity(raw.len());
+            let mut chars = raw.chars();
+            while let Some(c) = chars.next() {
+                segment.push(match c {
+                    '~' => match chars.next() {
+                        Some('0') => '~',
+                        Some('1') => '/',
+                        _ => return Err(PointerError::Syntax),
+                    },
+                    c => c,
+                });
+            }
+            Ok((segment, end))
+        })
+        .collect()
+}
+
+/// Parses an array index, which has no leading zeros.
+fn index(segment: &str) -> Option<usize> {
+    if segment.len() > 1 && segment.starts_with('0') || !segment.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    segment.parse().ok()
+}
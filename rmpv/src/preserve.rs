@@ -0,0 +1,280 @@
+//! Decoding and re-encoding a [`Value`] with its original markers, see [`PreservedValue`].
+
+use std::io::{self, Read, Write};
+
+use rmp::Marker;
+
+use crate::decode::{self, value::read_value_inner};
+use crate::encode;
+use crate::Value;
+
+/// A [`Value`] together with the marker each of its nodes was encoded with, as read by
+/// [`read_value_preserving`](decode::read_value_preserving).
+///
+/// A `Value` only keeps the data it holds, so re-encoding it with [`write_value`](encode::write_value)
+/// writes the smallest representation, such as a positive fixint for an integer which was sent as
+/// a `uint 64`. Writing a `PreservedValue` with
+/// [`write_value_preserving`](encode::write_value_preserving) reuses the original markers instead,
+/// so that a value which is decoded and re-encoded unchanged produces the same bytes.
+///
+/// The value may still be modified through [`value_mut`](Self::value_mut). Markers are reused,
+/// node by node in the order of encoding, for as long as they can represent the node they are used
+/// for: a `uint 8` can still encode an integer changed from 1 to 200, but not one changed to 300.
+/// The length of each array and map is recorded as well, so that adding or removing nodes is
+/// detected at the first container whose length differs, as the nodes after it would no longer
+/// line up with their markers. From the first node whose marker does not fit, or whose length
+/// changed, on, the rest of the value is written with the smallest representations.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PreservedValue {
+    value: Value,
+    markers: Vec<Marker>,
+    /// The lengths of the arrays and maps in the order of encoding.
+    lens: Vec<usize>,
+}
+
+impl PreservedValue {
+    /// Returns the value.
+    #[inline]
+    #[must_use]
+    pub fn value(&self) -> &Value {
+        &self.value
+    }
+
+    /// Returns the value, for modifying it in place.
+    #[inline]
+    #[must_use]
+    pub fn value_mut(&mut self) -> &mut Value {
+        &mut self.value
+    }
+
+    /// Returns the value, dropping the markers.
+    #[inline]
+    #[must_use]
+    pub fn into_value(self) -> Value {
+        self.value
+    }
+
+    /// Returns the markers of the nodes of the value in the order of encoding: each array before
+    /// its elements, and each map before its keys and values, alternating.
+    #[inline]
+    #[must_use]
+    pub fn markers(&self) -> &[Marker] {
+        &self.markers
+    }
+}
+
+/// Attempts to read a [`Value`] from the given reader like [`read_value`](decode::read_value)
+/// does, keeping the marker each of its nodes was encoded with.
+///
+/// # Errors
+///
+/// This function returns the same errors as [`read_value`](decode::read_value).
+///
+/// # Examples
+///
+/// ```
+/// use rmpv::Value;
+/// use rmpv::decode::read_value_preserving;
+/// use rmpv::encode::write_value_preserving;
+///
+/// // [uint 16 1, str8 "a"]
+/// let buf = [0x92, 0xcd, 0x00, 0x01, 0xd9, 0x01, 0x61];
+/// let val = read_value_preserving(&mut &buf[..]).unwrap();
+/// assert_eq!(&Value::Array(vec![Value::from(1), Value::from("a")]), val.value());
+///
+/// let mut out = Vec::new();
+/// write_value_preserving(&mut out, &val).unwrap();
+/// assert_eq!(buf, out[..]);
+///
+/// // Plain re-encoding uses the smallest representations.
+/// let mut out = Vec::new();
+/// rmpv::encode::write_value(&mut out, val.value()).unwrap();
+/// assert_eq!(vec![0x92, 0x01, 0xa1, 0x61], out);
+/// ```
+pub fn read_value_preserving<R: Read>(rd: &mut R) -> Result<PreservedValue, decode::Error> {
+    let mut rd = Recorder { rd, buf: Vec::new() };
+    let value = read_value_inner(&mut rd, decode::MAX_DEPTH as _, None)?;
+    let (markers, lens) = markers_of(&rd.buf);
+    Ok(PreservedValue { value, markers, lens })
+}
+
+/// Encodes the value with its original markers, see [`PreservedValue`].
+///
+/// # Errors
+///
+/// This function will return [`encode::Error`] on any I/O error occurred while writing.
+pub fn write_value_preserving<W: Write>(wr: &mut W, val: &PreservedValue) -> Result<(), encode::Error> {
+    MarkerWriter { markers: val.markers.iter(), lens: val.lens.iter(), diverged: false }.write(wr, &val.value)
+}
+
+/// Copies the bytes read from the underlying reader.
+struct Recorder<'a, R> {
+    rd: &'a mut R,
+    buf: Vec<u8>,
+}
+
+impl<R: Read> Read for Recorder<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let len = self.rd.read(buf)?;
+        self.buf.extend_from_slice(&buf[..len]);
+        Ok(len)
+    }
+}
+
+/// Returns the markers of the nodes of the well-formed value encoded in the buffer, and the lengths
+/// of its arrays and maps.
+fn markers_of(buf: &[u8]) -> (Vec<Marker>, Vec<usize>) {
+    let len_at = |pos: usize, size: usize| buf[pos..pos + size].iter().fold(0, |len, &b| len << 8 | usize::from(b));
+
+    let mut markers = Vec::new();
+    let mut lens = Vec::new();
+    let mut pos = 0;
+    while let Some(&byte) = buf.get(pos) {
+        let marker = Marker::from_u8(byte);
+        markers.push(marker);
+        pos += 1;
+        match marker {
+            Marker::FixArray(len) | Marker::FixMap(len) => lens.push(len.into()),
+            Marker::Array16 | Marker::Map16 => lens.push(len_at(pos, 2)),
+            Marker::Array32 | Marker::Map32 => lens.push(len_at(pos, 4)),
+            _ => {}
+        }
+        pos += match marker {
+            Marker::U8 | Marker::I8 => 1,
+            Marker::U16 | Marker::I16 | Marker::Array16 | Marker::Map16 => 2,
+            Marker::U32 | Marker::I32 | Marker::F32 | Marker::Array32 | Marker::Map32 => 4,
+            Marker::U64 | Marker::I64 | Marker::F64 => 8,
+            Marker::FixStr(len) => len.into(),
+            Marker::Str8 | Marker::Bin8 => 1 + len_at(pos, 1),
+            Marker::Str16 | Marker::Bin16 => 2 + len_at(pos, 2),
+            Marker::Str32 | Marker::Bin32 => 4 + len_at(pos, 4),
+            Marker::FixExt1 => 1 + 1,
+            Marker::FixExt2 => 1 + 2,
+            Marker::FixExt4 => 1 + 4,
+            Marker::FixExt8 => 1 + 8,
+            Marker::FixExt16 => 1 + 16,
+            Marker::Ext8 => 1 + 1 + len_at(pos, 1),
+            Marker::Ext16 => 2 + 1 + len_at(pos, 2),
+            Marker::Ext32 => 4 + 1 + len_at(pos, 4),
+            _ => 0,
+        };
+    }
+    (markers, lens)
+}
+
+struct MarkerWriter<'a> {
+    markers: std::slice::Iter<'a, Marker>,
+    lens: std::slice::Iter<'a, usize>,
+    /// Set once a marker did not fit its node or a length changed, after which no more markers are used.
+    diverged: bool,
+}
+
+impl MarkerWriter<'_> {
+    fn write<W: Write>(&mut self, wr: &mut W, val: &Value) -> Result<(), encode::Error> {
+        let header = match self.markers.next() {
+            Some(&marker) if !self.diverged => header(marker, val),
+            _ => None,
+        };
+        // A container whose length changed shifts every following node away from its marker.
+        let header = header.filter(|_| match *val {
+            Value::Array(ref vec) => self.lens.next() == Some(&vec.len()),
+            Value::Map(ref map) => self.lens.next() == Some(&map.len()),
+            _ => true,
+        });
+        let Some(header) = header else {
+            self.diverged = true;
+            return encode::write_value(wr, val);
+        };
+        wr.write_all(&header).map_err(encode::Error::InvalidMarkerWrite)?;
+
+        match *val {
+            Value::String(ref s) => wr.write_all(s.as_bytes()).map_err(encode::Error::InvalidDataWrite)?,
+            Value::Binary(ref data) | Value::Ext(_, ref data) => wr.write_all(data).map_err(encode::Error::InvalidDataWrite)?,
+            Value::Array(ref vec) => {
+                for val in vec {
+                    self.write(wr, val)?;
+                }
+            }
+            Value::Map(ref map) => {
+                for (key, val) in map {
+                    self.write(wr, key)?;
+                    self.write(wr, val)?;
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+/// Returns the encoding of the marker and the fixed-size data following it, or `None` if the
+/// marker can not represent the value.
+fn header(marker: Marker, val: &Value) -> Option<Vec<u8>> {
+    let m = marker.to_u8();
+    match (marker, val) {
+        (Marker::Null, Value::Nil) => Some(vec![m]),
+        (Marker::True | Marker::False, &Value::Boolean(val)) => {
+            Some(vec![if val { Marker::True } else { Marker::False }.to_u8()])
+        }
+        (_, Value::Integer(n)) => {
+            let n = n.as_u64().map_or_else(|| n.as_i64().map(i128::from), |n| Some(n.into()))?;
+            int_header(marker, n)
+        }
+        (Marker::F32, &Value::F32(val)) => Some([&[m][..], &val.to_be_bytes()].concat()),
+        (Marker::F64, &Value::F64(val)) => Some([&[m][..], &val.to_be_bytes()].concat()),
+        (Marker::FixStr(..) | Marker::Str8 | Marker::Str16 | Marker::Str32, Value::String(s)) => {
+            len_header(marker, s.as_bytes().len())
+        }
+        (Marker::Bin8 | Marker::Bin16 | Marker::Bin32, Value::Binary(data)) |
+        (Marker::Ext8 | Marker::Ext16 | Marker::Ext32, Value::Ext(_, data)) => len_header(marker, data.len()),
+        (Marker::FixArray(..) | Marker::Array16 | Marker::Array32, Value::Array(vec)) => len_header(marker, vec.len()),
+        (Marker::FixMap(..) | Marker::Map16 | Marker::Map32, Value::Map(map)) => len_header(marker, map.len()),
+        (Marker::FixExt1, Value::Ext(_, data)) if data.len() == 1 => Some(vec![m]),
+        (Marker::FixExt2, Value::Ext(_, data)) if data.len() == 2 => Some(vec![m]),
+        (Marker::FixExt4, Value::Ext(_, data)) if data.len() == 4 => Some(vec![m]),
+        (Marker::FixExt8, Value::Ext(_, data)) if data.len() == 8 => Some(vec![m]),
+        (Marker::FixExt16, Value::Ext(_, data)) if data.len() == 16 => Some(vec![m]),
+        _ => None,
+    }
+    .map(|mut header| {
+        // The type of an ext follows its length.
+        if let Value::Ext(ty, _) = *val {
+            header.push(ty as u8);
+        }
+        header
+    })
+}
+
+fn int_header(marker: Marker, n: i128) -> Option<Vec<u8>> {
+    let m = marker.to_u8();
+    Some(match marker {
+        Marker::FixPos(..) if (0..=0x7f).contains(&n) => vec![n as u8],
+        Marker::FixNeg(..) if (-32..0).contains(&n) => vec![n as u8],
+        Marker::U8 => vec![m, u8::try_from(n).ok()?],
+        Marker::U16 => [&[m][..], &u16::try_from(n).ok()?.to_be_bytes()].concat(),
+        Marker::U32 => [&[m][..], &u32::try_from(n).ok()?.to_be_bytes()].concat(),
+        Marker::U64 => [&[m][..], &u64::try_from(n).ok()?.to_be_bytes()].concat(),
+        Marker::I8 => [&[m][..], &i8::try_from(n).ok()?.to_be_bytes()].concat(),
+        Marker::I16 => [&[m][..], &i16::try_from(n).ok()?.to_be_bytes()].concat(),
+        Marker::I32 => [&[m][..], &i32::try_from(n).ok()?.to_be_bytes()].concat(),
+        Marker::I64 => [&[m][..], &i64::try_from(n).ok()?.to_be_bytes()].concat(),
+        _ => return None,
+    })
+}
+
+fn len_header(marker: Marker, len: usize) -> Option<Vec<u8>> {
+    let m = marker.to_u8();
+    Some(match marker {
+        Marker::FixStr(..) if len < 32 => vec![Marker::FixStr(len as u8).to_u8()],
+        Marker::FixArray(..) if len < 16 => vec![Marker::FixArray(len as u8).to_u8()],
+        Marker::FixMap(..) if len < 16 => vec![Marker::FixMap(len as u8).to_u8()],
+        Marker::Str8 | Marker::Bin8 | Marker::Ext8 => vec![m, u8::try_from(len).ok()?],
+        Marker::Str16 | Marker::Bin16 | Marker::Ext16 | Marker::Array16 | Marker::Map16 => {
+            [&[m][..], &u16::try_from(len).ok()?.to_be_bytes()].concat()
+        }
+        Marker::Str32 | Marker::Bin32 | Marker::Ext32 | Marker::Array32 | Marker::Map32 => {
+            [&[m][..], &u32::try_from(len).ok()?.to_be_bytes()].concat()
+        }
+        _ => return None,
+    })
+}
@@ -0,0 +1,253 @@
+//! Validation of the shape of a [`Value`], see [`Schema`].
+
+use std::fmt::{self, Display, Formatter, Write};
+
+use crate::diff::push_key;
+use crate::{TypeError, Value};
+
+/// The expected shape of a [`Value`], such as a map with some required string keys, against which
+/// values can be validated with [`validate`](Self::validate).
+///
+/// This is a small structural validator rather than a JSON Schema implementation: it checks the
+/// types of values and the presence of map keys, but not, for example, ranges or lengths.
+///
+/// # Examples
+///
+/// ```
+/// use rmpv::{Schema, ValidationErrorKind, Value};
+///
+/// let schema = Schema::object([
+///     ("name", Schema::string()),
+///     ("ports", Schema::array(Schema::integer())),
+/// ])
+/// .with_optional("debug", Schema::boolean());
+///
+/// let val = Value::Map(vec![
+///     (Value::from("name"), Value::from("server")),
+///     (Value::from("ports"), Value::Array(vec![Value::from(80), Value::from("443")])),
+/// ]);
+///
+/// let errors = schema.validate(&val).unwrap_err();
+/// assert_eq!(1, errors.len());
+/// assert_eq!("/ports/1", errors[0].path);
+/// assert_eq!("expected an integer, found String", errors[0].to_string());
+///
+/// let val = Value::Map(vec![(Value::from("debug"), Value::from(true))]);
+/// let errors = schema.validate(&val).unwrap_err();
+/// assert_eq!(ValidationErrorKind::MissingKey("name".into()), errors[0].kind);
+/// assert_eq!(ValidationErrorKind::MissingKey("ports".into()), errors[1].kind);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct Schema {
+    kind: Kind,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Kind {
+    Any,
+    Nil,
+    Boolean,
+    Integer,
+    Float,
+    String,
+    Binary,
+    Array(Box<Schema>),
+    Object(Vec<Field>),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Field {
+    key: String,
+    schema: Schema,
+    required: bool,
+}
+
+impl Schema {
+    /// Accepts any value.
+    #[inline]
+    #[must_use]
+    pub fn any() -> Self {
+        Self { kind: Kind::Any }
+    }
+
+    /// Accepts `Nil`.
+    #[inline]
+    #[must_use]
+    pub fn nil() -> Self {
+        Self { kind: Kind::Nil }
+    }
+
+    /// Accepts booleans.
+    #[inline]
+    #[must_use]
+    pub fn boolean() -> Self {
+        Self { kind: Kind::Boolean }
+    }
+
+    /// Accepts integers.
+    #[inline]
+    #[must_use]
+    pub fn integer() -> Self {
+        Self { kind: Kind::Integer }
+    }
+
+    /// Accepts `F32` and `F64` floats, but not integers.
+    #[inline]
+    #[must_use]
+    pub fn float() -> Self {
+        Self { kind: Kind::Float }
+    }
+
+    /// Accepts strings that are valid UTF-8.
+    #[inline]
+    #[must_use]
+    pub fn string() -> Self {
+        Self { kind: Kind::String }
+    }
+
+    /// Accepts binaries.
+    #[inline]
+    #[must_use]
+    pub fn binary() -> Self {
+        Self { kind: Kind::Binary }
+    }
+
+    /// Accepts arrays whose elements are all accepted by the given schema.
+    #[inline]
+    #[must_use]
+    pub fn array(element: Self) -> Self {
+        Self { kind: Kind::Array(Box::new(element)) }
+    }
+
+    /// Accepts maps which have all of the given string keys, with values accepted by the schema
+    /// given for each key.
+    ///
+    /// Other keys are allowed, and their values are not checked. If a key appears more than once,
+    /// only its first entry is checked, like [`Value::map_get`] finds it.
+    #[must_use]
+    pub fn object<K, I>(fields: I) -> Self
+    where
+        K: Into<String>,
+        I: IntoIterator<Item = (K, Self)>,
+    {
+        let fields = fields
+            .into_iter()
+            .map(|(key, schema)| Field { key: key.into(), schema, required: true })
+            .collect();
+        Self { kind: Kind::Object(fields) }
+    }
+
+    /// Adds a key to a schema created with [`object`](Self::object) which may be missing, but
+    /// whose value must be accepted by the given schema when present.
+    ///
+    /// # Panics
+    ///
+    /// Panics if this schema is not an object.
+    #[must_use]
+    pub fn with_optional(mut self, key: impl Into<String>, schema: Self) -> Self {
+        match self.kind {
+            Kind::Object(ref mut fields) => fields.push(Field { key: key.into(), schema, required: false }),
+            _ => panic!("optional keys can only be added to an object schema"),
+        }
+        self
+    }
+
+    /// Checks that the value is accepted by this schema.
+    ///
+    /// # Errors
+    ///
+    /// Returns every mismatch found, in the order of the schema. Once a value has the wrong type,
+    /// the values nested in it are not checked.
+    pub fn validate(&self, val: &Value) -> Result<(), Vec<ValidationError>> {
+        let mut errors = Vec::new();
+        self.validate_into(&mut String::new(), val, &mut errors);
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    fn validate_into(&self, path: &mut String, val: &Value, errors: &mut Vec<ValidationError>) {
+        let expected = match (&self.kind, val) {
+            (Kind::Any, _) |
+            (Kind::Nil, Value::Nil) |
+            (Kind::Boolean, Value::Boolean(..)) |
+            (Kind::Integer, Value::Integer(..)) |
+            (Kind::Float, Value::F32(..) | Value::F64(..)) |
+            (Kind::Binary, Value::Binary(..)) => return,
+            (Kind::String, val) if val.is_str() => return,
+            (Kind::Array(element), Value::Array(vec)) => {
+                for (idx, val) in vec.iter().enumerate() {
+                    let len = path.len();
+                    write!(path, "/{idx}").unwrap();
+                    element.validate_into(path, val, errors);
+                    path.truncate(len);
+                }
+                return;
+            }
+            (Kind::Object(fields), Value::Map(..)) => {
+                for field in fields {
+                    let key = Value::from(field.key.as_str());
+                    let len = push_key(path, &key);
+                    match val.map_get(&field.key) {
+                        Some(val) => field.schema.validate_into(path, val, errors),
+                        None if field.required => errors.push(ValidationError {
+                            path: path[..len].to_owned(),
+                            kind: ValidationErrorKind::MissingKey(field.key.clone()),
+                        }),
+                        None => {}
+                    }
+                    path.truncate(len);
+                }
+                return;
+            }
+            (Kind::Nil, _) => "nil",
+            (Kind::Boolean, _) => "a boolean",
+            (Kind::Integer, _) => "an integer",
+            (Kind::Float, _) => "a float",
+            (Kind::String, _) => "a string",
+            (Kind::Binary, _) => "a binary",
+            (Kind::Array(..), _) => "an array",
+            (Kind::Object(..), _) => "a map",
+        };
+        errors.push(ValidationError {
+            path: path.clone(),
+            kind: ValidationErrorKind::Type(TypeError::new(expected, val)),
+        });
+    }
+}
+
+/// A mismatch between a [`Value`] and a [`Schema`], as returned by [`Schema::validate`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct ValidationError {
+    /// The location of the mismatch as a JSON Pointer, in the same form as [`Change::path`]: the
+    /// path of the value with the wrong type, or of the map missing a key.
+    ///
+    /// [`Change::path`]: crate::Change::path
+    pub path: String,
+    /// What is wrong at that location.
+    pub kind: ValidationErrorKind,
+}
+
+/// The kind of a [`ValidationError`].
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum ValidationErrorKind {
+    /// The value has the wrong type.
+    Type(TypeError),
+    /// The map lacks the given required key.
+    MissingKey(String),
+}
+
+impl Display for ValidationError {
+    #[cold]
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self.kind {
+            ValidationErrorKind::Type(ref err) => Display::fmt(err, f),
+            ValidationErrorKind::MissingKey(ref key) => write!(f, "missing required key {key:?}"),
+        }
+    }
+}
+
+impl std::error::Error for ValidationError {}
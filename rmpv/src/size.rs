@@ -0,0 +1,315 @@
+//! Size of the encoding of a [`Value`], see [`Value::encoded_len`] and
+//! [`Value::truncate_to_bytes`].
+
+use crate::{IntPriv, Integer, Utf8String, Value};
+
+/// The suffix of the strings shortened by [`Value::truncate_to_bytes`].
+pub const TRUNCATION_MARKER: &str = "…";
+
+impl Value {
+    /// Returns the number of bytes written when encoding this value with
+    /// [`write_value`](crate::encode::write_value), without encoding it.
+    ///
+    /// Like [`walk`](Self::walk), this does not recurse.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmpv::Value;
+    ///
+    /// // fixarray, fixpos, uint 16, fixstr "le"
+    /// let val = Value::Array(vec![Value::from(1), Value::from(300), Value::from("le")]);
+    /// assert_eq!(1 + 1 + 3 + 3, val.encoded_len());
+    /// ```
+    #[must_use]
+    pub fn encoded_len(&self) -> usize {
+        let mut len = 0;
+        let mut stack = vec![self];
+
+        while let Some(node) = stack.pop() {
+            len += match *node {
+                Self::Nil | Self::Boolean(..) => 1,
+                Self::Integer(Integer { n: IntPriv::PosInt(n) }) => uint_len(n),
+                Self::Integer(Integer { n: IntPriv::NegInt(n) }) => sint_len(n),
+                Self::F32(..) => 5,
+                Self::F64(..) => 9,
+                Self::String(Utf8String { s: Ok(ref s) }) => str_header_len(s.len()) + s.len(),
+                Self::String(Utf8String { s: Err((ref data, _)) }) | Self::Binary(ref data) => {
+                    bin_header_len(data.len()) + data.len()
+                }
+                Self::Array(ref vec) => {
+                    stack.extend(vec);
+                    container_header_len(vec.len())
+                }
+                Self::Map(ref map) => {
+                    for (key, val) in map {
+                        stack.push(key);
+                        stack.push(val);
+                    }
+                    container_header_len(map.len())
+                }
+                Self::Ext(_, ref data) => ext_header_len(data.len()) + data.len(),
+            };
+        }
+
+        len
+    }
+
+    /// Shrinks this value until it is encoded in at most `max` bytes, as given by
+    /// [`encoded_len`](Self::encoded_len).
+    ///
+    /// The longest strings and binaries are shortened first, all to the same length, so that each
+    /// one keeps as much of its start as possible. A shortened string is cut on a character
+    /// boundary and ends with [`TRUNCATION_MARKER`], unless it is shorter than the marker itself.
+    /// Once every string and binary is empty, trailing elements are removed from the array or map
+    /// with the most elements. Map keys are kept as they are, and so are exts, whose data can not be
+    /// cut meaningfully.
+    ///
+    /// If this value can not be made small enough, for example because `max` is below the size of
+    /// its scalar values, it is shrunk as much as possible.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmpv::Value;
+    ///
+    /// let mut val = Value::Map(vec![
+    ///     (Value::from("msg"), Value::from("a very long message")),
+    ///     (Value::from("id"), Value::from(42)),
+    /// ]);
+    ///
+    /// val.truncate_to_bytes(16);
+    ///
+    /// assert!(val.encoded_len() <= 16);
+    /// assert_eq!(Value::Map(vec![
+    ///     (Value::from("msg"), Value::from("a v…")),
+    ///     (Value::from("id"), Value::from(42)),
+    /// ]), val);
+    /// ```
+    pub fn truncate_to_bytes(&mut self, max: usize) {
+        loop {
+            let len = self.encoded_len();
+            if len <= max {
+                return;
+            }
+            if !self.truncate_data(len - max) && !self.truncate_elements(len - max) {
+                return;
+            }
+        }
+    }
+
+    /// Shortens the longest strings and binaries to a common length, removing about `excess`
+    /// bytes. Returns `false` if all of them are already empty.
+    fn truncate_data(&mut self, excess: usize) -> bool {
+        let mut leaves = Vec::new();
+        let mut stack = vec![self];
+        while let Some(node) = stack.pop() {
+            match node {
+                Self::String(..) | Self::Binary(..) if data_len(node) > 0 => leaves.push(node),
+                node => push_children_mut(node, &mut stack),
+            }
+        }
+        if leaves.is_empty() {
+            return false;
+        }
+
+        let mut lens: Vec<usize> = leaves.iter().map(|leaf| data_len(leaf)).collect();
+        lens.sort_unstable_by(|a, b| b.cmp(a));
+        // The largest length to which cutting the longest leaves removes at least `excess` bytes.
+        let mut cap = 0;
+        let mut sum = 0;
+        for (idx, &len) in lens.iter().enumerate() {
+            sum += len;
+            let next = lens.get(idx + 1).copied().unwrap_or(0);
+            if let Some(removable) = sum.checked_sub(excess) {
+                if removable / (idx + 1) >= next {
+                    cap = removable / (idx + 1);
+                    break;
+                }
+            }
+        }
+
+        for leaf in leaves {
+            match leaf {
+                Self::String(Utf8String { s: Ok(s) }) => truncate_str(s, cap),
+                Self::String(s) => {
+                    let mut data = s.as_bytes().to_vec();
+                    data.truncate(cap);
+                    *s = utf8_string(data);
+                }
+                Self::Binary(data) => data.truncate(cap),
+                _ => {}
+            }
+        }
+        true
+    }
+
+    /// Removes trailing elements from the array or map with the most elements, until about
+    /// `excess` bytes are removed or it has as many elements as the next largest one. Returns
+    /// `false` if all of them are already empty.
+    fn truncate_elements(&mut self, excess: usize) -> bool {
+        // The lengths of the arrays and maps, in the order in which they are found below.
+        let mut lens = Vec::new();
+        let mut stack = vec![&*self];
+        while let Some(node) = stack.pop() {
+            if node.is_array() || node.is_map() {
+                lens.push(container_len(node));
+            }
+            push_children(node, &mut stack);
+        }
+        let Some((target, &len)) = lens.iter().enumerate().rev().max_by_key(|&(_, len)| len) else {
+            return false;
+        };
+        if len == 0 {
+            return false;
+        }
+        let next = lens.iter().enumerate()
+            .filter(|&(idx, _)| idx != target)
+            .map(|(_, &len)| len)
+            .max()
+            .unwrap_or(0);
+
+        let mut count = 0;
+        let mut stack = vec![self];
+        while let Some(node) = stack.pop() {
+            if node.is_array() || node.is_map() {
+                if count == target {
+                    remove_elements(node, excess, next);
+                    break;
+                }
+                count += 1;
+            }
+            push_children_mut(node, &mut stack);
+        }
+        true
+    }
+}
+
+/// Pushes the elements of an array or the values of a map, leaving the keys alone.
+fn push_children<'a>(node: &'a Value, stack: &mut Vec<&'a Value>) {
+    match *node {
+        Value::Array(ref vec) => stack.extend(vec),
+        Value::Map(ref map) => stack.extend(map.iter().map(|(_, val)| val)),
+        _ => {}
+    }
+}
+
+fn push_children_mut<'a>(node: &'a mut Value, stack: &mut Vec<&'a mut Value>) {
+    match *node {
+        Value::Array(ref mut vec) => stack.extend(vec.iter_mut()),
+        Value::Map(ref mut map) => stack.extend(map.iter_mut().map(|(_, val)| val)),
+        _ => {}
+    }
+}
+
+/// Removes at least one trailing element, and then more until `excess` bytes are removed or
+/// `min_len` elements are left.
+fn remove_elements(node: &mut Value, excess: usize, min_len: usize) {
+    let mut removed = 0;
+    loop {
+        removed += match *node {
+            Value::Array(ref mut vec) => vec.pop().map_or(0, |val| val.encoded_len()),
+            Value::Map(ref mut map) => map.pop().map_or(0, |(key, val)| key.encoded_len() + val.encoded_len()),
+            _ => 0,
+        };
+        if removed >= excess || container_len(node) <= min_len {
+            break;
+        }
+    }
+}
+
+fn data_len(val: &Value) -> usize {
+    match *val {
+        Value::String(ref s) => s.as_bytes().len(),
+        Value::Binary(ref data) => data.len(),
+        _ => 0,
+    }
+}
+
+fn container_len(val: &Value) -> usize {
+    match *val {
+        Value::Array(ref vec) => vec.len(),
+        Value::Map(ref map) => map.len(),
+        _ => 0,
+    }
+}
+
+/// Cuts the string to at most `cap` bytes, marker included.
+fn truncate_str(s: &mut String, cap: usize) {
+    if s.len() <= cap {
+        return;
+    }
+    let (mut end, marker) = match cap.checked_sub(TRUNCATION_MARKER.len()) {
+        Some(end) => (end, TRUNCATION_MARKER),
+        None => (cap, ""),
+    };
+    while !s.is_char_boundary(end) {
+        end -= 1;
+    }
+    s.truncate(end);
+    s.push_str(marker);
+}
+
+fn utf8_string(data: Vec<u8>) -> Utf8String {
+    match String::from_utf8(data) {
+        Ok(s) => Utf8String::from(s),
+        Err(err) => {
+            let e = err.utf8_error();
+            Utf8String { s: Err((err.into_bytes(), e)) }
+        }
+    }
+}
+
+fn uint_len(n: u64) -> usize {
+    match n {
+        0..=0x7f => 1,
+        0x80..=0xff => 2,
+        0x100..=0xffff => 3,
+        0x1_0000..=0xffff_ffff => 5,
+        _ => 9,
+    }
+}
+
+fn sint_len(n: i64) -> usize {
+    match n {
+        -32..=-1 => 1,
+        -128..=-33 => 2,
+        -32768..=-129 => 3,
+        -2147483648..=-32769 => 5,
+        _ => 9,
+    }
+}
+
+fn str_header_len(len: usize) -> usize {
+    match len {
+        0..=31 => 1,
+        32..=0xff => 2,
+        0x100..=0xffff => 3,
+        _ => 5,
+    }
+}
+
+fn bin_header_len(len: usize) -> usize {
+    match len {
+        0..=0xff => 2,
+        0x100..=0xffff => 3,
+        _ => 5,
+    }
+}
+
+fn container_header_len(len: usize) -> usize {
+    match len {
+        0..=15 => 1,
+        16..=0xffff => 3,
+        _ => 5,
+    }
+}
+
+fn ext_header_len(len: usize) -> usize {
+    match len {
+        1 | 2 | 4 | 8 | 16 => 2,
+        0..=0xff => 3,
+        0x100..=0xffff => 4,
+        _ => 6,
+    }
+}
@@ -0,0 +1,143 @@
+//! Conformance vectors, see [`test_vectors`].
+
+use crate::Value;
+
+/// Returns pairs of MessagePack bytes and the [`Value`] they represent, covering every marker.
+///
+/// Each pair holds in both directions: [`read_value`](crate::decode::read_value) decodes the bytes
+/// into the value, and [`write_value`](crate::encode::write_value) encodes the value into exactly
+/// these bytes, using the most efficient representation. The vectors include the bounds of each
+/// marker, such as the smallest and largest integers and lengths it is chosen for, so they can be
+/// used to check the compatibility of other implementations with this crate.
+///
+/// The largest vectors, for the 32-bit lengths, hold 65 536 elements or bytes.
+///
+/// # Examples
+///
+/// ```
+/// use rmpv::decode::read_value;
+/// use rmpv::encode::write_value;
+///
+/// for (bytes, val) in rmpv::test_vectors() {
+///     assert_eq!(val, read_value(&mut &bytes[..]).unwrap());
+///
+///     let mut buf = Vec::new();
+///     write_value(&mut buf, &val).unwrap();
+///     assert_eq!(bytes, buf);
+/// }
+/// ```
+#[must_use]
+pub fn test_vectors() -> Vec<(Vec<u8>, Value)> {
+    let mut vectors = vec![
+        (vec![0xc0], Value::Nil),
+        (vec![0xc2], Value::from(false)),
+        (vec![0xc3], Value::from(true)),
+        // Integers.
+        (vec![0x00], Value::from(0)),
+        (vec![0x7f], Value::from(127)),
+        (vec![0xcc, 0x80], Value::from(128)),
+        (vec![0xcc, 0xff], Value::from(255)),
+        (vec![0xcd, 0x01, 0x00], Value::from(256)),
+        (vec![0xcd, 0xff, 0xff], Value::from(u16::MAX)),
+        (vec![0xce, 0x00, 0x01, 0x00, 0x00], Value::from(65536)),
+        (vec![0xce, 0xff, 0xff, 0xff, 0xff], Value::from(u32::MAX)),
+        (vec![0xcf, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00], Value::from(u64::from(u32::MAX) + 1)),
+        (vec![0xcf, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff], Value::from(u64::MAX)),
+        (vec![0xff], Value::from(-1)),
+        (vec![0xe0], Value::from(-32)),
+        (vec![0xd0, 0xdf], Value::from(-33)),
+        (vec![0xd0, 0x80], Value::from(i8::MIN)),
+        (vec![0xd1, 0xff, 0x7f], Value::from(-129)),
+        (vec![0xd1, 0x80, 0x00], Value::from(i16::MIN)),
+        (vec![0xd2, 0xff, 0xff, 0x7f, 0xff], Value::from(-32769)),
+        (vec![0xd2, 0x80, 0x00, 0x00, 0x00], Value::from(i32::MIN)),
+        (vec![0xd3, 0xff, 0xff, 0xff, 0xff, 0x7f, 0xff, 0xff, 0xff], Value::from(i64::from(i32::MIN) - 1)),
+        (vec![0xd3, 0x80, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], Value::from(i64::MIN)),
+        // Floats.
+        (vec![0xca, 0x00, 0x00, 0x00, 0x00], Value::F32(0.0)),
+        (vec![0xca, 0x3f, 0xc0, 0x00, 0x00], Value::F32(1.5)),
+        (vec![0xca, 0xff, 0x80, 0x00, 0x00], Value::F32(f32::NEG_INFINITY)),
+        (vec![0xcb, 0x80, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], Value::F64(-0.0)),
+        (vec![0xcb, 0x3f, 0xf8, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], Value::F64(1.5)),
+        (vec![0xcb, 0x7f, 0xf0, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], Value::F64(f64::INFINITY)),
+        // Exts of every fixed length.
+        (vec![0xd4, 0x01, 0x00], Value::Ext(1, vec![0; 1])),
+        (vec![0xd5, 0x7f, 0x00, 0x00], Value::Ext(127, vec![0; 2])),
+        (vec![0xd6, 0xff, 0x00, 0x00, 0x00, 0x00], Value::Ext(-1, vec![0; 4])),
+        (vec![0xd7, 0x80, 0, 0, 0, 0, 0, 0, 0, 0], Value::Ext(-128, vec![0; 8])),
+        (vec![0xd8, 0x01, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0], Value::Ext(1, vec![0; 16])),
+        (vec![0xc7, 0x00, 0x01], Value::Ext(1, Vec::new())),
+        (vec![0xc7, 0x03, 0x01, 0x00, 0x00, 0x00], Value::Ext(1, vec![0; 3])),
+        // A string with multibyte characters, whose length is counted in bytes.
+        (vec![0xa3, 0x61, 0xc3, 0xa9], Value::from("aé")),
+    ];
+
+    // The bounds of every length.
+    let a = b'a';
+    for len in [0, 31] {
+        vectors.push((with_header(&[0xa0 | len as u8], &vec![a; len]), Value::from("a".repeat(len))));
+    }
+    for len in [32, 255] {
+        vectors.push((with_header(&[0xd9, len as u8], &vec![a; len]), Value::from("a".repeat(len))));
+    }
+    for len in [256, 65535] {
+        vectors.push((with_header(&header16(0xda, len), &vec![a; len]), Value::from("a".repeat(len))));
+    }
+    vectors.push((with_header(&header32(0xdb, 65536), &vec![a; 65536]), Value::from("a".repeat(65536))));
+
+    for len in [0, 255] {
+        vectors.push((with_header(&[0xc4, len as u8], &vec![1; len]), Value::Binary(vec![1; len])));
+    }
+    for len in [256, 65535] {
+        vectors.push((with_header(&header16(0xc5, len), &vec![1; len]), Value::Binary(vec![1; len])));
+    }
+    vectors.push((with_header(&header32(0xc6, 65536), &vec![1; 65536]), Value::Binary(vec![1; 65536])));
+
+    for len in [0, 15] {
+        vectors.push((with_header(&[0x90 | len as u8], &vec![0xc0; len]), Value::Array(vec![Value::Nil; len])));
+        let map = vec![(Value::Nil, Value::Nil); len];
+        vectors.push((with_header(&[0x80 | len as u8], &vec![0xc0; 2 * len]), Value::Map(map)));
+    }
+    for len in [16, 65535] {
+        vectors.push((with_header(&header16(0xdc, len), &vec![0xc0; len]), Value::Array(vec![Value::Nil; len])));
+        let map = vec![(Value::Nil, Value::Nil); len];
+        vectors.push((with_header(&header16(0xde, len), &vec![0xc0; 2 * len]), Value::Map(map)));
+    }
+    vectors.push((with_header(&header32(0xdd, 65536), &vec![0xc0; 65536]), Value::Array(vec![Value::Nil; 65536])));
+    let map = vec![(Value::Nil, Value::Nil); 65536];
+    vectors.push((with_header(&header32(0xdf, 65536), &vec![0xc0; 2 * 65536]), Value::Map(map)));
+
+    for len in [255, 256, 65535, 65536] {
+        let marker = match len {
+            0..=0xff => vec![0xc7, len as u8],
+            0x100..=0xffff => header16(0xc8, len),
+            _ => header32(0xc9, len),
+        };
+        vectors.push((with_header(&[&marker[..], &[0x05]].concat(), &vec![2; len]), Value::Ext(5, vec![2; len])));
+    }
+
+    // Nested containers.
+    vectors.push((
+        vec![0x82, 0xa1, 0x61, 0x91, 0x90, 0x01, 0x81, 0xc0, 0xc3],
+        Value::Map(vec![
+            (Value::from("a"), Value::Array(vec![Value::Array(Vec::new())])),
+            (Value::from(1), Value::Map(vec![(Value::Nil, Value::from(true))])),
+        ]),
+    ));
+
+    vectors
+}
+
+/// Returns the marker followed by the length as a big-endian 16-bit integer.
+fn header16(marker: u8, len: usize) -> Vec<u8> {
+    [&[marker][..], &(len as u16).to_be_bytes()].concat()
+}
+
+/// Returns the marker followed by the length as a big-endian 32-bit integer.
+fn header32(marker: u8, len: usize) -> Vec<u8> {
+    [&[marker][..], &(len as u32).to_be_bytes()].concat()
+}
+
+fn with_header(header: &[u8], data: &[u8]) -> Vec<u8> {
+    [header, data].concat()
+}
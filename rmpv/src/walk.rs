@@ -0,0 +1,118 @@
+//! Read-only traversal of a [`Value`] tree, see [`Value::walk`] and [`Value::collect_strings`].
+
+use crate::Value;
+
+/// A step of the path from the root of a [`Value`] tree to one of its nodes, as given by
+/// [`Value::walk`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PathSegment<'a> {
+    /// The value of the map entry with the given key.
+    Key(&'a Value),
+    /// The array element at the given index.
+    Index(usize),
+}
+
+impl Value {
+    /// Calls the given function on every node of this value, together with the path leading to it
+    /// from this value.
+    ///
+    /// Nodes are visited depth first, each one before its children, and the children in their
+    /// stored order. Map keys are not visited themselves, but appear in the paths of their values.
+    /// This value is visited first, with an empty path.
+    ///
+    /// The traversal uses a heap-allocated stack instead of recursion, so arbitrarily deep values
+    /// can not overflow the call stack.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmpv::{PathSegment, Value};
+    ///
+    /// let val = Value::Map(vec![
+    ///     (Value::from("name"), Value::from("le message")),
+    ///     (Value::from("tags"), Value::Array(vec![Value::from("a"), Value::from(42)])),
+    /// ]);
+    ///
+    /// let mut strings = Vec::new();
+    /// val.walk(|path, node| {
+    ///     if let Some(s) = node.as_str() {
+    ///         strings.push((path.to_vec(), s));
+    ///     }
+    /// });
+    ///
+    /// let tags = Value::from("tags");
+    /// assert_eq!(vec![
+    ///     (vec![PathSegment::Key(&Value::from("name"))], "le message"),
+    ///     (vec![PathSegment::Key(&tags), PathSegment::Index(0)], "a"),
+    /// ], strings);
+    /// ```
+    pub fn walk<'a, F>(&'a self, mut f: F)
+    where
+        F: FnMut(&[PathSegment<'a>], &'a Value),
+    {
+        let mut path = Vec::new();
+        // The length of the path of the parent, the step from the parent and the node to visit.
+        let mut stack = vec![(0, None, self)];
+
+        while let Some((len, segment, node)) = stack.pop() {
+            path.truncate(len);
+            path.extend(segment);
+            f(&path, node);
+
+            let len = path.len();
+            match node {
+                Value::Array(vec) => {
+                    stack.extend(vec.iter().enumerate().rev().map(|(idx, val)| (len, Some(PathSegment::Index(idx)), val)));
+                }
+                Value::Map(map) => {
+                    stack.extend(map.iter().rev().map(|(key, val)| (len, Some(PathSegment::Key(key)), val)));
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Returns the contents of every string in this value, in the order in which they are
+    /// encoded, borrowing them.
+    ///
+    /// Strings in map keys are included if `include_keys` is set, each one before the strings of
+    /// its value, and skipped otherwise, along with everything nested in the keys. Strings which
+    /// are not valid UTF-8 are always skipped. Like [`walk`](Self::walk), this does not recurse.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmpv::Value;
+    ///
+    /// let val = Value::Map(vec![
+    ///     (Value::from("title"), Value::from("Hello")),
+    ///     (Value::from("lines"), Value::Array(vec![Value::from("a"), Value::from(42), Value::from("b")])),
+    /// ]);
+    ///
+    /// assert_eq!(vec!["Hello", "a", "b"], val.collect_strings(false));
+    /// assert_eq!(vec!["title", "Hello", "lines", "a", "b"], val.collect_strings(true));
+    /// ```
+    #[must_use]
+    pub fn collect_strings(&self, include_keys: bool) -> Vec<&str> {
+        let mut strings = Vec::new();
+        let mut stack = vec![self];
+
+        while let Some(node) = stack.pop() {
+            match node {
+                Value::String(s) => strings.extend(s.as_str()),
+                Value::Array(vec) => stack.extend(vec.iter().rev()),
+                Value::Map(map) => {
+                    for (key, val) in map.iter().rev() {
+                        stack.push(val);
+                        if include_keys {
+                            stack.push(key);
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        strings
+    }
+}
@@ -0,0 +1,93 @@
+#![cfg(feature = "tokio")]
+
+use std::io::ErrorKind;
+
+use rmpv::async_decode::{read_value_async, FrameReader};
+use rmpv::decode::Error;
+use rmpv::encode::write_value;
+use rmpv::Value;
+use tokio::io::AsyncWriteExt;
+
+fn sample() -> Value {
+    Value::Map(vec![
+        (Value::from("id"), Value::from(42)),
+        (Value::from("tags"), Value::Array(vec![Value::from("a"), Value::Nil, Value::F64(1.5)])),
+        (Value::from("blob"), Value::Binary(vec![7; 100_000])),
+        (Value::from("ext"), Value::Ext(3, vec![1, 2, 3])),
+    ])
+}
+
+#[tokio::test]
+async fn from_stream_read_value_async() {
+    let mut buf = Vec::new();
+    write_value(&mut buf, &sample()).unwrap();
+    write_value(&mut buf, &Value::from(true)).unwrap();
+
+    // Feed the input through a small pipe, so that the value arrives in many reads.
+    let (mut tx, mut rx) = tokio::io::duplex(7);
+    let write = async move {
+        tx.write_all(&buf).await.unwrap();
+    };
+    let read = async move {
+        let first = read_value_async(&mut rx).await.unwrap();
+        let second = read_value_async(&mut rx).await.unwrap();
+        let eof = read_value_async(&mut rx).await.unwrap_err();
+        (first, second, eof)
+    };
+    let ((), (first, second, eof)) = tokio::join!(write, read);
+
+    assert_eq!(sample(), first);
+    assert_eq!(Value::from(true), second);
+    match eof {
+        Error::InvalidMarkerRead(err) => assert_eq!(ErrorKind::UnexpectedEof, err.kind()),
+        err => panic!("unexpected error: {err:?}"),
+    }
+}
+
+#[tokio::test]
+async fn from_truncated_read_value_async() {
+    // [1, "ab" with a missing byte
+    let buf = [0x92, 0x01, 0xa2, 0x61];
+    match read_value_async(&mut &buf[..]).await.unwrap_err() {
+        Error::InvalidDataRead(err) => assert_eq!(ErrorKind::UnexpectedEof, err.kind()),
+        err => panic!("unexpected error: {err:?}"),
+    }
+}
+
+#[tokio::test]
+async fn from_reserved_read_value_async() {
+    let buf = [0x91, 0xc1];
+    assert!(matches!(read_value_async(&mut &buf[..]).await, Err(Error::ReservedMarker)));
+}
+
+#[tokio::test]
+async fn from_frames_read_frame() {
+    let mut buf = Vec::new();
+    for val in [sample(), Value::from("b")] {
+        let mut frame = Vec::new();
+        write_value(&mut frame, &val).unwrap();
+        buf.extend_from_slice(&(frame.len() as u32).to_be_bytes());
+        buf.extend_from_slice(&frame);
+    }
+
+    let mut rd = FrameReader::new(&buf[..]);
+    assert_eq!(Some(sample()), rd.read_frame().await.unwrap());
+    assert_eq!(Some(Value::from("b")), rd.read_frame().await.unwrap());
+    assert_eq!(None, rd.read_frame().await.unwrap());
+}
+
+#[tokio::test]
+async fn from_invalid_frames_read_frame() {
+    // Frame longer than the maximum.
+    let buf = [0x00, 0x00, 0x00, 0x02, 0x91, 0x01];
+    let mut rd = FrameReader::new(&buf[..]).with_max_frame_len(1);
+    assert!(matches!(rd.read_frame().await, Err(Error::InvalidDataRead(..))));
+
+    // Trailing bytes after the value.
+    let buf = [0x00, 0x00, 0x00, 0x02, 0x01, 0x02];
+    assert!(matches!(FrameReader::new(&buf[..]).read_frame().await, Err(Error::InvalidDataRead(..))));
+
+    // Truncated length prefix.
+    let buf = [0x00, 0x00];
+    assert!(matches!(FrameReader::new(&buf[..]).read_frame().await, Err(Error::InvalidMarkerRead(..))));
+}
@@ -0,0 +1,53 @@
+#![cfg(feature = "tokio")]
+
+use rmpv::async_decode::{read_value_async, FrameReader};
+use rmpv::async_encode::{write_value_async, FrameWriter};
+use rmpv::Value;
+
+fn sample() -> Value {
+    Value::Map(vec![
+        (Value::from("id"), Value::from(-42)),
+        (Value::from("tags"), Value::Array(vec![Value::from("a"), Value::Nil, Value::F32(0.5)])),
+        (Value::from("blob"), Value::Binary(vec![7; 100_000])),
+        (Value::Ext(1, vec![0; 4]), Value::from(u64::MAX)),
+    ])
+}
+
+#[tokio::test]
+async fn pass_value_round_trip_over_duplex() {
+    let (mut tx, mut rx) = tokio::io::duplex(64);
+    let write = async move {
+        write_value_async(&mut tx, &sample()).await.unwrap();
+        write_value_async(&mut tx, &Value::from("end")).await.unwrap();
+    };
+    let read = async move {
+        (read_value_async(&mut rx).await.unwrap(), read_value_async(&mut rx).await.unwrap())
+    };
+    let ((), (first, second)) = tokio::join!(write, read);
+
+    assert_eq!(sample(), first);
+    assert_eq!(Value::from("end"), second);
+}
+
+#[tokio::test]
+async fn pass_frame_round_trip_over_duplex() {
+    let (tx, rx) = tokio::io::duplex(64);
+    let write = async move {
+        let mut wr = FrameWriter::new(tx);
+        wr.write_frame(&sample()).await.unwrap();
+        wr.write_frame(&Value::Nil).await.unwrap();
+        wr.flush().await.unwrap();
+        // Dropping the writer closes the pipe.
+    };
+    let read = async move {
+        let mut rd = FrameReader::new(rx);
+        let mut frames = Vec::new();
+        while let Some(val) = rd.read_frame().await.unwrap() {
+            frames.push(val);
+        }
+        frames
+    };
+    let ((), frames) = tokio::join!(write, read);
+
+    assert_eq!(vec![sample(), Value::Nil], frames);
+}
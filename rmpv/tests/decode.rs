@@ -260,6 +260,49 @@ fn from_ext32_decode_value() {
     );
 }
 
+#[test]
+fn from_fixext_decode_value_same_as_ext8() {
+    for len in [1u8, 2, 4, 8, 16] {
+        let marker = match len {
+            1 => 0xd4,
+            2 => 0xd5,
+            4 => 0xd6,
+            8 => 0xd7,
+            _ => 0xd8,
+        };
+        let data: Vec<u8> = (0..len).collect();
+
+        let fixext = [&[marker, 0x01][..], &data].concat();
+        let ext8 = [&[0xc7, len, 0x01][..], &data].concat();
+
+        assert_eq!(Value::Ext(1, data), read_value(&mut &fixext[..]).unwrap());
+        assert_eq!(read_value(&mut &ext8[..]).unwrap(), read_value(&mut &fixext[..]).unwrap());
+    }
+}
+
+#[test]
+fn from_timestamp32_decode_value() {
+    // The timestamp ext type is -1, seconds fit into fixext4.
+    let buf = [0xd6, 0xff, 0x00, 0x00, 0x00, 0x2a];
+    assert_eq!(Value::Ext(-1, vec![0x00, 0x00, 0x00, 0x2a]), read_value(&mut &buf[..]).unwrap());
+}
+
+#[test]
+fn from_timestamp64_decode_value() {
+    // 30-bit nanoseconds followed by 34-bit seconds in fixext8.
+    let buf = [0xd7, 0xff, 0x00, 0x00, 0x00, 0x04, 0x00, 0x00, 0x00, 0x2a];
+    assert_eq!(Value::Ext(-1, vec![0x00, 0x00, 0x00, 0x04, 0x00, 0x00, 0x00, 0x2a]),
+               read_value(&mut &buf[..]).unwrap());
+}
+
+#[test]
+fn from_timestamp96_decode_value() {
+    // 32-bit nanoseconds followed by 64-bit seconds in ext8.
+    let buf = [0xc7, 0x0c, 0xff, 0x00, 0x00, 0x00, 0x01,
+               0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x2a];
+    assert_eq!(Value::Ext(-1, buf[3..].to_vec()), read_value(&mut &buf[..]).unwrap());
+}
+
 #[test]
 fn from_str8_decode_value() {
     let buf: &[u8] = &[
@@ -401,3 +444,259 @@ fn invalid_buf_size_arr() {
         Err(e) => panic!("Unexpected error: {e}"),
     }
 }
+
+#[test]
+fn from_ext_decode_value_with_hook() {
+    use rmpv::decode::{read_value_with, ReadOptions};
+
+    fn hook(ty: i8, data: &[u8]) -> Option<Value> {
+        if ty == 5 {
+            Some(Value::Binary(data.to_vec()))
+        } else {
+            None
+        }
+    }
+
+    // {ext(5, [1]): [ext(5, [2, 3]), ext(1, [4])]}
+    let buf = [0x81, 0xd4, 0x05, 0x01, 0x92, 0xd5, 0x05, 0x02, 0x03, 0xd4, 0x01, 0x04];
+    let options = ReadOptions::default().with_ext_hook(hook);
+
+    assert_eq!(
+        Value::Map(vec![(
+            Value::Binary(vec![1]),
+            Value::Array(vec![Value::Binary(vec![2, 3]), Value::Ext(1, vec![4])]),
+        )]),
+        read_value_with(&mut &buf[..], options).unwrap()
+    );
+    assert_eq!(
+        read_value(&mut &buf[..]).unwrap(),
+        read_value_with(&mut &buf[..], ReadOptions::default()).unwrap()
+    );
+}
+
+#[test]
+fn from_nested_array_decode_value_with_max_depth() {
+    use rmpv::decode::{read_value_with, ReadOptions};
+
+    let buf = [0x91, 0x91, 0x90];
+    let options = ReadOptions::default().with_max_depth(2);
+
+    match read_value_with(&mut &buf[..], options) {
+        Err(Error::DepthLimitExceeded) => (),
+        other => panic!("unexpected result: {other:?}"),
+    }
+}
+
+#[test]
+fn from_map_decode_entries_one_at_a_time() {
+    use rmpv::decode::{read_array_iter, read_map_iter};
+
+    // A map of 1000 entries, followed by 42.
+    let mut buf = vec![0xde, 0x03, 0xe8];
+    for i in 0..1000u16 {
+        rmp::encode::write_uint(&mut buf, i.into()).unwrap();
+        rmp::encode::write_str(&mut buf, "v").unwrap();
+    }
+    buf.push(0x2a);
+
+    let mut rd = &buf[..];
+    let mut iter = read_map_iter(&mut rd).unwrap();
+    assert_eq!((0, Some(1000)), iter.size_hint());
+    let mut count = 0;
+    for (idx, entry) in iter.by_ref().enumerate() {
+        assert_eq!((Value::from(idx), Value::from("v")), entry.unwrap());
+        count += 1;
+    }
+    assert_eq!(1000, count);
+    assert!(iter.next().is_none());
+    assert_eq!(Value::from(42), read_value(&mut rd).unwrap());
+
+    // Truncated in the middle of an entry, between its key and its value.
+    let mut rd = &buf[..6];
+    let mut iter = read_map_iter(&mut rd).unwrap();
+    assert_eq!((Value::from(0), Value::from("v")), iter.next().unwrap().unwrap());
+    match iter.next() {
+        Some(Err(Error::InvalidMarkerRead(..))) => (),
+        other => panic!("unexpected result: {other:?}"),
+    }
+    assert!(iter.next().is_none());
+
+    // Not a map, and arrays.
+    assert!(read_map_iter(&mut &[0x90][..]).is_err());
+    let elems: Vec<_> = read_array_iter(&mut &[0x92, 0xc0, 0xc3][..]).unwrap().collect::<Result<_, _>>().unwrap();
+    assert_eq!(vec![Value::Nil, Value::from(true)], elems);
+    let mut rd = &[0x92, 0xcd, 0x01][..];
+    let mut iter = read_array_iter(&mut rd).unwrap();
+    assert!(matches!(iter.next(), Some(Err(Error::InvalidDataRead(..)))));
+    assert!(iter.next().is_none());
+}
+
+#[test]
+fn from_nested_array_decode_value_with_max_value_bytes() {
+    use rmpv::decode::{read_value_with, ReadOptions};
+
+    // [[1, 2], "abc"], in 8 bytes.
+    let buf = [0x92, 0x92, 0x01, 0x02, 0xa3, 0x61, 0x62, 0x63];
+    let expected = Value::Array(vec![Value::Array(vec![Value::from(1), Value::from(2)]), Value::from("abc")]);
+    let options = ReadOptions::default().with_max_value_bytes(8);
+    assert_eq!(expected, read_value_with(&mut &buf[..], options).unwrap());
+
+    for limit in [0, 3, 7] {
+        let options = ReadOptions::default().with_max_value_bytes(limit);
+        match read_value_with(&mut &buf[..], options) {
+            Err(Error::ByteLimitExceeded(l)) if l == limit => (),
+            other => panic!("unexpected result: {other:?}"),
+        }
+    }
+
+    // The limit applies to each value, and stops a huge array of small elements early.
+    let mut buf = vec![0xdd, 0xff, 0xff, 0xff, 0xff];
+    buf.resize(buf.len() + 100_000, 0xc0);
+    let mut rd = &buf[..];
+    let options = ReadOptions::default().with_max_value_bytes(1000);
+    match read_value_with(&mut rd, options) {
+        Err(Error::ByteLimitExceeded(1000)) => (),
+        other => panic!("unexpected result: {other:?}"),
+    }
+    assert_eq!(buf.len() - 1000, rd.len());
+
+    // A truncated input is still reported as such.
+    let options = ReadOptions::default().with_max_value_bytes(100);
+    match read_value_with(&mut &[0x92, 0x01][..], options) {
+        Err(Error::InvalidMarkerRead(..)) => (),
+        other => panic!("unexpected result: {other:?}"),
+    }
+}
+
+#[test]
+fn from_reserved_decode_value() {
+    // Alone, inside an array and as a map value.
+    for buf in [&[0xc1][..], &[0x92, 0x01, 0xc1], &[0x81, 0xa1, 0x61, 0xc1]] {
+        match read_value(&mut &buf[..]) {
+            Err(Error::ReservedMarker) => (),
+            other => panic!("unexpected result: {other:?}"),
+        }
+    }
+
+    let err = std::io::Error::from(read_value(&mut &[0xc1][..]).unwrap_err());
+    assert_eq!(std::io::ErrorKind::InvalidData, err.kind());
+}
+
+#[test]
+fn from_array_decode_prefix() {
+    use rmpv::decode::read_array_prefix;
+
+    // [[1], "a", nil], true
+    let buf = [0x93, 0x91, 0x01, 0xa1, 0x61, 0xc0, 0xc3];
+
+    let mut rd = &buf[..];
+    assert_eq!(vec![Value::Array(vec![Value::from(1)])], read_array_prefix(&mut rd, 1).unwrap());
+    assert_eq!(&buf[3..], rd);
+
+    let mut rd = &buf[..];
+    assert_eq!(Vec::<Value>::new(), read_array_prefix(&mut rd, 0).unwrap());
+    assert_eq!(&buf[1..], rd);
+
+    // Asking for more elements than the array has stops at its end.
+    let mut rd = &buf[..];
+    assert_eq!(
+        vec![Value::Array(vec![Value::from(1)]), Value::from("a"), Value::Nil],
+        read_array_prefix(&mut rd, 10).unwrap()
+    );
+    assert_eq!(Value::Boolean(true), read_value(&mut rd).unwrap());
+
+    // Only the requested prefix has to be present.
+    read_array_prefix(&mut &buf[..4], 2).unwrap_err();
+    let mut rd = &buf[..3];
+    assert_eq!(1, read_array_prefix(&mut rd, 1).unwrap().len());
+
+    read_array_prefix(&mut &[0xc0][..], 1).unwrap_err();
+}
+
+#[test]
+fn from_stream_decode_value_opt() {
+    use rmpv::decode::read_value_opt;
+    use std::io::ErrorKind;
+
+    // 1, [nil, "a"], true
+    let buf = [0x01, 0x92, 0xc0, 0xa1, 0x61, 0xc3];
+
+    let mut rd = &buf[..];
+    let mut values = Vec::new();
+    while let Some(val) = read_value_opt(&mut rd).unwrap() {
+        values.push(val);
+    }
+    assert_eq!(vec![Value::from(1), Value::Array(vec![Value::Nil, Value::from("a")]), Value::Boolean(true)], values);
+    assert_eq!(None, read_value_opt(&mut rd).unwrap());
+
+    // Truncated in the middle of a value, either before a marker or inside the data.
+    for len in [2, 4] {
+        let mut rd = &buf[..len];
+        assert_eq!(Some(Value::from(1)), read_value_opt(&mut rd).unwrap());
+        assert_eq!(ErrorKind::UnexpectedEof, read_value_opt(&mut rd).unwrap_err().kind());
+    }
+}
+
+#[test]
+fn from_non_minimal_markers_decode_value_preserving() {
+    use rmpv::decode::read_value_preserving;
+    use rmpv::encode::write_value_preserving;
+
+    let buf = [
+        0xdf, 0x00, 0x00, 0x00, 0x02, // map32(2)
+        0xda, 0x00, 0x01, 0x61, // str16 "a"
+        0xdc, 0x00, 0x05, // array16(5)
+        0xcf, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01, // u64 1
+        0xd0, 0x01, // i8 1
+        0xd1, 0xff, 0xff, // i16 -1
+        0xc6, 0x00, 0x00, 0x00, 0x01, 0x00, // bin32(1)
+        0xc7, 0x01, 0x05, 0x00, // ext8(1) type 5
+        0xa1, 0xff, // fixstr with invalid UTF-8
+        0xc3, // true
+    ];
+    let mut val = read_value_preserving(&mut &buf[..]).unwrap();
+    assert_eq!(10, val.markers().len());
+    assert_eq!(Some(Value::from(1)), val.value()["a"].as_array().map(|vec| vec[0].clone()));
+
+    let mut out = Vec::new();
+    write_value_preserving(&mut out, &val).unwrap();
+    assert_eq!(buf, out[..]);
+
+    // Markers are reused while they fit, and the rest falls back to the smallest representation.
+    if let Value::Map(ref mut map) = *val.value_mut() {
+        if let Value::Array(ref mut vec) = map[0].1 {
+            vec[0] = Value::from(200);
+            vec[1] = Value::from(300);
+        }
+    }
+    let mut out = Vec::new();
+    write_value_preserving(&mut out, &val).unwrap();
+    assert_eq!(&[
+        0xdf, 0x00, 0x00, 0x00, 0x02,
+        0xda, 0x00, 0x01, 0x61,
+        0xdc, 0x00, 0x05,
+        0xcf, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xc8, // u64 200
+        0xcd, 0x01, 0x2c, // u16 300
+        0xff,
+        0xc4, 0x01, 0x00,
+        0xd4, 0x05, 0x00,
+        0xc4, 0x01, 0xff,
+        0xc3,
+    ][..], &out[..]);
+    // Adding a node changes the length of the array, after which no marker matches its node.
+    let mut val = read_value_preserving(&mut &buf[..]).unwrap();
+    if let Value::Map(ref mut map) = *val.value_mut() {
+        if let Value::Array(ref mut vec) = map[0].1 {
+            vec.push(Value::from(2));
+        }
+    }
+    let mut out = Vec::new();
+    write_value_preserving(&mut out, &val).unwrap();
+    assert_eq!(&[
+        0xdf, 0x00, 0x00, 0x00, 0x02,
+        0xda, 0x00, 0x01, 0x61,
+        0x96, 0x01, 0x01, 0xff, 0xc4, 0x01, 0x00, 0xd4, 0x05, 0x00, 0x02,
+        0xc4, 0x01, 0xff,
+        0xc3,
+    ][..], &out[..]);
+}
@@ -643,7 +643,10 @@ fn from_reserved() {
 
     let mut rd = &buf[..];
 
-    assert_eq!(ValueRef::Nil, read_value_ref(&mut rd).unwrap());
+    match read_value_ref(&mut rd) {
+        Err(Error::ReservedMarker) => (),
+        other => panic!("unexpected result: {other:?}"),
+    }
 }
 
 #[test]
@@ -0,0 +1,190 @@
+use std::cmp::Ordering;
+
+use rmpv::encode::{write_value, write_value_canonical, write_value_minimal, write_value_with, WriteOptions};
+use rmpv::Value;
+
+fn by_str(a: &Value, b: &Value) -> Ordering {
+    a.as_str().cmp(&b.as_str())
+}
+
+#[test]
+fn pass_pack_with_default_options() {
+    let val = Value::Map(vec![
+        (Value::from("b"), Value::Array(vec![Value::Nil, Value::from(42)])),
+        (Value::from("a"), Value::from(true)),
+    ]);
+
+    let mut expected = Vec::new();
+    write_value(&mut expected, &val).unwrap();
+
+    let mut buf = Vec::new();
+    write_value_with(&mut buf, &val, WriteOptions::default()).unwrap();
+
+    assert_eq!(expected, buf);
+}
+
+#[test]
+fn pass_pack_with_key_order() {
+    let val = Value::Map(vec![
+        (Value::from("b"), Value::Map(vec![
+            (Value::from("d"), Value::from(1)),
+            (Value::from("c"), Value::from(2)),
+        ])),
+        (Value::from("a"), Value::Array(vec![Value::Map(vec![
+            (Value::from("f"), Value::Nil),
+            (Value::from("e"), Value::Nil),
+        ])])),
+    ]);
+
+    let mut buf = Vec::new();
+    write_value_with(&mut buf, &val, WriteOptions::default().with_key_order(by_str)).unwrap();
+
+    assert_eq!(vec![
+        0x82,
+        0xa1, 0x61, 0x91, 0x82, 0xa1, 0x65, 0xc0, 0xa1, 0x66, 0xc0,
+        0xa1, 0x62, 0x82, 0xa1, 0x63, 0x02, 0xa1, 0x64, 0x01,
+    ], buf);
+}
+
+#[test]
+fn pass_pack_with_key_order_is_stable() {
+    let val = Value::Map(vec![
+        (Value::from("b"), Value::from(1)),
+        (Value::from("a"), Value::from(2)),
+        (Value::from("b"), Value::from(3)),
+    ]);
+
+    let mut buf = Vec::new();
+    write_value_with(&mut buf, &val, WriteOptions::default().with_key_order(by_str)).unwrap();
+
+    assert_eq!(vec![0x83, 0xa1, 0x61, 0x02, 0xa1, 0x62, 0x01, 0xa1, 0x62, 0x03], buf);
+}
+
+#[test]
+fn pass_pack_with_write_to() {
+    let val = Value::Map(vec![(Value::from("b"), Value::Nil), (Value::from("a"), Value::from(1))]);
+
+    let mut buf = Vec::new();
+    val.write_to(&mut buf).unwrap();
+    let mut expected = Vec::new();
+    write_value(&mut expected, &val).unwrap();
+    assert_eq!(expected, buf);
+
+    buf.clear();
+    val.write_to_with(&mut buf, WriteOptions::default().with_key_order(by_str)).unwrap();
+    assert_eq!(vec![0x82, 0xa1, 0x61, 0x01, 0xa1, 0x62, 0xc0], buf);
+
+    // Running out of space is reported as an I/O error.
+    let mut small = [0u8; 2];
+    let err = val.write_to(&mut &mut small[..]).unwrap_err();
+    assert_eq!(std::io::ErrorKind::WriteZero, err.kind());
+}
+
+#[test]
+fn pass_pack_canonical() {
+    let val = Value::Map(vec![
+        (Value::from("b"), Value::Map(vec![(Value::from(300), Value::Nil), (Value::from(2), Value::Nil)])),
+        (Value::from(-1), Value::from(1)),
+        (Value::from("a"), Value::from(2)),
+    ]);
+
+    let mut buf = Vec::new();
+    write_value_canonical(&mut buf, &val).unwrap();
+
+    // Keys are ordered by their encoding: "a", "b", -1.
+    assert_eq!(vec![
+        0x83,
+        0xa1, 0x61, 0x02,
+        0xa1, 0x62, 0x82, 0x02, 0xc0, 0xcd, 0x01, 0x2c, 0xc0,
+        0xff, 0x01,
+    ], buf);
+}
+
+#[test]
+fn pass_pack_minimal() {
+    // {"b": [uint 16 1, str 8 "a", 1.5 as f64], "a": [0.1, -0.0, NaN, 1e300]}, all in wide forms.
+    let buf = [
+        0x82,
+        0xa1, 0x62, 0xdc, 0x00, 0x03,
+        0xcd, 0x00, 0x01,
+        0xd9, 0x01, 0x61,
+        0xcb, 0x3f, 0xf8, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0xa1, 0x61, 0x94,
+        0xcb, 0x3f, 0xb9, 0x99, 0x99, 0x99, 0x99, 0x99, 0x9a,
+        0xcb, 0x80, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0xcb, 0x7f, 0xf8, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01,
+        0xcb, 0x7e, 0x37, 0xe4, 0x3c, 0x88, 0x00, 0x75, 0x9c,
+    ];
+    let val = rmpv::decode::read_value(&mut &buf[..]).unwrap();
+
+    let mut out = Vec::new();
+    write_value(&mut out, &val).unwrap();
+    // Integers and lengths are already shortened, floats keep their width.
+    assert_eq!([0x82, 0xa1, 0x62, 0x93, 0x01, 0xa1, 0x61, 0xcb], out[..8]);
+    assert_eq!(buf.len() - 5, out.len());
+
+    let mut minimal = Vec::new();
+    write_value_minimal(&mut minimal, &val).unwrap();
+    // 1.5 and -0.0 become f32, the others can not, and the map keeps its order.
+    assert_eq!([0x82, 0xa1, 0x62, 0x93, 0x01, 0xa1, 0x61, 0xca, 0x3f, 0xc0, 0x00, 0x00], minimal[..12]);
+    assert_eq!(out.len() - 8, minimal.len());
+    assert_eq!([0xca, 0x80, 0x00, 0x00, 0x00], minimal[24..29]);
+
+    let decoded = rmpv::decode::read_value(&mut &minimal[..]).unwrap();
+    assert_eq!(Some(1.5), decoded["b"][2].as_f64());
+    assert_eq!(val["a"][0], decoded["a"][0]);
+    assert_eq!(val["a"][2].as_f64().unwrap().to_bits(), decoded["a"][2].as_f64().unwrap().to_bits());
+}
+
+#[test]
+fn pass_canonical_hash() {
+    use std::hash::Hasher;
+
+    /// Records everything it is fed.
+    #[derive(Default)]
+    struct Collect(Vec<u8>);
+
+    impl Hasher for Collect {
+        fn finish(&self) -> u64 {
+            0
+        }
+
+        fn write(&mut self, bytes: &[u8]) {
+            self.0.extend_from_slice(bytes);
+        }
+    }
+
+    let val = Value::Map(vec![
+        (Value::from("z"), Value::Array(vec![Value::Binary(vec![0; 1000]), Value::from("x")])),
+        (Value::from("a"), Value::Map(vec![(Value::from("d"), Value::Nil), (Value::from("c"), Value::Nil)])),
+    ]);
+
+    let mut expected = Vec::new();
+    write_value_canonical(&mut expected, &val).unwrap();
+    let mut hasher = Collect::default();
+    val.canonical_hash(&mut hasher);
+    assert_eq!(expected, hasher.0);
+
+    let reordered = Value::Map(vec![
+        (Value::from("a"), Value::Map(vec![(Value::from("c"), Value::Nil), (Value::from("d"), Value::Nil)])),
+        (Value::from("z"), Value::Array(vec![Value::Binary(vec![0; 1000]), Value::from("x")])),
+    ]);
+    let hash = |val: &Value| {
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        val.canonical_hash(&mut hasher);
+        hasher.finish()
+    };
+    assert_eq!(hash(&val), hash(&reordered));
+    assert_ne!(hash(&val), hash(&Value::from("a")));
+
+    // Equal floats have equal hashes, even when their bits differ.
+    for (zero, neg_zero) in [(Value::F32(0.0), Value::F32(-0.0)), (Value::F64(0.0), Value::F64(-0.0))] {
+        assert_eq!(zero, neg_zero);
+        assert_eq!(hash(&zero), hash(&neg_zero));
+        let key = |key: Value| Value::Map(vec![(key, Value::Nil)]);
+        assert_eq!(hash(&key(zero)), hash(&key(neg_zero)));
+    }
+    let mut buf = Vec::new();
+    write_value_canonical(&mut buf, &Value::F64(-0.0)).unwrap();
+    assert_eq!(vec![0xcb, 0, 0, 0, 0, 0, 0, 0, 0], buf);
+}
@@ -43,3 +43,82 @@ quickcheck! {
         mirror_test(xs)
     }
 }
+
+fn mirror_float_bits(val: Value) -> Value {
+    let mut buf = Vec::new();
+    write_value(&mut buf, &val).unwrap();
+    read_value(&mut &buf[..]).unwrap()
+}
+
+#[test]
+fn mirror_f32_special_bits() {
+    // Negative zero, quiet NaN, quiet NaN with a payload, signaling NaN and negative NaN.
+    for bits in [0x8000_0000u32, 0x7fc0_0000, 0x7fc0_1234, 0x7f80_0001, 0xffc0_0000] {
+        match mirror_float_bits(Value::from(f32::from_bits(bits))) {
+            Value::F32(v) => assert_eq!(bits, v.to_bits()),
+            other => panic!("unexpected value: {other:?}"),
+        }
+    }
+}
+
+#[test]
+fn mirror_f64_special_bits() {
+    for bits in [
+        0x8000_0000_0000_0000u64,
+        0x7ff8_0000_0000_0000,
+        0x7ff8_0000_dead_beef,
+        0x7ff0_0000_0000_0001,
+        0xfff8_0000_0000_0000,
+    ] {
+        match mirror_float_bits(Value::from(f64::from_bits(bits))) {
+            Value::F64(v) => assert_eq!(bits, v.to_bits()),
+            other => panic!("unexpected value: {other:?}"),
+        }
+    }
+}
+
+#[test]
+fn mirror_ext_registry() {
+    use rmpv::decode::{self, ReadOptions};
+    use rmpv::ExtRegistry;
+
+    // Ext type 7 holds a u8, represented as a `["byte", n]` array.
+    let mut registry = ExtRegistry::new();
+    registry.register(7, |_| None, |_| None);
+    registry.register(
+        7,
+        |val| match val.as_array()?.as_slice() {
+            [tag, n] if tag.as_str() == Some("byte") => Some(vec![u8::try_from(n.as_u64()?).ok()?]),
+            _ => None,
+        },
+        |data| match *data {
+            [n] => Some(Value::Array(vec![Value::from("byte"), Value::from(n)])),
+            _ => None,
+        },
+    );
+    assert!(registry.contains(7));
+    assert!(!registry.contains(8));
+
+    let byte = Value::Array(vec![Value::from("byte"), Value::from(42)]);
+    let val = Value::Map(vec![
+        (Value::from("a"), byte.clone()),
+        (Value::from("b"), Value::Array(vec![byte, Value::Ext(8, vec![1, 2])])),
+        (Value::from("c"), Value::Array(vec![Value::from("byte"), Value::from(300)])),
+    ]);
+
+    let mut buf = Vec::new();
+    registry.write_value(&mut buf, &val).unwrap();
+    assert_eq!(&[0x83, 0xa1, 0x61, 0xd4, 0x07, 0x2a], &buf[..6]);
+
+    assert_eq!(val, registry.read_value(&mut &buf[..]).unwrap());
+    assert_ne!(val, read_value(&mut &buf[..]).unwrap());
+
+    // The byte limit applies to values decoded through the registry as well.
+    let options = ReadOptions::default().with_max_value_bytes(buf.len() - 1);
+    match registry.read_value_with(&mut &buf[..], options) {
+        Err(decode::Error::ByteLimitExceeded(limit)) if limit == buf.len() - 1 => (),
+        other => panic!("unexpected result: {other:?}"),
+    }
+    let options = ReadOptions::default().with_max_value_bytes(buf.len());
+    assert_eq!(val, registry.read_value_with(&mut &buf[..], options).unwrap());
+}
@@ -112,6 +112,37 @@ fn from_isize() {
     assert_eq!(Value::from(-42), Value::from(-42isize));
 }
 
+#[test]
+fn from_u128() {
+    assert_eq!(Value::from(u64::MAX), Value::from(u128::from(u64::MAX)));
+    assert_eq!(Value::Binary([&[0x00; 7][..], &[0x01], &[0x00; 8]].concat()), Value::from(1u128 << 64));
+}
+
+#[test]
+fn from_i128() {
+    assert_eq!(Value::from(42), Value::from(42i128));
+    assert_eq!(Value::from(i64::MIN), Value::from(i128::from(i64::MIN)));
+    assert_eq!(Value::from(u64::MAX), Value::from(i128::from(u64::MAX)));
+    assert_eq!(Value::Binary([&[0xff; 7][..], &[0xfe], &[0x00; 8]].concat()), Value::from(-2i128 << 64));
+}
+
+#[test]
+fn from_option() {
+    assert_eq!(Value::Nil, Value::from(None::<u8>));
+    assert_eq!(Value::from("a"), Value::from(Some("a")));
+}
+
+#[test]
+fn from_hash_map_and_btree_map() {
+    use std::collections::{BTreeMap, HashMap};
+
+    let map = BTreeMap::from([(2, "b"), (1, "a")]);
+    assert_eq!(Value::Map(vec![(Value::from(1), Value::from("a")), (Value::from(2), Value::from("b"))]), Value::from(map));
+
+    let map = HashMap::from([("k", vec![Value::Nil])]);
+    assert_eq!(Value::Map(vec![(Value::from("k"), Value::Array(vec![Value::Nil]))]), Value::from(map));
+}
+
 #[test]
 fn from_f32() {
     assert_eq!(Value::F32(3.1415), Value::from(3.1415f32));
@@ -196,3 +227,654 @@ fn try_from_val() {
   assert_eq!(String::from("spook"), TryInto::<String>::try_into(Value::from("spook")).unwrap());
   assert_eq!(vec![0], TryInto::<Vec<u8>>::try_into(Value::Binary(vec![0u8])).unwrap());
 }
+
+#[test]
+fn try_as_val() {
+    assert_eq!(Ok(true), Value::Boolean(true).try_as_bool());
+    assert_eq!(Ok(42), Value::from(42).try_as_u64());
+    assert_eq!(Ok(-1), Value::from(-1).try_as_i64());
+    assert_eq!(Ok(&[1u8, 2][..]), Value::Binary(vec![1, 2]).try_as_slice());
+    assert_eq!(Ok((1, &[2u8][..])), Value::Ext(1, vec![2]).try_as_ext());
+
+    let err = Value::from(-1).try_as_u64().unwrap_err();
+    assert_eq!("u64", err.expected());
+    assert_eq!("Integer", err.actual());
+
+    let err = Value::Map(vec![]).try_as_array().unwrap_err();
+    assert_eq!("expected array, found Map", err.to_string());
+}
+
+#[test]
+fn into_val() {
+    assert_eq!(Ok(vec![Value::Nil]), Value::Array(vec![Value::Nil]).into_array());
+    assert_eq!(Ok(vec![]), Value::Map(vec![]).into_map());
+    assert_eq!(Ok(vec![1]), Value::Binary(vec![1]).into_bin());
+    assert_eq!(Ok((1, vec![2])), Value::Ext(1, vec![2]).into_ext());
+
+    assert_eq!(Err(Value::from(42)), Value::from(42).into_str());
+    assert_eq!(Err(Value::from("le message")), Value::from("le message").into_bin());
+}
+
+#[test]
+fn flatten_arrays() {
+    let mut val = Value::Array(vec![Value::Array(vec![Value::from(1), Value::from(2)])]);
+    val.flatten_arrays(usize::MAX);
+    assert_eq!(Value::Array(vec![Value::from(1), Value::from(2)]), val);
+
+    let nested = Value::Array(vec![Value::Array(vec![Value::Nil])]);
+    let mut val = Value::Array(vec![nested.clone(), Value::Nil]);
+    val.flatten_arrays(usize::MAX);
+    assert_eq!(Value::Array(vec![nested, Value::Nil]), val);
+
+    let mut val = Value::Array(vec![Value::Array(vec![])]);
+    val.flatten_arrays(0);
+    assert_eq!(Value::Array(vec![Value::Array(vec![])]), val);
+    val.flatten_arrays(1);
+    assert_eq!(Value::Array(vec![]), val);
+
+    let mut val = Value::from(42);
+    val.flatten_arrays(usize::MAX);
+    assert_eq!(Value::from(42), val);
+}
+
+#[test]
+fn as_ext() {
+    use rmpv::ValueRef;
+
+    let val = Value::Ext(-1, vec![1, 2]);
+    assert!(val.is_ext());
+    assert_eq!(Some((-1, &[1u8, 2][..])), val.as_ext());
+    assert!(!Value::Binary(vec![1, 2]).is_ext());
+
+    let buf = [3u8, 4];
+    let data = {
+        let val = ValueRef::Ext(5, &buf);
+        assert!(val.is_ext());
+        val.as_ext().unwrap().1
+    };
+    assert_eq!(&buf[..], data);
+    assert_eq!(None, ValueRef::Binary(&buf).as_ext());
+    assert!(!ValueRef::Nil.is_ext());
+}
+
+#[test]
+fn apply_merge_patch() {
+    fn map(entries: &[(&str, Value)]) -> Value {
+        Value::Map(entries.iter().map(|(k, v)| (Value::from(*k), v.clone())).collect())
+    }
+
+    // Deletion, replacement and addition.
+    let mut val = map(&[("a", Value::from("b")), ("c", Value::from("d"))]);
+    val.apply_merge_patch(&map(&[("a", Value::Nil), ("c", Value::from(1)), ("e", Value::from(true))]));
+    assert_eq!(map(&[("c", Value::from(1)), ("e", Value::from(true))]), val);
+
+    // Nested maps are merged, and nils inside new maps are dropped.
+    let mut val = map(&[("a", map(&[("b", Value::from(1)), ("c", Value::from(2))]))]);
+    val.apply_merge_patch(&map(&[
+        ("a", map(&[("b", Value::Nil), ("d", Value::from(3))])),
+        ("x", map(&[("y", Value::Nil), ("z", Value::from(4))])),
+    ]));
+    assert_eq!(map(&[
+        ("a", map(&[("c", Value::from(2)), ("d", Value::from(3))])),
+        ("x", map(&[("z", Value::from(4))])),
+    ]), val);
+
+    // Arrays are replaced rather than merged.
+    let mut val = map(&[("a", Value::Array(vec![Value::from(1), Value::from(2)]))]);
+    val.apply_merge_patch(&map(&[("a", Value::Array(vec![Value::Nil]))]));
+    assert_eq!(map(&[("a", Value::Array(vec![Value::Nil]))]), val);
+
+    // A non-map target becomes a map, and a non-map patch replaces the target.
+    let mut val = Value::from(42);
+    val.apply_merge_patch(&map(&[("a", Value::Nil), ("b", Value::from(1))]));
+    assert_eq!(map(&[("b", Value::from(1))]), val);
+    val.apply_merge_patch(&Value::Nil);
+    assert_eq!(Value::Nil, val);
+
+    // Deleting a missing key, or patching with an empty map, keeps the target.
+    let mut val = map(&[("a", Value::from(1))]);
+    val.apply_merge_patch(&map(&[("b", Value::Nil)]));
+    val.apply_merge_patch(&map(&[]));
+    assert_eq!(map(&[("a", Value::from(1))]), val);
+}
+
+#[test]
+fn collect_and_extend() {
+    let val: Value = (1..3).collect();
+    assert_eq!(Value::Array(vec![Value::from(1), Value::from(2)]), val);
+
+    let val: Value = vec![(Value::from("a"), Value::from(1)), (Value::from("a"), Value::Nil)]
+        .into_iter()
+        .collect();
+    assert_eq!(Value::Map(vec![(Value::from("a"), Value::from(1)), (Value::from("a"), Value::Nil)]), val);
+
+    let mut val = Value::Array(vec![Value::Nil]);
+    val.extend(vec!["a", "b"]);
+    assert_eq!(Value::Array(vec![Value::Nil, Value::from("a"), Value::from("b")]), val);
+
+    let mut val = Value::Map(vec![]);
+    val.extend(Some((Value::from(1), Value::from(true))));
+    assert_eq!(Value::Map(vec![(Value::from(1), Value::from(true))]), val);
+}
+
+#[test]
+#[should_panic(expected = "can not extend Map with array elements")]
+fn extend_map_with_elements() {
+    Value::Map(vec![]).extend(vec![1]);
+}
+
+#[test]
+fn diff() {
+    use rmpv::Change;
+
+    let val = Value::Map(vec![(Value::from("a"), Value::Array(vec![Value::from(1)]))]);
+    assert_eq!(Vec::<Change>::new(), rmpv::diff(&val, &val.clone()));
+
+    assert_eq!(
+        vec![Change { path: String::new(), old: Some(Value::from(1)), new: Some(Value::from("1")) }],
+        rmpv::diff(&Value::from(1), &Value::from("1"))
+    );
+
+    let old = Value::Map(vec![
+        (Value::from("a"), Value::Map(vec![(Value::from("x~y"), Value::from(1)), (Value::from(2), Value::Nil)])),
+        (Value::from("b"), Value::Array(vec![Value::from(1)])),
+        (Value::from("c"), Value::from(true)),
+    ]);
+    let new = Value::Map(vec![
+        (Value::from("b"), Value::Array(vec![Value::from(1), Value::Array(vec![])])),
+        (Value::from("a"), Value::Map(vec![(Value::from("x~y"), Value::from(2)), (Value::from(2), Value::Nil)])),
+        (Value::from("c"), Value::Map(vec![])),
+    ]);
+
+    assert_eq!(vec![
+        Change { path: "/a/x~0y".into(), old: Some(Value::from(1)), new: Some(Value::from(2)) },
+        Change { path: "/b/1".into(), old: None, new: Some(Value::Array(vec![])) },
+        Change { path: "/c".into(), old: Some(Value::from(true)), new: Some(Value::Map(vec![])) },
+    ], rmpv::diff(&old, &new));
+
+    let old = Value::Map(vec![(Value::from(2), Value::from("a"))]);
+    let new = Value::Map(vec![(Value::from(false), Value::from("a"))]);
+    assert_eq!(vec![
+        Change { path: "/2".into(), old: Some(Value::from("a")), new: None },
+        Change { path: "/false".into(), old: None, new: Some(Value::from("a")) },
+    ], rmpv::diff(&old, &new));
+}
+
+#[test]
+fn coerce_keys_to_string() {
+    let mut val = Value::Map(vec![
+        (Value::Nil, Value::from(1)),
+        (Value::from(false), Value::from(2)),
+        (Value::from(-42), Value::from(3)),
+        (Value::from(u64::MAX), Value::from(4)),
+        (Value::F32(0.5), Value::from(5)),
+        (Value::F64(f64::NEG_INFINITY), Value::from(6)),
+        (Value::from("s"), Value::Array(vec![Value::Map(vec![(Value::from(7), Value::Map(vec![]))])])),
+    ]);
+    val.coerce_keys_to_string().unwrap();
+
+    assert_eq!(Value::Map(vec![
+        (Value::from("nil"), Value::from(1)),
+        (Value::from("false"), Value::from(2)),
+        (Value::from("-42"), Value::from(3)),
+        (Value::from("18446744073709551615"), Value::from(4)),
+        (Value::from("0.5"), Value::from(5)),
+        (Value::from("-inf"), Value::from(6)),
+        (Value::from("s"), Value::Array(vec![Value::Map(vec![(Value::from("7"), Value::Map(vec![]))])])),
+    ]), val);
+
+    // Nothing is converted when any key can not be.
+    let orig = Value::Map(vec![
+        (Value::from(1), Value::Nil),
+        (Value::from("a"), Value::Map(vec![(Value::Binary(vec![0]), Value::Nil)])),
+    ]);
+    let mut val = orig.clone();
+    let err = val.coerce_keys_to_string().unwrap_err();
+    assert_eq!("Binary", err.actual());
+    assert_eq!(orig, val);
+
+    for key in [
+        // A string with invalid UTF-8.
+        rmpv::decode::read_value(&mut &[0xa1, 0xff][..]).unwrap(),
+        Value::Ext(1, vec![]),
+        Value::Map(vec![]),
+    ] {
+        let mut val = Value::Map(vec![(key, Value::Nil)]);
+        assert!(val.coerce_keys_to_string().is_err());
+    }
+}
+
+#[test]
+fn remap_ext_tags() {
+    let mut val = Value::Map(vec![
+        (Value::Ext(3, vec![0x00]), Value::Array(vec![Value::Ext(3, vec![0x01]), Value::Ext(-1, vec![])])),
+        (Value::from("deep"), Value::Array(vec![Value::Array(vec![Value::Ext(5, vec![0x02])])])),
+        (Value::from("other"), Value::from(3)),
+    ]);
+
+    let mut seen = Vec::new();
+    val.remap_ext_tags(|ty| {
+        seen.push(ty);
+        if ty == 3 { 7 } else { ty }
+    });
+
+    assert_eq!(vec![3, 3, -1, 5], seen);
+    assert_eq!(Value::Map(vec![
+        (Value::Ext(7, vec![0x00]), Value::Array(vec![Value::Ext(7, vec![0x01]), Value::Ext(-1, vec![])])),
+        (Value::from("deep"), Value::Array(vec![Value::Array(vec![Value::Ext(5, vec![0x02])])])),
+        (Value::from("other"), Value::from(3)),
+    ]), val);
+}
+
+#[test]
+fn shrink_to_fit() {
+    fn slack<T: Clone>(items: &[T]) -> Vec<T> {
+        let mut vec = Vec::with_capacity(64);
+        vec.extend_from_slice(items);
+        vec
+    }
+
+    fn assert_compact(val: &Value) {
+        match *val {
+            Value::Binary(ref data) | Value::Ext(_, ref data) => assert_eq!(data.len(), data.capacity()),
+            Value::Array(ref vec) => {
+                assert_eq!(vec.len(), vec.capacity());
+                vec.iter().for_each(assert_compact);
+            }
+            Value::Map(ref map) => {
+                assert_eq!(map.len(), map.capacity());
+                for (key, val) in map {
+                    assert_compact(key);
+                    assert_compact(val);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let mut val = Value::Map(slack(&[
+        (Value::Binary(slack(&[1, 2])), Value::Array(slack(&[Value::Ext(1, slack(&[3]))]))),
+        (Value::from("k"), Value::Array(slack(&[Value::Array(slack(&[Value::Nil]))]))),
+    ]));
+    let expected = val.clone();
+    assert_compact(&expected);
+
+    val.shrink_to_fit();
+    assert_compact(&val);
+    assert_eq!(expected, val);
+}
+
+#[test]
+fn encoded_len_matches_write_value() {
+    let vals = [
+        Value::Nil,
+        Value::from(true),
+        Value::from(127),
+        Value::from(128),
+        Value::from(65536),
+        Value::from(u64::MAX),
+        Value::from(-32),
+        Value::from(-33),
+        Value::from(-32769),
+        Value::from(i64::MIN),
+        Value::F32(1.5),
+        Value::F64(1.5),
+        Value::from("a".repeat(31)),
+        Value::from("a".repeat(32)),
+        Value::from("a".repeat(65536)),
+        Value::Binary(vec![0; 256]),
+        Value::Ext(1, vec![0; 4]),
+        Value::Ext(1, vec![0; 3]),
+        Value::Ext(1, vec![0; 256]),
+        Value::Array(vec![Value::Nil; 16]),
+        Value::Map(vec![(Value::from("k"), Value::Array(vec![Value::from(1); 65536]))]),
+    ];
+
+    for val in vals {
+        let mut buf = Vec::new();
+        rmpv::encode::write_value(&mut buf, &val).unwrap();
+        assert_eq!(buf.len(), val.encoded_len(), "{}", val.type_name());
+    }
+}
+
+#[test]
+fn truncate_to_bytes() {
+    let record = |name: &str| Value::Map(vec![
+        (Value::from("name"), Value::from(name)),
+        (Value::from("blob"), Value::Binary(vec![7; 300])),
+    ]);
+    let mut val = Value::Array(vec![record(&"é".repeat(100)), record("short")]);
+
+    val.truncate_to_bytes(200);
+    assert!(val.encoded_len() <= 200);
+    // Keys are kept, the long string and the binaries are cut to a common length.
+    let records = val.as_array().unwrap();
+    assert_eq!(2, records.len());
+    let name = records[0].map_get("name").unwrap().as_str().unwrap();
+    assert!(name.ends_with(rmpv::TRUNCATION_MARKER));
+    assert!(name.starts_with("éé"));
+    assert_eq!(Some("short"), records[1].map_get("name").unwrap().as_str());
+    let blob = records[0].map_get("blob").unwrap().as_slice().unwrap();
+    assert!(!blob.is_empty() && blob.len() < 300);
+
+    // Elements are removed once strings and binaries are empty.
+    let mut val = Value::Array((0..100).map(Value::from).collect());
+    val.truncate_to_bytes(10);
+    assert!(val.encoded_len() <= 10);
+    let vec = val.as_array().unwrap();
+    assert!(!vec.is_empty());
+    assert_eq!(&(0..vec.len()).map(Value::from).collect::<Vec<_>>(), vec);
+
+    // Values that fit are left alone, and scalars can not be shrunk.
+    let mut val = record("a");
+    let expected = val.clone();
+    val.truncate_to_bytes(1000);
+    assert_eq!(expected, val);
+
+    let mut val = Value::from(u64::MAX);
+    val.truncate_to_bytes(0);
+    assert_eq!(Value::from(u64::MAX), val);
+
+    let mut val = Value::Array(vec![Value::from("abc"), Value::Ext(1, vec![0; 8])]);
+    val.truncate_to_bytes(0);
+    assert_eq!(Value::Array(vec![]), val);
+}
+
+#[test]
+fn test_vectors_cover_every_marker() {
+    use std::collections::HashSet;
+    use std::mem::discriminant;
+
+    use rmp::Marker;
+
+    let covered: HashSet<_> = rmpv::test_vectors().iter()
+        .map(|(bytes, _)| discriminant(&Marker::from_u8(bytes[0])))
+        .collect();
+    let all: HashSet<_> = (0..=u8::MAX)
+        .map(Marker::from_u8)
+        .filter(|marker| *marker != Marker::Reserved)
+        .map(|marker| discriminant(&marker))
+        .collect();
+    assert_eq!(all, covered);
+
+    let mut buf = Vec::new();
+    for (bytes, val) in rmpv::test_vectors() {
+        assert_eq!(val, rmpv::decode::read_value(&mut &bytes[..]).unwrap());
+        buf.clear();
+        rmpv::encode::write_value(&mut buf, &val).unwrap();
+        assert_eq!(bytes, buf);
+    }
+}
+
+#[test]
+fn ext_validates() {
+    use rmpv::ExtError;
+
+    assert_eq!(Ok(Value::Ext(1, vec![])), Value::ext(1, Vec::new()));
+    assert_eq!(Ok(Value::Ext(-2, vec![1])), Value::ext(-2, &[1][..]));
+    assert_eq!(Ok(Value::Ext(-1, vec![0; 4])), Value::ext(-1, [0; 4]));
+    assert_eq!(Ok(Value::Ext(-1, vec![0; 12])), Value::ext(-1, [0; 12]));
+    assert_eq!(Err(ExtError::InvalidTimestampLen(0)), Value::ext(-1, []));
+
+    // timestamp 64 and 96 with 1 000 000 000 nanoseconds
+    let nanos = 1_000_000_000u32;
+    let ts64 = (u64::from(nanos) << 34).to_be_bytes();
+    assert_eq!(Err(ExtError::InvalidNanos(nanos)), Value::ext(-1, ts64));
+    let ts96 = [&nanos.to_be_bytes()[..], &[0; 8]].concat();
+    assert_eq!(Err(ExtError::InvalidNanos(nanos)), Value::ext(-1, ts96));
+}
+
+#[test]
+fn timestamp_uses_smallest_format() {
+    let data = |secs, nanos| Value::timestamp(secs, nanos).unwrap().as_ext().unwrap().1.to_vec();
+
+    assert_eq!(vec![0xff, 0xff, 0xff, 0xff], data(u32::MAX.into(), 0));
+    assert_eq!(vec![0x00, 0x00, 0x00, 0x04, 0x00, 0x00, 0x00, 0x01], data(1, 1));
+    assert_eq!(vec![0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00], data(u32::MAX as i64 + 1, 0));
+    assert_eq!([&[0, 0, 0, 0][..], &(1i64 << 34).to_be_bytes()].concat(), data(1 << 34, 0));
+    assert_eq!([&500_000_000u32.to_be_bytes()[..], &(-2i64).to_be_bytes()].concat(), data(-2, 500_000_000));
+
+    // Each of them is accepted by `Value::ext`.
+    for (secs, nanos) in [(0, 0), (1, 999_999_999), (1 << 34, 1), (i64::MIN, 0)] {
+        let val = Value::timestamp(secs, nanos).unwrap();
+        let (ty, data) = val.as_ext().unwrap();
+        assert_eq!(Ok(val.clone()), Value::ext(ty, data));
+    }
+}
+
+#[test]
+fn iterate_arrays_and_maps() {
+    let array = Value::Array(vec![Value::from(1), Value::from("a")]);
+    let map = Value::Map(vec![(Value::from("k"), Value::Nil), (Value::from(2), Value::from(true))]);
+
+    assert_eq!(vec![&Value::from(1), &Value::from("a")], array.iter_array().unwrap().collect::<Vec<_>>());
+    assert_eq!(2, map.iter_map().unwrap().len());
+    assert!(array.iter_map().is_none());
+    assert!(map.iter_array().is_none());
+
+    assert_eq!(vec![Value::from(1), Value::from("a")], array.clone().into_array_iter().unwrap().collect::<Vec<_>>());
+    let keys: Vec<_> = map.clone().into_map_iter().unwrap().map(|(key, _)| key).collect();
+    assert_eq!(vec![Value::from("k"), Value::from(2)], keys);
+    assert_eq!(Err(map.clone()), map.clone().into_array_iter().map(|_| ()));
+    assert_eq!(Err(array.clone()), array.clone().into_map_iter().map(|_| ()));
+}
+
+#[test]
+fn collect_strings() {
+    // Keys holding strings nested in arrays, and a string which is not valid UTF-8.
+    let val = Value::Array(vec![
+        Value::from("a"),
+        Value::Map(vec![
+            (Value::from("k"), Value::Map(vec![(Value::from(1), Value::from("b"))])),
+            (Value::Array(vec![Value::from("nested key")]), Value::from("c")),
+        ]),
+        rmpv::decode::read_value(&mut &[0xa2, 0xc3, 0x28][..]).unwrap(),
+        Value::Binary(b"bin".to_vec()),
+        Value::from("d"),
+    ]);
+
+    assert_eq!(vec!["a", "b", "c", "d"], val.collect_strings(false));
+    assert_eq!(vec!["a", "k", "b", "nested key", "c", "d"], val.collect_strings(true));
+    assert_eq!(vec!["e"], Value::from("e").collect_strings(false));
+    assert!(Value::Nil.collect_strings(true).is_empty());
+}
+
+#[test]
+fn try_into_collections() {
+    use std::collections::HashMap;
+    use std::convert::TryFrom;
+
+    use rmpv::CollectionError;
+
+    let rows = Value::Array(vec![
+        Value::Map(vec![(Value::from("a"), Value::from(1))]),
+        Value::Map(Vec::new()),
+    ]);
+    let mut first = HashMap::new();
+    first.insert("a".to_owned(), 1u64);
+    assert_eq!(vec![first, HashMap::new()], rows.clone().try_into_vec::<HashMap<String, u64>>().unwrap());
+
+    // The failing element and entry are reported.
+    let bad = Value::Array(vec![Value::Map(Vec::new()), Value::Map(vec![(Value::from("b"), Value::from(-1))])]);
+    assert_eq!(
+        Err(CollectionError::Element(1, CollectionError::Element(0, Value::from(-1)))),
+        bad.try_into_vec::<HashMap<String, u64>>()
+    );
+    assert_eq!(Err(CollectionError::Type(Value::from(1))), Value::from(1).try_into_vec::<u64>());
+    assert_eq!(
+        Err(CollectionError::Type(rows.clone())),
+        HashMap::<String, u64>::try_from(rows)
+    );
+    assert_eq!(
+        "invalid element at index 1: invalid element at index 0: -1",
+        Value::Array(vec![Value::Map(Vec::new()), Value::Map(vec![(Value::from("b"), Value::from(-1))])])
+            .try_into_vec::<HashMap<String, u64>>()
+            .unwrap_err()
+            .to_string()
+    );
+
+    // Options map nil to None.
+    let vals = Value::Array(vec![Value::Nil, Value::from(true)]);
+    let opts: Vec<Option<bool>> = vals.into_array().unwrap().into_iter().map(|val| val.try_into_option().unwrap()).collect();
+    assert_eq!(vec![None, Some(true)], opts);
+    assert_eq!(Ok(Some(vec![Value::Nil])), Value::Array(vec![Value::Nil]).try_into_option::<Vec<Value>>());
+}
+
+#[test]
+fn set_at_creates_and_replaces() {
+    use rmpv::PointerError;
+
+    let mut val = Value::Map(Vec::new());
+    val.set_at("/a/b", Value::from(1)).unwrap();
+    val.set_at("/list/0", Value::from("x")).unwrap();
+    val.set_at("/list/1", Value::from("y")).unwrap();
+    val.set_at("/list/0", Value::from("z")).unwrap();
+    val.set_at("/~0k~1", Value::Nil).unwrap();
+    assert_eq!(Value::Map(vec![
+        (Value::from("a"), Value::Map(vec![(Value::from("b"), Value::from(1))])),
+        (Value::from("list"), Value::Array(vec![Value::from("z"), Value::from("y")])),
+        (Value::from("~k/"), Value::Nil),
+    ]), val);
+    assert_eq!(Some(&Value::Nil), val.pointer("/~0k~1"));
+    assert_eq!(Some(&Value::from(1)), val.pointer("/a/b"));
+    assert_eq!(None, val.pointer("/list/01"));
+
+    // The value is left unchanged on errors.
+    let before = val.clone();
+    assert_eq!(Err(PointerError::NotAContainer("/a/b".into())), val.set_at("/a/b/c/d", Value::Nil));
+    assert_eq!(Err(PointerError::NotAContainer("/~0k~1".into())), val.set_at("/~0k~1/0", Value::Nil));
+    assert_eq!(Err(PointerError::InvalidIndex("/list/3".into())), val.set_at("/list/3", Value::Nil));
+    assert_eq!(Err(PointerError::InvalidIndex("/list/name".into())), val.set_at("/list/name/x", Value::Nil));
+    assert_eq!(Err(PointerError::Syntax), val.set_at("a", Value::Nil));
+    assert_eq!(Err(PointerError::Syntax), val.set_at("/a~2", Value::Nil));
+    assert_eq!(before, val);
+
+    val.set_at("", Value::from(42)).unwrap();
+    assert_eq!(Value::from(42), val);
+}
+
+#[test]
+fn integers_are_normalized() {
+    assert_eq!(Value::from(42u64), Value::from(42i64));
+    assert_eq!(Value::from(0u8), Value::from(0i8));
+    assert_eq!(Value::from(i64::MAX as u64), Value::from(i64::MAX));
+    assert_ne!(Value::from(u64::MAX), Value::from(-1i64));
+
+    let mut buf = Vec::new();
+    rmpv::encode::write_value(&mut buf, &Value::Array(vec![Value::from(42i64), Value::from(-1i32)])).unwrap();
+    assert_eq!(vec![0x92, 0x2a, 0xff], buf);
+
+    // uint 8 42 and int 8 42 decode to the same value.
+    let pos = rmpv::decode::read_value(&mut &[0xcc, 0x2a][..]).unwrap();
+    let neg = rmpv::decode::read_value(&mut &[0xd0, 0x2a][..]).unwrap();
+    assert_eq!(pos, neg);
+    assert_eq!(Some(42), neg.as_u64());
+}
+
+#[test]
+fn as_f64_promotes_integers() {
+    assert_eq!(Some(-1.0), Value::from(-1).as_f64());
+    assert_eq!(Some(18446744073709551615.0), Value::from(u64::MAX).as_f64());
+    assert_eq!(Some(-9223372036854775808.0), Value::from(i64::MIN).as_f64());
+    assert_eq!(Some(1.5), Value::F32(1.5).as_f64());
+    assert_eq!(None, Value::from("1").as_f64());
+}
+
+#[test]
+fn walk() {
+    use rmpv::PathSegment;
+
+    let key = Value::from("a");
+    let val = Value::Map(vec![
+        (key.clone(), Value::Array(vec![Value::Nil, Value::Map(vec![(Value::from(1), Value::from(true))])])),
+        (Value::from("b"), Value::from(2)),
+    ]);
+
+    let mut visited = Vec::new();
+    val.walk(|path, node| visited.push((path.to_vec(), node.type_name())));
+
+    let one = Value::from(1);
+    let b = Value::from("b");
+    assert_eq!(vec![
+        (vec![], "Map"),
+        (vec![PathSegment::Key(&key)], "Array"),
+        (vec![PathSegment::Key(&key), PathSegment::Index(0)], "Nil"),
+        (vec![PathSegment::Key(&key), PathSegment::Index(1)], "Map"),
+        (vec![PathSegment::Key(&key), PathSegment::Index(1), PathSegment::Key(&one)], "Boolean"),
+        (vec![PathSegment::Key(&b)], "Integer"),
+    ], visited);
+
+    // Deep values are walked without recursion.
+    let mut deep = Value::Nil;
+    for _ in 0..10_000 {
+        deep = Value::Array(vec![deep]);
+    }
+    let mut max_len = 0;
+    deep.walk(|path, _| max_len = max_len.max(path.len()));
+    assert_eq!(10_000, max_len);
+}
+
+#[test]
+fn map_get() {
+    let val = Value::Map(vec![
+        (Value::from(1), Value::from("int key")),
+        (Value::from("a"), Value::from(1)),
+        (Value::from("b"), Value::Array(vec![])),
+        (Value::from("a"), Value::from(2)),
+    ]);
+
+    assert_eq!(Some(&Value::from(1)), val.map_get("a"));
+    assert_eq!(Some(&Value::Array(vec![])), val.map_get("b"));
+    // Only String keys match.
+    assert_eq!(None, val.map_get("1"));
+    assert_eq!(None, val.map_get("c"));
+    assert_eq!(None, Value::Array(vec![Value::from("a")]).map_get("a"));
+
+    assert_eq!(val["a"], *val.map_get("a").unwrap());
+}
+
+#[test]
+fn schema_validate() {
+    use rmpv::{Schema, ValidationErrorKind};
+
+    let schema = Schema::object([
+        ("id", Schema::integer()),
+        ("tags", Schema::array(Schema::string())),
+        ("owner", Schema::object([("name", Schema::string())]).with_optional("email", Schema::string())),
+    ])
+    .with_optional("ratio", Schema::float());
+
+    let val = Value::Map(vec![
+        (Value::from("id"), Value::from(1)),
+        (Value::from("tags"), Value::Array(vec![Value::from("a")])),
+        (Value::from("owner"), Value::Map(vec![(Value::from("name"), Value::from("me"))])),
+        (Value::from("extra"), Value::Nil),
+    ]);
+    assert_eq!(Ok(()), schema.validate(&val));
+
+    let val = Value::Map(vec![
+        (Value::from("id"), Value::from("1")),
+        (Value::from("tags"), Value::Array(vec![Value::from("a"), Value::Nil, Value::from(2)])),
+        (Value::from("owner"), Value::Map(vec![(Value::from("email"), Value::from(true))])),
+        (Value::from("ratio"), Value::from(1)),
+    ]);
+    let errors = schema.validate(&val).unwrap_err();
+    let errors: Vec<_> = errors.iter().map(|err| (err.path.as_str(), err.to_string())).collect();
+    assert_eq!(vec![
+        ("/id", "expected an integer, found String".to_owned()),
+        ("/tags/1", "expected a string, found Nil".to_owned()),
+        ("/tags/2", "expected a string, found Integer".to_owned()),
+        ("/owner", "missing required key \"name\"".to_owned()),
+        ("/owner/email", "expected a string, found Boolean".to_owned()),
+        ("/ratio", "expected a float, found Integer".to_owned()),
+    ], errors);
+
+    // Nothing nested in a value of the wrong type is checked.
+    let errors = schema.validate(&Value::Array(vec![])).unwrap_err();
+    assert_eq!(1, errors.len());
+    assert_eq!("", errors[0].path);
+    assert!(matches!(errors[0].kind, ValidationErrorKind::Type(err) if err.actual() == "Array"));
+
+    assert_eq!(Ok(()), Schema::any().validate(&Value::Ext(1, vec![])));
+    assert!(Schema::string().validate(&rmpv::decode::read_value(&mut &[0xa1, 0xff][..]).unwrap()).is_err());
+}